@@ -0,0 +1,11 @@
+// 把 MathJax 的 SVG 输出脚本从 npm 包复制到 public/mathjax/，随应用分发，
+// 使用 MathJax 公式引擎导出时不依赖网络（见 src-tauri/src/math_engine.rs）
+import { copyFileSync, mkdirSync } from 'node:fs';
+import { createRequire } from 'node:module';
+import { dirname, join } from 'node:path';
+
+const require = createRequire(import.meta.url);
+const source = require.resolve('mathjax/es5/tex-svg-full.js');
+const target = join('public', 'mathjax', 'tex-svg-full.js');
+mkdirSync(dirname(target), { recursive: true });
+copyFileSync(source, target);
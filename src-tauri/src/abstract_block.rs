@@ -0,0 +1,105 @@
+//! 摘要与关键词：front matter 中的 `abstract` 与 `keywords` 生成论文样式的摘要区块，
+//! 位于标题之下、正文之前：
+//!
+//! ```yaml
+//! title: 基于注意力机制的文本分类方法
+//! author: [张三, 李四]
+//! abstract: |
+//!   本文提出了一种……
+//!
+//!   实验结果表明……
+//! keywords: [文本分类, 注意力机制, 深度学习]
+//! ```
+//!
+//! 正文以一级标题开头时插入在该标题之后；否则没有封面时按 `title`、`author`、`date` 生成标题区，
+//! 摘要跟在标题区之后。`lang` 为英文时标签为 “Abstract” / “Keywords”。
+
+use crate::cover::text_of;
+use crate::html_util::escape_html;
+use regex::Regex;
+use serde_yaml::Value;
+
+/// 关键词列表：列表，或以逗号、分号分隔的文字
+fn keywords(front_matter: &Value) -> Vec<String> {
+    match front_matter.get("keywords") {
+        Some(Value::Sequence(items)) => items.iter().filter_map(text_of).collect(),
+        Some(value) => text_of(value)
+            .map(|text| {
+                text.split([',', '，', ';', '；', '、'])
+                    .map(|k| k.trim().to_string())
+                    .filter(|k| !k.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        None => Vec::new(),
+    }
+}
+
+pub fn is_english(front_matter: &Value) -> bool {
+    front_matter
+        .get("lang")
+        .and_then(Value::as_str)
+        .is_some_and(|lang| lang.to_ascii_lowercase().starts_with("en"))
+}
+
+/// 摘要与关键词区块；两者都没有时返回 `None`
+fn abstract_html(front_matter: &Value) -> Option<String> {
+    let summary = front_matter.get("abstract").and_then(text_of);
+    let keywords = keywords(front_matter);
+    if summary.is_none() && keywords.is_empty() {
+        return None;
+    }
+    let english = is_english(front_matter);
+
+    let mut html = String::from(r#"<section class="abstract-block">"#);
+    if let Some(summary) = summary {
+        let label = if english { "Abstract" } else { "摘要" };
+        // 摘要按 Markdown 渲染，支持分段与强调
+        let mut body = String::new();
+        pulldown_cmark::html::push_html(&mut body, pulldown_cmark::Parser::new(&summary));
+        html.push_str(&format!(
+            r#"<div class="abstract-title">{}</div><div class="abstract-body">{}</div>"#,
+            label, body
+        ));
+    }
+    if !keywords.is_empty() {
+        let (label, separator) = if english { ("Keywords: ", "; ") } else { ("关键词：", "；") };
+        let list = keywords.iter().map(|k| escape_html(k)).collect::<Vec<_>>().join(separator);
+        html.push_str(&format!(
+            r#"<p class="abstract-keywords"><span class="abstract-keywords-label">{}</span>{}</p>"#,
+            label, list
+        ));
+    }
+    html.push_str("</section>");
+    Some(html)
+}
+
+/// 标题区：标题、作者与日期
+fn title_block(front_matter: &Value) -> Option<String> {
+    let title = front_matter.get("title").and_then(text_of)?;
+    let mut html = format!(r#"<header class="title-block"><div class="title-block-title">{}</div>"#, escape_html(&title));
+    for key in ["author", "date"] {
+        if let Some(text) = front_matter.get(key).and_then(text_of) {
+            html.push_str(&format!(r#"<div class="title-block-{}">{}</div>"#, key, escape_html(&text)));
+        }
+    }
+    html.push_str("</header>");
+    Some(html)
+}
+
+/// 在正文开头插入摘要与关键词；`has_cover` 为真时标题已在封面上，不再生成标题区
+pub fn insert_abstract(html: &str, front_matter: Option<&Value>, has_cover: bool) -> String {
+    let Some(front_matter) = front_matter else {
+        return html.to_string();
+    };
+    let Some(block) = abstract_html(front_matter) else {
+        return html.to_string();
+    };
+
+    let re_leading_h1 = Regex::new(r"(?s)^\s*<h1\b.*?</h1>").unwrap();
+    if let Some(m) = re_leading_h1.find(html) {
+        return format!("{}\n{}{}", &html[..m.end()], block, &html[m.end()..]);
+    }
+    let title = if has_cover { None } else { title_block(front_matter) };
+    format!("{}{}\n{}", title.unwrap_or_default(), block, html)
+}
@@ -0,0 +1,418 @@
+//! 本地资源服务器：只监听 `127.0.0.1` 的随机端口，地址中带随机令牌，
+//! 为预览与导出页面提供同一套地址，避免 `file://` 在 Windows 上的路径问题与字体的跨域限制。
+//!
+//! 地址格式（`<token>` 不匹配时返回 404）：
+//!  - `/<token>/katex/...`：随应用分发的 KaTeX 样式与字体
+//!  - `/<token>/highlight/<主题>.css`：代码块配色主题（见 highlight 模块）
+//!  - `/<token>/fs/<绝对路径>`：本地文件（文档中的图片等）；Windows 上为 `/fs/C:/...`
+//!  - 导出页面本身登记在文档所在目录下（`/<token>/fs/<目录>/.md2pdf-page-N.html`），
+//!    只存在于内存中，文档中的相对路径按该目录解析
+//!
+//! 文档中的原始 HTML 可以在页面里发起请求，所以 `fs/` 只提供已登记目录（当前打开的文档目录、
+//! 登记页面的目录）之下的文件，切换文档或注销页面时撤销对应目录：规范化（解析符号链接）后
+//! 不在这些目录中的路径返回 404，指向文档目录之外的 `../` 图片因此无法加载。
+//! 响应不带 CORS 头，其他来源的页面读不到内容。
+//!
+//! 启动失败时（端口不可用等）导出退回到 `file://` 加载。
+
+use crate::checker::percent_decode;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// 读取请求头的超时时间
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+static SERVER: OnceLock<AssetServer> = OnceLock::new();
+static NEXT_PAGE_ID: AtomicU64 = AtomicU64::new(1);
+/// 预览中当前文档目录的授权，切换文档时替换
+static PREVIEW_GRANT: Mutex<Option<DirGrant>> = Mutex::new(None);
+
+pub struct AssetServer {
+    port: u16,
+    token: String,
+    /// 随应用分发的 `public` 目录（KaTeX、MathJax）
+    public_dir: Option<PathBuf>,
+    /// 登记的页面：地址路径（不含令牌）→ HTML
+    pages: Mutex<HashMap<String, Arc<String>>>,
+    /// 允许访问的目录（已规范化），每登记一次记一项
+    roots: Mutex<Vec<PathBuf>>,
+}
+
+/// 对一个目录的访问授权，释放时撤销
+pub struct DirGrant {
+    root: PathBuf,
+}
+
+impl Drop for DirGrant {
+    fn drop(&mut self) {
+        if let Some(server) = SERVER.get() {
+            let mut roots = server.roots.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(index) = roots.iter().position(|root| *root == self.root) {
+                roots.remove(index);
+            }
+        }
+    }
+}
+
+/// 登记在服务器上的页面，释放时注销页面及其目录
+pub struct ServedPage {
+    key: String,
+    _grant: DirGrant,
+    pub url: String,
+}
+
+impl Drop for ServedPage {
+    fn drop(&mut self) {
+        if let Some(server) = SERVER.get() {
+            server.pages.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.key);
+        }
+    }
+}
+
+/// 随机令牌（来自操作系统的随机数，128 位）
+fn random_token() -> Result<String, getrandom::Error> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 地址路径中的一段：非保留字符原样保留，其余按 UTF-8 字节转义
+fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// 本地路径对应的地址路径（不含令牌，以 `fs/` 开头）
+fn fs_key(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    // Windows 上规范化后的路径带有 `\\?\` 前缀
+    let path = path.strip_prefix("//?/").unwrap_or(&path);
+    let segments: Vec<String> = path.split('/').filter(|s| !s.is_empty()).map(encode_segment).collect();
+    format!("fs/{}", segments.join("/"))
+}
+
+/// `fs/` 之后的地址路径还原为本地路径；含 `..` 时拒绝
+fn fs_path(rest: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(rest);
+    let path = if cfg!(windows) { PathBuf::from(&decoded) } else { PathBuf::from(format!("/{}", decoded)) };
+    path.components().all(|c| !matches!(c, Component::ParentDir)).then_some(path)
+}
+
+/// 规范化后位于某个允许目录之下的文件
+fn contained_file(path: &Path, roots: &[PathBuf]) -> Option<PathBuf> {
+    let path = std::fs::canonicalize(path).ok()?;
+    (path.is_file() && roots.iter().any(|root| path.starts_with(root))).then_some(path)
+}
+
+fn canonical_dir(dir: &Path) -> PathBuf {
+    std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
+
+fn content_type(path: &str) -> &'static str {
+    let extension = path.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8], head_only: bool) {
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    let _ = stream.write_all(header.as_bytes());
+    if !head_only {
+        let _ = stream.write_all(body);
+    }
+    let _ = stream.flush();
+}
+
+impl AssetServer {
+    pub fn origin(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("{}/{}/{}", self.origin(), self.token, key)
+    }
+
+    /// 本地目录对应的地址（以 `/` 结尾），页面中的相对路径按此解析
+    pub fn dir_url(&self, dir: &Path) -> String {
+        format!("{}/", self.url(&fs_key(dir)))
+    }
+
+    /// KaTeX 资源的地址；应用未分发 KaTeX 时为空
+    pub fn katex_url(&self, file: &str) -> Option<String> {
+        self.vendor_url("katex", file)
+    }
+
+    /// MathJax 脚本的地址；应用未分发 MathJax 时为空
+    pub fn mathjax_url(&self, file: &str) -> Option<String> {
+        self.vendor_url("mathjax", file)
+    }
+
+    fn vendor_url(&self, library: &str, file: &str) -> Option<String> {
+        let dir = self.public_dir.as_ref()?.join(library);
+        dir.join(file).is_file().then(|| self.url(&format!("{}/{}", library, file)))
+    }
+
+    /// 代码块配色主题的地址；未知主题为空
+    pub fn highlight_url(&self, theme: &str) -> Option<String> {
+        crate::highlight::theme_css(theme).map(|_| self.url(&format!("highlight/{}.css", encode_segment(theme))))
+    }
+
+    /// 允许访问 `dir` 之下的文件，直到返回的授权被释放
+    fn allow_dir(&self, dir: &Path) -> DirGrant {
+        let root = canonical_dir(dir);
+        self.roots.lock().unwrap_or_else(|e| e.into_inner()).push(root.clone());
+        DirGrant { root }
+    }
+
+    /// 登记一个页面，地址位于 `base_dir` 下；页面注销前 `base_dir` 之下的文件可以访问
+    pub fn serve_page(&self, html: String, base_dir: &Path) -> ServedPage {
+        let grant = self.allow_dir(base_dir);
+        let id = NEXT_PAGE_ID.fetch_add(1, Ordering::Relaxed);
+        let key = format!("{}/.md2pdf-page-{}.html", fs_key(&grant.root), id);
+        self.pages.lock().unwrap_or_else(|e| e.into_inner()).insert(key.clone(), Arc::new(html));
+        ServedPage { url: self.url(&key), key, _grant: grant }
+    }
+
+    fn handle(&self, mut stream: TcpStream) {
+        let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+        let mut reader = BufReader::new(match stream.try_clone() {
+            Ok(s) => s,
+            Err(_) => return,
+        });
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).is_err() {
+            return;
+        }
+        // 读完请求头
+        let mut line = String::new();
+        while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+            line.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or("");
+        let target = parts.next().unwrap_or("");
+        let head_only = method == "HEAD";
+        if method != "GET" && !head_only {
+            respond(&mut stream, "405 Method Not Allowed", "text/plain", b"", head_only);
+            return;
+        }
+        let path = target.split(['?', '#']).next().unwrap_or("");
+        let Some(key) = path.strip_prefix(&format!("/{}/", self.token)) else {
+            respond(&mut stream, "404 Not Found", "text/plain", b"", head_only);
+            return;
+        };
+
+        let page = self.pages.lock().unwrap_or_else(|e| e.into_inner()).get(key).cloned();
+        if let Some(html) = page {
+            respond(&mut stream, "200 OK", content_type("page.html"), html.as_bytes(), head_only);
+            return;
+        }
+        if let Some(css) = key
+            .strip_prefix("highlight/")
+            .and_then(|rest| rest.strip_suffix(".css"))
+            .and_then(|theme| crate::highlight::theme_css(&percent_decode(theme)))
+        {
+            respond(&mut stream, "200 OK", content_type(key), css.as_bytes(), head_only);
+            return;
+        }
+        let file = if key.starts_with("katex/") || key.starts_with("mathjax/") {
+            let relative = PathBuf::from(percent_decode(key));
+            let safe = relative.components().all(|c| matches!(c, Component::Normal(_)));
+            self.public_dir.as_ref().filter(|_| safe).map(|dir| dir.join(relative)).filter(|f| f.is_file())
+        } else {
+            let roots = self.roots.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            key.strip_prefix("fs/").and_then(fs_path).and_then(|path| contained_file(&path, &roots))
+        };
+        match file.and_then(|f| std::fs::read(&f).ok()) {
+            Some(body) => respond(&mut stream, "200 OK", content_type(key), &body, head_only),
+            None => respond(&mut stream, "404 Not Found", "text/plain", b"", head_only),
+        }
+    }
+}
+
+/// 启动服务器（只启动一次）；`public_dir` 为随应用分发的 `public` 目录，提供其中的 `katex/` 与 `mathjax/`
+pub fn start(public_dir: Option<PathBuf>) {
+    if SERVER.get().is_some() {
+        return;
+    }
+    let listener = match TcpListener::bind("127.0.0.1:0") {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::warn!(error = %e, "本地资源服务器启动失败，导出将使用 file:// 加载");
+            return;
+        }
+    };
+    let token = match random_token() {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::warn!(error = %e, "无法生成资源服务器令牌，导出将使用 file:// 加载");
+            return;
+        }
+    };
+    let port = listener.local_addr().map(|addr| addr.port()).unwrap_or(0);
+    let server = AssetServer {
+        port,
+        token,
+        public_dir,
+        pages: Mutex::new(HashMap::new()),
+        roots: Mutex::new(Vec::new()),
+    };
+    if SERVER.set(server).is_err() {
+        return;
+    }
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            std::thread::spawn(move || {
+                if let Some(server) = SERVER.get() {
+                    server.handle(stream);
+                }
+            });
+        }
+    });
+    tracing::info!(port, "本地资源服务器已启动");
+}
+
+/// 正在运行的服务器
+pub fn get() -> Option<&'static AssetServer> {
+    SERVER.get()
+}
+
+/// 地址来源是否为本地资源服务器（不算外部资源）
+pub fn is_local_origin(origin: &str) -> bool {
+    get().is_some_and(|server| server.origin() == origin)
+}
+
+/// 文档目录在资源服务器上的地址，预览中的相对图片路径按此解析；
+/// 同时允许访问该目录，并撤销上一个文档目录的授权。服务器未启动或文档未保存时为空
+#[tauri::command]
+pub fn get_asset_base_url(path: Option<String>) -> Option<String> {
+    let server = get();
+    let dir = path.as_deref().and_then(|path| Path::new(path).parent());
+    let grant = dir.zip(server).map(|(dir, server)| server.allow_dir(dir));
+    let url = server.zip(grant.as_ref()).map(|(server, grant)| server.dir_url(&grant.root));
+    *PREVIEW_GRANT.lock().unwrap_or_else(|e| e.into_inner()) = grant;
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fs_key_round_trips_through_fs_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = canonical_dir(dir.path()).join("图片 1.png");
+        let key = fs_key(&path);
+        assert!(key.starts_with("fs/"));
+        assert!(!key.contains(' '));
+        assert_eq!(fs_path(key.strip_prefix("fs/").unwrap()), Some(path));
+    }
+
+    #[test]
+    fn fs_path_rejects_parent_components() {
+        assert_eq!(fs_path("home/user/../../etc/passwd"), None);
+        assert_eq!(fs_path("home/user/%2E%2E/secret"), None);
+    }
+
+    #[test]
+    fn only_files_under_roots_are_served() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let inside_file = root.path().join("a.png");
+        let outside_file = outside.path().join("id_rsa");
+        std::fs::write(&inside_file, b"png").unwrap();
+        std::fs::write(&outside_file, b"key").unwrap();
+        let roots = vec![canonical_dir(root.path())];
+
+        assert!(contained_file(&inside_file, &roots).is_some());
+        assert!(contained_file(&outside_file, &roots).is_none());
+        assert!(contained_file(&root.path().join("missing.png"), &roots).is_none());
+        assert!(contained_file(root.path(), &roots).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_a_root_are_not_served() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let target = outside.path().join("secret.txt");
+        std::fs::write(&target, b"secret").unwrap();
+        let link = root.path().join("link.txt");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        assert!(contained_file(&link, &[canonical_dir(root.path())]).is_none());
+    }
+
+    #[test]
+    fn directory_grants_are_revoked() {
+        start(None);
+        let server = get().unwrap();
+        let roots = || server.roots.lock().unwrap().clone();
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let (first_dir, second_dir) = (canonical_dir(first.path()), canonical_dir(second.path()));
+        let doc = |dir: &Path| Some(dir.join("doc.md").to_string_lossy().to_string());
+
+        // 切换文档时撤销上一个目录
+        assert!(get_asset_base_url(doc(first.path())).is_some());
+        assert!(roots().contains(&first_dir));
+        get_asset_base_url(doc(second.path()));
+        assert!(!roots().contains(&first_dir) && roots().contains(&second_dir));
+        assert_eq!(get_asset_base_url(None), None);
+        assert!(!roots().contains(&second_dir));
+
+        // 页面注销时撤销它的目录
+        let page = server.serve_page("<p>page</p>".to_string(), first.path());
+        assert!(roots().contains(&first_dir));
+        drop(page);
+        assert!(!roots().contains(&first_dir));
+    }
+
+    #[test]
+    fn tokens_are_random_hex() {
+        let a = random_token().unwrap();
+        let b = random_token().unwrap();
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn unknown_highlight_themes_have_no_stylesheet() {
+        assert!(crate::highlight::theme_css("github").is_some());
+        assert!(crate::highlight::theme_css("../../etc/passwd").is_none());
+    }
+}
@@ -0,0 +1,456 @@
+//! 文档引用的资源：
+//!  - 资源清单：列出图片、样式表与字体（本地路径、大小，或远程地址），供界面展示依赖与导出复用
+//!  - 图片整理：收集文档引用的所有图片（本地与远程），复制到同一个 `assets/` 目录并改写链接，
+//!    生成可以整体拷贝、离线打开的文档文件夹
+
+use crate::checker::{is_external_url, percent_decode};
+use crate::error::AppError;
+use comrak::nodes::NodeValue;
+use comrak::{parse_document, Arena};
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 整理后的图片目录名
+pub const ASSETS_DIR: &str = "assets";
+/// 远程图片的大小上限
+const MAX_REMOTE_BYTES: u64 = 50 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PackagedAsset {
+    /// 文档中原来的链接
+    pub source: String,
+    /// 改写后的相对路径
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetFailure {
+    pub source: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageReport {
+    pub markdown_path: String,
+    pub assets: Vec<PackagedAsset>,
+    pub failures: Vec<AssetFailure>,
+}
+
+/// 图片链接的来源
+pub enum AssetSource {
+    Local(PathBuf),
+    Remote(String),
+    /// data URI 等无需处理的链接
+    Inline,
+}
+
+/// 解析图片链接：远程地址、相对于文档目录的本地路径（支持 `file://`），或内联数据
+pub fn resolve_asset(url: &str, base_dir: &Path) -> AssetSource {
+    let lower = url.to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        return AssetSource::Remote(url.to_string());
+    }
+    if lower.starts_with("//") {
+        return AssetSource::Remote(format!("https:{}", url));
+    }
+    let path_part = if lower.starts_with("file://") {
+        let rest = &url["file://".len()..];
+        // file:///C:/... → C:/...
+        if rest.len() > 2 && rest.as_bytes()[2] == b':' { &rest[1..] } else { rest }
+    } else if is_external_url(url) && !is_windows_drive(url) {
+        return AssetSource::Inline;
+    } else {
+        url
+    };
+    let path_part = path_part.split(['?', '#']).next().unwrap_or("");
+    let decoded = percent_decode(path_part);
+    let path = Path::new(&decoded);
+    AssetSource::Local(if path.is_absolute() { path.to_path_buf() } else { base_dir.join(path) })
+}
+
+/// `C:/...` 形式的 Windows 绝对路径会被误判为带协议的链接
+fn is_windows_drive(url: &str) -> bool {
+    let bytes = url.as_bytes();
+    bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'/' | b'\\')
+}
+
+/// 代码块所在的行（1-indexed），这些行中的内容不是图片链接
+pub fn code_block_lines(markdown: &str) -> HashSet<usize> {
+    let arena = Arena::new();
+    let options = crate::get_comrak_options();
+    let root = parse_document(&arena, markdown, &options);
+    root.descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::CodeBlock(_)))
+        .flat_map(|node| {
+            let pos = node.data.borrow().sourcepos;
+            pos.start.line..=pos.end.line
+        })
+        .collect()
+}
+
+/// 图片链接的匹配规则：`![alt](url "title")`、`<img src="url">`，以及被图片引用的链接定义 `[id]: url`
+struct ImagePatterns {
+    inline: Regex,
+    html: Regex,
+    definition: Regex,
+    reference: Regex,
+}
+
+impl ImagePatterns {
+    fn new() -> Self {
+        ImagePatterns {
+            inline: Regex::new(r#"(!\[(?:[^\]\\]|\\.)*\]\(\s*)(<[^>\n]*>|[^\s)]+)"#).unwrap(),
+            html: Regex::new(r#"(?i)(<img\b[^>]*?\bsrc\s*=\s*)("[^"]*"|'[^']*')"#).unwrap(),
+            definition: Regex::new(r"^(\s{0,3}\[([^\]]+)\]:\s*)(<[^>\n]*>|\S+)").unwrap(),
+            reference: Regex::new(r"!\[(?:[^\]\\]|\\.)*\]\[([^\]]*)\]").unwrap(),
+        }
+    }
+}
+
+fn strip_delimiters(raw: &str) -> &str {
+    raw.trim_start_matches(['<', '"', '\'']).trim_end_matches(['>', '"', '\''])
+}
+
+/// 文档中所有图片链接（按出现顺序去重）
+pub fn collect_image_urls(markdown: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    rewrite_image_urls(markdown, |url| {
+        if !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+        None
+    });
+    urls
+}
+
+/// 对文档中的每个图片链接调用 `f`，返回 `Some(新链接)` 时替换；代码块中的内容保持不变
+pub fn rewrite_image_urls(markdown: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    let patterns = ImagePatterns::new();
+    let code_lines = code_block_lines(markdown);
+
+    // 被 `![alt][id]` 引用的链接定义
+    let referenced: HashSet<String> = markdown
+        .lines()
+        .enumerate()
+        .filter(|(idx, _)| !code_lines.contains(&(idx + 1)))
+        .flat_map(|(_, line)| patterns.reference.captures_iter(line).map(|c| c[1].to_lowercase()).collect::<Vec<_>>())
+        .collect();
+
+    let mut out = String::with_capacity(markdown.len());
+    for (idx, line) in markdown.split_inclusive('\n').enumerate() {
+        if code_lines.contains(&(idx + 1)) {
+            out.push_str(line);
+            continue;
+        }
+        let mut replace = |prefix: &str, raw: &str| -> String {
+            let url = strip_delimiters(raw);
+            match f(url) {
+                Some(new_url) => format!("{}{}", prefix, raw.replacen(url, &new_url, 1)),
+                None => format!("{}{}", prefix, raw),
+            }
+        };
+        let line = patterns.inline.replace_all(line, |c: &Captures| replace(&c[1], &c[2])).to_string();
+        let line = patterns.html.replace_all(&line, |c: &Captures| replace(&c[1], &c[2])).to_string();
+        let line = patterns
+            .definition
+            .replace(&line, |c: &Captures| {
+                if referenced.contains(&c[2].to_lowercase()) {
+                    replace(&c[1], &c[3])
+                } else {
+                    c[0].to_string()
+                }
+            })
+            .to_string();
+        out.push_str(&line);
+    }
+    out
+}
+
+/// 按扩展名或 Content-Type 推断图片扩展名
+fn image_extension(url: &str, content_type: Option<&str>) -> String {
+    let from_url = Path::new(url.split(['?', '#']).next().unwrap_or(""))
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .filter(|e| matches!(e.as_str(), "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "bmp" | "ico" | "avif"));
+    from_url
+        .or_else(|| {
+            let mime = content_type?.split(';').next()?.trim().to_ascii_lowercase();
+            Some(match mime.as_str() {
+                "image/jpeg" => "jpg".to_string(),
+                "image/svg+xml" => "svg".to_string(),
+                other => other.strip_prefix("image/")?.to_string(),
+            })
+        })
+        .unwrap_or_else(|| "img".to_string())
+}
+
+fn download(url: &str) -> Result<(Vec<u8>, Option<String>), String> {
+    let mut response = ureq::get(url).call().map_err(|e| e.to_string())?;
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let data = response
+        .body_mut()
+        .with_config()
+        .limit(MAX_REMOTE_BYTES)
+        .read_to_vec()
+        .map_err(|e| e.to_string())?;
+    Ok((data, content_type))
+}
+
+/// 读取图片内容（本地文件或下载），返回数据与扩展名
+pub fn fetch_asset(url: &str, base_dir: &Path) -> Result<Option<(Vec<u8>, String)>, String> {
+    match resolve_asset(url, base_dir) {
+        AssetSource::Inline => Ok(None),
+        AssetSource::Local(path) => {
+            let data = fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Ok(Some((data, image_extension(&path.to_string_lossy(), None))))
+        }
+        AssetSource::Remote(remote) => {
+            let (data, content_type) = download(&remote)?;
+            Ok(Some((data, image_extension(&remote, content_type.as_deref()))))
+        }
+    }
+}
+
+/// 把资源以内容哈希命名写入 `doc_dir` 下的 `assets/`（相同内容只写一次），返回相对于 `doc_dir` 的路径
+pub fn write_hashed_asset(doc_dir: &Path, data: &[u8], extension: &str) -> Result<String, AppError> {
+    let hash = crate::hashing::sha256_hex(data);
+    let file_name = format!("{}.{}", &hash[..16], extension);
+    let assets_dir = doc_dir.join(ASSETS_DIR);
+    let target = assets_dir.join(&file_name);
+    if !target.exists() {
+        fs::create_dir_all(&assets_dir).map_err(|e| AppError::file(&assets_dir, e))?;
+        fs::write(&target, data).map_err(|e| AppError::file(&target, e))?;
+    }
+    Ok(format!("{}/{}", ASSETS_DIR, file_name))
+}
+
+/// 把文档与其引用的图片整理到 `output_dir`：图片以内容哈希命名放入 `assets/`，文档中的链接改写为相对路径
+pub fn package(markdown: &str, doc_path: &Path, output_dir: &Path) -> Result<PackageReport, AppError> {
+    let base_dir = doc_path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(output_dir).map_err(|e| AppError::file(output_dir, e))?;
+
+    let mut assets = Vec::new();
+    let mut failures = Vec::new();
+    let mut rewritten: HashMap<String, String> = HashMap::new();
+    for url in collect_image_urls(markdown) {
+        match fetch_asset(&url, base_dir) {
+            Ok(Some((data, extension))) => {
+                let relative = write_hashed_asset(output_dir, &data, &extension)?;
+                assets.push(PackagedAsset { source: url.clone(), target: relative.clone() });
+                rewritten.insert(url, relative);
+            }
+            Ok(None) => {}
+            Err(reason) => {
+                tracing::warn!(url = %url, "图片整理失败: {}", reason);
+                failures.push(AssetFailure { source: url, reason });
+            }
+        }
+    }
+
+    let content = rewrite_image_urls(markdown, |url| rewritten.get(url).cloned());
+    let file_name = doc_path.file_name().map(|n| n.to_os_string()).unwrap_or_else(|| "document.md".into());
+    let markdown_path = output_dir.join(file_name);
+    fs::write(&markdown_path, content).map_err(|e| AppError::file(&markdown_path, e))?;
+
+    Ok(PackageReport {
+        markdown_path: markdown_path.to_string_lossy().to_string(),
+        assets,
+        failures,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetKind {
+    Image,
+    Stylesheet,
+    Font,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetReference {
+    pub kind: AssetKind,
+    /// 文档中原来的链接
+    pub source: String,
+    pub remote: bool,
+    /// 本地资源的绝对路径（文档未保存且链接为相对路径时无法解析）
+    pub path: Option<String>,
+    /// 远程资源的完整地址
+    pub url: Option<String>,
+    pub exists: bool,
+    /// 本地文件大小（字节）
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentAnalysis {
+    pub assets: Vec<AssetReference>,
+    /// 本地资源的总大小（字节）
+    pub local_size: u64,
+    pub remote_count: usize,
+    pub missing_count: usize,
+}
+
+const FONT_EXTENSIONS: [&str; 5] = ["woff2", "woff", "ttf", "otf", "eot"];
+
+/// 内嵌 HTML 中引用的样式表与字体：`<link rel="stylesheet">`、`<style>` 中的 `@import` 与 `url(...)`
+fn collect_style_urls(markdown: &str) -> Vec<(AssetKind, String)> {
+    let code_lines = code_block_lines(markdown);
+    let text: String = markdown
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(idx, line)| if code_lines.contains(&(idx + 1)) { "\n" } else { line })
+        .collect();
+
+    let re_link = Regex::new(r"(?i)<link\b[^>]*>").unwrap();
+    let re_rel = Regex::new(r#"(?i)\brel\s*=\s*["']?stylesheet\b"#).unwrap();
+    let re_href = Regex::new(r#"(?i)\bhref\s*=\s*("[^"]*"|'[^']*'|[^\s>]+)"#).unwrap();
+    let re_style = Regex::new(r"(?is)<style\b[^>]*>(.*?)</style>").unwrap();
+    let re_import = Regex::new(r#"@import\s+(?:url\(\s*)?["']?([^"')\s;]+)"#).unwrap();
+    let re_url = Regex::new(r#"url\(\s*["']?([^"')]+?)["']?\s*\)"#).unwrap();
+
+    let mut urls = Vec::new();
+    for link in re_link.find_iter(&text) {
+        if re_rel.is_match(link.as_str()) {
+            if let Some(caps) = re_href.captures(link.as_str()) {
+                urls.push((AssetKind::Stylesheet, strip_delimiters(&caps[1]).to_string()));
+            }
+        }
+    }
+    for style in re_style.captures_iter(&text) {
+        let css = &style[1];
+        let imports: Vec<String> = re_import.captures_iter(css).map(|caps| caps[1].to_string()).collect();
+        for caps in re_url.captures_iter(css) {
+            let url = caps[1].trim().to_string();
+            if imports.contains(&url) {
+                continue;
+            }
+            let extension = Path::new(url.split(['?', '#']).next().unwrap_or(""))
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_ascii_lowercase())
+                .unwrap_or_default();
+            let kind = if FONT_EXTENSIONS.contains(&extension.as_str()) { AssetKind::Font } else { AssetKind::Image };
+            urls.push((kind, url));
+        }
+        urls.extend(imports.into_iter().map(|url| (AssetKind::Stylesheet, url)));
+    }
+    urls
+}
+
+/// 列出文档引用的全部资源（按出现顺序去重）；`base_dir` 为文档所在目录，未保存的文档为 `None`
+pub fn analyze(markdown: &str, base_dir: Option<&Path>) -> DocumentAnalysis {
+    let mut urls: Vec<(AssetKind, String)> =
+        collect_image_urls(markdown).into_iter().map(|url| (AssetKind::Image, url)).collect();
+    if let Some(logo) = crate::front_matter::parse(markdown).as_ref().and_then(crate::cover::logo) {
+        urls.push((AssetKind::Image, logo));
+    }
+    urls.extend(collect_style_urls(markdown));
+
+    let mut seen = HashSet::new();
+    let mut assets = Vec::new();
+    for (kind, source) in urls {
+        if source.is_empty() || !seen.insert((kind, source.clone())) {
+            continue;
+        }
+        match resolve_asset(&source, base_dir.unwrap_or(Path::new(""))) {
+            AssetSource::Inline => {}
+            AssetSource::Remote(url) => assets.push(AssetReference {
+                kind,
+                source,
+                remote: true,
+                path: None,
+                url: Some(url),
+                exists: true,
+                size: None,
+            }),
+            // 未保存的文档无法解析相对路径
+            AssetSource::Local(path) if !path.is_absolute() => assets.push(AssetReference {
+                kind,
+                source,
+                remote: false,
+                path: None,
+                url: None,
+                exists: false,
+                size: None,
+            }),
+            AssetSource::Local(path) => {
+                let size = fs::metadata(&path).ok().filter(|m| m.is_file()).map(|m| m.len());
+                assets.push(AssetReference {
+                    kind,
+                    source,
+                    remote: false,
+                    path: Some(path.to_string_lossy().to_string()),
+                    url: None,
+                    exists: size.is_some(),
+                    size,
+                });
+            }
+        }
+    }
+
+    DocumentAnalysis {
+        local_size: assets.iter().filter_map(|a| a.size).sum(),
+        remote_count: assets.iter().filter(|a| a.remote).count(),
+        missing_count: assets.iter().filter(|a| !a.exists).count(),
+        assets,
+    }
+}
+
+/// 列出文档引用的图片、样式表与字体，以及本地文件的绝对路径与大小。
+/// `content` 为编辑器中尚未保存的内容，未提供时读取 `path`；`path` 为空表示文档尚未保存。
+#[tauri::command]
+pub fn analyze_document(path: Option<String>, content: Option<String>) -> Result<DocumentAnalysis, AppError> {
+    let markdown = match (content, path.as_deref()) {
+        (Some(content), _) => content,
+        (None, Some(path)) => fs::read_to_string(path).map_err(|e| AppError::file(path, e))?,
+        (None, None) => String::new(),
+    };
+    let base_dir = path.as_deref().map(Path::new).and_then(Path::parent);
+    crate::error::catch_panic("analyze_document", || Ok(analyze(&markdown, base_dir)))
+}
+
+/// 生成可移植的文档文件夹：复制（或下载）所有图片到 `output_dir/assets/` 并改写链接。
+/// `content` 为编辑器中尚未保存的内容，未提供时读取 `doc_path`。
+#[tauri::command]
+pub async fn package_document(
+    doc_path: String,
+    output_dir: String,
+    content: Option<String>,
+) -> Result<PackageReport, AppError> {
+    tokio::task::spawn_blocking(move || {
+        crate::error::catch_panic("package_document", || {
+            let markdown = match content {
+                Some(content) => content,
+                None => fs::read_to_string(&doc_path).map_err(|e| AppError::file(&doc_path, e))?,
+            };
+            package(&markdown, Path::new(&doc_path), Path::new(&output_dir))
+        })
+    })
+    .await
+    .map_err(|e| crate::error::join_error("package_document", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashed_assets_are_written_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_hashed_asset(dir.path(), b"image", "png").unwrap();
+        let second = write_hashed_asset(dir.path(), b"image", "png").unwrap();
+        assert_eq!(first, second);
+        assert!(first.starts_with("assets/") && first.ends_with(".png"));
+        assert_eq!(fs::read(dir.path().join(&first)).unwrap(), b"image");
+        assert_ne!(write_hashed_asset(dir.path(), b"other", "png").unwrap(), first);
+    }
+}
@@ -0,0 +1,74 @@
+//! 状态徽章与进度条：用于项目状态报告等文档。
+//!
+//!  - `[badge: 进行中|orange]`：行内徽章，颜色可省略（灰色），可用预设颜色名或 `#rrggbb`
+//!  - `[progress: 70%]`：进度条，百分号可省略，超出 0–100 时截断；`[progress: 70%|green]` 指定颜色
+//!
+//! 预设颜色：`gray`、`blue`、`green`、`yellow`、`orange`、`red`、`purple`。
+//! 表格单元格中的 `|` 需写成 `\|`。属于扩展语法，代码与公式中的内容保持不变。
+
+use crate::html_util::safe_color;
+use regex::{Captures, Regex};
+
+/// 预设颜色名对应的色值，其他合法颜色值原样使用
+fn palette(name: &str) -> Option<&str> {
+    let color = match name.trim().to_ascii_lowercase().as_str() {
+        "gray" | "grey" => "#6e7781",
+        "blue" => "#0969da",
+        "green" => "#1a7f37",
+        "yellow" => "#bf8700",
+        "orange" => "#d9730d",
+        "red" => "#cf222e",
+        "purple" => "#8250df",
+        _ => return safe_color(name),
+    };
+    Some(color)
+}
+
+/// `label` 取自 HTML 文本，已经转义
+fn badge_html(label: &str, color: Option<&str>) -> String {
+    let color = color.and_then(palette).unwrap_or("#6e7781");
+    format!(r#"<span class="badge" style="--badge-color: {}">{}</span>"#, color, label.trim())
+}
+
+fn progress_html(value: &str, color: Option<&str>) -> Option<String> {
+    let percent: f64 = value.trim().trim_end_matches('%').trim().parse().ok()?;
+    let percent = percent.clamp(0.0, 100.0);
+    let color = color.and_then(palette).unwrap_or("#0969da");
+    Some(format!(
+        r#"<span class="progress" role="progressbar" aria-valuemin="0" aria-valuemax="100" aria-valuenow="{value}" style="--progress-color: {color}"><span class="progress-track"><span class="progress-fill" style="width: {value}%"></span></span><span class="progress-label">{value}%</span></span>"#,
+        value = percent,
+        color = color
+    ))
+}
+
+/// 把 HTML 正文中的徽章与进度条标记转换为对应元素
+pub fn apply_badges(html: &str) -> String {
+    if !html.contains("[badge:") && !html.contains("[progress:") {
+        return html.to_string();
+    }
+    let re_protected =
+        Regex::new(r"(?s)<pre\b.*?</pre>|<code\b.*?</code>|<math\b.*?</math>|<script\b.*?</script>|<style\b.*?</style>|<[^>]*>")
+            .unwrap();
+    let re_marker = Regex::new(r"\[(badge|progress):\s*([^\]|\n]+?)\s*(?:\|\s*([^\]|\n]+?)\s*)?\]").unwrap();
+    let replace = |text: &str| {
+        re_marker
+            .replace_all(text, |caps: &Captures| {
+                let color = caps.get(3).map(|m| m.as_str());
+                match &caps[1] {
+                    "badge" => badge_html(&caps[2], color),
+                    _ => progress_html(&caps[2], color).unwrap_or_else(|| caps[0].to_string()),
+                }
+            })
+            .into_owned()
+    };
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(html) {
+        out.push_str(&replace(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&replace(&html[last..]));
+    out
+}
@@ -0,0 +1,163 @@
+//! 块解析器的属性测试：用随机拼接的病态 Markdown 检查 `split_markdown_blocks`
+//! 与 `split_lines_with_math` 的行号计算。
+//!
+//! 不变量：
+//!  - 不会 panic
+//!  - 块按行号递增且互不重叠，每个非空行恰好属于一个块
+//!  - 块 id 唯一，且在文档开头插入段落后其余块的 id 不变
+//!  - `split_lines_with_math` 返回的区间连续覆盖请求的行范围
+//!
+//! 运行更多用例：`PROPTEST_CASES=20000 cargo test block_fuzz`
+
+use crate::parser_mode::ParserMode;
+use crate::{split_lines_with_math, split_markdown_blocks};
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+/// 容易触发边界情况的行片段
+const FRAGMENTS: &[&str] = &[
+    "",
+    "   ",
+    "\t",
+    "text",
+    "中文段落",
+    "# heading",
+    "Setext",
+    "===",
+    "---",
+    "***",
+    "$$",
+    "$$x+y$$",
+    "$$ a",
+    "b $$",
+    "$",
+    "```",
+    "```rust",
+    "~~~",
+    "    indented code",
+    "<table>",
+    "<table border=\"1\">",
+    "</table>",
+    "<tr><td>1</td></tr>",
+    "<div>",
+    "</div>",
+    "<!-- comment -->",
+    "- item",
+    "  - nested",
+    "1. one",
+    "10) ten",
+    "- [ ] task",
+    "> quote",
+    "> $$",
+    "| a | b |",
+    "|---|---|",
+    "| 1 | 2 |",
+    "[^1]: footnote",
+    "[link]: http://example.com",
+    "![img](a.png)",
+    "\\$\\$",
+    "$$\\begin{aligned}",
+    "\\end{aligned}$$",
+];
+
+fn markdown_strategy() -> impl Strategy<Value = String> {
+    let line = prop_oneof![
+        4 => proptest::sample::select(FRAGMENTS).prop_map(str::to_string),
+        1 => "[ -~\u{4e00}-\u{4e10}$`<>|#*\\-]{0,12}",
+    ];
+    (proptest::collection::vec(line, 0..40), any::<bool>(), any::<bool>()).prop_map(|(lines, crlf, trailing)| {
+        let mut text = lines.join(if crlf { "\r\n" } else { "\n" });
+        if trailing {
+            text.push('\n');
+        }
+        text
+    })
+}
+
+fn check_blocks(markdown: &str, mode: ParserMode) -> Result<(), TestCaseError> {
+    let blocks = split_markdown_blocks(markdown, mode);
+    let normalized = markdown.replace("\r\n", "\n");
+    let lines: Vec<&str> = normalized.lines().collect();
+
+    let mut ids = HashSet::new();
+    let mut covered = vec![0usize; lines.len() + 1];
+    let mut previous_end = 0;
+    for block in &blocks {
+        prop_assert!(ids.insert(block.id.clone()), "重复的块 id: {}", block.id);
+        prop_assert!(block.start_line >= 1 && block.start_line <= block.end_line, "无效的行范围: {:?}", (block.start_line, block.end_line));
+        prop_assert!(block.end_line <= lines.len(), "行号越界: {} > {}", block.end_line, lines.len());
+        prop_assert!(block.start_line > previous_end, "块重叠或乱序: {} 起始于 {}，上一块结束于 {}", block.id, block.start_line, previous_end);
+        previous_end = block.end_line;
+        for count in &mut covered[block.start_line..=block.end_line] {
+            *count += 1;
+        }
+    }
+    for (idx, line) in lines.iter().enumerate() {
+        if !line.trim().is_empty() {
+            prop_assert_eq!(covered[idx + 1], 1, "第 {} 行 {:?} 未被唯一覆盖", idx + 1, line);
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn blocks_cover_every_line_once(markdown in markdown_strategy()) {
+        check_blocks(&markdown, ParserMode::Extended)?;
+        check_blocks(&markdown, ParserMode::Strict)?;
+    }
+
+    #[test]
+    fn block_ids_survive_insertion(markdown in markdown_strategy()) {
+        // 开头的 `---` / `+++` 可能被识别为 front matter，插入段落后不再成立
+        prop_assume!(!markdown.starts_with("---") && !markdown.starts_with("+++"));
+        let inserted = "插入的段落";
+        let before = split_markdown_blocks(&markdown, ParserMode::Extended);
+        let after = split_markdown_blocks(&format!("{}\n\n{}", inserted, markdown), ParserMode::Extended);
+        let ids = |blocks: &[crate::MarkdownBlock]| -> Vec<String> {
+            blocks.iter().filter(|block| block.content != inserted).map(|block| block.id.clone()).collect()
+        };
+        prop_assert_eq!(ids(&before), ids(&after));
+    }
+
+    #[test]
+    fn blocks_survive_arbitrary_text(markdown in "\\PC{0,200}") {
+        check_blocks(&markdown, ParserMode::Extended)?;
+    }
+
+    #[test]
+    fn math_split_is_contiguous(
+        lines in proptest::collection::vec(proptest::sample::select(FRAGMENTS), 0..30),
+        start in 1usize..35,
+        len in 0usize..35,
+    ) {
+        let end = start + len;
+        let ranges = split_lines_with_math(start, end, &lines);
+        let mut expected = start;
+        for &(s, e) in &ranges {
+            prop_assert_eq!(s, expected);
+            prop_assert!(e >= s && e <= end);
+            expected = e + 1;
+        }
+        prop_assert_eq!(expected, end + 1);
+    }
+}
+
+#[test]
+fn block_ids_are_stable_when_a_line_is_inserted_above() {
+    let markdown = "# 标题\n\n第一段\n\n$$\nx + y\n$$\n\n第一段\n";
+    let edited = "# 标题\n\n新插入的一行\n\n第一段\n\n$$\nx + y\n$$\n\n第一段\n";
+    let before = split_markdown_blocks(markdown, ParserMode::Extended);
+    let after = split_markdown_blocks(edited, ParserMode::Extended);
+    assert_eq!(after.len(), before.len() + 1);
+
+    // 同内容的两段由出现序号区分
+    assert_ne!(before[1].id, before[3].id);
+    assert_eq!(after[0].id, before[0].id);
+    for (old, new) in before[1..].iter().zip(&after[2..]) {
+        assert_eq!(new.id, old.id);
+        assert_eq!(new.start_line, old.start_line + 2);
+    }
+}
@@ -0,0 +1,322 @@
+//! Headless Chrome 相关：浏览器可执行文件探测、启动与进程生命周期。
+//!
+//! 每个启动的浏览器使用单独的用户数据目录（临时目录下的 `md2pdf-chrome-*`），进程号与目录
+//! 记录在 `md2pdf-browsers.json` 中：正常释放时结束进程并删除目录；导出被取消时立即结束对应的进程；
+//! 应用退出时结束所有进程；应用崩溃后，下次启动时结束残留的进程并删除记录中的目录。
+//! 结束进程前都核对进程的命令行中含有它的用户数据目录，进程号被其他程序（如用户自己的浏览器）
+//! 复用时不会误杀；记录之外的目录与仍在运行的进程的目录不会被删除。
+//! 空闲超时按各阶段的超时预算设置，卡住的浏览器不会一直存在。
+
+use crate::error::AppError;
+use crate::timeouts::StageTimeouts;
+use headless_chrome::{Browser, LaunchOptions};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// 用户数据目录名的前缀
+const PROFILE_PREFIX: &str = "md2pdf-chrome-";
+
+/// 本应用启动的、尚未结束的浏览器进程
+static LIVE: Mutex<Vec<BrowserProcess>> = Mutex::new(Vec::new());
+static NEXT_PROFILE: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BrowserProcess {
+    pid: u32,
+    profile_dir: PathBuf,
+    /// 正在使用该浏览器的导出任务
+    export_id: Option<String>,
+}
+
+/// 记录进程的文件（位于临时目录，应用崩溃后仍可读取）
+fn registry_path() -> PathBuf {
+    std::env::temp_dir().join("md2pdf-browsers.json")
+}
+
+fn save_registry(live: &[BrowserProcess]) {
+    let json = serde_json::to_string(live).unwrap_or_else(|_| "[]".to_string());
+    if let Err(e) = fs::write(registry_path(), json) {
+        tracing::warn!(error = %e, "无法记录浏览器进程");
+    }
+}
+
+/// 进程的命令行；进程不存在或无法查询时为空
+fn command_line(pid: u32) -> Option<String> {
+    let output = if cfg!(windows) {
+        let script = format!("(Get-CimInstance Win32_Process -Filter 'ProcessId = {}').CommandLine", pid);
+        Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", &script]).output()
+    } else {
+        Command::new("ps").args(["-p", &pid.to_string(), "-o", "command="]).output()
+    };
+    let output = output.ok().filter(|o| o.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// 进程是否仍在运行，并且是用 `profile_dir` 启动的浏览器（避免误杀复用了进程号的其他程序）
+fn is_our_browser(pid: u32, profile_dir: &Path) -> bool {
+    command_line(pid).is_some_and(|command| command.contains(&*profile_dir.to_string_lossy()))
+}
+
+/// 核对是本应用启动的浏览器后强制结束（Windows 上连同子进程），返回进程是否已不在运行
+fn kill_browser(process: &BrowserProcess) -> bool {
+    if !is_our_browser(process.pid, &process.profile_dir) {
+        return true;
+    }
+    kill_process(process.pid);
+    !is_our_browser(process.pid, &process.profile_dir)
+}
+
+/// 强制结束进程（Windows 上连同子进程）；调用前须用 `is_our_browser` 核对
+fn kill_process(pid: u32) {
+    let result = if cfg!(windows) {
+        Command::new("taskkill").args(["/PID", &pid.to_string(), "/T", "/F"]).output()
+    } else {
+        Command::new("kill").args(["-9", &pid.to_string()]).output()
+    };
+    match result {
+        Ok(_) => tracing::info!(pid, "已结束浏览器进程"),
+        Err(e) => tracing::warn!(pid, error = %e, "无法结束浏览器进程"),
+    }
+}
+
+/// 由本模块管理的浏览器：释放时结束进程、删除用户数据目录并注销
+pub struct ManagedBrowser {
+    browser: Option<Browser>,
+    pid: Option<u32>,
+    profile_dir: PathBuf,
+}
+
+impl Deref for ManagedBrowser {
+    type Target = Browser;
+
+    fn deref(&self) -> &Browser {
+        self.browser.as_ref().expect("浏览器已释放")
+    }
+}
+
+impl Drop for ManagedBrowser {
+    fn drop(&mut self) {
+        drop(self.browser.take());
+        let mut live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+        live.retain(|p| Some(p.pid) != self.pid);
+        save_registry(&live);
+        drop(live);
+        let _ = fs::remove_dir_all(&self.profile_dir);
+    }
+}
+
+/// 把浏览器关联到导出任务，取消导出时据此结束进程
+pub fn attach_export(browser: &ManagedBrowser, export_id: &str) {
+    let mut live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(process) = live.iter_mut().find(|p| Some(p.pid) == browser.pid) {
+        process.export_id = Some(export_id.to_string());
+    }
+}
+
+/// 结束导出任务使用的浏览器，进行中的 DevTools 调用随之失败返回
+pub fn kill_for_export(export_id: &str) {
+    let live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+    for process in live.iter().filter(|p| p.export_id.as_deref() == Some(export_id)) {
+        kill_browser(process);
+    }
+}
+
+/// 应用退出时结束所有浏览器并删除用户数据目录
+pub fn kill_all() {
+    let mut live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+    for process in live.drain(..) {
+        if kill_browser(&process) {
+            let _ = fs::remove_dir_all(&process.profile_dir);
+        }
+    }
+    save_registry(&live);
+}
+
+/// 记录中的目录是否是本模块创建的用户数据目录（位于临时目录下，名称带前缀）
+fn is_profile_dir(dir: &Path) -> bool {
+    dir.parent() == Some(std::env::temp_dir().as_path())
+        && dir.file_name().is_some_and(|name| name.to_string_lossy().starts_with(PROFILE_PREFIX))
+}
+
+/// 启动时清理上次运行遗留的浏览器进程与用户数据目录（上次异常退出时才会存在）。
+/// 只处理记录中的浏览器；进程确认已结束后才删除目录，仍在运行的留在记录中下次再试
+pub fn cleanup_orphans() {
+    let leaked: Vec<BrowserProcess> = fs::read_to_string(registry_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let mut remaining = Vec::new();
+    let mut removed = 0;
+    for process in leaked.iter().filter(|p| is_profile_dir(&p.profile_dir)) {
+        if is_our_browser(process.pid, &process.profile_dir) {
+            tracing::warn!(pid = process.pid, "结束上次遗留的浏览器进程");
+        }
+        if !kill_browser(process) {
+            remaining.push(process.clone());
+            continue;
+        }
+        if fs::remove_dir_all(&process.profile_dir).is_ok() {
+            removed += 1;
+        }
+    }
+    if !leaked.is_empty() {
+        tracing::info!(processes = leaked.len(), directories = removed, "已清理上次遗留的浏览器");
+    }
+    let mut live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+    live.extend(remaining);
+    save_registry(&live);
+}
+
+/// 探测浏览器时检查过的位置（与 headless_chrome 的自动探测顺序一致），用于错误详情
+pub fn probed_browser_locations() -> Vec<String> {
+    let mut probed = vec!["$CHROME".to_string()];
+    probed.extend(
+        [
+            "google-chrome-stable",
+            "google-chrome-beta",
+            "google-chrome-dev",
+            "google-chrome-unstable",
+            "chromium",
+            "chromium-browser",
+            "microsoft-edge-stable",
+            "microsoft-edge-beta",
+            "microsoft-edge-dev",
+            "chrome",
+            "chrome-browser",
+            "msedge",
+            "microsoft-edge",
+        ]
+        .iter()
+        .map(|app| format!("PATH:{}", app)),
+    );
+    if cfg!(target_os = "macos") {
+        probed.push("/Applications/Google Chrome.app".to_string());
+        probed.push("/Applications/Chromium.app".to_string());
+        probed.push("/Applications/Microsoft Edge.app".to_string());
+    }
+    if cfg!(windows) {
+        probed.push("HKLM\\SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\App Paths\\chrome.exe".to_string());
+        probed.push(r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe".to_string());
+    }
+    probed
+}
+
+/// 查找可用的 Chrome/Edge 可执行文件
+pub fn find_browser_executable() -> Result<PathBuf, AppError> {
+    headless_chrome::browser::default_executable().map_err(|_| AppError::BrowserNotFound {
+        probed: probed_browser_locations(),
+    })
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BrowserInfo {
+    pub path: Option<String>,
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 探测浏览器路径与版本（版本通过实际启动浏览器获取），用于诊断报告
+pub fn detect_browser_info() -> BrowserInfo {
+    let mut info = BrowserInfo::default();
+    match find_browser_executable() {
+        Ok(path) => info.path = Some(path.to_string_lossy().to_string()),
+        Err(e) => {
+            info.error = Some(e.to_string());
+            return info;
+        }
+    }
+    match launch_headless_browser(&StageTimeouts::default()).and_then(|browser| {
+        browser.get_version().map_err(|e| AppError::BrowserError(e.to_string()))
+    }) {
+        Ok(version) => info.version = Some(version.product),
+        Err(e) => info.error = Some(e.to_string()),
+    }
+    info
+}
+
+/// 以导出所需的参数启动 Headless Chrome；超过 `timeouts` 中最长的阶段预算没有任何 DevTools 消息时，
+/// 浏览器连接断开并结束进程
+pub fn launch_headless_browser(timeouts: &StageTimeouts) -> Result<ManagedBrowser, AppError> {
+    let executable = find_browser_executable()?;
+    let profile_dir = std::env::temp_dir().join(format!(
+        "{}{}-{}",
+        PROFILE_PREFIX,
+        std::process::id(),
+        NEXT_PROFILE.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    // 配置浏览器启动选项
+    let launch_options = LaunchOptions::default_builder()
+        .path(Some(executable))
+        .headless(true)
+        .sandbox(false)
+        .user_data_dir(Some(profile_dir.clone()))
+        .idle_browser_timeout(timeouts.idle_timeout())
+        .args(vec![
+            std::ffi::OsStr::new("--no-sandbox"),
+            std::ffi::OsStr::new("--disable-setuid-sandbox"),
+            std::ffi::OsStr::new("--disable-dev-shm-usage"),
+            std::ffi::OsStr::new("--disable-extensions"),
+            std::ffi::OsStr::new("--disable-gpu"),
+            std::ffi::OsStr::new("--disable-background-timer-throttling"),
+            std::ffi::OsStr::new("--disable-renderer-backgrounding"),
+            std::ffi::OsStr::new("--disable-backgrounding-occluded-windows"),
+            std::ffi::OsStr::new("--disable-hang-monitor"),
+        ])
+        .build()
+        .map_err(|e| AppError::BrowserError(e.to_string()))?;
+
+    // 启动浏览器
+    let browser = match Browser::new(launch_options) {
+        Ok(browser) => browser,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&profile_dir);
+            return Err(AppError::BrowserError(e.to_string()));
+        }
+    };
+    let pid = browser.get_process_id();
+    if let Some(pid) = pid {
+        let mut live = LIVE.lock().unwrap_or_else(|e| e.into_inner());
+        live.push(BrowserProcess { pid, profile_dir: profile_dir.clone(), export_id: None });
+        save_registry(&live);
+    }
+    Ok(ManagedBrowser { browser: Some(browser), pid, profile_dir })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_processes_started_with_the_profile_dir_are_ours() {
+        let profile_dir = std::env::temp_dir().join(format!("{}test-{}", PROFILE_PREFIX, std::process::id()));
+        // 命令末尾的 `:` 让 shell 不直接 exec sleep（bash 会这样做），标记参数因此留在命令行中
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 30; :", &profile_dir.to_string_lossy()])
+            .spawn()
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(100));
+
+        assert!(is_our_browser(child.id(), &profile_dir));
+        assert!(!is_our_browser(child.id(), Path::new("/tmp/md2pdf-chrome-other")));
+        // 进程号被其他程序复用时（这里用测试进程本身）不会被认作浏览器
+        assert!(!is_our_browser(std::process::id(), &profile_dir));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(!is_our_browser(child.id(), &profile_dir));
+    }
+
+    #[test]
+    fn only_prefixed_temp_dirs_are_profile_dirs() {
+        assert!(is_profile_dir(&std::env::temp_dir().join("md2pdf-chrome-1-1")));
+        assert!(!is_profile_dir(&std::env::temp_dir().join("other")));
+        assert!(!is_profile_dir(Path::new("/home/user/md2pdf-chrome-1-1")));
+    }
+}
@@ -0,0 +1,191 @@
+//! 提示块（callout / admonition）：GitHub 风格的 `> [!NOTE]` 引用块渲染为带图标与标题的彩色区块。
+//!
+//! ```markdown
+//! > [!WARNING] 升级前请备份
+//! > 旧版本的配置文件不兼容。
+//! ```
+//!
+//! 内置 `note`、`tip`、`important`、`warning`、`caution`、`danger` 六种类型。每种类型的图标、颜色与标题
+//! 可以覆盖，也可以声明新的类型，优先级从低到高：
+//!  - 设置中的 `callouts`
+//!  - 项目配置：文档所在目录（或其上级目录）中的 `.md2pdf.yaml` 的 `callouts`
+//!  - front matter 中的 `callouts`
+//!
+//! ```yaml
+//! callouts:
+//!   warning: { icon: "⚠", color: "#c05600" }
+//!   legal: { icon: "§", color: "#1f4e79", title: 法务提示 }
+//! ```
+//!
+//! 图标为文字（含 emoji）、`<svg>` 或图片路径。属于扩展语法。
+
+use crate::html_util::{escape_html, find_closing_tag, safe_color};
+use crate::settings::{AppSettings, SettingsState};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// 项目配置文件名
+const PROJECT_CONFIG: &str = ".md2pdf.yaml";
+
+/// 提示块样式；作为覆盖项时未填写的字段沿用下层的值
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalloutStyle {
+    pub icon: Option<String>,
+    pub color: Option<String>,
+    /// 未在标记后写标题时显示的默认标题
+    pub title: Option<String>,
+}
+
+impl CalloutStyle {
+    fn new(icon: &str, color: &str, title: &str) -> Self {
+        CalloutStyle { icon: Some(icon.to_string()), color: Some(color.to_string()), title: Some(title.to_string()) }
+    }
+
+    fn merge(&mut self, other: &CalloutStyle) {
+        if other.icon.is_some() {
+            self.icon = other.icon.clone();
+        }
+        if other.color.is_some() {
+            self.color = other.color.clone();
+        }
+        if other.title.is_some() {
+            self.title = other.title.clone();
+        }
+    }
+}
+
+fn builtin_styles() -> BTreeMap<String, CalloutStyle> {
+    [
+        ("note", CalloutStyle::new("ℹ", "#0969da", "注意")),
+        ("tip", CalloutStyle::new("💡", "#1a7f37", "提示")),
+        ("important", CalloutStyle::new("❗", "#8250df", "重要")),
+        ("warning", CalloutStyle::new("⚠", "#9a6700", "警告")),
+        ("caution", CalloutStyle::new("⛔", "#cf222e", "小心")),
+        ("danger", CalloutStyle::new("⛔", "#cf222e", "危险")),
+    ]
+    .into_iter()
+    .map(|(kind, style)| (kind.to_string(), style))
+    .collect()
+}
+
+/// 从 YAML 中读取 `callouts` 覆盖项（类型名不区分大小写）
+fn overrides_from(value: Option<&Value>) -> BTreeMap<String, CalloutStyle> {
+    value
+        .and_then(|value| serde_yaml::from_value::<BTreeMap<String, CalloutStyle>>(value.clone()).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(kind, style)| (kind.to_ascii_lowercase(), style))
+        .collect()
+}
+
+/// 从文档所在目录向上查找项目配置，返回其中的 `callouts`
+fn project_overrides(source_path: Option<&Path>) -> BTreeMap<String, CalloutStyle> {
+    let Some(dir) = source_path.and_then(Path::parent) else {
+        return BTreeMap::new();
+    };
+    for dir in dir.ancestors() {
+        let path = dir.join(PROJECT_CONFIG);
+        if let Ok(content) = fs::read_to_string(&path) {
+            return match serde_yaml::from_str::<Value>(&content) {
+                Ok(config) => overrides_from(config.get("callouts")),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "项目配置解析失败");
+                    BTreeMap::new()
+                }
+            };
+        }
+    }
+    BTreeMap::new()
+}
+
+/// 各类型最终使用的样式：内置样式依次被设置、项目配置与 front matter 覆盖
+pub fn resolve(
+    settings: &AppSettings,
+    source_path: Option<&Path>,
+    front_matter: Option<&Value>,
+) -> BTreeMap<String, CalloutStyle> {
+    let mut styles = builtin_styles();
+    let layers = [
+        settings.callouts.iter().map(|(kind, style)| (kind.to_ascii_lowercase(), style.clone())).collect(),
+        project_overrides(source_path),
+        overrides_from(front_matter.and_then(|fm| fm.get("callouts"))),
+    ];
+    for layer in layers {
+        for (kind, style) in layer {
+            styles.entry(kind).or_default().merge(&style);
+        }
+    }
+    styles
+}
+
+fn icon_html(icon: &str) -> String {
+    let icon = icon.trim();
+    if icon.starts_with("<svg") {
+        icon.to_string()
+    } else if icon.contains('/') || icon.contains('\\') || icon.contains('.') {
+        format!(r#"<img src="{}" alt="">"#, escape_html(icon))
+    } else {
+        escape_html(icon)
+    }
+}
+
+/// 把标记为 `[!TYPE]` 的引用块转换为提示块；未知类型保持为普通引用块
+pub fn apply_callouts(html: &str, styles: &BTreeMap<String, CalloutStyle>) -> String {
+    if !html.contains("[!") {
+        return html.to_string();
+    }
+    let re_marker = Regex::new(r"(?i)<blockquote>\s*<p>\[!([a-z][a-z0-9_-]*)\][ \t]*([^\n<]*)(?:\n|<br\s*/?>\n?)?").unwrap();
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    while let Some(caps) = re_marker.captures_at(html, last) {
+        let m = caps.get(0).unwrap();
+        let kind = caps[1].to_ascii_lowercase();
+        let (Some(style), Some((content_end, end))) =
+            (styles.get(&kind), find_closing_tag(html, "blockquote", m.start() + "<blockquote>".len()))
+        else {
+            out.push_str(&html[last..m.end()]);
+            last = m.end();
+            continue;
+        };
+
+        let title = match caps[2].trim() {
+            "" => style.title.clone().map(|t| escape_html(&t)).unwrap_or_else(|| escape_html(&caps[1])),
+            // 标题已是转义后的 HTML 文本
+            custom => custom.to_string(),
+        };
+        let body = format!("<p>{}", &html[m.end()..content_end]);
+        let body = body.replacen("<p></p>", "", 1);
+        let color = style.color.as_deref().and_then(safe_color).unwrap_or("#0969da");
+        let icon = style.icon.as_deref().map(icon_html).unwrap_or_default();
+
+        out.push_str(&html[last..m.start()]);
+        out.push_str(&format!(
+            r#"<div class="callout callout-{kind}" style="--callout-color: {color}"><div class="callout-title"><span class="callout-icon">{icon}</span>{title}</div><div class="callout-body">{body}</div></div>"#,
+            kind = escape_html(&kind),
+            color = color,
+            icon = icon,
+            title = title,
+            body = body.trim()
+        ));
+        last = end;
+    }
+    out.push_str(&html[last..]);
+    out
+}
+
+/// 文档使用的提示块样式（供前端预览）
+#[tauri::command]
+pub fn get_callout_styles(
+    markdown: &str,
+    source_path: Option<String>,
+    settings: tauri::State<'_, SettingsState>,
+) -> BTreeMap<String, CalloutStyle> {
+    let front_matter = crate::front_matter::parse(markdown);
+    resolve(&settings.snapshot(), source_path.as_deref().map(Path::new), front_matter.as_ref())
+}
@@ -0,0 +1,72 @@
+//! 导出任务的取消：每个导出在开始时登记一个取消标记，`cancel_export` 置位后，
+//! 导出流程在下一个阶段边界或轮询间隔处以 `AppError::Cancelled` 结束；
+//! 该导出使用的浏览器进程被立即结束，卡住的浏览器调用随之返回。
+
+use crate::error::AppError;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+static ACTIVE: Mutex<BTreeMap<String, Arc<AtomicBool>>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 导出任务的取消标记，释放时自动注销
+pub struct CancelToken {
+    id: String,
+    flag: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// 登记一个导出任务；未指定 ID 时自动生成
+    pub fn register(id: Option<String>) -> Self {
+        let id = id.unwrap_or_else(|| format!("export-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.insert(id.clone(), flag.clone());
+        }
+        CancelToken { id, flag }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+    }
+
+    /// 已取消时返回 `AppError::Cancelled`
+    pub fn check(&self) -> Result<(), AppError> {
+        if self.is_cancelled() {
+            Err(AppError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for CancelToken {
+    fn drop(&mut self) {
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.remove(&self.id);
+        }
+    }
+}
+
+/// 取消指定的导出任务；未指定 ID 时取消所有进行中的导出。返回被取消的任务数
+#[tauri::command]
+pub fn cancel_export(export_id: Option<String>) -> usize {
+    let Ok(active) = ACTIVE.lock() else {
+        return 0;
+    };
+    active
+        .iter()
+        .filter(|(id, _)| export_id.as_ref().is_none_or(|target| target == *id))
+        .map(|(id, flag)| {
+            tracing::info!(export_id = %id, "取消导出");
+            flag.store(true, Ordering::Relaxed);
+            // 卡住的浏览器调用不会自己返回，直接结束该导出的浏览器
+            crate::browser::kill_for_export(id);
+        })
+        .count()
+}
@@ -0,0 +1,261 @@
+//! 文档检查：在导出前发现损坏的链接、缺失的图片、无效锚点、重复的标题 slug 和未闭合的公式块
+
+use crate::error::AppError;
+use crate::parser_mode::{self, ParserMode};
+use crate::slug::Slugger;
+use comrak::nodes::{AstNode, NodeValue};
+use comrak::{parse_document, Arena};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentIssue {
+    /// 1-indexed
+    pub line: usize,
+    pub column: usize,
+    /// `error` | `warning`
+    pub severity: String,
+    /// `missing_file` | `missing_image` | `broken_anchor` | `duplicate_slug` | `unclosed_math`
+    pub kind: String,
+    pub target: String,
+    pub message: String,
+}
+
+impl DocumentIssue {
+    pub(crate) fn new(line: usize, column: usize, severity: &str, kind: &str, target: &str, message: String) -> Self {
+        DocumentIssue {
+            line,
+            column,
+            severity: severity.to_string(),
+            kind: kind.to_string(),
+            target: target.to_string(),
+            message,
+        }
+    }
+}
+
+/// 收集节点下的纯文本
+pub fn node_text<'a>(node: &'a AstNode<'a>) -> String {
+    let mut text = String::new();
+    for descendant in node.descendants() {
+        match &descendant.data.borrow().value {
+            NodeValue::Text(t) => text.push_str(t),
+            NodeValue::Code(c) => text.push_str(&c.literal),
+            NodeValue::Math(m) => text.push_str(&m.literal),
+            NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// 是否为外部链接（带协议、协议相对地址或 data URI）
+pub fn is_external_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    lower.starts_with("//")
+        || lower.starts_with("data:")
+        || lower
+            .split_once(':')
+            .map(|(scheme, _)| {
+                scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            })
+            .unwrap_or(false)
+}
+
+/// 解码 URL 中的 %XX 转义
+pub fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = |b: u8| (b as char).to_digit(16);
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push((hi * 16 + lo) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// 文档中所有标题的 slug
+fn heading_slugs(markdown: &str) -> HashSet<String> {
+    let arena = Arena::new();
+    let options = parser_mode::comrak_options(ParserMode::resolve(None, markdown), markdown);
+    let root = parse_document(&arena, markdown, &options);
+    let mut slugger = Slugger::new();
+    root.descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::Heading(_)))
+        .map(|node| slugger.slug(&node_text(node)))
+        .collect()
+}
+
+/// 检查未闭合的 $$ 公式块（跳过代码块内的内容）
+fn check_math_blocks(markdown: &str, issues: &mut Vec<DocumentIssue>) {
+    let mut fence: Option<String> = None;
+    let mut open_math: Option<usize> = None;
+
+    for (idx, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim();
+        if let Some(marker) = &fence {
+            if trimmed.starts_with(marker.as_str()) {
+                fence = None;
+            }
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(trimmed[..3].to_string());
+            continue;
+        }
+        if trimmed == "$$" || (trimmed.starts_with("$$") && !(trimmed.len() > 2 && trimmed.ends_with("$$"))) {
+            open_math = match open_math {
+                Some(_) => None,
+                None => Some(idx + 1),
+            };
+        } else if open_math.is_some() && trimmed.ends_with("$$") {
+            open_math = None;
+        }
+    }
+
+    if let Some(line) = open_math {
+        issues.push(DocumentIssue::new(
+            line,
+            1,
+            "error",
+            "unclosed_math",
+            "$$",
+            "公式块 $$ 未闭合".to_string(),
+        ));
+    }
+}
+
+/// 检查 Markdown 内容；`base_dir` 用于解析相对路径
+pub fn check_markdown(markdown: &str, base_dir: Option<&Path>, mode: ParserMode) -> Vec<DocumentIssue> {
+    let content = markdown.replace("\r\n", "\n");
+    let arena = Arena::new();
+    let options = parser_mode::comrak_options(mode, &content);
+    let root = parse_document(&arena, &content, &options);
+    let mut issues = Vec::new();
+
+    // 标题 slug（同时检查重复）
+    let mut slugger = Slugger::new();
+    let mut slugs = HashSet::new();
+    for node in root.descendants() {
+        if !matches!(node.data.borrow().value, NodeValue::Heading(_)) {
+            continue;
+        }
+        let text = node_text(node);
+        let (slug, duplicate) = slugger.slug_with_duplicate(&text);
+        if duplicate {
+            let pos = node.data.borrow().sourcepos.start;
+            issues.push(DocumentIssue::new(
+                pos.line,
+                pos.column,
+                "warning",
+                "duplicate_slug",
+                &slug,
+                format!("标题「{}」与前面的标题锚点重复，已重命名为 #{}", text.trim(), slug),
+            ));
+        }
+        slugs.insert(slug);
+    }
+
+    // 链接与图片
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        let (url, is_image) = match &data.value {
+            NodeValue::Link(link) => (link.url.clone(), false),
+            NodeValue::Image(link) => (link.url.clone(), true),
+            _ => continue,
+        };
+        let pos = data.sourcepos.start;
+        if url.is_empty() || is_external_url(&url) {
+            continue;
+        }
+
+        let (path_part, anchor) = match url.split_once('#') {
+            Some((p, a)) => (p.to_string(), Some(percent_decode(a))),
+            None => (url.clone(), None),
+        };
+
+        // 页内锚点
+        if path_part.is_empty() {
+            if let Some(anchor) = anchor {
+                if !slugs.contains(&anchor) {
+                    issues.push(DocumentIssue::new(
+                        pos.line,
+                        pos.column,
+                        "warning",
+                        "broken_anchor",
+                        &url,
+                        format!("找不到锚点 #{}", anchor),
+                    ));
+                }
+            }
+            continue;
+        }
+
+        let Some(base_dir) = base_dir else { continue };
+        let decoded = percent_decode(path_part.split('?').next().unwrap_or(""));
+        let target: PathBuf = base_dir.join(&decoded);
+        if !target.exists() {
+            let (kind, message) = if is_image {
+                ("missing_image", format!("图片不存在: {}", decoded))
+            } else {
+                ("missing_file", format!("链接的文件不存在: {}", decoded))
+            };
+            issues.push(DocumentIssue::new(pos.line, pos.column, "error", kind, &url, message));
+            continue;
+        }
+
+        // 指向其他 Markdown 文件的锚点
+        if let Some(anchor) = anchor {
+            if crate::open_file::is_markdown_file(&target) {
+                let target_slugs = fs::read_to_string(&target)
+                    .map(|c| heading_slugs(&c.replace("\r\n", "\n")))
+                    .unwrap_or_default();
+                if !target_slugs.contains(&anchor) {
+                    issues.push(DocumentIssue::new(
+                        pos.line,
+                        pos.column,
+                        "warning",
+                        "broken_anchor",
+                        &url,
+                        format!("{} 中找不到锚点 #{}", decoded, anchor),
+                    ));
+                }
+            }
+        }
+    }
+
+    // 严格模式下没有公式语法
+    if mode == ParserMode::Extended {
+        check_math_blocks(&content, &mut issues);
+    }
+
+    issues.sort_by(|a, b| a.line.cmp(&b.line).then(a.column.cmp(&b.column)));
+    issues
+}
+
+/// 检查文档中的链接、图片、锚点与公式块。
+/// `content` 为编辑器中尚未保存的内容，未提供时读取 `path`。
+#[tauri::command]
+pub fn check_document(
+    path: String,
+    content: Option<String>,
+    mode: Option<ParserMode>,
+) -> Result<Vec<DocumentIssue>, AppError> {
+    let markdown = match content {
+        Some(content) => content,
+        None => fs::read_to_string(&path).map_err(|e| AppError::file(&path, e))?,
+    };
+    let mode = ParserMode::resolve(mode, &markdown);
+    let base_dir = Path::new(&path).parent();
+    crate::error::catch_panic("check_document", || Ok(check_markdown(&markdown, base_dir, mode)))
+}
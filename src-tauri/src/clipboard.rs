@@ -0,0 +1,41 @@
+//! 从剪贴板粘贴图片：保存到文档旁的 `assets/` 目录，文件名为内容哈希（相同图片只保存一次）
+
+use crate::error::AppError;
+use std::path::Path;
+
+/// 把 RGBA 像素编码为 PNG
+fn encode_png(width: usize, height: usize, rgba: &[u8]) -> Result<Vec<u8>, AppError> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|e| AppError::localized("CLIPBOARD", "CLIPBOARD_ENCODE", [("reason", e.to_string())]))?;
+    Ok(out)
+}
+
+/// 读取剪贴板中的图片，保存为 `assets/<哈希>.png`，返回可直接插入的 Markdown 图片链接
+#[tauri::command]
+pub fn save_clipboard_image(doc_path: String) -> Result<String, AppError> {
+    crate::error::catch_panic("save_clipboard_image", || {
+        let doc_dir = Path::new(&doc_path)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .ok_or_else(|| AppError::localized("CLIPBOARD", "CLIPBOARD_UNSAVED", []))?;
+
+        let image = arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_image())
+            .map_err(|e| match e {
+                arboard::Error::ContentNotAvailable => AppError::localized("CLIPBOARD", "CLIPBOARD_EMPTY", []),
+                other => AppError::ClipboardError(other.to_string()),
+            })?;
+        let png_data = encode_png(image.width, image.height, &image.bytes)?;
+
+        let relative = crate::assets::write_hashed_asset(doc_dir, &png_data, "png")?;
+        tracing::info!(path = %doc_dir.join(&relative).display(), "已保存剪贴板图片");
+
+        Ok(format!("![]({})", relative))
+    })
+}
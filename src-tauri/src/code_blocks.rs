@@ -0,0 +1,204 @@
+//! 代码块排版：行号、自动折行、高亮行与文件名标题栏。
+//!
+//! 设置中的 `code_blocks` 为默认值，front matter 中的 `code_blocks` 按字段覆盖；
+//! 单个代码块可以在围栏信息串中用属性覆盖：
+//!
+//! ````markdown
+//! ```rust {3-5,8} title="main.rs" linenos start=10 nowrap
+//! ````
+//!
+//!  - `{3-5,8}`：高亮的行（按代码块内的行号，从 1 开始）
+//!  - `title="..."` / `filename="..."`：代码块上方的标题栏
+//!  - `linenos` / `nolinenos`：显示或隐藏行号；`start=N`：行号起始值
+//!  - `wrap` / `nowrap`：长行自动折行或保持横向溢出（打印时超出部分会被裁掉）
+//!
+//! 前端把围栏信息串写入 `<code data-meta="...">`，这里在 HTML 层处理。
+
+use crate::figure::parse_attributes;
+use crate::html_util::{escape_html, find_closing_tag, unescape_html};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CodeBlockSettings {
+    /// 显示行号
+    pub line_numbers: Option<bool>,
+    /// 长行自动折行
+    pub wrap: Option<bool>,
+    /// 配色主题（见 highlight 模块），未指定时使用页面内置样式
+    pub theme: Option<String>,
+}
+
+impl CodeBlockSettings {
+    /// 用 `other` 中已指定的字段覆盖当前值
+    pub fn merged(&self, other: &CodeBlockSettings) -> CodeBlockSettings {
+        CodeBlockSettings {
+            line_numbers: other.line_numbers.or(self.line_numbers),
+            wrap: other.wrap.or(self.wrap),
+            theme: other.theme.clone().or_else(|| self.theme.clone()),
+        }
+    }
+
+    /// 设置与 front matter 中 `code_blocks` 合并后的配置
+    pub fn resolve(settings: &CodeBlockSettings, front_matter: Option<&serde_yaml::Value>) -> CodeBlockSettings {
+        let overrides = front_matter
+            .and_then(|fm| fm.get("code_blocks"))
+            .and_then(|value| serde_yaml::from_value::<CodeBlockSettings>(value.clone()).ok());
+        match overrides {
+            Some(overrides) => settings.merged(&overrides),
+            None => settings.clone(),
+        }
+    }
+}
+
+/// 单个代码块的排版选项
+#[derive(Debug, Default)]
+struct BlockOptions {
+    title: Option<String>,
+    highlighted: Vec<(usize, usize)>,
+    line_numbers: bool,
+    start: usize,
+    wrap: bool,
+}
+
+/// `3-5,8` 形式的行范围
+fn parse_ranges(raw: &str) -> Vec<(usize, usize)> {
+    raw.split([',', ' '])
+        .filter_map(|part| {
+            let part = part.trim();
+            match part.split_once('-') {
+                Some((a, b)) => Some((a.trim().parse().ok()?, b.trim().parse().ok()?)),
+                None => part.parse().ok().map(|n| (n, n)),
+            }
+        })
+        .collect()
+}
+
+fn block_options(meta: &str, defaults: &CodeBlockSettings) -> BlockOptions {
+    let re_braces = Regex::new(r"\{([^}]*)\}").unwrap();
+    let mut options = BlockOptions {
+        line_numbers: defaults.line_numbers.unwrap_or(false),
+        wrap: defaults.wrap.unwrap_or(false),
+        start: 1,
+        ..Default::default()
+    };
+    // 花括号中的行范围（`{run}` 等其他标记不受影响）
+    for caps in re_braces.captures_iter(meta) {
+        if caps[1].trim().chars().all(|c| c.is_ascii_digit() || matches!(c, '-' | ',' | ' ')) {
+            options.highlighted.extend(parse_ranges(&caps[1]));
+        }
+    }
+    let rest = re_braces.replace_all(meta, " ");
+    for (key, value) in parse_attributes(&rest) {
+        match key.as_str() {
+            "title" | "filename" | "caption" if !value.is_empty() => options.title = Some(value),
+            "start" => {
+                if let Ok(start) = value.parse() {
+                    options.start = start;
+                    options.line_numbers = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    let re_value = Regex::new(r#"[A-Za-z-]+\s*=\s*(?:"[^"]*"|'[^']*'|\S+)"#).unwrap();
+    for flag in re_value.replace_all(&rest, " ").split_whitespace() {
+        match flag {
+            "linenos" | "line-numbers" => options.line_numbers = true,
+            "nolinenos" | "no-line-numbers" => options.line_numbers = false,
+            "wrap" => options.wrap = true,
+            "nowrap" => options.wrap = false,
+            _ => {}
+        }
+    }
+    options
+}
+
+/// 把代码按行包裹为 `<span class="code-line">`，高亮行加上 `highlighted`；
+/// 内容中含有标签（已被其他步骤处理过）时不拆分
+fn wrap_lines(code: &str, highlighted: &[(usize, usize)]) -> Option<String> {
+    if code.contains('<') {
+        return None;
+    }
+    let code = code.strip_suffix('\n').unwrap_or(code);
+    let lines: Vec<String> = code
+        .split('\n')
+        .enumerate()
+        .map(|(i, line)| {
+            let n = i + 1;
+            let class = if highlighted.iter().any(|&(a, b)| a <= n && n <= b) {
+                "code-line highlighted"
+            } else {
+                "code-line"
+            };
+            // 空行保留高度
+            let line = if line.is_empty() { "\u{200B}" } else { line };
+            format!(r#"<span class="{}">{}</span>"#, class, line)
+        })
+        .collect();
+    Some(lines.concat())
+}
+
+/// 按设置与围栏属性处理导出页面中的代码块
+pub fn apply_code_blocks(html: &str, defaults: &CodeBlockSettings) -> String {
+    let re_block = Regex::new(r#"(?s)<pre>\s*<code((?:\s+[a-zA-Z-]+="[^"]*")*)\s*>"#).unwrap();
+    let re_attr = Regex::new(r#"\s+([a-zA-Z-]+)="([^"]*)""#).unwrap();
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for caps in re_block.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        if whole.start() < last {
+            continue;
+        }
+        let Some((code_end, _)) = find_closing_tag(html, "code", whole.end()) else { continue };
+        let Some((_, pre_end)) = find_closing_tag(html, "pre", whole.start() + "<pre>".len()) else { continue };
+        let attrs: Vec<Captures> = re_attr.captures_iter(&caps[1]).collect();
+        let attr = |name: &str| attrs.iter().find(|a| &a[1] == name).map(|a| unescape_html(&a[2]));
+        let class = attr("class").unwrap_or_default();
+        // 未经公式引擎处理的公式块不是代码
+        if class.contains("math-display") || class.contains("math-inline") {
+            continue;
+        }
+
+        let options = block_options(&attr("data-meta").unwrap_or_default(), defaults);
+        let code = &html[whole.end()..code_end];
+        let body = if options.line_numbers || !options.highlighted.is_empty() {
+            wrap_lines(code, &options.highlighted).unwrap_or_else(|| code.to_string())
+        } else {
+            code.to_string()
+        };
+
+        let mut classes = vec!["code-block"];
+        if options.line_numbers {
+            classes.push("line-numbers");
+        }
+        if options.wrap {
+            classes.push("wrap");
+        }
+        let class_attr = if class.is_empty() { String::new() } else { format!(r#" class="{}""#, escape_html(&class)) };
+        let counter = if options.line_numbers && options.start != 1 {
+            format!(r#" style="counter-reset: code-line {}""#, options.start.saturating_sub(1))
+        } else {
+            String::new()
+        };
+        let title = options
+            .title
+            .map(|t| format!(r#"<div class="code-title">{}</div>"#, escape_html(&t)))
+            .unwrap_or_default();
+
+        out.push_str(&html[last..whole.start()]);
+        out.push_str(&format!(
+            r#"<div class="{}">{}<pre><code{}{}>{}</code></pre></div>"#,
+            classes.join(" "),
+            title,
+            class_attr,
+            counter,
+            body
+        ));
+        last = pre_end;
+    }
+    out.push_str(&html[last..]);
+    out
+}
@@ -0,0 +1,338 @@
+//! 外部资源检查：列出导出页面会访问的所有外部地址（CDN 样式、远程图片、脚本等），
+//! 按设置决定是否允许无头浏览器访问。
+//!
+//!  - `allow`（默认）：不做限制
+//!  - `ask`：页面会访问外部地址时先返回 `EXTERNAL_RESOURCES` 错误，列出这些来源；
+//!    用户确认后带上 `allowed_origins` 重新导出，只允许访问确认过的来源
+//!  - `block`：离线导出，拒绝所有外部请求
+//!
+//! 导出选项中的 `offline` 对单次导出生效。被拒绝的请求记录为导出警告。
+
+use crate::error::AppError;
+use crate::html_util::unescape_html;
+use headless_chrome::browser::tab::RequestPausedDecision;
+use headless_chrome::protocol::cdp::{Fetch, Network};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// 外部资源的访问策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalResourcePolicy {
+    #[default]
+    Allow,
+    Ask,
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceKind {
+    Stylesheet,
+    Script,
+    Image,
+    Font,
+    Media,
+    Frame,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalResource {
+    pub url: String,
+    pub kind: ResourceKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalOrigin {
+    /// `https://cdn.example.com` 形式的来源
+    pub origin: String,
+    pub resources: Vec<ExternalResource>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContentSecurityReport {
+    pub origins: Vec<ExternalOrigin>,
+}
+
+impl ContentSecurityReport {
+    pub fn origin_names(&self) -> Vec<String> {
+        self.origins.iter().map(|o| o.origin.clone()).collect()
+    }
+}
+
+/// 无头浏览器的网络访问范围
+#[derive(Debug, Clone)]
+pub enum NetworkAccess {
+    Unrestricted,
+    /// 只允许访问这些来源，其他外部请求被拒绝（为空时完全离线）
+    Restricted(HashSet<String>),
+}
+
+/// 地址的来源（协议、主机与端口，小写）；不是 http(s) 地址时返回 `None`
+pub fn origin_of(url: &str) -> Option<String> {
+    let url = url.trim();
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+        None => ("https".to_string(), url.strip_prefix("//")?),
+    };
+    if scheme != "http" && scheme != "https" {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or("").to_ascii_lowercase();
+    if host.is_empty() {
+        return None;
+    }
+    let host = host
+        .strip_suffix(if scheme == "https" { ":443" } else { ":80" })
+        .unwrap_or(&host)
+        .to_string();
+    Some(format!("{}://{}", scheme, host))
+}
+
+/// CSS 中 `url(...)` 引用的资源类型
+fn css_url_kind(url: &str) -> ResourceKind {
+    let path = url.split(['?', '#']).next().unwrap_or("").to_ascii_lowercase();
+    if [".woff2", ".woff", ".ttf", ".otf", ".eot"].iter().any(|ext| path.ends_with(ext)) {
+        ResourceKind::Font
+    } else if path.ends_with(".css") {
+        ResourceKind::Stylesheet
+    } else {
+        ResourceKind::Image
+    }
+}
+
+/// 样式表文本中引用的地址
+fn css_urls(css: &str, found: &mut Vec<(String, ResourceKind)>) {
+    let re_import = Regex::new(r#"@import\s+(?:url\(\s*)?['"]?([^'")\s;]+)"#).unwrap();
+    let re_url = Regex::new(r#"url\(\s*['"]?([^'")\s]+)['"]?\s*\)"#).unwrap();
+    for caps in re_import.captures_iter(css) {
+        found.push((caps[1].to_string(), ResourceKind::Stylesheet));
+    }
+    for caps in re_url.captures_iter(css) {
+        found.push((caps[1].to_string(), css_url_kind(&caps[1])));
+    }
+}
+
+/// 列出页面加载时会访问的外部地址（链接 `<a href>` 不会被加载，不计入）
+pub fn analyze(html: &str) -> ContentSecurityReport {
+    let re_tag = Regex::new(r"(?s)<([a-zA-Z][a-zA-Z0-9-]*)\b([^>]*)>").unwrap();
+    let re_attr = Regex::new(r#"(?s)\s([a-zA-Z-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    let re_style = Regex::new(r"(?s)<style\b[^>]*>(.*?)</style>").unwrap();
+
+    let mut found: Vec<(String, ResourceKind)> = Vec::new();
+    for caps in re_tag.captures_iter(html) {
+        let tag = caps[1].to_ascii_lowercase();
+        let attrs: BTreeMap<String, String> = re_attr
+            .captures_iter(&caps[2])
+            .map(|a| {
+                let value = a.get(2).or_else(|| a.get(3)).map(|m| m.as_str()).unwrap_or("");
+                (a[1].to_ascii_lowercase(), unescape_html(value))
+            })
+            .collect();
+        let get = |name: &str| attrs.get(name).map(String::as_str);
+        match tag.as_str() {
+            "img" | "input" => {
+                if let Some(src) = get("src") {
+                    found.push((src.to_string(), ResourceKind::Image));
+                }
+            }
+            "script" => {
+                if let Some(src) = get("src") {
+                    found.push((src.to_string(), ResourceKind::Script));
+                }
+            }
+            "link" => {
+                let rel = get("rel").unwrap_or("").to_ascii_lowercase();
+                let fetched = ["stylesheet", "icon", "preload", "prefetch", "modulepreload", "manifest"]
+                    .iter()
+                    .any(|r| rel.split_whitespace().any(|x| x == *r));
+                if let (true, Some(href)) = (fetched, get("href")) {
+                    let kind = if rel.contains("stylesheet") { ResourceKind::Stylesheet } else { ResourceKind::Other };
+                    found.push((href.to_string(), kind));
+                }
+            }
+            "iframe" | "frame" => {
+                if let Some(src) = get("src") {
+                    found.push((src.to_string(), ResourceKind::Frame));
+                }
+            }
+            "video" | "audio" | "source" | "track" => {
+                if let Some(src) = get("src") {
+                    found.push((src.to_string(), ResourceKind::Media));
+                }
+                if let Some(poster) = get("poster") {
+                    found.push((poster.to_string(), ResourceKind::Image));
+                }
+            }
+            "object" | "embed" => {
+                if let Some(src) = get("data").or_else(|| get("src")) {
+                    found.push((src.to_string(), ResourceKind::Other));
+                }
+            }
+            _ => {}
+        }
+        // srcset 中的每个候选地址
+        if let Some(srcset) = get("srcset") {
+            for candidate in srcset.split(',') {
+                if let Some(url) = candidate.split_whitespace().next() {
+                    found.push((url.to_string(), ResourceKind::Image));
+                }
+            }
+        }
+        if let Some(style) = get("style") {
+            css_urls(style, &mut found);
+        }
+    }
+    for caps in re_style.captures_iter(html) {
+        css_urls(&caps[1], &mut found);
+    }
+
+    let mut origins: BTreeMap<String, Vec<ExternalResource>> = BTreeMap::new();
+    for (url, kind) in found {
+        let Some(origin) = origin_of(&url) else { continue };
+        // 本地资源服务器不算外部来源
+        if crate::asset_server::is_local_origin(&origin) {
+            continue;
+        }
+        let resources = origins.entry(origin).or_default();
+        if !resources.iter().any(|r| r.url == url) {
+            resources.push(ExternalResource { url, kind });
+        }
+    }
+    ContentSecurityReport {
+        origins: origins.into_iter().map(|(origin, resources)| ExternalOrigin { origin, resources }).collect(),
+    }
+}
+
+/// 按策略决定本次导出的网络访问范围；需要用户确认时返回 `ExternalResources` 错误
+pub fn resolve_access(
+    html: &str,
+    policy: ExternalResourcePolicy,
+    allowed_origins: Option<&[String]>,
+    offline: bool,
+) -> Result<NetworkAccess, AppError> {
+    if offline || policy == ExternalResourcePolicy::Block {
+        return Ok(NetworkAccess::Restricted(HashSet::new()));
+    }
+    if policy == ExternalResourcePolicy::Allow {
+        return Ok(NetworkAccess::Unrestricted);
+    }
+    let report = analyze(html);
+    match allowed_origins {
+        Some(allowed) => Ok(NetworkAccess::Restricted(allowed.iter().filter_map(|o| origin_of(o)).collect())),
+        None if report.origins.is_empty() => Ok(NetworkAccess::Restricted(HashSet::new())),
+        None => Err(AppError::ExternalResources { origins: report.origin_names() }),
+    }
+}
+
+/// 受限模式下是否放行请求：本地资源服务器、`file:`、`data:`、`blob:`、`about:` 与允许的来源；
+/// 其他协议（`ftp:`、`ws:` 等）一律拒绝
+fn is_request_allowed(url: &str, allowed: &HashSet<String>) -> bool {
+    let scheme = url.split_once(':').map(|(scheme, _)| scheme.trim().to_ascii_lowercase()).unwrap_or_default();
+    match scheme.as_str() {
+        "data" | "blob" | "about" | "file" => true,
+        "http" | "https" => origin_of(url)
+            .is_some_and(|origin| allowed.contains(&origin) || crate::asset_server::is_local_origin(&origin)),
+        _ => false,
+    }
+}
+
+/// 在标签页上拦截请求：不在允许范围内的请求被拒绝，返回被拒绝的地址列表
+pub fn install_guard(tab: &headless_chrome::Tab, allowed: HashSet<String>) -> Result<Arc<Mutex<Vec<String>>>, AppError> {
+    let blocked = Arc::new(Mutex::new(Vec::new()));
+    let record = blocked.clone();
+    let patterns = [Fetch::RequestPattern { url_pattern: Some("*".to_string()), resource_Type: None, request_stage: None }];
+    tab.enable_fetch(Some(&patterns), None)
+        .map_err(|e| AppError::browser("BROWSER_INTERCEPT", e))?;
+    tab.enable_request_interception(Arc::new(move |_transport, _session_id, event: Fetch::events::RequestPausedEvent| {
+        let url = event.params.request.url;
+        if is_request_allowed(&url, &allowed) {
+            return RequestPausedDecision::Continue(None);
+        }
+        tracing::warn!(url = %url, "已拒绝外部请求");
+        record.lock().unwrap_or_else(|e| e.into_inner()).push(url);
+        RequestPausedDecision::Fail(Fetch::FailRequest {
+            request_id: event.params.request_id,
+            error_reason: Network::ErrorReason::BlockedByClient,
+        })
+    }))
+    .map_err(|e| AppError::browser("BROWSER_INTERCEPT", e))?;
+    Ok(blocked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origins_are_normalized() {
+        assert_eq!(origin_of("https://CDN.example.com/a.css").as_deref(), Some("https://cdn.example.com"));
+        assert_eq!(origin_of("http://example.com:80/x").as_deref(), Some("http://example.com"));
+        assert_eq!(origin_of("https://example.com:8443?q").as_deref(), Some("https://example.com:8443"));
+        assert_eq!(origin_of("https://user@example.com/").as_deref(), Some("https://example.com"));
+        assert_eq!(origin_of("//fonts.example.com/f.woff2").as_deref(), Some("https://fonts.example.com"));
+        for url in ["images/a.png", "data:image/png;base64,AA", "file:///tmp/a.png", "ftp://example.com/a", "https:///x"] {
+            assert_eq!(origin_of(url), None, "{}", url);
+        }
+    }
+
+    #[test]
+    fn analyze_lists_loaded_resources_by_origin() {
+        let html = r#"
+            <link rel="stylesheet" href="https://cdn.example.com/a.css">
+            <link rel="canonical" href="https://site.example.com/">
+            <a href="https://site.example.com/page">link</a>
+            <img src="local.png" srcset="https://img.example.com/a.png 1x, https://img.example.com/b.png 2x">
+            <div style="background: url('https://img.example.com/bg.jpg')"></div>
+            <style>@import url("https://cdn.example.com/b.css"); @font-face { src: url(https://fonts.example.com/f.woff2) }</style>
+            <script src="https://cdn.example.com/a.js"></script>
+        "#;
+        let report = analyze(html);
+        assert_eq!(
+            report.origin_names(),
+            ["https://cdn.example.com", "https://fonts.example.com", "https://img.example.com"]
+        );
+        let kinds: Vec<ResourceKind> = report.origins[0].resources.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, [ResourceKind::Stylesheet, ResourceKind::Script, ResourceKind::Stylesheet]);
+        assert_eq!(report.origins[1].resources[0].kind, ResourceKind::Font);
+        assert_eq!(report.origins[2].resources.len(), 3);
+    }
+
+    #[test]
+    fn access_follows_the_policy() {
+        let html = r#"<img src="https://img.example.com/a.png">"#;
+        assert!(matches!(resolve_access(html, ExternalResourcePolicy::Allow, None, false), Ok(NetworkAccess::Unrestricted)));
+        assert!(matches!(
+            resolve_access(html, ExternalResourcePolicy::Allow, None, true),
+            Ok(NetworkAccess::Restricted(allowed)) if allowed.is_empty()
+        ));
+        assert!(matches!(
+            resolve_access(html, ExternalResourcePolicy::Ask, None, false),
+            Err(AppError::ExternalResources { origins }) if origins == ["https://img.example.com"]
+        ));
+        let confirmed = ["https://img.example.com/".to_string()];
+        assert!(matches!(
+            resolve_access(html, ExternalResourcePolicy::Ask, Some(&confirmed), false),
+            Ok(NetworkAccess::Restricted(allowed)) if allowed.contains("https://img.example.com")
+        ));
+        assert!(matches!(
+            resolve_access("<p>offline</p>", ExternalResourcePolicy::Ask, None, false),
+            Ok(NetworkAccess::Restricted(allowed)) if allowed.is_empty()
+        ));
+    }
+
+    #[test]
+    fn restricted_mode_only_passes_local_and_allowed_requests() {
+        let allowed: HashSet<String> = ["https://img.example.com".to_string()].into();
+        for url in ["data:image/png;base64,AA", "blob:null/1", "about:blank", "file:///tmp/a.png", "https://img.example.com/a.png"] {
+            assert!(is_request_allowed(url, &allowed), "{}", url);
+        }
+        for url in ["https://cdn.example.com/a.css", "ftp://example.com/a", "ws://example.com/", "chrome-extension://x/y", "unknown"] {
+            assert!(!is_request_allowed(url, &allowed), "{}", url);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! 封面：front matter 中 `cover: true` 时，用 `title`、`subtitle`、`author`、`date`、`logo`
+//! 生成单独一页的封面，位于正文之前。`cover` 也可以是映射，其中的字段覆盖顶层同名字段：
+//!
+//! ```yaml
+//! title: 年度技术报告
+//! author: [张三, 李四]
+//! date: 2024-12-01
+//! cover:
+//!   subtitle: 内部评审版
+//!   logo: D:/brand/logo.png
+//! ```
+
+use crate::html_util::escape_html;
+use serde_yaml::Value;
+use std::path::Path;
+
+/// 标量或列表转换为文字（列表以顿号连接）
+pub fn text_of(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Sequence(items) => items.iter().filter_map(text_of).collect::<Vec<_>>().join("、"),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// 取 `cover` 映射中的字段，没有时取顶层字段
+fn field(front_matter: &Value, key: &str) -> Option<String> {
+    front_matter
+        .get("cover")
+        .and_then(|cover| cover.get(key))
+        .or_else(|| front_matter.get(key))
+        .and_then(text_of)
+}
+
+pub fn cover_enabled(front_matter: &Value) -> bool {
+    matches!(front_matter.get("cover"), Some(Value::Bool(true) | Value::Mapping(_)))
+}
+
+/// 封面使用的 logo 链接；没有开启 `cover` 时返回 `None`
+pub fn logo(front_matter: &Value) -> Option<String> {
+    cover_enabled(front_matter).then(|| field(front_matter, "logo")).flatten()
+}
+
+/// 按 front matter 生成封面；没有开启 `cover` 时返回 `None`。未指定标题时使用 `fallback_title`
+pub fn cover_html(front_matter: &Value, fallback_title: &str) -> Option<String> {
+    if !cover_enabled(front_matter) {
+        return None;
+    }
+
+    let mut html = String::from(r#"<section class="cover-page">"#);
+    if let Some(logo) = logo(front_matter) {
+        let src = if Path::new(&logo).is_absolute() { crate::file_url(Path::new(&logo)) } else { logo };
+        html.push_str(&format!(r#"<img class="cover-logo" src="{}" alt="" />"#, escape_html(&src)));
+    }
+    let title = field(front_matter, "title").unwrap_or_else(|| fallback_title.to_string());
+    html.push_str(&format!(r#"<div class="cover-title">{}</div>"#, escape_html(&title)));
+    if let Some(subtitle) = field(front_matter, "subtitle") {
+        html.push_str(&format!(r#"<div class="cover-subtitle">{}</div>"#, escape_html(&subtitle)));
+    }
+    let meta: String = [("author", "cover-author"), ("date", "cover-date")]
+        .iter()
+        .filter_map(|(key, class)| {
+            field(front_matter, key).map(|text| format!(r#"<div class="{}">{}</div>"#, class, escape_html(&text)))
+        })
+        .collect();
+    if !meta.is_empty() {
+        html.push_str(&format!(r#"<div class="cover-meta">{}</div>"#, meta));
+    }
+    html.push_str("</section>");
+    Some(html)
+}
@@ -0,0 +1,216 @@
+//! 数据表格：将 ```table-json / ```table-yaml 代码块渲染为格式化表格
+//!
+//! 代码块内容可以直接是行数组，也可以是带配置的对象：
+//!
+//! ```yaml
+//! caption: 季度销售
+//! columns:
+//!   - region
+//!   - { key: amount, label: 金额, format: { decimals: 2, thousands: true, prefix: "¥" } }
+//! format:
+//!   ratio: { decimals: 1, percent: true }
+//! data:
+//!   - { region: 华东, amount: 1234567.8, ratio: 0.42 }
+//! ```
+
+use crate::html_util::{escape_html, unescape_html};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct NumberFormat {
+    /// 保留小数位数
+    decimals: Option<usize>,
+    /// 是否使用千分位分隔符
+    thousands: bool,
+    /// 按百分比显示（数值乘以 100 并追加 %）
+    percent: bool,
+    prefix: String,
+    suffix: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum ColumnSpec {
+    Key(String),
+    Detailed {
+        key: String,
+        label: Option<String>,
+        format: Option<NumberFormat>,
+    },
+}
+
+impl ColumnSpec {
+    fn key(&self) -> &str {
+        match self {
+            ColumnSpec::Key(key) | ColumnSpec::Detailed { key, .. } => key,
+        }
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            ColumnSpec::Detailed { label: Some(label), .. } => label,
+            _ => self.key(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TableSpec {
+    caption: Option<String>,
+    columns: Vec<ColumnSpec>,
+    format: HashMap<String, NumberFormat>,
+    data: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TableSource {
+    Rows(Vec<Value>),
+    Spec(TableSpec),
+}
+
+fn format_number(value: f64, format: &NumberFormat) -> String {
+    let value = if format.percent { value * 100.0 } else { value };
+    let text = match format.decimals {
+        Some(decimals) => format!("{:.*}", decimals, value),
+        None => value.to_string(),
+    };
+
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned.to_string()),
+        None => ("", text),
+    };
+
+    let text = if format.thousands {
+        let (int_part, frac_part) = match text.split_once('.') {
+            Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+            None => (text.as_str(), None),
+        };
+        let groups: Vec<&str> = int_part
+            .as_bytes()
+            .rchunks(3)
+            .rev()
+            .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+            .collect();
+        match frac_part {
+            Some(frac) => format!("{}.{}", groups.join(","), frac),
+            None => groups.join(","),
+        }
+    } else {
+        text
+    };
+
+    let percent = if format.percent { "%" } else { "" };
+    format!("{}{}{}{}{}", sign, format.prefix, text, percent, format.suffix)
+}
+
+fn format_cell(value: Option<&Value>, format: Option<&NumberFormat>) -> (String, bool) {
+    match value {
+        None | Some(Value::Null) => (String::new(), false),
+        Some(Value::Number(n)) => {
+            let text = match (n.as_f64(), format) {
+                (Some(f), Some(format)) => format_number(f, format),
+                _ => n.to_string(),
+            };
+            (text, true)
+        }
+        Some(Value::String(s)) => (s.clone(), false),
+        Some(Value::Bool(b)) => (b.to_string(), false),
+        Some(other) => (other.to_string(), false),
+    }
+}
+
+fn render_table(spec: TableSpec) -> String {
+    let mut columns = spec.columns;
+    let mut rows = spec.data;
+
+    // 行为数组时：未指定列则把第一行当作表头
+    if rows.first().is_some_and(Value::is_array) && columns.is_empty() {
+        if let Value::Array(header) = rows.remove(0) {
+            columns = header
+                .iter()
+                .map(|v| ColumnSpec::Key(v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string())))
+                .collect();
+        }
+    }
+
+    // 未指定列：按出现顺序收集所有对象键
+    if columns.is_empty() {
+        for row in &rows {
+            if let Value::Object(map) = row {
+                for key in map.keys() {
+                    if !columns.iter().any(|c| c.key() == key) {
+                        columns.push(ColumnSpec::Key(key.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut html = String::from("<table class=\"data-table\">");
+    if let Some(caption) = &spec.caption {
+        html.push_str(&format!("<caption>{}</caption>", escape_html(caption)));
+    }
+    html.push_str("<thead><tr>");
+    for column in &columns {
+        html.push_str(&format!("<th>{}</th>", escape_html(column.label())));
+    }
+    html.push_str("</tr></thead><tbody>");
+
+    for row in &rows {
+        html.push_str("<tr>");
+        for (idx, column) in columns.iter().enumerate() {
+            let value = match row {
+                Value::Object(map) => map.get(column.key()),
+                Value::Array(items) => items.get(idx),
+                _ => None,
+            };
+            let format = match column {
+                ColumnSpec::Detailed { format: Some(format), .. } => Some(format),
+                _ => spec.format.get(column.key()),
+            };
+            let (text, numeric) = format_cell(value, format);
+            let class = if numeric { " class=\"num\"" } else { "" };
+            html.push_str(&format!("<td{}>{}</td>", class, escape_html(&text)));
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</tbody></table>");
+    html
+}
+
+fn parse_source(kind: &str, source: &str) -> Result<TableSpec, String> {
+    let parsed: TableSource = match kind {
+        "json" => serde_json::from_str(source).map_err(|e| e.to_string())?,
+        _ => serde_yaml::from_str(source).map_err(|e| e.to_string())?,
+    };
+    Ok(match parsed {
+        TableSource::Rows(data) => TableSpec { data, ..Default::default() },
+        TableSource::Spec(spec) => spec,
+    })
+}
+
+/// 将 `<pre><code class="language-table-json|yaml">` 代码块替换为表格；解析失败时保留原代码块并附带错误提示
+pub fn apply_data_tables(html: &str) -> String {
+    let re_block =
+        Regex::new(r#"(?s)<pre><code class="language-table-(json|yaml)">(.*?)</code></pre>"#).unwrap();
+
+    re_block
+        .replace_all(html, |caps: &regex::Captures| {
+            let source = unescape_html(&caps[2]);
+            match parse_source(&caps[1], &source) {
+                Ok(spec) => render_table(spec),
+                Err(e) => format!(
+                    "<div class=\"data-table-error\">表格数据解析失败: {}</div>{}",
+                    escape_html(&e),
+                    &caps[0]
+                ),
+            }
+        })
+        .to_string()
+}
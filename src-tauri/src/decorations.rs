@@ -0,0 +1,166 @@
+//! 页面装饰：分级横幅与页脚声明。
+//!
+//! 通过 Chrome 的页眉/页脚模板打印在每一页的页边距中，不属于正文，
+//! 文档中的 HTML/CSS 无法隐藏或覆盖。
+
+use crate::html_util::escape_html;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 无页眉页脚时的页边距（英寸）
+pub const BASE_MARGIN_IN: f64 = 0.4;
+/// 每条横幅或页脚文字占用的高度（英寸）
+const LINE_HEIGHT_IN: f64 = 0.22;
+
+fn default_banner_background() -> String {
+    "#c00000".to_string()
+}
+
+fn default_banner_color() -> String {
+    "#ffffff".to_string()
+}
+
+/// 横幅：同时显示在每页的顶部与底部
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Banner {
+    pub text: String,
+    #[serde(default = "default_banner_background")]
+    pub background: String,
+    #[serde(default = "default_banner_color")]
+    pub color: String,
+}
+
+impl Banner {
+    pub fn new(text: &str) -> Self {
+        Banner {
+            text: text.to_string(),
+            background: default_banner_background(),
+            color: default_banner_color(),
+        }
+    }
+
+    /// 按常见的密级标识配色，未知密级使用默认的红色
+    pub fn for_classification(text: &str) -> Self {
+        let upper = text.trim().to_uppercase();
+        let (background, color) = match upper.as_str() {
+            "UNCLASSIFIED" | "PUBLIC" | "公开" => ("#007a33", "#ffffff"),
+            "CUI" | "INTERNAL" | "INTERNAL USE ONLY" | "内部" => ("#502b85", "#ffffff"),
+            "CONFIDENTIAL" | "秘密" => ("#0033a0", "#ffffff"),
+            "SECRET" | "机密" => ("#c8102e", "#ffffff"),
+            "TOP SECRET" | "绝密" => ("#ff8c00", "#000000"),
+            _ => return Banner::new(text.trim()),
+        };
+        Banner {
+            text: text.trim().to_string(),
+            background: background.to_string(),
+            color: color.to_string(),
+        }
+    }
+}
+
+/// front matter 中的 `classification`：可以是密级文字，也可以是 `{ text, background, color }`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ClassificationSpec {
+    Text(String),
+    Detailed {
+        text: String,
+        background: Option<String>,
+        color: Option<String>,
+    },
+}
+
+/// 从 front matter 的 `classification` 字段生成横幅
+pub fn classification_banner(front_matter: &serde_yaml::Value) -> Option<Banner> {
+    let spec: ClassificationSpec = serde_yaml::from_value(front_matter.get("classification")?.clone()).ok()?;
+    let banner = match spec {
+        ClassificationSpec::Text(text) => Banner::for_classification(&text),
+        ClassificationSpec::Detailed { text, background, color } => {
+            let preset = Banner::for_classification(&text);
+            Banner {
+                background: background.unwrap_or(preset.background),
+                color: color.unwrap_or(preset.color),
+                text: preset.text,
+            }
+        }
+    };
+    (!banner.text.is_empty()).then_some(banner)
+}
+
+/// 只接受 `#rgb`、颜色名与 `rgb()/rgba()`，避免注入样式
+pub fn safe_color(color: &str, fallback: &str) -> String {
+    let re_color = Regex::new(r"^(#[0-9A-Fa-f]{3,8}|[A-Za-z]+|rgba?\([0-9.,%\s]+\))$").unwrap();
+    let color = color.trim();
+    if re_color.is_match(color) {
+        color.to_string()
+    } else {
+        fallback.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PageDecorations {
+    /// 按顺序叠放的横幅
+    pub banners: Vec<Banner>,
+    /// 页脚文字（位于底部横幅上方）
+    pub footer_lines: Vec<String>,
+}
+
+impl PageDecorations {
+    pub fn is_empty(&self) -> bool {
+        self.banners.is_empty() && self.footer_lines.is_empty()
+    }
+
+    /// 添加横幅（相同文字的横幅只保留一条）
+    pub fn add_banner(&mut self, banner: Banner) {
+        if !self.banners.iter().any(|b| b.text == banner.text) {
+            self.banners.push(banner);
+        }
+    }
+
+    pub fn margin_top(&self) -> f64 {
+        BASE_MARGIN_IN + self.banners.len() as f64 * LINE_HEIGHT_IN
+    }
+
+    pub fn margin_bottom(&self) -> f64 {
+        BASE_MARGIN_IN + (self.banners.len() + self.footer_lines.len()) as f64 * LINE_HEIGHT_IN
+    }
+
+    fn banners_html(&self) -> String {
+        self.banners
+            .iter()
+            .map(|banner| {
+                format!(
+                    r#"<div style="text-align:center;font-weight:bold;letter-spacing:1px;padding:2px 0;background:{};color:{};">{}</div>"#,
+                    safe_color(&banner.background, "#c00000"),
+                    safe_color(&banner.color, "#ffffff"),
+                    escape_html(&banner.text)
+                )
+            })
+            .collect()
+    }
+
+    fn wrap(content: &str) -> String {
+        // 模板默认字号为 0，需要显式设置；背景色需要 print-color-adjust 才会打印
+        format!(
+            r#"<div style="width:100%;padding:0 {margin}in;box-sizing:border-box;font-family:sans-serif;font-size:9px;-webkit-print-color-adjust:exact;print-color-adjust:exact;">{content}</div>"#,
+            margin = BASE_MARGIN_IN,
+            content = content
+        )
+    }
+
+    /// Chrome 页眉模板
+    pub fn header_template(&self) -> String {
+        Self::wrap(&self.banners_html())
+    }
+
+    /// Chrome 页脚模板
+    pub fn footer_template(&self) -> String {
+        let footer: String = self
+            .footer_lines
+            .iter()
+            .map(|line| format!(r#"<div style="text-align:center;color:#666;padding:2px 0;">{}</div>"#, escape_html(line)))
+            .collect();
+        Self::wrap(&format!("{}{}", footer, self.banners_html()))
+    }
+}
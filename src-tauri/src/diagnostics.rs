@@ -0,0 +1,228 @@
+//! 诊断与日志：
+//!  - 使用 `tracing` 记录导出流程，按天滚动写入应用数据目录下的 `logs/`
+//!  - 在内存中保留最近的错误与最近一次导出的各阶段耗时
+//!  - `collect_diagnostics` 将以上信息连同日志文件打包为 zip，方便用户提交问题
+
+use crate::browser;
+use crate::error::AppError;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+/// 保留的日志文件天数
+const MAX_LOG_FILES: usize = 7;
+/// 内存中保留的最近错误条数
+const MAX_RECENT_ERRORS: usize = 50;
+
+/// 持有日志写入线程的 guard，应用退出前不能被释放
+pub struct LogGuard {
+    _guard: WorkerGuard,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorRecord {
+    pub timestamp_ms: u128,
+    pub context: String,
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageTiming {
+    pub stage: String,
+    pub duration_ms: u128,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportTimings {
+    pub timestamp_ms: u128,
+    pub output_path: String,
+    pub success: bool,
+    pub total_ms: u128,
+    pub stages: Vec<StageTiming>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub browser_path: Option<String>,
+    pub browser_version: Option<String>,
+    pub browser_error: Option<String>,
+    pub last_export: Option<ExportTimings>,
+    pub recent_errors: Vec<ErrorRecord>,
+    pub log_dir: Option<String>,
+    pub archive_path: String,
+}
+
+/// 按阶段计时：调用 `start` 开始新阶段时自动结束上一阶段
+pub struct StageTimer {
+    started: Instant,
+    current: Option<(String, Instant)>,
+    stages: Vec<StageTiming>,
+}
+
+impl StageTimer {
+    pub fn new() -> Self {
+        StageTimer { started: Instant::now(), current: None, stages: Vec::new() }
+    }
+
+    pub fn start(&mut self, stage: &str) {
+        self.end_current();
+        tracing::debug!(stage, "阶段开始");
+        self.current = Some((stage.to_string(), Instant::now()));
+    }
+
+    fn end_current(&mut self) {
+        if let Some((stage, started)) = self.current.take() {
+            self.stages.push(StageTiming { stage, duration_ms: started.elapsed().as_millis() });
+        }
+    }
+
+    pub fn finish(mut self, output_path: &str, success: bool) -> ExportTimings {
+        self.end_current();
+        ExportTimings {
+            timestamp_ms: now_ms(),
+            output_path: output_path.to_string(),
+            success,
+            total_ms: self.started.elapsed().as_millis(),
+            stages: self.stages,
+        }
+    }
+}
+
+static RECENT_ERRORS: Mutex<VecDeque<ErrorRecord>> = Mutex::new(VecDeque::new());
+static LAST_EXPORT: Mutex<Option<ExportTimings>> = Mutex::new(None);
+
+pub fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+fn log_dir(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join("logs"))
+}
+
+/// 初始化日志：写入按天滚动的日志文件；失败时不影响应用运行
+pub fn init_logging(app: &tauri::AppHandle) -> Option<LogGuard> {
+    let dir = log_dir(app)?;
+    fs::create_dir_all(&dir).ok()?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("md2pdf")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .ok()?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_target(false)
+        .try_init()
+        .ok()?;
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "日志已初始化");
+    Some(LogGuard { _guard: guard })
+}
+
+/// 记录一条错误，供诊断报告使用
+pub fn record_error(context: &str, error: &AppError) {
+    tracing::error!(context, code = error.code(), "{}", error);
+    if let Ok(mut errors) = RECENT_ERRORS.lock() {
+        if errors.len() >= MAX_RECENT_ERRORS {
+            errors.pop_front();
+        }
+        errors.push_back(ErrorRecord {
+            timestamp_ms: now_ms(),
+            context: context.to_string(),
+            code: error.code().to_string(),
+            message: error.to_string(),
+        });
+    }
+}
+
+/// 记录最近一次导出的耗时
+pub fn record_export(timings: ExportTimings) {
+    tracing::info!(
+        output = %timings.output_path,
+        success = timings.success,
+        total_ms = timings.total_ms as u64,
+        "导出结束"
+    );
+    if let Ok(mut last) = LAST_EXPORT.lock() {
+        *last = Some(timings);
+    }
+}
+
+fn add_file_to_zip<W: Write + std::io::Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    name: &str,
+    path: &Path,
+) -> Result<(), AppError> {
+    let content = fs::read(path).map_err(|e| AppError::file(path, e))?;
+    zip.start_file(name, zip::write::SimpleFileOptions::default())
+        .map_err(|e| AppError::DiagnosticsError(e.to_string()))?;
+    zip.write_all(&content)?;
+    Ok(())
+}
+
+/// 收集诊断信息并打包为 zip（包含 diagnostics.json 与日志文件）
+#[tauri::command]
+pub async fn collect_diagnostics(app: tauri::AppHandle, output_path: String) -> Result<DiagnosticsReport, AppError> {
+    tokio::task::spawn_blocking(move || crate::error::catch_panic("collect_diagnostics", || {
+        let browser_info = browser::detect_browser_info();
+        let log_dir = log_dir(&app);
+        let report = DiagnosticsReport {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            browser_path: browser_info.path,
+            browser_version: browser_info.version,
+            browser_error: browser_info.error,
+            last_export: LAST_EXPORT.lock().ok().and_then(|last| last.clone()),
+            recent_errors: RECENT_ERRORS
+                .lock()
+                .map(|errors| errors.iter().cloned().collect())
+                .unwrap_or_default(),
+            log_dir: log_dir.as_ref().map(|dir| dir.to_string_lossy().to_string()),
+            archive_path: output_path.clone(),
+        };
+
+        let file = fs::File::create(&output_path).map_err(|e| AppError::file(&output_path, e))?;
+        let mut zip = zip::ZipWriter::new(file);
+
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| AppError::DiagnosticsError(e.to_string()))?;
+        zip.start_file("diagnostics.json", zip::write::SimpleFileOptions::default())
+            .map_err(|e| AppError::DiagnosticsError(e.to_string()))?;
+        zip.write_all(json.as_bytes())?;
+
+        if let Some(dir) = &log_dir {
+            if let Ok(entries) = fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.is_file() {
+                        let name = format!("logs/{}", entry.file_name().to_string_lossy());
+                        add_file_to_zip(&mut zip, &name, &path)?;
+                    }
+                }
+            }
+        }
+
+        zip.finish().map_err(|e| AppError::DiagnosticsError(e.to_string()))?;
+        tracing::info!(archive = %output_path, "诊断信息已导出");
+        Ok(report)
+    }))
+    .await
+    .map_err(|e| crate::error::join_error("collect_diagnostics", e))?
+}
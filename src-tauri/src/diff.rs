@@ -0,0 +1,253 @@
+//! 文档版本对比：按编辑器的块（见 `split_markdown_blocks`）对齐两个版本，公式、表格与代码块作为整体比较。
+//!
+//!  - `diff_markdown`：返回按顺序排列的差异段（相同、新增、删除、替换），行号从 1 开始
+//!  - 修订导出：导出选项 `compare_with` 指定旧版本文件时，新旧内容合并为一篇文档，
+//!    新增的块包在 `<div class="diff-insert">` 中、删除的块包在 `<div class="diff-delete">` 中，再按普通文档导出
+//!
+//! 多行段落在块模型中逐行拆分，修改段落中的一行时该段落会在修改处断开。front matter 始终取新版本，不标记。
+
+use crate::error::{catch_panic, AppError};
+use crate::parser_mode::ParserMode;
+use crate::MarkdownBlock;
+use serde::Serialize;
+use std::fs;
+
+/// 动态规划表的最大单元数，超过时中间部分整体视为替换
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+    Equal,
+    Insert,
+    Delete,
+    Replace,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffHunk {
+    pub kind: DiffKind,
+    /// 旧版本中的行范围（首行、末行），新增时为空
+    pub old_lines: Option<(usize, usize)>,
+    /// 新版本中的行范围，删除时为空
+    pub new_lines: Option<(usize, usize)>,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+/// 比较用的块内容：忽略行尾空白
+fn block_key(block: &MarkdownBlock) -> String {
+    block.content.lines().map(str::trim_end).collect::<Vec<_>>().join("\n")
+}
+
+/// 最长公共子序列对齐，返回相同块的下标对
+fn align(old: &[String], new: &[String]) -> Vec<(usize, usize)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (n, m) = (old.len() - prefix - suffix, new.len() - prefix - suffix);
+
+    let mut pairs: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    if n > 0 && m > 0 && (n + 1) * (m + 1) <= MAX_LCS_CELLS {
+        let a = &old[prefix..prefix + n];
+        let b = &new[prefix..prefix + m];
+        // lengths[i][j]：a[i..] 与 b[j..] 的公共子序列长度
+        let width = m + 1;
+        let mut lengths = vec![0u32; (n + 1) * width];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i * width + j] = if a[i] == b[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if a[i] == b[j] {
+                pairs.push((prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+    pairs.extend((0..suffix).map(|k| (old.len() - suffix + k, new.len() - suffix + k)));
+    pairs
+}
+
+/// 一组连续块在原文中的行范围与文本（保留块之间的空行）
+fn span(blocks: &[MarkdownBlock], lines: &[&str]) -> (Option<(usize, usize)>, String) {
+    match (blocks.first(), blocks.last()) {
+        (Some(first), Some(last)) => {
+            let text = lines.get(first.start_line - 1..last.end_line).unwrap_or(&[]).join("\n");
+            (Some((first.start_line, last.end_line)), text)
+        }
+        _ => (None, String::new()),
+    }
+}
+
+/// `skip_front_matter` 时不比较 front matter
+fn diff_blocks(old: &str, new: &str, mode: ParserMode, skip_front_matter: bool) -> Vec<DiffHunk> {
+    let old = old.replace("\r\n", "\n");
+    let new = new.replace("\r\n", "\n");
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let blocks = |markdown: &str| -> Vec<MarkdownBlock> {
+        crate::split_markdown_blocks(markdown, mode)
+            .into_iter()
+            .filter(|block| !(skip_front_matter && block.block_type == "yaml"))
+            .collect()
+    };
+    let old_blocks = blocks(&old);
+    let new_blocks = blocks(&new);
+    let old_keys: Vec<String> = old_blocks.iter().map(block_key).collect();
+    let new_keys: Vec<String> = new_blocks.iter().map(block_key).collect();
+
+    let mut hunks: Vec<DiffHunk> = Vec::new();
+    let mut push = |kind: DiffKind, old_range: &[MarkdownBlock], new_range: &[MarkdownBlock]| {
+        if old_range.is_empty() && new_range.is_empty() {
+            return;
+        }
+        let (old_span, old_text) = span(old_range, &old_lines);
+        let (new_span, new_text) = span(new_range, &new_lines);
+        hunks.push(DiffHunk { kind, old_lines: old_span, new_lines: new_span, old_text, new_text });
+    };
+
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = align(&old_keys, &new_keys).into_iter().peekable();
+    while i < old_blocks.len() || j < new_blocks.len() {
+        let (next_i, next_j) = pairs.peek().copied().unwrap_or((old_blocks.len(), new_blocks.len()));
+        let kind = match (next_i > i, next_j > j) {
+            (true, true) => DiffKind::Replace,
+            (true, false) => DiffKind::Delete,
+            (false, true) => DiffKind::Insert,
+            (false, false) => DiffKind::Equal,
+        };
+        if kind != DiffKind::Equal {
+            push(kind, &old_blocks[i..next_i], &new_blocks[j..next_j]);
+            (i, j) = (next_i, next_j);
+            continue;
+        }
+        // 连续相同的块合并为一段
+        let (start_i, start_j) = (i, j);
+        while pairs.peek() == Some(&(i, j)) {
+            pairs.next();
+            i += 1;
+            j += 1;
+        }
+        push(DiffKind::Equal, &old_blocks[start_i..i], &new_blocks[start_j..j]);
+    }
+    hunks
+}
+
+fn wrap(class: &str, text: &str) -> String {
+    format!("<div class=\"{}\">\n\n{}\n\n</div>", class, text)
+}
+
+/// 新旧版本合并为带修订标记的 Markdown
+pub fn tracked_markdown(old: &str, new: &str, mode: ParserMode) -> String {
+    let new_body = crate::front_matter::body(new);
+    let front = &new[..new.len() - new_body.len()];
+    let parts: Vec<String> = diff_blocks(old, new, mode, true)
+        .into_iter()
+        .map(|hunk| match hunk.kind {
+            DiffKind::Equal => hunk.new_text,
+            DiffKind::Insert => wrap("diff-insert", &hunk.new_text),
+            DiffKind::Delete => wrap("diff-delete", &hunk.old_text),
+            DiffKind::Replace => {
+                format!("{}\n\n{}", wrap("diff-delete", &hunk.old_text), wrap("diff-insert", &hunk.new_text))
+            }
+        })
+        .collect();
+    format!("{}{}\n", front, parts.join("\n\n"))
+}
+
+/// 导出选项中指定了旧版本文件时，返回合并后的修订文档，否则原样返回
+pub(crate) fn apply_compare(markdown: &str, compare_with: Option<&str>, mode: ParserMode) -> Result<String, AppError> {
+    let Some(path) = compare_with else { return Ok(markdown.to_string()) };
+    let old = fs::read_to_string(path).map_err(|e| AppError::file(path, e))?;
+    Ok(tracked_markdown(&old, markdown, mode))
+}
+
+/// 按块比较两个版本
+#[tauri::command]
+pub fn diff_markdown(old: &str, new: &str, mode: Option<ParserMode>) -> Result<Vec<DiffHunk>, AppError> {
+    let mode = ParserMode::resolve(mode, new);
+    catch_panic("diff_markdown", || Ok(diff_blocks(old, new, mode, false)))
+}
+
+/// 带修订标记的合并文档（导出前使用）；`old_path` 为旧版本文件
+#[tauri::command]
+pub fn track_changes(old_path: String, markdown: &str, mode: Option<ParserMode>) -> Result<String, AppError> {
+    let mode = ParserMode::resolve(mode, markdown);
+    apply_compare(markdown, Some(&old_path), mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn align_trims_common_prefix_and_suffix() {
+        let pairs = align(&keys(&["a", "b", "c", "d"]), &keys(&["a", "x", "c", "d"]));
+        assert_eq!(pairs, vec![(0, 0), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn align_finds_the_longest_common_subsequence_between_the_ends() {
+        let pairs = align(&keys(&["a", "b", "c", "y", "d"]), &keys(&["a", "c", "x", "y", "d"]));
+        assert_eq!(pairs, vec![(0, 0), (2, 1), (3, 3), (4, 4)]);
+        assert!(align(&keys(&["a"]), &keys(&[])).is_empty());
+    }
+
+    #[test]
+    fn oversized_middle_is_left_unaligned() {
+        // 中间部分超过 MAX_LCS_CELLS 时不再求 LCS，只保留首尾相同的块
+        let side = |prefix: &str| -> Vec<String> {
+            let mut items = vec!["start".to_string()];
+            items.extend((0..2000).map(|i| format!("{}{}", prefix, i)));
+            items.push("common".to_string());
+            items.push("end".to_string());
+            items.insert(1000, "shared".to_string());
+            items
+        };
+        let (old, new) = (side("old"), side("new"));
+        // 去掉 1 个相同的开头块与 2 个相同的结尾块后，(n + 1) * (m + 1) 超过上限
+        assert!((old.len() - 2) * (new.len() - 2) > MAX_LCS_CELLS);
+        let pairs = align(&old, &new);
+        assert_eq!(pairs, vec![(0, 0), (old.len() - 2, new.len() - 2), (old.len() - 1, new.len() - 1)]);
+    }
+
+    #[test]
+    fn diff_blocks_reports_hunks_with_line_ranges() {
+        let old = "# 标题\n\n第一段\n\n第二段\n\n旧结尾\n";
+        let new = "# 标题\n\n第一段（修改）\n\n第二段\n";
+        let hunks = diff_blocks(old, new, ParserMode::default(), false);
+        let kinds: Vec<DiffKind> = hunks.iter().map(|h| h.kind).collect();
+        assert_eq!(kinds, vec![DiffKind::Equal, DiffKind::Replace, DiffKind::Equal, DiffKind::Delete]);
+        assert_eq!((hunks[1].old_lines, hunks[1].new_lines), (Some((3, 3)), Some((3, 3))));
+        assert_eq!((hunks[1].old_text.as_str(), hunks[1].new_text.as_str()), ("第一段", "第一段（修改）"));
+        assert_eq!((hunks[3].old_lines, hunks[3].new_lines), (Some((7, 7)), None));
+    }
+
+    #[test]
+    fn tracked_markdown_wraps_replaced_blocks() {
+        let old = "---\ntitle: 旧\n---\n\n第一段\n\n第二段\n";
+        let new = "---\ntitle: 新\n---\n\n第一段\n\n第二段（修改）\n\n新增段落\n";
+        let tracked = tracked_markdown(old, new, ParserMode::default());
+        assert!(tracked.starts_with("---\ntitle: 新\n---\n"));
+        assert!(!tracked.contains("title: 旧"));
+        assert!(tracked.contains(
+            "<div class=\"diff-delete\">\n\n第二段\n\n</div>\n\n<div class=\"diff-insert\">\n\n第二段（修改）\n\n新增段落\n\n</div>"
+        ));
+        assert!(tracked.contains("第一段\n\n<div class=\"diff-delete\">"));
+    }
+}
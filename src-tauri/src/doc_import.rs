@@ -0,0 +1,1089 @@
+//! 导入 Word（.docx）与 HTML 文档，转换为 Markdown。
+//!
+//! 保留标题、段落、粗体 / 斜体 / 删除线 / 行内代码、链接、列表（含嵌套）、表格、引用与代码块；
+//! 其余格式（字体、颜色、对齐等）忽略。图片保存到文档旁的 `assets/` 目录（文件名为内容哈希），
+//! 远程图片保留原地址。转换结果写入源文件旁的同名 `.md` 文件（已存在时加序号），不覆盖已有文件。
+
+use crate::error::AppError;
+use base64::Engine;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportResult {
+    /// 生成的 Markdown 文件
+    pub markdown_path: String,
+    pub markdown: String,
+    /// 保存到 `assets/` 的图片数量（内容相同的图片只计一次）
+    pub images: usize,
+    /// 转换中跳过的内容（如找不到的图片）
+    pub warnings: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// 标记解析：XML 与 HTML 共用的简单 DOM
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|element| element.name == name)
+    }
+
+    /// 深度优先查找第一个同名的后代元素
+    fn find(&self, name: &str) -> Option<&Element> {
+        self.elements().find_map(|element| if element.name == name { Some(element) } else { element.find(name) })
+    }
+
+    /// 所有文本内容
+    fn text(&self) -> String {
+        let mut out = String::new();
+        for child in &self.children {
+            match child {
+                Node::Text(text) => out.push_str(text),
+                Node::Element(element) => out.push_str(&element.text()),
+            }
+        }
+        out
+    }
+}
+
+/// HTML 中没有结束标签的元素
+const VOID_ELEMENTS: &[&str] =
+    &["area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr"];
+
+/// 内容不是标记的元素，导入时整体跳过
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "head", "noscript", "template", "title"];
+
+/// 元素树的最大嵌套深度；更深的元素去掉标签，内容并入上层，避免后续递归遍历栈溢出
+const MAX_DEPTH: usize = 128;
+
+/// 还原字符实体（数字实体与常见的命名实体）
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let re_entity = regex::Regex::new(r"&(#[0-9]{1,7}|#[xX][0-9a-fA-F]{1,6}|[a-zA-Z][a-zA-Z0-9]{1,10});").unwrap();
+    re_entity
+        .replace_all(text, |caps: &regex::Captures| {
+            let name = &caps[1];
+            let code = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok()
+            } else if let Some(dec) = name.strip_prefix('#') {
+                dec.parse().ok()
+            } else {
+                let c = match name {
+                    "amp" => '&',
+                    "lt" => '<',
+                    "gt" => '>',
+                    "quot" => '"',
+                    "apos" => '\'',
+                    "nbsp" => '\u{a0}',
+                    "copy" => '©',
+                    "reg" => '®',
+                    "trade" => '™',
+                    "mdash" => '—',
+                    "ndash" => '–',
+                    "hellip" => '…',
+                    "lsquo" => '‘',
+                    "rsquo" => '’',
+                    "ldquo" => '“',
+                    "rdquo" => '”',
+                    "middot" => '·',
+                    "times" => '×',
+                    "deg" => '°',
+                    _ => return caps[0].to_string(),
+                };
+                Some(c as u32)
+            };
+            code.and_then(char::from_u32).map(String::from).unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+/// 打开 `name` 时需要先关闭的元素（HTML 中可省略结束标签的情况）
+fn implied_close(stack: &[Element], name: &str) -> Option<usize> {
+    let find_open = |targets: &[&str], scope: &[&str]| {
+        for (index, element) in stack.iter().enumerate().rev() {
+            if targets.contains(&element.name.as_str()) {
+                return Some(index);
+            }
+            if scope.contains(&element.name.as_str()) {
+                return None;
+            }
+        }
+        None
+    };
+    match name {
+        "li" => find_open(&["li"], &["ul", "ol"]),
+        "dt" | "dd" => find_open(&["dt", "dd"], &["dl"]),
+        "tr" => find_open(&["tr"], &["table", "thead", "tbody", "tfoot"]),
+        "td" | "th" => find_open(&["td", "th"], &["tr", "table"]),
+        "thead" | "tbody" | "tfoot" => find_open(&["thead", "tbody", "tfoot"], &["table"]),
+        "p" | "div" | "ul" | "ol" | "table" | "pre" | "blockquote" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+        | "hr" | "section" | "article" => {
+            stack.last().filter(|element| element.name == "p").map(|_| stack.len() - 1)
+        }
+        _ => None,
+    }
+}
+
+/// 把 XML 或 HTML 解析为元素树；HTML 模式下名称转为小写，并处理空元素与省略的结束标签
+fn parse_markup(source: &str, html: bool) -> Element {
+    let re_token = regex::Regex::new(
+        r#"(?s)<!--.*?-->|<!\[CDATA\[(.*?)\]\]>|<[!?][^>]*>|<(/?)([A-Za-z][\w:.-]*)((?:[^>"']|"[^"]*"|'[^']*')*?)(/?)>"#,
+    )
+    .unwrap();
+    let re_attr = regex::Regex::new(r#"([^\s=/]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#).unwrap();
+
+    let mut stack: Vec<Element> = vec![Element { name: "#root".to_string(), ..Default::default() }];
+    let push_text = |stack: &mut Vec<Element>, text: &str| {
+        if !text.is_empty() {
+            stack.last_mut().unwrap().children.push(Node::Text(decode_entities(text)));
+        }
+    };
+    let close_to = |stack: &mut Vec<Element>, index: usize| {
+        while stack.len() > index.max(1) {
+            let element = stack.pop().unwrap();
+            stack.last_mut().unwrap().children.push(Node::Element(element));
+        }
+    };
+
+    let mut last = 0;
+    let mut skip_until: Option<String> = None;
+    // 超过深度上限而未入栈的元素名，用于匹配它们的结束标签
+    let mut flattened: Vec<String> = Vec::new();
+    for caps in re_token.captures_iter(source) {
+        let m = caps.get(0).unwrap();
+        let closing = caps.get(2).is_some_and(|c| !c.as_str().is_empty());
+        let name = caps.get(3).map(|n| if html { n.as_str().to_ascii_lowercase() } else { n.as_str().to_string() });
+
+        if let Some(skipped) = &skip_until {
+            if closing && name.as_deref() == Some(skipped.as_str()) {
+                skip_until = None;
+                last = m.end();
+            }
+            continue;
+        }
+        push_text(&mut stack, &source[last..m.start()]);
+        last = m.end();
+
+        if let Some(cdata) = caps.get(1) {
+            stack.last_mut().unwrap().children.push(Node::Text(cdata.as_str().to_string()));
+            continue;
+        }
+        let Some(name) = name else { continue };
+        if closing {
+            if flattened.last() == Some(&name) {
+                flattened.pop();
+            } else if let Some(index) = stack.iter().rposition(|element| element.name == name) {
+                flattened.clear();
+                close_to(&mut stack, index);
+            }
+            continue;
+        }
+        if html && SKIPPED_ELEMENTS.contains(&name.as_str()) {
+            if caps[5].is_empty() {
+                skip_until = Some(name);
+            }
+            continue;
+        }
+        if html {
+            if let Some(index) = implied_close(&stack, &name) {
+                flattened.clear();
+                close_to(&mut stack, index);
+            }
+        }
+        let attrs = re_attr
+            .captures_iter(&caps[4])
+            .map(|a| {
+                let value = a.get(2).or_else(|| a.get(3)).or_else(|| a.get(4)).map(|v| v.as_str()).unwrap_or("");
+                let key = if html { a[1].to_ascii_lowercase() } else { a[1].to_string() };
+                (key, decode_entities(value))
+            })
+            .collect();
+        let element = Element { name, attrs, children: Vec::new() };
+        if !caps[5].is_empty() || (html && VOID_ELEMENTS.contains(&element.name.as_str())) {
+            stack.last_mut().unwrap().children.push(Node::Element(element));
+        } else if stack.len() > MAX_DEPTH {
+            flattened.push(element.name);
+        } else {
+            stack.push(element);
+        }
+    }
+    if skip_until.is_none() {
+        push_text(&mut stack, &source[last..]);
+    }
+    close_to(&mut stack, 1);
+    stack.pop().unwrap()
+}
+
+// ---------------------------------------------------------------------------
+// Markdown 输出
+// ---------------------------------------------------------------------------
+
+/// 行内格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    code: bool,
+}
+
+/// 转义文本中会被当作 Markdown 语法的字符
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '|') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// 用格式标记包裹文本，首尾空白放在标记之外
+fn wrap(text: &str, style: Style) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    let mut inner = trimmed.to_string();
+    if style.code {
+        // 行内代码中不转义，反引号用更长的分隔符包裹
+        let fence = if inner.contains('`') { "``" } else { "`" };
+        let pad = if inner.starts_with('`') || inner.ends_with('`') { " " } else { "" };
+        inner = format!("{fence}{pad}{inner}{pad}{fence}");
+    }
+    if style.strike {
+        inner = format!("~~{}~~", inner);
+    }
+    if style.italic {
+        inner = format!("*{}*", inner);
+    }
+    if style.bold {
+        inner = format!("**{}**", inner);
+    }
+    format!("{}{}{}", leading, inner, trailing)
+}
+
+/// 合并相邻的同格式片段后生成行内 Markdown；片段文本已转义（行内代码为原文）
+fn join_segments(segments: &[(String, Style)]) -> String {
+    let mut merged: Vec<(String, Style)> = Vec::new();
+    for (text, style) in segments {
+        match merged.last_mut() {
+            Some((last, last_style)) if last_style == style => last.push_str(text),
+            _ => merged.push((text.clone(), *style)),
+        }
+    }
+    merged.iter().map(|(text, style)| wrap(text, *style)).collect()
+}
+
+/// 段落开头会被当作块语法的字符
+fn escape_block_start(text: &str) -> String {
+    let re_block = regex::Regex::new(r"^(#{1,6}\s|>|[-+]\s|\d+[.)]\s|={3,}|-{3,})").unwrap();
+    if re_block.is_match(text) {
+        format!("\\{}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// 表格：第一行作为表头，单元格内容中的换行写成 `<br>`
+fn table_markdown(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return String::new();
+    }
+    let row_line = |row: &Vec<String>| {
+        let cells: Vec<String> = (0..columns)
+            .map(|i| row.get(i).map(|cell| cell.trim().replace('\n', "<br>")).unwrap_or_default())
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = vec![row_line(&rows[0]), format!("|{}|", vec![" --- "; columns].join("|"))];
+    lines.extend(rows[1..].iter().map(row_line));
+    lines.join("\n")
+}
+
+/// 输出的块：相邻的列表项之间不空行
+struct Blocks(Vec<(String, bool)>);
+
+impl Blocks {
+    fn push(&mut self, text: String) {
+        if !text.trim().is_empty() {
+            self.0.push((text, false));
+        }
+    }
+
+    fn push_list_item(&mut self, text: String) {
+        self.0.push((text, true));
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::new();
+        let mut previous_list = false;
+        for (index, (text, list)) in self.0.into_iter().enumerate() {
+            if index > 0 {
+                out.push_str(if list && previous_list { "\n" } else { "\n\n" });
+            }
+            out.push_str(&text);
+            previous_list = list;
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// 保存图片到文档旁的 `assets/`，返回 Markdown 中的相对路径
+struct AssetWriter {
+    doc_dir: PathBuf,
+    /// 本次导入引用的图片路径（按内容哈希去重）
+    saved: HashSet<String>,
+}
+
+impl AssetWriter {
+    fn save(&mut self, data: &[u8], extension: &str) -> Result<String, AppError> {
+        let extension = extension.trim_start_matches('.').to_ascii_lowercase();
+        let extension = if extension.is_empty() { "png" } else { &extension };
+        let path = crate::assets::write_hashed_asset(&self.doc_dir, data, extension)?;
+        self.saved.insert(path.clone());
+        Ok(path)
+    }
+}
+
+fn image_markdown(alt: &str, target: &str) -> String {
+    let target = if target.contains([' ', '(', ')']) { format!("<{}>", target) } else { target.to_string() };
+    format!("![{}]({})", escape_markdown(alt.trim()), target)
+}
+
+// ---------------------------------------------------------------------------
+// DOCX
+// ---------------------------------------------------------------------------
+
+/// 列表层级的编号格式：`(numId, ilvl)` → 是否为有序列表
+type Numbering = HashMap<(String, String), bool>;
+
+struct Docx {
+    archive: zip::ZipArchive<fs::File>,
+    /// 关系 ID → 目标（`word/` 下的路径或外部地址）
+    relations: HashMap<String, String>,
+    /// 样式 ID → 标题级别（引用样式为 0）
+    heading_styles: HashMap<String, usize>,
+    numbering: Numbering,
+    assets: AssetWriter,
+    warnings: Vec<String>,
+}
+
+fn read_zip_text(archive: &mut zip::ZipArchive<fs::File>, name: &str) -> Option<String> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut text = String::new();
+    file.read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+impl Docx {
+    fn open(path: &Path, assets: AssetWriter) -> Result<(Self, Element), AppError> {
+        let file = fs::File::open(path).map_err(|e| AppError::file(path, e))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| AppError::localized("IMPORT", "IMPORT_INVALID_DOCX", [("reason", e.to_string())]))?;
+        let document = read_zip_text(&mut archive, "word/document.xml")
+            .ok_or_else(|| AppError::localized("IMPORT", "IMPORT_DOCX_NO_DOCUMENT", []))?;
+
+        let relations = read_zip_text(&mut archive, "word/_rels/document.xml.rels")
+            .map(|xml| {
+                parse_markup(&xml, false)
+                    .find("Relationships")
+                    .map(|rels| {
+                        rels.elements()
+                            .filter_map(|rel| {
+                                let id = rel.attr("Id")?.to_string();
+                                let target = rel.attr("Target")?;
+                                let target = if rel.attr("TargetMode") == Some("External") {
+                                    target.to_string()
+                                } else if let Some(absolute) = target.strip_prefix('/') {
+                                    absolute.to_string()
+                                } else {
+                                    format!("word/{}", target)
+                                };
+                                Some((id, target))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default();
+
+        let re_heading = regex::Regex::new(r"^heading\s*([1-6])$").unwrap();
+        let mut heading_styles = HashMap::new();
+        if let Some(xml) = read_zip_text(&mut archive, "word/styles.xml") {
+            let root = parse_markup(&xml, false);
+            for style in root.find("w:styles").into_iter().flat_map(|s| s.elements()) {
+                let (Some(id), Some(name)) = (style.attr("w:styleId"), style.child("w:name").and_then(|n| n.attr("w:val")))
+                else {
+                    continue;
+                };
+                let name = name.to_ascii_lowercase();
+                let level = if let Some(caps) = re_heading.captures(&name) {
+                    caps[1].parse().ok()
+                } else if name == "title" {
+                    Some(1)
+                } else if name.contains("quote") {
+                    Some(0)
+                } else {
+                    None
+                };
+                if let Some(level) = level {
+                    heading_styles.insert(id.to_string(), level);
+                }
+            }
+        }
+
+        let mut numbering = Numbering::new();
+        if let Some(xml) = read_zip_text(&mut archive, "word/numbering.xml") {
+            let root = parse_markup(&xml, false);
+            let mut abstract_formats: HashMap<String, HashMap<String, bool>> = HashMap::new();
+            let definitions: Vec<&Element> = root.find("w:numbering").into_iter().flat_map(|n| n.elements()).collect();
+            for abstract_num in definitions.iter().filter(|e| e.name == "w:abstractNum") {
+                let Some(id) = abstract_num.attr("w:abstractNumId") else { continue };
+                let levels = abstract_num
+                    .elements()
+                    .filter(|e| e.name == "w:lvl")
+                    .filter_map(|lvl| {
+                        let format = lvl.child("w:numFmt").and_then(|f| f.attr("w:val")).unwrap_or("bullet");
+                        Some((lvl.attr("w:ilvl")?.to_string(), format != "bullet" && format != "none"))
+                    })
+                    .collect();
+                abstract_formats.insert(id.to_string(), levels);
+            }
+            for num in definitions.iter().filter(|e| e.name == "w:num") {
+                let (Some(num_id), Some(abstract_id)) =
+                    (num.attr("w:numId"), num.child("w:abstractNumId").and_then(|a| a.attr("w:val")))
+                else {
+                    continue;
+                };
+                for (level, ordered) in abstract_formats.get(abstract_id).into_iter().flatten() {
+                    numbering.insert((num_id.to_string(), level.clone()), *ordered);
+                }
+            }
+        }
+
+        let docx = Docx { archive, relations, heading_styles, numbering, assets, warnings: Vec::new() };
+        Ok((docx, parse_markup(&document, false)))
+    }
+
+    /// 图片：保存关系指向的媒体文件
+    fn image(&mut self, drawing: &Element) -> Option<String> {
+        let id = drawing
+            .find("a:blip")
+            .and_then(|blip| blip.attr("r:embed"))
+            .or_else(|| drawing.find("v:imagedata").and_then(|image| image.attr("r:id")))?;
+        let alt = drawing
+            .find("wp:docPr")
+            .and_then(|doc_pr| doc_pr.attr("descr").filter(|d| !d.is_empty()).or_else(|| doc_pr.attr("title")))
+            .unwrap_or("")
+            .to_string();
+        let target = self.relations.get(id)?.clone();
+        if target.contains("://") {
+            return Some(image_markdown(&alt, &target));
+        }
+        let mut data = Vec::new();
+        let read = self.archive.by_name(&target).ok().map(|mut file| file.read_to_end(&mut data));
+        if !matches!(read, Some(Ok(_))) {
+            self.warnings.push(format!("找不到图片: {}", target));
+            return None;
+        }
+        let extension = Path::new(&target).extension().and_then(|e| e.to_str()).unwrap_or("png");
+        match self.assets.save(&data, extension) {
+            Ok(path) => Some(image_markdown(&alt, &path)),
+            Err(e) => {
+                self.warnings.push(e.to_string());
+                None
+            }
+        }
+    }
+
+    /// 收集段落（或超链接等容器）中的行内片段
+    fn collect_runs(&mut self, element: &Element, segments: &mut Vec<(String, Style)>) {
+        for child in element.elements() {
+            match child.name.as_str() {
+                "w:pPr" | "w:del" | "w:moveFrom" | "w:proofErr" | "w:bookmarkStart" | "w:bookmarkEnd" => {}
+                "w:r" => self.run(child, segments),
+                "w:hyperlink" => {
+                    let mut inner = Vec::new();
+                    self.collect_runs(child, &mut inner);
+                    let text = join_segments(&inner);
+                    let target = child
+                        .attr("r:id")
+                        .and_then(|id| self.relations.get(id).cloned())
+                        .or_else(|| child.attr("w:anchor").map(|anchor| format!("#{}", anchor)));
+                    let link = match target {
+                        Some(target) if !text.trim().is_empty() => format!("[{}]({})", text.trim(), target.replace(' ', "%20")),
+                        _ => text,
+                    };
+                    segments.push((link, Style::default()));
+                }
+                _ => self.collect_runs(child, segments),
+            }
+        }
+    }
+
+    fn run(&mut self, run: &Element, segments: &mut Vec<(String, Style)>) {
+        let on = |name: &str| {
+            run.child("w:rPr")
+                .and_then(|props| props.child(name))
+                .is_some_and(|flag| !matches!(flag.attr("w:val"), Some("0" | "false" | "none")))
+        };
+        let monospace = run
+            .child("w:rPr")
+            .and_then(|props| props.child("w:rFonts"))
+            .and_then(|fonts| fonts.attr("w:ascii").or_else(|| fonts.attr("w:hAnsi")))
+            .is_some_and(|font| ["courier", "consolas", "mono", "menlo"].iter().any(|m| font.to_ascii_lowercase().contains(m)));
+        let style = Style { bold: on("w:b"), italic: on("w:i"), strike: on("w:strike") || on("w:dstrike"), code: monospace };
+
+        for child in run.elements() {
+            match child.name.as_str() {
+                "w:t" if style.code => segments.push((child.text(), style)),
+                "w:t" => segments.push((escape_markdown(&child.text()), style)),
+                "w:tab" => segments.push((" ".to_string(), style)),
+                "w:br" | "w:cr" if child.attr("w:type") != Some("page") => {
+                    segments.push(("\\\n".to_string(), Style::default()))
+                }
+                "w:drawing" | "w:pict" => {
+                    if let Some(image) = self.image(child) {
+                        segments.push((image, Style::default()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn paragraph_text(&mut self, paragraph: &Element) -> String {
+        let mut segments = Vec::new();
+        self.collect_runs(paragraph, &mut segments);
+        join_segments(&segments).trim().trim_end_matches('\\').trim_end().to_string()
+    }
+
+    fn table(&mut self, table: &Element) -> String {
+        let mut rows = Vec::new();
+        for row in table.elements().filter(|e| e.name == "w:tr") {
+            let mut cells = Vec::new();
+            for cell in row.elements().filter(|e| e.name == "w:tc") {
+                let texts: Vec<String> = cell
+                    .elements()
+                    .filter(|e| e.name == "w:p")
+                    .map(|p| self.paragraph_text(p))
+                    .filter(|text| !text.is_empty())
+                    .collect();
+                cells.push(texts.join("<br>").replace("\\\n", "<br>"));
+                let span: usize = cell
+                    .child("w:tcPr")
+                    .and_then(|props| props.child("w:gridSpan"))
+                    .and_then(|span| span.attr("w:val"))
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(1);
+                cells.extend(std::iter::repeat_n(String::new(), span.saturating_sub(1)));
+            }
+            rows.push(cells);
+        }
+        table_markdown(&rows)
+    }
+
+    fn convert_body(&mut self, body: &Element, blocks: &mut Blocks) {
+        for child in body.elements() {
+            match child.name.as_str() {
+                "w:p" => self.paragraph(child, blocks),
+                "w:tbl" => {
+                    let table = self.table(child);
+                    blocks.push(table);
+                }
+                "w:sdt" | "w:sdtContent" | "w:customXml" => self.convert_body(child, blocks),
+                _ => {}
+            }
+        }
+    }
+
+    fn paragraph(&mut self, paragraph: &Element, blocks: &mut Blocks) {
+        let text = self.paragraph_text(paragraph);
+        if text.is_empty() {
+            return;
+        }
+        let props = paragraph.child("w:pPr");
+        let style = props.and_then(|p| p.child("w:pStyle")).and_then(|s| s.attr("w:val"));
+        let outline = props
+            .and_then(|p| p.child("w:outlineLvl"))
+            .and_then(|o| o.attr("w:val"))
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|&level| level < 6)
+            .map(|level| level + 1);
+        let list = props.and_then(|p| p.child("w:numPr")).and_then(|num| {
+            let id = num.child("w:numId")?.attr("w:val")?.to_string();
+            let level = num.child("w:ilvl").and_then(|l| l.attr("w:val")).unwrap_or("0").to_string();
+            (id != "0").then_some((id, level))
+        });
+
+        match (style.and_then(|s| self.heading_styles.get(s)).copied().or(outline), list) {
+            (Some(0), _) => blocks.push(format!("> {}", text.replace('\n', "\n> "))),
+            (Some(level), _) => blocks.push(format!("{} {}", "#".repeat(level), text.replace("\\\n", " "))),
+            (None, Some((id, level))) => {
+                let ordered = self.numbering.get(&(id, level.clone())).copied().unwrap_or(false);
+                let depth: usize = level.parse().unwrap_or(0);
+                let marker = if ordered { "1." } else { "-" };
+                let indent = "    ".repeat(depth);
+                let continuation = format!("\n{}{}", indent, " ".repeat(marker.len() + 1));
+                blocks.push_list_item(format!("{}{} {}", indent, marker, text.replace('\n', &continuation)));
+            }
+            (None, None) => blocks.push(escape_block_start(&text)),
+        }
+    }
+}
+
+fn import_docx(path: &Path, assets: AssetWriter) -> Result<(String, AssetWriter, Vec<String>), AppError> {
+    let (mut docx, document) = Docx::open(path, assets)?;
+    let body = document
+        .find("w:body")
+        .ok_or_else(|| AppError::localized("IMPORT", "IMPORT_DOCX_NO_BODY", []))?;
+    let mut blocks = Blocks(Vec::new());
+    docx.convert_body(body, &mut blocks);
+    Ok((blocks.finish(), docx.assets, docx.warnings))
+}
+
+// ---------------------------------------------------------------------------
+// HTML
+// ---------------------------------------------------------------------------
+
+struct HtmlImporter {
+    /// HTML 文件所在目录，相对路径的图片按此解析
+    base_dir: PathBuf,
+    assets: AssetWriter,
+    warnings: Vec<String>,
+}
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "section", "article", "main", "header", "footer", "nav", "aside", "figure", "figcaption", "h1", "h2",
+    "h3", "h4", "h5", "h6", "ul", "ol", "dl", "dt", "dd", "li", "pre", "blockquote", "table", "hr", "body", "html",
+    "details", "summary", "address", "center",
+];
+
+fn is_block(element: &Element) -> bool {
+    BLOCK_ELEMENTS.contains(&element.name.as_str())
+}
+
+/// 折叠连续空白（HTML 渲染规则）
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !space {
+                out.push(' ');
+            }
+            space = true;
+        } else {
+            out.push(c);
+            space = false;
+        }
+    }
+    out
+}
+
+impl HtmlImporter {
+    fn image(&mut self, img: &Element) -> Option<String> {
+        let src = img.attr("src")?.trim();
+        let alt = img.attr("alt").unwrap_or("");
+        if src.is_empty() {
+            return None;
+        }
+        if let Some(data_uri) = src.strip_prefix("data:") {
+            let (meta, data) = data_uri.split_once(',')?;
+            let extension = match meta.split(';').next().unwrap_or("") {
+                "image/jpeg" => "jpg",
+                "image/gif" => "gif",
+                "image/svg+xml" => "svg",
+                "image/webp" => "webp",
+                _ => "png",
+            };
+            let bytes = if meta.ends_with(";base64") {
+                base64::engine::general_purpose::STANDARD.decode(data.trim()).ok()?
+            } else {
+                crate::checker::percent_decode(data).into_bytes()
+            };
+            return match self.assets.save(&bytes, extension) {
+                Ok(path) => Some(image_markdown(alt, &path)),
+                Err(e) => {
+                    self.warnings.push(e.to_string());
+                    None
+                }
+            };
+        }
+        match crate::assets::resolve_asset(src, &self.base_dir) {
+            crate::assets::AssetSource::Local(path) => {
+                let Ok(bytes) = fs::read(&path) else {
+                    self.warnings.push(format!("找不到图片: {}", src));
+                    return Some(image_markdown(alt, src));
+                };
+                let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("png");
+                match self.assets.save(&bytes, extension) {
+                    Ok(path) => Some(image_markdown(alt, &path)),
+                    Err(e) => {
+                        self.warnings.push(e.to_string());
+                        None
+                    }
+                }
+            }
+            _ => Some(image_markdown(alt, src)),
+        }
+    }
+
+    /// 行内内容，`style` 为外层元素的格式
+    fn inline(&mut self, element: &Element, style: Style, segments: &mut Vec<(String, Style)>) {
+        for child in &element.children {
+            let child = match child {
+                Node::Text(text) => {
+                    segments.push((escape_markdown(&collapse_whitespace(text)), style));
+                    continue;
+                }
+                Node::Element(child) => child,
+            };
+            match child.name.as_str() {
+                "br" => segments.push(("\\\n".to_string(), Style::default())),
+                "img" => {
+                    if let Some(image) = self.image(child) {
+                        segments.push((image, Style::default()));
+                    }
+                }
+                "strong" | "b" => self.inline(child, Style { bold: true, ..style }, segments),
+                "em" | "i" | "cite" => self.inline(child, Style { italic: true, ..style }, segments),
+                "del" | "s" | "strike" => self.inline(child, Style { strike: true, ..style }, segments),
+                "code" | "kbd" | "samp" | "tt" => {
+                    segments.push((collapse_whitespace(&child.text()), Style { code: true, ..style }))
+                }
+                "sup" | "sub" => {
+                    let mut inner = Vec::new();
+                    self.inline(child, style, &mut inner);
+                    segments.push((format!("<{0}>{1}</{0}>", child.name, join_segments(&inner).trim()), Style::default()));
+                }
+                "a" => {
+                    let mut inner = Vec::new();
+                    self.inline(child, style, &mut inner);
+                    let text = join_segments(&inner);
+                    let link = match child.attr("href").map(str::trim).filter(|href| !href.is_empty()) {
+                        Some(href) if !text.trim().is_empty() => {
+                            format!("[{}]({})", text.trim(), href.replace(' ', "%20"))
+                        }
+                        _ => text,
+                    };
+                    segments.push((link, Style::default()));
+                }
+                _ => self.inline(child, style, segments),
+            }
+        }
+    }
+
+    fn inline_text(&mut self, element: &Element) -> String {
+        let mut segments = Vec::new();
+        self.inline(element, Style::default(), &mut segments);
+        let text = join_segments(&segments);
+        let lines: Vec<&str> = text.split('\n').map(str::trim).collect();
+        lines.join("\n").trim().trim_end_matches('\\').trim_end().to_string()
+    }
+
+    fn list(&mut self, list: &Element, depth: usize, blocks: &mut Blocks) {
+        let ordered = list.name == "ol";
+        let marker = if ordered { "1." } else { "-" };
+        let indent = "    ".repeat(depth);
+        let continuation = format!("\n{}{}", indent, " ".repeat(marker.len() + 1));
+        for item in list.elements().filter(|e| e.name == "li") {
+            // 列表项中的嵌套列表单独输出，其余内容作为该项的文本
+            let mut own = Element { name: "li".to_string(), ..Default::default() };
+            let mut nested = Vec::new();
+            for child in &item.children {
+                match child {
+                    Node::Element(e) if e.name == "ul" || e.name == "ol" => nested.push(e),
+                    Node::Element(e) => own.children.push(Node::Element(self.shallow_copy(e))),
+                    Node::Text(text) => own.children.push(Node::Text(text.clone())),
+                }
+            }
+            let mut text = self.inline_text(&own);
+            let checkbox = item.find("input").filter(|input| input.attr("type") == Some("checkbox"));
+            if let Some(checkbox) = checkbox {
+                text = format!("[{}] {}", if checkbox.attr("checked").is_some() { "x" } else { " " }, text);
+            }
+            blocks.push_list_item(format!("{}{} {}", indent, marker, text.replace('\n', &continuation)));
+            for nested in nested {
+                self.list(nested, depth + 1, blocks);
+            }
+        }
+    }
+
+    /// 复制元素（块级子元素中的段落在列表项内按行内内容处理）
+    fn shallow_copy(&self, element: &Element) -> Element {
+        let mut children: Vec<Node> = element
+            .children
+            .iter()
+            .map(|child| match child {
+                Node::Element(e) => Node::Element(self.shallow_copy(e)),
+                Node::Text(text) => Node::Text(text.clone()),
+            })
+            .collect();
+        if is_block(element) {
+            children.push(Node::Text(" ".to_string()));
+        }
+        Element {
+            name: if is_block(element) { "span".to_string() } else { element.name.clone() },
+            attrs: element.attrs.clone(),
+            children,
+        }
+    }
+
+    fn table(&mut self, table: &Element) -> String {
+        let mut rows = Vec::new();
+        let visit = |element: &Element, rows: &mut Vec<Vec<String>>, this: &mut Self| {
+            for row in element.elements().filter(|e| e.name == "tr") {
+                let mut cells = Vec::new();
+                for cell in row.elements().filter(|e| e.name == "td" || e.name == "th") {
+                    cells.push(this.inline_text(cell).replace("\\\n", "<br>"));
+                    let span: usize = cell.attr("colspan").and_then(|v| v.parse().ok()).unwrap_or(1);
+                    cells.extend(std::iter::repeat_n(String::new(), span.saturating_sub(1)));
+                }
+                rows.push(cells);
+            }
+        };
+        visit(table, &mut rows, self);
+        for section in table.elements().filter(|e| matches!(e.name.as_str(), "thead" | "tbody" | "tfoot")) {
+            visit(section, &mut rows, self);
+        }
+        table_markdown(&rows)
+    }
+
+    /// 块级内容：行内内容累积为段落，遇到块级元素时输出
+    fn blocks(&mut self, element: &Element, blocks: &mut Blocks) {
+        let mut paragraph = Element { name: "p".to_string(), ..Default::default() };
+        let flush = |this: &mut Self, paragraph: &mut Element, blocks: &mut Blocks| {
+            let text = this.inline_text(paragraph);
+            blocks.push(escape_block_start(&text));
+            paragraph.children.clear();
+        };
+        for child in &element.children {
+            let child = match child {
+                Node::Element(child) if is_block(child) => child,
+                Node::Element(child) => {
+                    paragraph.children.push(Node::Element(self.shallow_copy(child)));
+                    continue;
+                }
+                Node::Text(text) => {
+                    paragraph.children.push(Node::Text(text.clone()));
+                    continue;
+                }
+            };
+            flush(self, &mut paragraph, blocks);
+            match child.name.as_str() {
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    let level: usize = child.name[1..].parse().unwrap_or(1);
+                    let text = self.inline_text(child).replace("\\\n", " ");
+                    if !text.is_empty() {
+                        blocks.push(format!("{} {}", "#".repeat(level), text));
+                    }
+                }
+                "ul" | "ol" => self.list(child, 0, blocks),
+                "pre" => {
+                    let language = child
+                        .find("code")
+                        .and_then(|code| code.attr("class"))
+                        .and_then(|class| class.split_whitespace().find_map(|c| c.strip_prefix("language-")))
+                        .unwrap_or("")
+                        .to_string();
+                    let code = child.text();
+                    let code = code.strip_prefix('\n').unwrap_or(&code).trim_end();
+                    let fence = if code.contains("```") { "~~~" } else { "```" };
+                    blocks.push(format!("{fence}{language}\n{code}\n{fence}"));
+                }
+                "blockquote" => {
+                    let mut inner = Blocks(Vec::new());
+                    self.blocks(child, &mut inner);
+                    let text = inner.finish();
+                    let quoted: Vec<String> = text
+                        .trim_end()
+                        .lines()
+                        .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+                        .collect();
+                    blocks.push(quoted.join("\n"));
+                }
+                "table" => {
+                    let table = self.table(child);
+                    blocks.push(table);
+                }
+                "hr" => blocks.push("---".to_string()),
+                _ => self.blocks(child, blocks),
+            }
+        }
+        flush(self, &mut paragraph, blocks);
+    }
+}
+
+fn import_html(path: &Path, assets: AssetWriter) -> Result<(String, AssetWriter, Vec<String>), AppError> {
+    let bytes = fs::read(path).map_err(|e| AppError::file(path, e))?;
+    let source = String::from_utf8_lossy(&bytes);
+    let root = parse_markup(&source, true);
+    let body = root.find("body").unwrap_or(&root);
+
+    let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut importer = HtmlImporter { base_dir, assets, warnings: Vec::new() };
+    let mut blocks = Blocks(Vec::new());
+    importer.blocks(body, &mut blocks);
+    Ok((blocks.finish(), importer.assets, importer.warnings))
+}
+
+// ---------------------------------------------------------------------------
+// 命令
+// ---------------------------------------------------------------------------
+
+/// 源文件旁未被占用的 `.md` 路径
+fn markdown_target(source: &Path) -> PathBuf {
+    let stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or("imported");
+    let dir = source.parent().unwrap_or(Path::new("."));
+    let mut target = dir.join(format!("{}.md", stem));
+    let mut index = 1;
+    while target.exists() {
+        target = dir.join(format!("{}-{}.md", stem, index));
+        index += 1;
+    }
+    target
+}
+
+/// 转换 `.docx` 或 `.html` 文件为 Markdown，写入源文件旁的 `.md` 文件
+pub fn import(path: &Path) -> Result<ImportResult, AppError> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+    let target = markdown_target(path);
+    let doc_dir = target.parent().map(Path::to_path_buf).unwrap_or_default();
+    let assets = AssetWriter { doc_dir, saved: HashSet::new() };
+    let (markdown, assets, warnings) = match extension.as_str() {
+        "docx" => import_docx(path, assets)?,
+        "html" | "htm" | "xhtml" => import_html(path, assets)?,
+        _ => return Err(AppError::localized("IMPORT", "IMPORT_UNSUPPORTED", [("extension", extension)])),
+    };
+    fs::write(&target, &markdown).map_err(|e| AppError::file(&target, e))?;
+    tracing::info!(source = %path.display(), target = %target.display(), images = assets.saved.len(), "已导入文档");
+    Ok(ImportResult {
+        markdown_path: target.to_string_lossy().to_string(),
+        markdown,
+        images: assets.saved.len(),
+        warnings,
+    })
+}
+
+/// 导入 Word 或 HTML 文档，返回生成的 Markdown 文件
+#[tauri::command]
+pub async fn import_document(path: String) -> Result<ImportResult, AppError> {
+    crate::error::run_blocking("import_document", move || import(Path::new(&path))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html_to_markdown(html: &str) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("page.html");
+        fs::write(&source, html).unwrap();
+        import(&source).unwrap().markdown
+    }
+
+    fn depth(element: &Element) -> usize {
+        1 + element.elements().map(depth).max().unwrap_or(0)
+    }
+
+    #[test]
+    fn parses_entities_void_elements_and_implied_close() {
+        let root = parse_markup("<UL><li>a &amp; b<li>c&#x4e2d;<br>d</ul><p>x<p>y", true);
+        let list = root.child("ul").unwrap();
+        let items: Vec<String> = list.elements().map(Element::text).collect();
+        assert_eq!(items, ["a & b", "c中d"]);
+        assert!(list.elements().nth(1).unwrap().child("br").is_some());
+        let paragraphs: Vec<String> = root.elements().filter(|e| e.name == "p").map(Element::text).collect();
+        assert_eq!(paragraphs, ["x", "y"]);
+    }
+
+    #[test]
+    fn skips_script_and_keeps_xml_case() {
+        let root = parse_markup("<p>a<script>if (a < b) {}</script>b</p>", true);
+        assert_eq!(root.text(), "ab");
+        let root = parse_markup("<w:p w:val=\"1\"><w:t>x</w:t></w:p>", false);
+        assert_eq!(root.child("w:p").unwrap().attr("w:val"), Some("1"));
+    }
+
+    #[test]
+    fn caps_nesting_depth() {
+        let source = format!("{}text{}<p>after</p>", "<div>".repeat(100_000), "</div>".repeat(100_000));
+        let root = parse_markup(&source, true);
+        assert!(depth(&root) <= MAX_DEPTH + 2);
+        assert_eq!(root.text(), "textafter");
+        assert_eq!(root.elements().last().unwrap().name, "p");
+    }
+
+    #[test]
+    fn deeply_nested_html_imports() {
+        let html = format!("{}deep{}", "<blockquote><b>".repeat(50_000), "</b></blockquote>".repeat(50_000));
+        assert!(html_to_markdown(&html).contains("deep"));
+    }
+
+    #[test]
+    fn converts_html_blocks() {
+        let markdown = html_to_markdown(
+            "<html><head><title>t</title></head><body>\
+             <h2>Title</h2><p>Some <strong>bold</strong> and <em>it</em> <a href=\"https://e.com\">link</a></p>\
+             <ul><li>one<li>two<ol><li>nested</ol></ul>\
+             <pre><code class=\"language-rust\">fn main() {}</code></pre>\
+             <table><tr><th>A<th>B<tr><td>1<td>2</table></body></html>",
+        );
+        assert!(markdown.contains("## Title"), "{markdown}");
+        assert!(markdown.contains("Some **bold** and *it* [link](https://e.com)"), "{markdown}");
+        assert!(markdown.contains("- one\n- two\n"), "{markdown}");
+        assert!(markdown.contains("1. nested"), "{markdown}");
+        assert!(markdown.contains("```rust\nfn main() {}\n```"), "{markdown}");
+        assert!(markdown.contains("| A | B |"), "{markdown}");
+        assert!(markdown.starts_with("## Title"), "{markdown}");
+    }
+
+    #[test]
+    fn counts_deduplicated_images_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut assets = AssetWriter { doc_dir: dir.path().to_path_buf(), saved: HashSet::new() };
+        let first = assets.save(b"image", "PNG").unwrap();
+        let second = assets.save(b"image", "png").unwrap();
+        assets.save(b"other", "jpg").unwrap();
+        assert_eq!(first, second);
+        assert_eq!(assets.saved.len(), 2);
+        assert!(dir.path().join(&first).is_file());
+    }
+}
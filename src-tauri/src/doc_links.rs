@@ -0,0 +1,216 @@
+//! 文档间链接：`[[Other Note]]`、`[[Other Note|显示文字]]`、`[[Other Note#标题]]` 形式的 wiki 链接，
+//! 以及指向其他 Markdown 文件的相对链接 `[文字](other.md#标题)`。
+//!
+//!  - 预览：改写为 `md2pdf://open-document?target=...` 深链接，前端点击时调用 `resolve_document_link` 打开目标文档
+//!  - 导出：目标是导出内容中的文档时改写为页内锚点；其他存在的文档改写为绝对 `file://` 链接；
+//!    找不到的目标转换为纯文本
+//!
+//! wiki 链接的目标按文档所在目录查找 `<名称>.md` / `<名称>.markdown`，找不到时在子目录中按文件名查找。
+
+use crate::checker::{is_external_url, percent_decode};
+use crate::error::AppError;
+use crate::html_util::{escape_html, unescape_html};
+use regex::{Captures, Regex};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 前端处理的打开文档深链接
+pub const OPEN_DOCUMENT_URL: &str = "md2pdf://open-document";
+/// 按文件名查找 wiki 链接目标时的最大目录深度
+const MAX_SEARCH_DEPTH: usize = 4;
+
+/// 链接的改写方式
+pub enum LinkMode<'a> {
+    Preview,
+    Export {
+        /// 导出文档所在目录，未保存的文档为 `None`
+        base_dir: Option<&'a Path>,
+        /// 导出内容中包含的文档，指向它们的链接改写为页内锚点
+        included: &'a [PathBuf],
+    },
+}
+
+fn is_markdown_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.ends_with(".md") || lower.ends_with(".markdown")
+}
+
+/// 在目录及其子目录中查找文件名（不含扩展名）与 `name` 相同的 Markdown 文件
+fn find_by_name(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+    let mut subdirs = Vec::new();
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let hidden = path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with('.'));
+            if !hidden {
+                subdirs.push(path);
+            }
+        } else if is_markdown_path(&path.to_string_lossy())
+            && path.file_stem().and_then(|s| s.to_str()).is_some_and(|stem| stem.eq_ignore_ascii_case(name))
+        {
+            return Some(path);
+        }
+    }
+    if depth == 0 {
+        return None;
+    }
+    subdirs.sort();
+    subdirs.iter().find_map(|sub| find_by_name(sub, name, depth - 1))
+}
+
+/// 解析链接目标（wiki 名称或相对 `.md` 路径，不含 `#锚点`）为文档路径；文档不存在时返回 `None`
+pub fn resolve_target(target: &str, base_dir: &Path) -> Option<PathBuf> {
+    let decoded = percent_decode(target.trim());
+    if decoded.is_empty() {
+        return None;
+    }
+    let direct = base_dir.join(&decoded);
+    if is_markdown_path(&decoded) {
+        return direct.is_file().then_some(direct);
+    }
+    ["md", "markdown"]
+        .iter()
+        .map(|ext| base_dir.join(format!("{}.{}", decoded, ext)))
+        .find(|path| path.is_file())
+        .or_else(|| {
+            let name = Path::new(&decoded).file_name()?.to_str()?.to_string();
+            find_by_name(base_dir, &name, MAX_SEARCH_DEPTH)
+        })
+}
+
+/// 百分号编码 URL 查询参数或锚点（保留非保留字符）
+fn encode_component(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// 链接目标：文档（为空表示当前文档）与标题锚点
+struct DocTarget {
+    document: String,
+    heading: Option<String>,
+}
+
+/// 改写单个文档链接，返回 `<a>` 开始标签中的 href 属性值；`None` 表示转换为纯文本
+fn rewrite_target(target: &DocTarget, mode: &LinkMode) -> Option<String> {
+    let anchor = target.heading.as_deref().map(crate::slug::slugify).filter(|s| !s.is_empty());
+    match mode {
+        LinkMode::Preview => {
+            if target.document.is_empty() {
+                return Some(format!("#{}", anchor.unwrap_or_default()));
+            }
+            let mut url = format!("{}?target={}", OPEN_DOCUMENT_URL, encode_component(&target.document));
+            if let Some(anchor) = anchor {
+                url.push_str(&format!("&anchor={}", encode_component(&anchor)));
+            }
+            Some(url)
+        }
+        LinkMode::Export { base_dir, included } => {
+            if target.document.is_empty() {
+                return anchor.map(|a| format!("#{}", a));
+            }
+            let path = resolve_target(&target.document, (*base_dir)?)?;
+            if included.iter().any(|doc| doc == &path) {
+                return Some(format!("#{}", anchor.unwrap_or_default()));
+            }
+            let mut url = crate::file_url(&path);
+            if let Some(anchor) = anchor {
+                url.push_str(&format!("#{}", encode_component(&anchor)));
+            }
+            Some(url)
+        }
+    }
+}
+
+/// 改写 HTML 中的 wiki 链接（`wiki_links` 为 `false` 时不处理，属于扩展语法）与指向 `.md` 文件的相对链接
+pub fn rewrite_document_links(html: &str, mode: &LinkMode, wiki_links: bool) -> String {
+    let html = rewrite_markdown_hrefs(html, mode);
+    if wiki_links { rewrite_wiki_links(&html, mode) } else { html }
+}
+
+/// `<a href="other.md#标题">文字</a>`
+fn rewrite_markdown_hrefs(html: &str, mode: &LinkMode) -> String {
+    let re_anchor = Regex::new(r#"(?s)<a\b([^>]*?)\bhref="([^"]*)"([^>]*)>(.*?)</a>"#).unwrap();
+    re_anchor
+        .replace_all(html, |caps: &Captures| {
+            let href = unescape_html(&caps[2]);
+            if href.starts_with('#') || (is_external_url(&href) && !href.to_ascii_lowercase().starts_with("file:")) {
+                return caps[0].to_string();
+            }
+            let (document, heading) = match href.split_once('#') {
+                Some((doc, anchor)) => (doc, Some(percent_decode(anchor))),
+                None => (href.as_str(), None),
+            };
+            let document = document.strip_prefix("file://").unwrap_or(document);
+            if !is_markdown_path(document.split('?').next().unwrap_or("")) {
+                return caps[0].to_string();
+            }
+            // 锚点已经是 slug，重新生成时保持不变
+            let target = DocTarget { document: document.to_string(), heading };
+            match rewrite_target(&target, mode) {
+                Some(url) => format!(r#"<a{}href="{}"{}>{}</a>"#, &caps[1], escape_html(&url), &caps[3], &caps[4]),
+                None => format!(r#"<span class="doc-link-unresolved">{}</span>"#, &caps[4]),
+            }
+        })
+        .to_string()
+}
+
+/// 正文中的 `[[目标#标题|文字]]`，跳过代码、公式与已有链接
+fn rewrite_wiki_links(html: &str, mode: &LinkMode) -> String {
+    let re_protected =
+        Regex::new(r#"(?s)<pre\b.*?</pre>|<code\b.*?</code>|<a\b.*?</a>|<annotation\b.*?</annotation>|<[^>]*>"#)
+            .unwrap();
+    let re_wiki = Regex::new(r"\[\[([^\[\]|#\n]*)(?:#([^\[\]|\n]*))?(?:\|([^\[\]\n]+))?\]\]").unwrap();
+    let replace = |text: &str| {
+        re_wiki
+            .replace_all(text, |caps: &Captures| {
+                let document = unescape_html(caps[1].trim());
+                let heading = caps.get(2).map(|m| unescape_html(m.as_str().trim())).filter(|h| !h.is_empty());
+                let label = match caps.get(3) {
+                    Some(label) => label.as_str().trim().to_string(),
+                    None => {
+                        let text = match (&heading, document.is_empty()) {
+                            (Some(heading), true) => heading.clone(),
+                            (Some(heading), false) => format!("{} › {}", document, heading),
+                            (None, _) => document.clone(),
+                        };
+                        escape_html(&text)
+                    }
+                };
+                if document.is_empty() && heading.is_none() {
+                    return caps[0].to_string();
+                }
+                match rewrite_target(&DocTarget { document, heading }, mode) {
+                    Some(url) => format!(r#"<a class="wiki-link" href="{}">{}</a>"#, escape_html(&url), label),
+                    None => format!(r#"<span class="doc-link-unresolved">{}</span>"#, label),
+                }
+            })
+            .into_owned()
+    };
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(html) {
+        out.push_str(&replace(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&replace(&html[last..]));
+    out
+}
+
+/// 解析预览中 wiki 链接或相对 `.md` 链接的目标，返回文档的绝对路径。
+/// `from` 为当前文档路径，未保存的文档只能打开绝对路径。
+#[tauri::command]
+pub fn resolve_document_link(from: Option<String>, target: String) -> Result<String, AppError> {
+    let base_dir = from.as_deref().and_then(|p| Path::new(p).parent()).unwrap_or(Path::new(""));
+    resolve_target(&target, base_dir)
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| AppError::file(base_dir.join(&target), std::io::ErrorKind::NotFound.into()))
+}
@@ -0,0 +1,153 @@
+//! 自动保存草稿与崩溃恢复：编辑中的内容定期写入应用数据目录下的 `drafts/`，
+//! 应用或系统崩溃后下次启动时列出可恢复的草稿；显式保存成功后删除对应草稿。
+//!
+//! 每个文档一个草稿文件，文件名为文档 ID（通常是文档路径）的哈希。
+
+use crate::diagnostics::now_ms;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+/// 超过该时长未更新的草稿在启动时清理
+const MAX_DRAFT_AGE_MS: u128 = 30 * 24 * 3600 * 1000;
+
+/// 未保存文档的 ID 前缀（`untitled:<uuid>`），这类 ID 不是文件路径
+const UNTITLED_PREFIX: &str = "untitled:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Draft {
+    /// 文档 ID：已保存文档为其路径，未保存的文档为前端生成的 `untitled:<uuid>`
+    pub doc_id: String,
+    pub content: String,
+    /// 最后一次自动保存的时间（毫秒时间戳）
+    pub updated_ms: u128,
+}
+
+fn drafts_dir(app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("drafts"))
+        .map_err(|e| AppError::Internal { context: "drafts".to_string(), reason: e.to_string() })
+}
+
+fn draft_path(dir: &Path, doc_id: &str) -> PathBuf {
+    let hash = crate::hashing::sha256_hex(doc_id.as_bytes());
+    dir.join(format!("{}.json", &hash[..16]))
+}
+
+fn read_draft(path: &Path) -> Option<Draft> {
+    fs::read_to_string(path).ok().and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// 写入草稿：先写临时文件再替换，写入过程中崩溃也不会损坏上一份草稿；内容未变化时不写入
+fn write_draft(dir: &Path, doc_id: &str, content: &str) -> Result<(), AppError> {
+    let path = draft_path(dir, doc_id);
+    if read_draft(&path).is_some_and(|draft| draft.doc_id == doc_id && draft.content == content) {
+        return Ok(());
+    }
+    fs::create_dir_all(dir).map_err(|e| AppError::file(dir, e))?;
+    let draft = Draft { doc_id: doc_id.to_string(), content: content.to_string(), updated_ms: now_ms() };
+    let json = serde_json::to_string(&draft)
+        .map_err(|e| AppError::Internal { context: "drafts".to_string(), reason: e.to_string() })?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| AppError::file(&tmp, e))?;
+    fs::rename(&tmp, &path).map_err(|e| AppError::file(&path, e))
+}
+
+/// 列出可恢复的草稿（最新的在前），同时清理过期的草稿以及与磁盘上文档内容相同的草稿
+fn recover_drafts(dir: &Path) -> Vec<Draft> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let now = now_ms();
+    let mut drafts = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            // 未完成写入的临时文件
+            let _ = fs::remove_file(&path);
+            continue;
+        }
+        let Some(draft) = read_draft(&path) else {
+            let _ = fs::remove_file(&path);
+            continue;
+        };
+        let expired = now.saturating_sub(draft.updated_ms) > MAX_DRAFT_AGE_MS;
+        let unchanged = !draft.doc_id.starts_with(UNTITLED_PREFIX)
+            && fs::read_to_string(&draft.doc_id).is_ok_and(|saved| saved == draft.content);
+        if expired || unchanged {
+            let _ = fs::remove_file(&path);
+            continue;
+        }
+        drafts.push(draft);
+    }
+    drafts.sort_by_key(|draft| std::cmp::Reverse(draft.updated_ms));
+    drafts
+}
+
+/// 自动保存文档的草稿（前端在有未保存更改时定期调用）
+#[tauri::command]
+pub fn autosave_draft(app: tauri::AppHandle, doc_id: String, content: String) -> Result<(), AppError> {
+    write_draft(&drafts_dir(&app)?, &doc_id, &content)
+}
+
+/// 启动时列出上次未正常保存的草稿
+#[tauri::command]
+pub fn list_recovered_drafts(app: tauri::AppHandle) -> Result<Vec<Draft>, AppError> {
+    let drafts = recover_drafts(&drafts_dir(&app)?);
+    if !drafts.is_empty() {
+        tracing::info!(count = drafts.len(), "发现可恢复的草稿");
+    }
+    Ok(drafts)
+}
+
+/// 删除文档的草稿（显式保存成功后，或用户放弃恢复时）
+#[tauri::command]
+pub fn discard_draft(app: tauri::AppHandle, doc_id: String) -> Result<(), AppError> {
+    let path = draft_path(&drafts_dir(&app)?, &doc_id);
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(AppError::file(&path, e)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drafts_round_trip_and_skip_unchanged_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        write_draft(dir.path(), "untitled:1", "a").unwrap();
+        let first = read_draft(&draft_path(dir.path(), "untitled:1")).unwrap();
+        write_draft(dir.path(), "untitled:1", "a").unwrap();
+        let second = read_draft(&draft_path(dir.path(), "untitled:1")).unwrap();
+        assert_eq!(first.updated_ms, second.updated_ms);
+        assert_eq!(second.content, "a");
+    }
+
+    #[test]
+    fn untitled_drafts_are_recovered_without_touching_the_file_system() {
+        let dir = tempfile::tempdir().unwrap();
+        write_draft(dir.path(), "untitled:2", "内容").unwrap();
+        let drafts = recover_drafts(dir.path());
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].doc_id, "untitled:2");
+    }
+
+    #[test]
+    fn drafts_matching_the_saved_file_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let doc = dir.path().join("doc.md");
+        fs::write(&doc, "saved").unwrap();
+        let drafts_dir = dir.path().join("drafts");
+        write_draft(&drafts_dir, &doc.to_string_lossy(), "saved").unwrap();
+        write_draft(&drafts_dir, "untitled:3", "x").unwrap();
+        fs::write(drafts_dir.join("partial.json.tmp"), "{").unwrap();
+
+        let drafts = recover_drafts(&drafts_dir);
+        assert_eq!(drafts.iter().map(|d| d.doc_id.as_str()).collect::<Vec<_>>(), ["untitled:3"]);
+        assert_eq!(fs::read_dir(&drafts_dir).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,295 @@
+//! 尾注：把脚注转换为尾注，适用于要求尾注而非脚注的出版社。
+//!
+//!  - `footnotes`（默认）：保持脚注
+//!  - `chapter`：按章（一级标题，没有一级标题时为二级标题）分组，放在每章末尾，每章重新编号
+//!  - `document`：全部放在文档末尾带标题的“注释”一节
+//!  - `page`：放在引用所在页的页面底部，全文连续编号。Chrome 不支持 CSS 页脚注，
+//!    由导出页面中的排版脚本（`PAGE_NOTES_SCRIPT`）按打印尺寸测量正文，逐页在页底插入注释并强制分页；
+//!    分栏或竖排时无法按页测量，退回到文末注释
+//!
+//! 正文中的引用与尾注之间保留双向链接（同一注释被多次引用时每处引用各有一个返回链接）。
+//! 导出选项中的 `notes` 优先，其次是 front matter 中的 `endnotes`（`true` 等同于 `document`）。
+
+use crate::abstract_block::is_english;
+use crate::html_util::find_closing_tag;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotePlacement {
+    #[default]
+    Footnotes,
+    Chapter,
+    Document,
+    Page,
+}
+
+impl NotePlacement {
+    /// 导出选项优先，其次是 front matter 中的 `endnotes`
+    pub fn resolve(option: Option<Self>, front_matter: Option<&Value>) -> Self {
+        option
+            .or_else(|| match front_matter?.get("endnotes")? {
+                Value::Bool(true) => Some(NotePlacement::Document),
+                Value::Bool(false) => Some(NotePlacement::Footnotes),
+                value => serde_yaml::from_value(value.clone()).ok(),
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// 脚注区中的一条注释
+struct Note {
+    id: String,
+    body: String,
+}
+
+/// 取出脚注区（remark-gfm 与 comrak 生成的 `<section data-footnotes>`），返回去掉脚注区的正文与注释
+fn extract_notes(html: &str) -> Option<(String, Vec<Note>)> {
+    let re_section = Regex::new(r"<section\b[^>]*\bdata-footnotes\b[^>]*>").unwrap();
+    let re_item = Regex::new(r#"<li\b[^>]*\bid="([^"]+)"[^>]*>"#).unwrap();
+    let re_backref = Regex::new(r"(?s)\s*<a\b[^>]*\bdata-footnote-backref\b[^>]*>.*?</a>").unwrap();
+
+    let open = re_section.find(html)?;
+    let (content_end, section_end) = find_closing_tag(html, "section", open.end())?;
+    let section = &html[open.end()..content_end];
+
+    let mut notes = Vec::new();
+    let mut pos = 0;
+    while let Some(caps) = re_item.captures_at(section, pos) {
+        let item = caps.get(0).unwrap();
+        let Some((body_end, item_end)) = find_closing_tag(section, "li", item.end()) else {
+            break;
+        };
+        let body = re_backref.replace_all(&section[item.end()..body_end], "");
+        notes.push(Note { id: caps[1].to_string(), body: body.trim().to_string() });
+        pos = item_end;
+    }
+
+    let rest = format!("{}{}", &html[..open.start()], &html[section_end..]);
+    Some((rest, notes))
+}
+
+/// 章节的起始位置：有一级标题时按一级标题分章，否则按二级标题；都没有时全文为一章
+fn chapter_starts(html: &str) -> Vec<usize> {
+    let re_heading = Regex::new(r"<h([12])\b").unwrap();
+    let headings: Vec<(usize, &str)> =
+        re_heading.captures_iter(html).map(|caps| (caps.get(0).unwrap().start(), caps.get(1).unwrap().as_str())).collect();
+    let level = if headings.iter().any(|(_, level)| *level == "1") { "1" } else { "2" };
+    let mut starts = vec![0];
+    starts.extend(headings.iter().filter(|(start, l)| *l == level && *start > 0).map(|(start, _)| *start));
+    starts
+}
+
+/// 一组尾注（一章或全文）：按首次引用的顺序编号
+struct NoteGroup {
+    chapter: usize,
+    /// 注释 ID 及其被引用的次数
+    order: Vec<(String, usize)>,
+}
+
+impl NoteGroup {
+    /// 记录一次引用，返回 (编号, 第几次引用)
+    fn cite(&mut self, id: &str) -> (usize, usize) {
+        match self.order.iter_mut().position(|(note, _)| note == id) {
+            Some(index) => {
+                self.order[index].1 += 1;
+                (index + 1, self.order[index].1)
+            }
+            None => {
+                self.order.push((id.to_string(), 1));
+                (self.order.len(), 1)
+            }
+        }
+    }
+
+    fn anchor(&self, number: usize) -> String {
+        format!("endnote-{}-{}", self.chapter, number)
+    }
+
+    fn ref_anchor(&self, number: usize, occurrence: usize) -> String {
+        if occurrence == 1 {
+            format!("endnote-ref-{}-{}", self.chapter, number)
+        } else {
+            format!("endnote-ref-{}-{}-{}", self.chapter, number, occurrence)
+        }
+    }
+
+    /// 尾注列表；全文尾注的标题进入 PDF 书签，章末尾注只有小标题；
+    /// 页底注释先整体放在隐藏的 `page-notes-source` 中，由排版脚本分配到各页
+    fn to_html(&self, notes: &HashMap<&str, &str>, title: &str, placement: NotePlacement) -> String {
+        if self.order.is_empty() {
+            return String::new();
+        }
+        let mut html = match placement {
+            NotePlacement::Document => {
+                format!(r#"<section class="endnotes"><h2 class="endnotes-title">{}</h2><ol>"#, title)
+            }
+            NotePlacement::Page => {
+                format!(r#"<section class="endnotes page-notes-source"><div class="endnotes-title">{}</div><ol>"#, title)
+            }
+            _ => format!(r#"<section class="endnotes endnotes-chapter"><div class="endnotes-title">{}</div><ol>"#, title),
+        };
+        for (index, (id, count)) in self.order.iter().enumerate() {
+            let number = index + 1;
+            let backrefs: String = (1..=*count)
+                .map(|occurrence| {
+                    let mark = if occurrence == 1 { String::new() } else { format!("<sup>{}</sup>", occurrence) };
+                    format!(
+                        r##" <a href="#{}" class="endnote-backref" aria-label="返回正文">↩{}</a>"##,
+                        self.ref_anchor(number, occurrence),
+                        mark
+                    )
+                })
+                .collect();
+            let body = notes.get(id.as_str()).copied().unwrap_or_default();
+            // 返回链接放在最后一段之内，不单独成行
+            let body = match body.strip_suffix("</p>") {
+                Some(head) => format!("{}{}</p>", head, backrefs),
+                None => format!("{}{}", body, backrefs),
+            };
+            html.push_str(&format!(
+                r#"<li id="{}" value="{}" data-note="{}">{}</li>"#,
+                self.anchor(number),
+                number,
+                number,
+                body
+            ));
+        }
+        html.push_str("</ol></section>");
+        html
+    }
+}
+
+/// 把脚注转换为尾注；`Footnotes` 或文档没有脚注时原样返回
+pub fn apply_endnotes(html: &str, placement: NotePlacement, front_matter: Option<&Value>) -> String {
+    if placement == NotePlacement::Footnotes {
+        return html.to_string();
+    }
+    let Some((body, notes)) = extract_notes(html) else {
+        return html.to_string();
+    };
+    let notes: HashMap<&str, &str> = notes.iter().map(|note| (note.id.as_str(), note.body.as_str())).collect();
+    let title = if front_matter.is_some_and(is_english) { "Notes" } else { "注释" };
+
+    let re_ref = Regex::new(r#"(?s)<a\b[^>]*\bdata-footnote-ref\b[^>]*>.*?</a>"#).unwrap();
+    let re_href = Regex::new(r##"href="#([^"]+)""##).unwrap();
+
+    let starts = match placement {
+        NotePlacement::Chapter => chapter_starts(&body),
+        _ => vec![0],
+    };
+    let mut out = String::with_capacity(body.len());
+    for (chapter, &start) in starts.iter().enumerate() {
+        let end = starts.get(chapter + 1).copied().unwrap_or(body.len());
+        let text = &body[start..end];
+        let mut group = NoteGroup { chapter: chapter + 1, order: Vec::new() };
+
+        let mut last = 0;
+        for m in re_ref.find_iter(text) {
+            let Some(id) = re_href.captures(m.as_str()).map(|caps| caps[1].to_string()) else {
+                continue;
+            };
+            if !notes.contains_key(id.as_str()) {
+                continue;
+            }
+            let (number, occurrence) = group.cite(&id);
+            out.push_str(&text[last..m.start()]);
+            out.push_str(&format!(
+                r##"<a href="#{}" id="{}" class="endnote-ref" data-note="{}">{}</a>"##,
+                group.anchor(number),
+                group.ref_anchor(number, occurrence),
+                number,
+                number
+            ));
+            last = m.end();
+        }
+        out.push_str(&text[last..]);
+        out.push_str(&group.to_html(&notes, title, placement));
+    }
+    out
+}
+
+/// 页底注释的排版脚本：导出页面加载完成后、添加渲染完成信号之前执行。
+///
+/// 按打印时的正文宽度排版后，从前往后把顶层块分配到各页：每页放入的块与其引用的注释
+/// 总高度不超过页面高度；页底用空白撑开后放入注释并强制分页。超过一页的块按 Chrome 的分页推算。
+/// 页面高度与宽度来自 `--page-content-height` / `--page-content-width`（见 layout 模块）。
+pub const PAGE_NOTES_SCRIPT: &str = r#"
+        // 把页底注释分配到各页（见后端 endnotes 模块）
+        function placePageNotes() {
+            const source = document.querySelector('.page-notes-source');
+            const container = document.querySelector('.markdown-preview');
+            if (!source || !container) return;
+            const root = getComputedStyle(document.documentElement);
+            // 留出余量，抵消屏幕与打印排版之间的细微差异
+            const pageHeight = parseFloat(root.getPropertyValue('--page-content-height')) * 0.96;
+            const pageWidth = parseFloat(root.getPropertyValue('--page-content-width'));
+            const style = getComputedStyle(container);
+            const multiColumn = style.columnCount !== 'auto' && Number(style.columnCount) > 1;
+            if (!pageHeight || !pageWidth || multiColumn || !root.writingMode.startsWith('horizontal')) {
+                // 无法按页测量：退回到文末注释
+                source.classList.remove('page-notes-source');
+                return;
+            }
+
+            // 与打印时相同的正文宽度（打印样式中 body 的内边距为 20px）
+            document.body.style.maxWidth = 'none';
+            document.body.style.width = pageWidth + 'px';
+            document.body.style.padding = '20px';
+
+            // 每条注释在页底占用的高度
+            source.style.cssText = 'display: block; position: absolute; visibility: hidden; width: ' + container.clientWidth + 'px';
+            const items = new Map();
+            for (const li of source.querySelectorAll('li[data-note]')) {
+                const margin = parseFloat(getComputedStyle(li).marginTop) + parseFloat(getComputedStyle(li).marginBottom);
+                items.set(li.dataset.note, { li, height: li.offsetHeight + margin });
+            }
+            const BOX_EXTRA = 24;
+            const notesHeight = notes => notes.length === 0 ? 0
+                : BOX_EXTRA + notes.reduce((sum, n) => sum + (items.get(n)?.height || 0), 0);
+            const top = el => el.getBoundingClientRect().top + window.scrollY;
+            const bottom = el => el.getBoundingClientRect().bottom + window.scrollY;
+
+            const blocks = Array.from(container.children).filter(el => el !== source);
+            let pageStart = 0;
+            let i = 0;
+            while (i < blocks.length) {
+                let pageNotes = [];
+                let end = i;
+                for (let j = i; j < blocks.length; j++) {
+                    const block = blocks[j];
+                    if (j > i && getComputedStyle(block).breakBefore === 'page') break;
+                    const refs = Array.from(block.querySelectorAll('[data-note]'), a => a.dataset.note).filter(n => items.has(n));
+                    const candidate = pageNotes.concat(refs.filter(n => !pageNotes.includes(n)));
+                    if (j > i && bottom(block) - pageStart + notesHeight(candidate) > pageHeight) break;
+                    pageNotes = candidate;
+                    end = j + 1;
+                }
+
+                const last = blocks[end - 1];
+                const used = bottom(last) - pageStart;
+                const box = document.createElement('section');
+                box.className = 'endnotes page-notes';
+                const list = document.createElement('ol');
+                for (const n of pageNotes) {
+                    list.appendChild(items.get(n).li);
+                    items.delete(n);
+                }
+                box.appendChild(list);
+                if (end < blocks.length) {
+                    // 超过一页的块由 Chrome 分页，按其最后一页的剩余空间计算
+                    const remaining = used > pageHeight ? pageHeight - (used % pageHeight) : pageHeight - used;
+                    box.style.marginTop = Math.max(0, remaining - notesHeight(pageNotes)) + 'px';
+                    box.style.breakAfter = 'page';
+                }
+                if (pageNotes.length === 0) box.classList.add('page-notes-empty');
+                last.after(box);
+                if (end < blocks.length) pageStart = top(blocks[end]);
+                i = end;
+            }
+            source.remove();
+        }
+"#;
@@ -0,0 +1,234 @@
+//! 公式编号与交叉引用：
+//!  - 行间公式按文档顺序编号，编号右对齐；`\tag{...}` / `\tag*{...}` 指定编号，`\nonumber` / `\notag` 不编号
+//!  - `\label{...}` 为公式设置锚点，正文或行内公式中的 `\eqref{...}`（带括号）/ `\ref{...}` 解析为指向它的链接
+//!  - 编号范围由设置中的 `equation_numbering` 决定，front matter 中的同名字段覆盖：
+//!    `all`（全部行间公式）、`labeled`（默认，仅带 `\label` 的公式）、`none`（仅 `\tag`）
+//!
+//! 编号在导出前的 HTML 中统一分配，不依赖公式引擎，因此引用与 PDF 中的编号始终一致。
+//! 每个行间公式块只有一个编号（`align` 等多行环境也按一个整体编号）。
+//! KaTeX 已在前端渲染，`\tag` 由 KaTeX 自行显示；保留 TeX 原文的公式（MathJax / none）
+//! 去掉 `\label`、`\tag` 后交给公式引擎。
+
+use crate::html_util::{escape_html, find_closing_tag, unescape_html};
+use crate::slug::Slugger;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EquationNumbering {
+    All,
+    #[default]
+    Labeled,
+    None,
+}
+
+impl EquationNumbering {
+    /// front matter 中的 `equation_numbering` 覆盖设置中的默认值
+    pub fn resolve(default: Self, front_matter: Option<&Value>) -> Self {
+        front_matter
+            .and_then(|fm| fm.get("equation_numbering"))
+            .and_then(|value| serde_yaml::from_value(value.clone()).ok())
+            .unwrap_or(default)
+    }
+}
+
+const KATEX_DISPLAY_OPEN: &str = r#"<span class="katex-display">"#;
+const RAW_DISPLAY_OPEN: &str = r#"<pre><code class="language-math math-display">"#;
+const RAW_DISPLAY_CLOSE: &str = "</code></pre>";
+
+/// 已编号的公式：`number` 为不含括号的编号，`starred` 表示 `\tag*`（显示时不加括号）
+struct Numbered {
+    number: String,
+    starred: bool,
+}
+
+impl Numbered {
+    fn display(&self) -> String {
+        if self.starred { self.number.clone() } else { format!("({})", self.number) }
+    }
+}
+
+/// 从 TeX 中读取 `\label`、`\tag` 与 `\nonumber`
+struct EquationTex {
+    label: Option<String>,
+    tag: Option<Numbered>,
+    no_number: bool,
+}
+
+fn parse_tex(tex: &str) -> EquationTex {
+    let re_label = Regex::new(r"\\label\s*\{([^{}]*)\}").unwrap();
+    let re_tag = Regex::new(r"\\tag(\*?)\s*\{([^{}]*)\}").unwrap();
+    let re_notag = Regex::new(r"\\(?:nonumber|notag)\b").unwrap();
+    EquationTex {
+        label: re_label.captures(tex).map(|caps| caps[1].trim().to_string()).filter(|l| !l.is_empty()),
+        tag: re_tag.captures(tex).map(|caps| Numbered { number: caps[2].trim().to_string(), starred: &caps[1] == "*" }),
+        no_number: re_notag.is_match(tex),
+    }
+}
+
+/// 去掉编号相关的命令，剩余部分交给公式引擎
+fn strip_numbering_commands(tex: &str) -> String {
+    let re = Regex::new(r"\\(?:label|tag\*?)\s*\{[^{}]*\}|\\(?:nonumber|notag)\b").unwrap();
+    re.replace_all(tex, "").trim().to_string()
+}
+
+/// 标签对应的锚点与编号
+struct Target {
+    id: String,
+    number: Option<Numbered>,
+}
+
+/// 为行间公式编号并解析 `\eqref` / `\ref`
+pub fn number_equations(html: &str, numbering: EquationNumbering) -> String {
+    let re_annotation = Regex::new(r#"(?s)<annotation encoding="application/x-tex">(.*?)</annotation>"#).unwrap();
+
+    let mut out = String::with_capacity(html.len());
+    let mut targets: HashMap<String, Target> = HashMap::new();
+    let mut slugger = Slugger::new();
+    let mut counter = 0usize;
+    let mut rest = html;
+
+    loop {
+        let katex = rest.find(KATEX_DISPLAY_OPEN);
+        let raw = rest.find(RAW_DISPLAY_OPEN);
+        let (start, is_katex) = match (katex, raw) {
+            (Some(k), Some(r)) => if k < r { (k, true) } else { (r, false) },
+            (Some(k), None) => (k, true),
+            (None, Some(r)) => (r, false),
+            (None, None) => break,
+        };
+
+        // 公式块的范围与 TeX 原文
+        let (end, tex) = if is_katex {
+            let Some((_, end)) = find_closing_tag(rest, "span", start + KATEX_DISPLAY_OPEN.len()) else { break };
+            let tex = re_annotation.captures(&rest[start..end]).map(|caps| unescape_html(&caps[1]));
+            (end, tex.unwrap_or_default())
+        } else {
+            let body_start = start + RAW_DISPLAY_OPEN.len();
+            let Some(body_len) = rest[body_start..].find(RAW_DISPLAY_CLOSE) else { break };
+            (body_start + body_len + RAW_DISPLAY_CLOSE.len(), unescape_html(&rest[body_start..body_start + body_len]))
+        };
+        out.push_str(&rest[..start]);
+        let block = &rest[start..end];
+        rest = &rest[end..];
+
+        let parsed = parse_tex(&tex);
+        let auto_number = !parsed.no_number
+            && match numbering {
+                EquationNumbering::All => true,
+                EquationNumbering::Labeled => parsed.label.is_some(),
+                EquationNumbering::None => false,
+            };
+        let tagged = parsed.tag.is_some();
+        let number = parsed.tag.or_else(|| {
+            auto_number.then(|| {
+                counter += 1;
+                Numbered { number: counter.to_string(), starred: false }
+            })
+        });
+
+        let id = parsed.label.as_ref().map(|label| {
+            let slug = slugger.slug(label);
+            if slug.is_empty() { format!("eq-{}", targets.len() + 1) } else { format!("eq-{}", slug) }
+        });
+
+        // KaTeX 已显示 `\tag`，其余情况由这里显示编号
+        let body = if is_katex {
+            block.to_string()
+        } else {
+            format!("{}{}{}", RAW_DISPLAY_OPEN, escape_html(&strip_numbering_commands(&tex)), RAW_DISPLAY_CLOSE)
+        };
+        let number_html = match &number {
+            Some(n) if !(is_katex && tagged) => {
+                format!(r#"<span class="equation-number">{}</span>"#, escape_html(&n.display()))
+            }
+            _ => String::new(),
+        };
+        let id_attr = id.as_ref().map(|id| format!(r#" id="{}""#, escape_html(id))).unwrap_or_default();
+        out.push_str(&format!(r#"<div class="equation"{}>{}{}</div>"#, id_attr, body, number_html));
+
+        if let (Some(label), Some(id)) = (parsed.label, id) {
+            targets.entry(label).or_insert(Target { id, number });
+        }
+    }
+    out.push_str(rest);
+
+    resolve_references(&out, &targets)
+}
+
+/// 生成引用链接；未知标签显示 `??`，链接指向不存在的锚点，由导出报告给出警告
+fn reference_html(kind: &str, label: &str, targets: &HashMap<String, Target>) -> String {
+    let (href, number) = match targets.get(label) {
+        Some(target) => (target.id.clone(), target.number.as_ref()),
+        None => (format!("eq-{}", crate::slug::slugify(label)), None),
+    };
+    let text = match (kind, number) {
+        ("eqref", Some(n)) => n.display(),
+        ("eqref", None) => "(??)".to_string(),
+        (_, Some(n)) => n.number.clone(),
+        (_, None) => "??".to_string(),
+    };
+    format!(r##"<a class="equation-ref" href="#{}">{}</a>"##, escape_html(&href), escape_html(&text))
+}
+
+/// 把 `\eqref{...}` / `\ref{...}` 替换为链接：行内公式中只含引用时替换整个公式，
+/// 正文中的引用在代码与公式之外替换
+fn resolve_references(html: &str, targets: &HashMap<String, Target>) -> String {
+    let re_ref_tex = Regex::new(r"^\s*\\(eqref|ref)\s*\{([^{}]*)\}\s*$").unwrap();
+    let re_annotation = Regex::new(r#"(?s)<annotation encoding="application/x-tex">(.*?)</annotation>"#).unwrap();
+
+    // KaTeX 渲染的行内公式
+    let katex_open = r#"<span class="katex">"#;
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(katex_open) {
+        let Some((_, end)) = find_closing_tag(rest, "span", start + katex_open.len()) else { break };
+        out.push_str(&rest[..start]);
+        let span = &rest[start..end];
+        let tex = re_annotation.captures(span).map(|caps| unescape_html(&caps[1])).unwrap_or_default();
+        match re_ref_tex.captures(&tex) {
+            Some(caps) => out.push_str(&reference_html(&caps[1], caps[2].trim(), targets)),
+            None => out.push_str(span),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+
+    // 保留 TeX 原文的行内公式
+    let re_raw_inline = Regex::new(
+        r#"(?s)<code class="language-math math-inline">(.*?)</code>|<span class="math math-inline">(.*?)</span>"#,
+    )
+    .unwrap();
+    let out = re_raw_inline.replace_all(&out, |caps: &regex::Captures| {
+        let tex = unescape_html(caps.get(1).or_else(|| caps.get(2)).map_or("", |m| m.as_str()));
+        match re_ref_tex.captures(&tex) {
+            Some(ref_caps) => reference_html(&ref_caps[1], ref_caps[2].trim(), targets),
+            None => caps[0].to_string(),
+        }
+    });
+
+    // 正文：跳过代码、预格式文本与公式
+    let re_protected =
+        Regex::new(r#"(?s)<pre\b.*?</pre>|<code\b.*?</code>|<annotation\b.*?</annotation>|<span class="math math-inline">.*?</span>"#)
+            .unwrap();
+    let re_ref_text = Regex::new(r"\\(eqref|ref)\{([^{}<>]*)\}").unwrap();
+    let replace_text = |text: &str| {
+        re_ref_text
+            .replace_all(text, |caps: &regex::Captures| {
+                reference_html(&caps[1], &unescape_html(caps[2].trim()), targets)
+            })
+            .into_owned()
+    };
+    let mut result = String::with_capacity(out.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(&out) {
+        result.push_str(&replace_text(&out[last..m.start()]));
+        result.push_str(m.as_str());
+        last = m.end();
+    }
+    result.push_str(&replace_text(&out[last..]));
+    result
+}
@@ -0,0 +1,365 @@
+//! 统一错误类型。
+//!
+//! 序列化给前端时为对象 `{ code, message, details }`：
+//!  - `code`：稳定的错误码，前端据此分支处理
+//!  - `message`：按当前语言（设置中的 `locale`）生成的提示文本
+//!  - `details`：机器可读的附加信息（出错路径、已探测的浏览器位置等）
+
+use crate::i18n::{self, Locale};
+use serde::ser::SerializeStruct;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// `!include` 被拒绝的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncludeFailure {
+    /// 循环引用或嵌套过深
+    Cycle,
+    /// 目标不在文档所在目录之下
+    OutsideDocument,
+    /// 文档未保存，没有基准目录
+    UnsavedDocument,
+}
+
+impl IncludeFailure {
+    fn key(self) -> &'static str {
+        match self {
+            IncludeFailure::Cycle => "INCLUDE_CYCLE",
+            IncludeFailure::OutsideDocument => "INCLUDE_OUTSIDE",
+            IncludeFailure::UnsavedDocument => "INCLUDE_UNSAVED",
+        }
+    }
+}
+
+/// 改写 PDF 时所处的步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfOperation {
+    /// 解析对象
+    Parse,
+    /// 转换为 PDF/A
+    Archive,
+    /// 加密
+    Protect,
+}
+
+impl PdfOperation {
+    fn key(self) -> &'static str {
+        match self {
+            PdfOperation::Parse => "PDF_MALFORMED",
+            PdfOperation::Archive => "PDF_ARCHIVE_UNSUPPORTED",
+            PdfOperation::Protect => "PDF_PROTECT_UNSUPPORTED",
+        }
+    }
+}
+
+/// 改写 PDF 时遇到的结构问题
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfIssue {
+    UnterminatedObject,
+    UnterminatedStream,
+    UnterminatedString,
+    UnterminatedHexString,
+    MissingLength,
+    MissingTrailer,
+    MissingRoot,
+    MissingCatalog,
+    CatalogNotDictionary,
+    NoObjects,
+    /// 对象流与交叉引用流
+    CompressedObjects,
+    AlreadyEncrypted,
+}
+
+impl PdfIssue {
+    fn key(self) -> &'static str {
+        match self {
+            PdfIssue::UnterminatedObject => "PDF_UNTERMINATED_OBJECT",
+            PdfIssue::UnterminatedStream => "PDF_UNTERMINATED_STREAM",
+            PdfIssue::UnterminatedString => "PDF_UNTERMINATED_STRING",
+            PdfIssue::UnterminatedHexString => "PDF_UNTERMINATED_HEX_STRING",
+            PdfIssue::MissingLength => "PDF_MISSING_LENGTH",
+            PdfIssue::MissingTrailer => "PDF_MISSING_TRAILER",
+            PdfIssue::MissingRoot => "PDF_MISSING_ROOT",
+            PdfIssue::MissingCatalog => "PDF_MISSING_CATALOG",
+            PdfIssue::CatalogNotDictionary => "PDF_CATALOG_NOT_DICTIONARY",
+            PdfIssue::NoObjects => "PDF_NO_OBJECTS",
+            PdfIssue::CompressedObjects => "PDF_COMPRESSED_OBJECTS",
+            PdfIssue::AlreadyEncrypted => "PDF_ALREADY_ENCRYPTED",
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AppError {
+    #[error("{}", self.message(Locale::ZhCn))]
+    FileReadError(#[from] std::io::Error),
+    #[error("{}", self.message(Locale::ZhCn))]
+    FileAccessError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{}", self.message(Locale::ZhCn))]
+    BrowserError(String),
+    #[error("{}", self.message(Locale::ZhCn))]
+    BrowserNotFound { probed: Vec<String> },
+    #[error("{}", self.message(Locale::ZhCn))]
+    PdfError(String),
+    #[error("{}", self.message(Locale::ZhCn))]
+    SettingsError(String),
+    #[error("{}", self.message(Locale::ZhCn))]
+    DiagnosticsError(String),
+    #[error("{}", self.message(Locale::ZhCn))]
+    PolicyError { path: String, reason: String },
+    #[error("{}", self.message(Locale::ZhCn))]
+    ClipboardError(String),
+    #[error("{}", self.message(Locale::ZhCn))]
+    InvalidPattern(String),
+    #[error("{}", self.message(Locale::ZhCn))]
+    ImportError(String),
+    #[error("{}", self.message(Locale::ZhCn))]
+    ExternalResources { origins: Vec<String> },
+    #[error("{}", self.message(Locale::ZhCn))]
+    Cancelled,
+    #[error("{}", self.message(Locale::ZhCn))]
+    StageTimeout { stage: String, seconds: u64 },
+    #[error("{}", self.message(Locale::ZhCn))]
+    ArchiveProtected,
+    #[error("{}", self.message(Locale::ZhCn))]
+    CorruptStore { path: String, reason: String },
+    #[error("{}", self.message(Locale::ZhCn))]
+    UntrustedCode { path: String, fingerprint: String },
+    #[error("{}", self.message(Locale::ZhCn))]
+    IncludeError { path: String, failure: IncludeFailure },
+    #[error("{}", self.message(Locale::ZhCn))]
+    PdfStructure { operation: PdfOperation, issue: PdfIssue },
+    /// 应用自身给出的提示：`code` 为错误码，`key` 为消息目录中的键，`args` 填充模板占位符
+    #[error("{}", self.message(Locale::ZhCn))]
+    Localized {
+        code: &'static str,
+        key: &'static str,
+        args: Vec<(&'static str, String)>,
+    },
+    #[error("{}", self.message(Locale::ZhCn))]
+    Internal { context: String, reason: String },
+}
+
+impl AppError {
+    /// 带路径的文件错误
+    pub fn file(path: impl AsRef<std::path::Path>, source: std::io::Error) -> Self {
+        AppError::FileAccessError {
+            path: path.as_ref().to_string_lossy().to_string(),
+            source,
+        }
+    }
+
+    /// 消息目录中的提示，`args` 为占位符参数
+    pub fn localized<const N: usize>(code: &'static str, key: &'static str, args: [(&'static str, String); N]) -> Self {
+        AppError::Localized { code, key, args: args.into() }
+    }
+
+    /// 浏览器启动后某一步操作失败（错误码 `BROWSER`，与启动失败区分），`key` 为说明该步骤的消息目录键
+    pub fn browser(key: &'static str, error: impl std::fmt::Display) -> Self {
+        AppError::localized("BROWSER", key, [("reason", error.to_string())])
+    }
+
+    /// 稳定的错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::FileReadError(_) => "FILE_READ",
+            AppError::FileAccessError { .. } => "FILE_ACCESS",
+            AppError::BrowserError(_) => "BROWSER_LAUNCH",
+            AppError::BrowserNotFound { .. } => "BROWSER_NOT_FOUND",
+            AppError::PdfError(_) => "PDF_GENERATION",
+            AppError::SettingsError(_) => "SETTINGS",
+            AppError::DiagnosticsError(_) => "DIAGNOSTICS",
+            AppError::PolicyError { .. } => "POLICY",
+            AppError::ClipboardError(_) => "CLIPBOARD",
+            AppError::InvalidPattern(_) => "INVALID_PATTERN",
+            AppError::ImportError(_) => "IMPORT",
+            AppError::ExternalResources { .. } => "EXTERNAL_RESOURCES",
+            AppError::Cancelled => "CANCELLED",
+            AppError::StageTimeout { .. } => "TIMEOUT",
+            AppError::ArchiveProtected => "PDFA_PROTECTED",
+            AppError::CorruptStore { .. } => "CORRUPT_STORE",
+            AppError::UntrustedCode { .. } => "UNTRUSTED_CODE",
+            AppError::IncludeError { .. } => "INCLUDE",
+            AppError::PdfStructure { .. } => "PDF_GENERATION",
+            AppError::Localized { code, .. } => code,
+            AppError::Internal { .. } => "INTERNAL",
+        }
+    }
+
+    /// 消息目录中的键：一般与错误码相同，同一错误码下按原因区分时更细
+    fn message_key(&self) -> &'static str {
+        match self {
+            AppError::IncludeError { failure, .. } => failure.key(),
+            AppError::PdfStructure { operation, .. } => operation.key(),
+            AppError::Localized { key, .. } => key,
+            _ => self.code(),
+        }
+    }
+
+    /// 机器可读的附加信息
+    pub fn details(&self) -> Value {
+        match self {
+            AppError::FileReadError(e) => json!({ "reason": e.to_string(), "kind": format!("{:?}", e.kind()) }),
+            AppError::FileAccessError { path, source } => json!({
+                "path": path,
+                "reason": source.to_string(),
+                "kind": format!("{:?}", source.kind()),
+            }),
+            AppError::BrowserNotFound { probed } => json!({ "probed": probed }),
+            AppError::ExternalResources { origins } => json!({ "origins": origins }),
+            AppError::PolicyError { path, reason } | AppError::CorruptStore { path, reason } => {
+                json!({ "path": path, "reason": reason })
+            }
+            AppError::Internal { context, reason } => json!({ "context": context, "reason": reason }),
+            AppError::UntrustedCode { path, fingerprint } => json!({ "path": path, "fingerprint": fingerprint }),
+            AppError::IncludeError { path, failure } => json!({ "path": path, "failure": failure.key() }),
+            AppError::PdfStructure { issue, .. } => json!({ "issue": issue.key() }),
+            AppError::Localized { args, .. } => {
+                Value::Object(args.iter().map(|(name, value)| (name.to_string(), json!(value))).collect())
+            }
+            AppError::Cancelled | AppError::ArchiveProtected => json!({}),
+            AppError::StageTimeout { stage, seconds } => json!({ "stage": stage, "seconds": seconds }),
+            AppError::BrowserError(reason)
+            | AppError::PdfError(reason)
+            | AppError::SettingsError(reason)
+            | AppError::DiagnosticsError(reason)
+            | AppError::ClipboardError(reason)
+            | AppError::InvalidPattern(reason)
+            | AppError::ImportError(reason) => json!({ "reason": reason }),
+        }
+    }
+
+    /// 指定语言的提示文本，`details` 中的字段作为模板占位符
+    pub fn message(&self, locale: Locale) -> String {
+        let mut args: Vec<(String, String)> = match self.details() {
+            Value::Object(map) => map
+                .into_iter()
+                .map(|(key, value)| {
+                    let text = match value {
+                        Value::String(s) => s,
+                        Value::Array(items) => items
+                            .iter()
+                            .map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string()))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        other => other.to_string(),
+                    };
+                    (key, text)
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        // 结构问题的说明同样来自消息目录
+        if let AppError::PdfStructure { issue, .. } = self {
+            args = vec![("issue".to_string(), i18n::translate(locale, issue.key(), &[]))];
+        }
+        i18n::translate(locale, self.message_key(), &args)
+    }
+}
+
+impl serde::Serialize for AppError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.message(i18n::current_locale()))?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+/// 从 panic 负载中取出消息
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// 安装 panic hook：把 panic 信息与调用栈写入日志，再交给默认 hook
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_default();
+        tracing::error!(%location, "panic: {}\n{}", panic_message(info.payload()), backtrace);
+        default_hook(info);
+    }));
+}
+
+/// 执行命令主体，把其中的 panic 转换为 `AppError::Internal`，避免异步运行时线程被终止
+pub fn catch_panic<T>(context: &str, f: impl FnOnce() -> Result<T, AppError>) -> Result<T, AppError> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let error = AppError::Internal {
+            context: context.to_string(),
+            reason: panic_message(payload.as_ref()),
+        };
+        crate::diagnostics::record_error(context, &error);
+        Err(error)
+    })
+}
+
+/// 后台任务失败（panic 或被取消）时转换为 `AppError::Internal`
+pub fn join_error(context: &str, error: tokio::task::JoinError) -> AppError {
+    let reason = if error.is_panic() {
+        panic_message(error.into_panic().as_ref())
+    } else {
+        error.to_string()
+    };
+    AppError::Internal { context: context.to_string(), reason }
+}
+
+/// 在阻塞线程池中执行一段同步代码（如一次 DevTools 调用），panic 与任务失败都转换为 `AppError`
+pub async fn run_blocking<T: Send + 'static>(
+    context: &'static str,
+    f: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    tokio::task::spawn_blocking(move || catch_panic(context, f))
+        .await
+        .map_err(|e| join_error(context, e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn localized_errors_use_the_catalog() {
+        let error = AppError::localized("SETTINGS", "SETTINGS_UNKNOWN_TRANSFORM", [("name", "smart".to_string())]);
+        assert_eq!(error.code(), "SETTINGS");
+        assert_eq!(error.details(), json!({ "name": "smart" }));
+        assert_eq!(error.message(Locale::ZhCn), "设置保存失败: 没有名为 smart 的转换");
+        assert_eq!(error.message(Locale::EnUs), "Failed to save settings: no transform named smart");
+    }
+
+    #[test]
+    fn pdf_structure_issue_is_translated() {
+        let error = AppError::PdfStructure { operation: PdfOperation::Protect, issue: PdfIssue::AlreadyEncrypted };
+        assert_eq!(error.code(), "PDF_GENERATION");
+        assert_eq!(error.details(), json!({ "issue": "PDF_ALREADY_ENCRYPTED" }));
+        assert_eq!(error.message(Locale::ZhCn), "PDF 生成错误: 无法加密该 PDF：文档已加密");
+        assert_eq!(
+            error.message(Locale::EnUs),
+            "PDF generation failed: cannot encrypt this PDF: the document is already encrypted"
+        );
+    }
+
+    #[test]
+    fn browser_step_failures_are_not_launch_errors() {
+        let error = AppError::browser("BROWSER_SCREENSHOT", "timeout");
+        assert_eq!(error.code(), "BROWSER");
+        assert_eq!(error.message(Locale::ZhCn), "浏览器错误: 截图失败: timeout");
+        assert_eq!(error.message(Locale::EnUs), "Browser error: screenshot failed: timeout");
+        assert_eq!(AppError::BrowserError("no sandbox".to_string()).code(), "BROWSER_LAUNCH");
+    }
+}
@@ -0,0 +1,189 @@
+//! 图片排版：
+//!  - 排版属性：`![alt](src){align=center}`、`{float=right width=40%}`、`{page=full}`
+//!  - 并排图片网格：相邻（无空行分隔）的多张图片，或 `:::gallery ... :::` 容器
+//!
+//! Markdown 解析器会把这些语法原样输出为文本或普通段落，
+//! 这里在 HTML 层把它们识别出来，转换为导出模板中对应的 class / style。
+
+use regex::Regex;
+
+/// 解析 `key=value` 形式的属性列表，值可以带引号
+pub fn parse_attributes(raw: &str) -> Vec<(String, String)> {
+    let raw = raw.replace("&quot;", "\"");
+    let re_attr = Regex::new(r#"([A-Za-z-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"']+))"#).unwrap();
+    re_attr
+        .captures_iter(&raw)
+        .map(|caps| {
+            let key = caps[1].to_ascii_lowercase();
+            let value = caps
+                .get(2)
+                .or_else(|| caps.get(3))
+                .or_else(|| caps.get(4))
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default();
+            (key, value)
+        })
+        .collect()
+}
+
+/// 仅允许常见的长度写法，避免把任意内容写进 style
+fn is_valid_length(value: &str) -> bool {
+    let re_len = Regex::new(r"^\d+(\.\d+)?(%|px|em|rem|cm|mm|in|pt)?$").unwrap();
+    re_len.is_match(value)
+}
+
+/// 把属性转换为 (class 列表, style 列表)；若没有任何可识别的属性则返回 None
+fn attributes_to_css(attrs: &[(String, String)]) -> Option<(Vec<String>, Vec<String>)> {
+    let mut classes = Vec::new();
+    let mut styles = Vec::new();
+
+    for (key, value) in attrs {
+        let value = value.to_ascii_lowercase();
+        match key.as_str() {
+            "align" if matches!(value.as_str(), "left" | "center" | "right") => {
+                classes.push(format!("figure-align-{}", value));
+            }
+            "float" if matches!(value.as_str(), "left" | "right") => {
+                classes.push(format!("figure-float-{}", value));
+            }
+            "page" if value == "full" => {
+                classes.push("figure-page-full".to_string());
+            }
+            "width" | "height" if is_valid_length(&value) => {
+                let value = if value.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                    format!("{}px", value)
+                } else {
+                    value
+                };
+                styles.push(format!("{}: {}", key, value));
+            }
+            _ => {}
+        }
+    }
+
+    if classes.is_empty() && styles.is_empty() {
+        None
+    } else {
+        Some((classes, styles))
+    }
+}
+
+/// 在标签属性串中设置 `name`：已有该属性时把 `value` 用 `separator` 接在原值之后，否则追加新属性
+fn merge_attribute(attrs: &str, name: &str, value: &str, separator: &str) -> String {
+    let re_existing = Regex::new(&format!(r#"(?i)(\s{}\s*=\s*)(?:"([^"]*)"|'([^']*)')"#, name)).unwrap();
+    let Some(caps) = re_existing.captures(attrs) else {
+        return format!("{} {}=\"{}\"", attrs, name, value);
+    };
+    let existing = caps.get(2).or_else(|| caps.get(3)).unwrap().as_str().trim().trim_end_matches(';');
+    let merged = if existing.is_empty() { value.to_string() } else { format!("{}{}{}", existing, separator, value) };
+    let whole = caps.get(0).unwrap();
+    format!("{}{}\"{}\"{}", &attrs[..whole.start()], &caps[1], merged, &attrs[whole.end()..])
+}
+
+/// 将 `<img ...>{attrs}` 改写为带有排版 class / style 的 `<img>`，与图片已有的 class / style 合并
+pub fn apply_figure_attributes(html: &str) -> String {
+    let re_img_attrs = Regex::new(r"(?s)<img\b([^>]*?)\s*/?>\s*\{([^{}\n]*)\}").unwrap();
+
+    re_img_attrs
+        .replace_all(html, |caps: &regex::Captures| {
+            let img_attrs = &caps[1];
+            let Some((classes, styles)) = attributes_to_css(&parse_attributes(&caps[2])) else {
+                return caps[0].to_string();
+            };
+
+            let mut attrs = img_attrs.to_string();
+            if !classes.is_empty() {
+                attrs = merge_attribute(&attrs, "class", &classes.join(" "), " ");
+            }
+            if !styles.is_empty() {
+                attrs = merge_attribute(&attrs, "style", &styles.join("; "), "; ");
+            }
+            format!("<img{} />", attrs)
+        })
+        .to_string()
+}
+
+/// 网格最多的列数，超过后自动换行
+const MAX_GRID_COLUMNS: usize = 4;
+
+fn render_image_grid(images: &[&str]) -> String {
+    let columns = images.len().clamp(1, MAX_GRID_COLUMNS);
+    let cells: String = images
+        .iter()
+        .map(|img| format!("<div class=\"image-grid-cell\">{}</div>", img))
+        .collect();
+    format!(
+        "<div class=\"image-grid\" style=\"grid-template-columns: repeat({}, 1fr)\">{}</div>",
+        columns, cells
+    )
+}
+
+/// 将多张图片排成一行网格：
+///  1. `:::gallery` ... `:::` 容器内的所有图片
+///  2. 只由两张及以上图片组成的段落（Markdown 中图片之间没有空行）
+pub fn apply_image_grids(html: &str) -> String {
+    let re_img = Regex::new(r"<img\b[^>]*>").unwrap();
+
+    // 1. :::gallery 容器（开始/结束标记可能与图片处于同一段落）
+    let re_gallery = Regex::new(r"(?s)<p>\s*:::\s*gallery\s*(.*?)\s*:::\s*</p>").unwrap();
+    let content = re_gallery.replace_all(html, |caps: &regex::Captures| {
+        let images: Vec<&str> = re_img.find_iter(&caps[1]).map(|m| m.as_str()).collect();
+        if images.is_empty() {
+            return caps[0].to_string();
+        }
+        render_image_grid(&images)
+    });
+
+    // 2. 纯图片段落
+    let re_image_paragraph =
+        Regex::new(r"<p>((?:\s*<img\b[^>]*>\s*(?:<br\s*/?>)?)+)\s*</p>").unwrap();
+    re_image_paragraph
+        .replace_all(&content, |caps: &regex::Captures| {
+            let images: Vec<&str> = re_img.find_iter(&caps[1]).map(|m| m.as_str()).collect();
+            if images.len() < 2 {
+                return caps[0].to_string();
+            }
+            render_image_grid(&images)
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placement_attributes_become_classes_and_styles() {
+        assert_eq!(
+            apply_figure_attributes(r#"<p><img src="a.png" alt="a" />{align=center}</p>"#),
+            r#"<p><img src="a.png" alt="a" class="figure-align-center" /></p>"#
+        );
+        assert_eq!(
+            apply_figure_attributes(r#"<img src="a.png">{float=right width=40%}"#),
+            r#"<img src="a.png" class="figure-float-right" style="width: 40%" />"#
+        );
+        assert_eq!(
+            apply_figure_attributes(r#"<img src="a.png">{page=full height=120}"#),
+            r#"<img src="a.png" class="figure-page-full" style="height: 120px" />"#
+        );
+    }
+
+    #[test]
+    fn merges_with_existing_class_and_style() {
+        assert_eq!(
+            apply_figure_attributes(r#"<img class="emoji" style="border: 0;" src="a.png">{align=right width=2em}"#),
+            r#"<img class="emoji figure-align-right" style="border: 0; width: 2em" src="a.png" />"#
+        );
+        assert_eq!(
+            apply_figure_attributes(r#"<img src="a.png" CLASS='wide'>{float=left}"#),
+            r#"<img src="a.png" CLASS="wide figure-float-left" />"#
+        );
+    }
+
+    #[test]
+    fn unknown_or_unsafe_attributes_are_left_alone() {
+        let html = r#"<img src="a.png">{width=expression(alert(1)) color=red}"#;
+        assert_eq!(apply_figure_attributes(html), html);
+        assert_eq!(apply_figure_attributes("<p>{align=center}</p>"), "<p>{align=center}</p>");
+    }
+}
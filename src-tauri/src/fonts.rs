@@ -0,0 +1,302 @@
+//! 字体配置：正文、标题、等宽字体与基础字号、行高。
+//!
+//! 设置中的 `fonts` 为默认值，front matter 中的 `fonts` 按字段覆盖。选择的字体后面总会跟上
+//! 对应风格的中文字体作为后备，避免所选字体缺少汉字时出现方框或回退到不协调的字体。
+//!
+//! `list_system_fonts` 扫描系统字体目录，读取字体文件 `name` 表中的家族名（含中文名）。
+
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontSettings {
+    /// 正文字体
+    pub body: Option<String>,
+    /// 标题字体，未指定时与正文相同
+    pub heading: Option<String>,
+    /// 代码字体
+    pub monospace: Option<String>,
+    /// 正文字号（pt）
+    pub base_size: Option<f64>,
+    /// 正文行高（倍数）
+    pub line_height: Option<f64>,
+}
+
+impl FontSettings {
+    /// 用 `other` 中已指定的字段覆盖当前值
+    pub fn merged(&self, other: &FontSettings) -> FontSettings {
+        FontSettings {
+            body: other.body.clone().or_else(|| self.body.clone()),
+            heading: other.heading.clone().or_else(|| self.heading.clone()),
+            monospace: other.monospace.clone().or_else(|| self.monospace.clone()),
+            base_size: other.base_size.or(self.base_size),
+            line_height: other.line_height.or(self.line_height),
+        }
+    }
+
+    /// 设置与 front matter 中 `fonts` 合并后的配置
+    pub fn resolve(settings: &FontSettings, front_matter: Option<&serde_yaml::Value>) -> FontSettings {
+        let overrides = front_matter
+            .and_then(|fm| fm.get("fonts"))
+            .and_then(|value| serde_yaml::from_value::<FontSettings>(value.clone()).ok());
+        match overrides {
+            Some(overrides) => settings.merged(&overrides),
+            None => settings.clone(),
+        }
+    }
+}
+
+/// 正文（衬线）字体的中文后备
+const SERIF_FALLBACK: &str = r#""SimSun", "宋体", "Songti SC", "Noto Serif CJK SC", "Source Han Serif SC", serif"#;
+/// 标题（无衬线）字体的中文后备
+const SANS_FALLBACK: &str =
+    r#""Microsoft YaHei", "微软雅黑", "PingFang SC", "Noto Sans CJK SC", "Source Han Sans SC", sans-serif"#;
+/// 代码字体的后备：先西文等宽字体，再中文无衬线字体
+const MONO_FALLBACK: &str =
+    r#"Consolas, "Cascadia Code", Menlo, "DejaVu Sans Mono", "Microsoft YaHei", "PingFang SC", "Noto Sans CJK SC", monospace"#;
+
+/// 去掉可能破坏样式表的字符后加上引号
+fn quote_family(family: &str) -> Option<String> {
+    let cleaned: String = family
+        .chars()
+        .filter(|c| !matches!(c, '"' | '\'' | '\\' | ';' | '{' | '}' | '<' | '>'))
+        .collect();
+    let cleaned = cleaned.trim();
+    (!cleaned.is_empty()).then(|| format!("\"{}\"", cleaned))
+}
+
+fn family_list(family: Option<&str>, fallback: &str) -> Option<String> {
+    family.and_then(quote_family).map(|quoted| format!("{}, {}", quoted, fallback))
+}
+
+/// 覆盖导出模板默认字体的样式；未配置任何字段时返回空字符串
+pub fn font_css(fonts: &FontSettings) -> String {
+    let mut css = String::new();
+    let mut body_rules = Vec::new();
+    if let Some(list) = family_list(fonts.body.as_deref(), SERIF_FALLBACK) {
+        body_rules.push(format!("font-family: {};", list));
+    }
+    if let Some(size) = fonts.base_size.filter(|s| (6.0..=48.0).contains(s)) {
+        body_rules.push(format!("font-size: {}pt;", size));
+    }
+    if let Some(height) = fonts.line_height.filter(|h| (0.8..=4.0).contains(h)) {
+        body_rules.push(format!("line-height: {};", height));
+    }
+    if !body_rules.is_empty() {
+        css.push_str(&format!("body {{ {} }}\n", body_rules.join(" ")));
+    }
+    if let Some(list) = family_list(fonts.heading.as_deref(), SANS_FALLBACK) {
+        css.push_str(&format!("h1, h2, h3, h4, h5, h6, .cover-title {{ font-family: {}; }}\n", list));
+    }
+    if let Some(list) = family_list(fonts.monospace.as_deref(), MONO_FALLBACK) {
+        css.push_str(&format!("code, pre, kbd, samp {{ font-family: {}; }}\n", list));
+    }
+    css
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemFont {
+    /// 家族名（英文名优先）
+    pub family: String,
+    /// 其他语言的家族名，如 SimSun 的“宋体”
+    pub localized_names: Vec<String>,
+    pub monospace: bool,
+}
+
+/// 字体文件中的一个字体：家族名（按出现顺序去重）与是否等宽
+struct FontFace {
+    names: Vec<String>,
+    monospace: bool,
+}
+
+fn read_at(file: &mut File, offset: u64, len: usize) -> Option<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    file.read_exact(&mut buf).ok()?;
+    Some(buf)
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*data.get(pos)?, *data.get(pos + 1)?]))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes([*data.get(pos)?, *data.get(pos + 1)?, *data.get(pos + 2)?, *data.get(pos + 3)?]))
+}
+
+/// 解码 `name` 表中的字符串：Unicode 与 Windows 平台为 UTF-16BE，Mac 平台只接受 ASCII
+fn decode_name(platform: u16, data: &[u8]) -> Option<String> {
+    match platform {
+        0 | 3 => {
+            let units: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+            String::from_utf16(&units).ok()
+        }
+        1 if data.is_ascii() => Some(String::from_utf8_lossy(data).to_string()),
+        _ => None,
+    }
+}
+
+/// 读取位于 `offset` 的一个字体（sfnt）的家族名与等宽标记
+fn read_face(file: &mut File, offset: u64) -> Option<FontFace> {
+    let header = read_at(file, offset, 12)?;
+    let num_tables = u16_at(&header, 4)? as usize;
+    let records = read_at(file, offset + 12, num_tables * 16)?;
+    let mut name_table = None;
+    let mut post_table = None;
+    for i in 0..num_tables {
+        let record = &records[i * 16..i * 16 + 16];
+        let table_offset = u32_at(record, 8)? as u64;
+        let length = u32_at(record, 12)? as usize;
+        match &record[..4] {
+            b"name" => name_table = Some((table_offset, length)),
+            b"post" => post_table = Some(table_offset),
+            _ => {}
+        }
+    }
+
+    let (name_offset, name_length) = name_table?;
+    let name = read_at(file, name_offset, name_length.min(1 << 20))?;
+    let count = u16_at(&name, 2)? as usize;
+    let string_offset = u16_at(&name, 4)? as usize;
+    // 优先使用排版家族名（ID 16），没有时使用家族名（ID 1）
+    let mut by_id: BTreeMap<u16, Vec<(u16, String)>> = BTreeMap::new();
+    for i in 0..count {
+        let base = 6 + i * 12;
+        let platform = u16_at(&name, base)?;
+        let language = u16_at(&name, base + 4)?;
+        let name_id = u16_at(&name, base + 6)?;
+        if name_id != 1 && name_id != 16 {
+            continue;
+        }
+        let length = u16_at(&name, base + 8)? as usize;
+        let start = string_offset + u16_at(&name, base + 10)? as usize;
+        let Some(text) = name.get(start..start + length).and_then(|data| decode_name(platform, data)) else {
+            continue;
+        };
+        let text = text.trim().to_string();
+        if !text.is_empty() {
+            // 英语（0x0409 / Mac 0）排在前面
+            let rank = if language == 0x0409 || (platform == 1 && language == 0) { 0 } else { 1 };
+            by_id.entry(name_id).or_default().push((rank, text));
+        }
+    }
+    let mut entries = by_id.remove(&16).or_else(|| by_id.remove(&1))?;
+    entries.sort_by_key(|(rank, _)| *rank);
+    let mut names: Vec<String> = Vec::new();
+    for (_, text) in entries {
+        if !names.contains(&text) {
+            names.push(text);
+        }
+    }
+
+    let monospace = post_table
+        .and_then(|post| read_at(file, post + 12, 4))
+        .and_then(|data| u32_at(&data, 0))
+        .is_some_and(|fixed| fixed != 0);
+    Some(FontFace { names, monospace })
+}
+
+/// 读取字体文件中的所有字体（`.ttc` 字体集合包含多个）
+fn read_font_file(path: &Path) -> Vec<FontFace> {
+    let Ok(mut file) = File::open(path) else {
+        return Vec::new();
+    };
+    let Some(header) = read_at(&mut file, 0, 12) else {
+        return Vec::new();
+    };
+    if &header[..4] != b"ttcf" {
+        return read_face(&mut file, 0).into_iter().collect();
+    }
+    let count = u32_at(&header, 8).unwrap_or(0).min(256) as usize;
+    let Some(offsets) = read_at(&mut file, 12, count * 4) else {
+        return Vec::new();
+    };
+    (0..count)
+        .filter_map(|i| u32_at(&offsets, i * 4))
+        .filter_map(|offset| read_face(&mut file, offset as u64))
+        .collect()
+}
+
+/// 各平台的系统与用户字体目录
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        let windir = std::env::var_os("WINDIR").map(PathBuf::from).unwrap_or_else(|| PathBuf::from("C:\\Windows"));
+        dirs.push(windir.join("Fonts"));
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(PathBuf::from(local).join("Microsoft").join("Windows").join("Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        if let Some(home) = &home {
+            dirs.push(home.join("Library/Fonts"));
+        }
+    } else {
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Some(home) = &home {
+            dirs.push(home.join(".local/share/fonts"));
+            dirs.push(home.join(".fonts"));
+        }
+    }
+    dirs
+}
+
+/// 递归收集目录下的字体文件
+fn collect_font_files(dir: &Path, files: &mut Vec<PathBuf>, depth: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < 4 {
+                collect_font_files(&path, files, depth + 1);
+            }
+            continue;
+        }
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        if matches!(extension.as_deref(), Some("ttf" | "otf" | "ttc" | "otc")) {
+            files.push(path);
+        }
+    }
+}
+
+/// 扫描系统中已安装的字体，按家族名合并、排序
+pub fn scan_system_fonts() -> Vec<SystemFont> {
+    let mut files = Vec::new();
+    for dir in font_dirs() {
+        collect_font_files(&dir, &mut files, 0);
+    }
+
+    let mut families: BTreeMap<String, SystemFont> = BTreeMap::new();
+    for face in files.iter().flat_map(|path| read_font_file(path)) {
+        let Some((family, others)) = face.names.split_first() else {
+            continue;
+        };
+        let entry = families.entry(family.clone()).or_insert_with(|| SystemFont {
+            family: family.clone(),
+            localized_names: Vec::new(),
+            monospace: face.monospace,
+        });
+        for name in others {
+            if !entry.localized_names.contains(name) {
+                entry.localized_names.push(name.clone());
+            }
+        }
+    }
+    families.into_values().collect()
+}
+
+/// 列出系统中已安装的字体家族
+#[tauri::command]
+pub async fn list_system_fonts() -> Result<Vec<SystemFont>, AppError> {
+    crate::error::run_blocking("list_system_fonts", || Ok(scan_system_fonts())).await
+}
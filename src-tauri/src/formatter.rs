@@ -0,0 +1,824 @@
+//! Markdown 格式化器
+//!
+//! 步骤：
+//!  1. 统一换行符
+//!  2. 将单行 $$公式$$ 展开为独立的块级公式（多行格式），并确保 $$ 行前后各有一个空行
+//!  3. 逐行处理（跳过代码块、公式块、front matter 与 HTML 块）：
+//!     - 标题统一为 ATX 风格（`# 标题`），去掉闭合 `#`，前后各留一个空行
+//!     - 有序列表重新编号
+//!     - 统一强调标记（`*`/`_`），代码、链接地址、网址、公式与转义字符不受影响
+//!     - 按行宽折行（中日韩字符按双宽计算，可在任意两个汉字之间断行，标点不出现在行首，
+//!       续行不以列表、标题、引用等块级标记开头）
+//!     - 表格竖线对齐
+//!     - 中日文排版规范化（中西文间空格、全角标点、半角字母数字，见 typography 模块）
+//!  4. 压缩连续空行，trim
+//!
+//! 所有规则都可以通过 `FormatOptions` 单独开关。
+
+use crate::typography::{self, TypographySettings};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmphasisMarker {
+    /// 保持原样
+    Keep,
+    /// `*斜体*`、`**粗体**`
+    #[default]
+    Asterisk,
+    /// `_斜体_`、`__粗体__`
+    Underscore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    /// 展开单行 $$公式$$ 并在公式块前后留空行
+    pub normalize_math: bool,
+    /// 标题统一为 ATX 风格并在前后留空行
+    pub normalize_headings: bool,
+    /// 有序列表重新编号
+    pub renumber_lists: bool,
+    /// 对齐表格竖线
+    pub align_tables: bool,
+    /// 强调标记风格
+    pub emphasis: EmphasisMarker,
+    /// 最大行宽（按显示宽度计算），0 表示不折行
+    pub line_width: usize,
+    /// 中日文排版规范化规则，未指定时由调用方按设置与 front matter 填入
+    pub typography: Option<TypographySettings>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            normalize_math: true,
+            normalize_headings: true,
+            renumber_lists: true,
+            align_tables: true,
+            emphasis: EmphasisMarker::Asterisk,
+            line_width: 0,
+            typography: None,
+        }
+    }
+}
+
+/// 中日韩文字与全角标点
+const CJK_CLASS: &str = r"\p{Han}\p{Hiragana}\p{Katakana}\p{Hangul}　-〿＀-￯";
+
+/// 不能出现在行首的标点
+fn is_closing_punctuation(token: &str) -> bool {
+    matches!(
+        token,
+        "，" | "。" | "、" | "；" | "：" | "？" | "！" | "）" | "】" | "」" | "』" | "》" | "〉" | "”" | "’" | "…" | "," | "." | ";" | ":" | "?" | "!" | ")"
+    )
+}
+
+fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// 步骤 2：展开单行 $$公式$$，并确保 $$ 行前后各有一个空行
+fn normalize_math_blocks(content: &str) -> String {
+    let re_inline_block = Regex::new(r"\$\$([^\$\n]+?)\$\$").unwrap();
+    let content = re_inline_block.replace_all(content, "\n\n$$$$\n$1\n$$$$\n\n").to_string();
+
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    let mut in_formula = false;
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].trim() == "$$" {
+            if !in_formula {
+                // 公式开始：确保前面有空行
+                if i > 0 && !lines[i - 1].trim().is_empty() {
+                    lines.insert(i, String::new());
+                    i += 1; // 跳过刚插入的空行，仍处理当前 $$
+                }
+                in_formula = true;
+            } else {
+                // 公式结束：确保后面有空行
+                if i + 1 < lines.len() && !lines[i + 1].trim().is_empty() {
+                    lines.insert(i + 1, String::new());
+                }
+                in_formula = false;
+            }
+        }
+        i += 1;
+    }
+    lines.join("\n")
+}
+
+/// 行的类别：受保护的行原样输出
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LineKind {
+    Text,
+    Protected,
+}
+
+/// 标记代码块、公式块、front matter 与 HTML 块中的行
+pub(crate) fn classify_lines(lines: &[String]) -> Vec<LineKind> {
+    let re_fence = Regex::new(r"^\s{0,3}(`{3,}|~{3,})").unwrap();
+    let re_html = Regex::new(r"^\s{0,3}</?[A-Za-z][A-Za-z0-9-]*(\s|/?>|$)|^\s{0,3}<!--").unwrap();
+    let mut kinds = vec![LineKind::Text; lines.len()];
+    let mut i = 0;
+
+    // front matter
+    if lines.first().map(|l| l.trim() == "---").unwrap_or(false) {
+        if let Some(end) = (1..lines.len()).find(|&j| matches!(lines[j].trim(), "---" | "...")) {
+            for kind in kinds.iter_mut().take(end + 1) {
+                *kind = LineKind::Protected;
+            }
+            i = end + 1;
+        }
+    }
+
+    while i < lines.len() {
+        let line = &lines[i];
+        let trimmed = line.trim();
+        if let Some(caps) = re_fence.captures(line) {
+            let marker = caps[1].to_string();
+            let fence_char = marker.chars().next().unwrap_or('`');
+            let end = (i + 1..lines.len())
+                .find(|&j| {
+                    let t = lines[j].trim();
+                    t.len() >= marker.len() && t.chars().all(|c| c == fence_char)
+                })
+                .unwrap_or(lines.len() - 1);
+            for kind in kinds.iter_mut().take(end + 1).skip(i) {
+                *kind = LineKind::Protected;
+            }
+            i = end + 1;
+            continue;
+        }
+        if trimmed == "$$" || (trimmed.starts_with("$$") && !(trimmed.len() > 2 && trimmed.ends_with("$$"))) {
+            let end = (i + 1..lines.len())
+                .find(|&j| lines[j].trim().ends_with("$$"))
+                .unwrap_or(lines.len() - 1);
+            for kind in kinds.iter_mut().take(end + 1).skip(i) {
+                *kind = LineKind::Protected;
+            }
+            i = end + 1;
+            continue;
+        }
+        if trimmed.starts_with("$$") {
+            kinds[i] = LineKind::Protected;
+            i += 1;
+            continue;
+        }
+        if re_html.is_match(line) {
+            // HTML 块持续到空行
+            while i < lines.len() && !lines[i].trim().is_empty() {
+                kinds[i] = LineKind::Protected;
+                i += 1;
+            }
+            continue;
+        }
+        i += 1;
+    }
+    kinds
+}
+
+/// 标题：`#标题` → `# 标题`，去掉闭合的 `#`，Setext 标题转为 ATX
+fn normalize_headings(lines: &mut Vec<String>, kinds: &mut Vec<LineKind>) {
+    let re_atx = Regex::new(r"^ {0,3}(#{1,6})(?:[ \t]+|([^#\s]))(.*?)(?:[ \t]+#+)?[ \t]*$").unwrap();
+    let re_empty_atx = Regex::new(r"^ {0,3}(#{1,6})[ \t]*$").unwrap();
+    let re_setext = Regex::new(r"^ {0,3}(=+|-+)[ \t]*$").unwrap();
+    let re_block_start = Regex::new(r"^\s*([-*+]\s|\d+[.)]\s|>|\||#)").unwrap();
+
+    let mut i = 0;
+    while i < lines.len() {
+        if kinds[i] != LineKind::Text {
+            i += 1;
+            continue;
+        }
+
+        // Setext：单行段落下方的 === / ---（上一行为空行或标题）
+        let prev_separated = i == 0 || lines[i - 1].trim().is_empty() || re_atx.is_match(&lines[i - 1]);
+        if prev_separated
+            && !lines[i].trim().is_empty()
+            && !re_block_start.is_match(&lines[i])
+            && i + 1 < lines.len()
+            && kinds[i + 1] == LineKind::Text
+        {
+            if let Some(caps) = re_setext.captures(&lines[i + 1]) {
+                let level = if caps[1].starts_with('=') { "#" } else { "##" };
+                lines[i] = format!("{} {}", level, lines[i].trim());
+                lines.remove(i + 1);
+                kinds.remove(i + 1);
+            }
+        }
+
+        if re_empty_atx.is_match(&lines[i]) {
+            i += 1;
+            continue;
+        }
+        if let Some(caps) = re_atx.captures(&lines[i]) {
+            let glued = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+            let text = format!("{}{}", glued, &caps[3]);
+            lines[i] = format!("{} {}", &caps[1], text.trim());
+        }
+        i += 1;
+    }
+}
+
+/// 确保标题前后各有一个空行
+fn pad_headings(lines: &mut Vec<String>, kinds: &mut Vec<LineKind>) {
+    let re_heading = Regex::new(r"^#{1,6} ").unwrap();
+    let mut i = 0;
+    while i < lines.len() {
+        if kinds[i] == LineKind::Text && re_heading.is_match(&lines[i]) {
+            if i > 0 && !lines[i - 1].trim().is_empty() {
+                lines.insert(i, String::new());
+                kinds.insert(i, LineKind::Text);
+                i += 1;
+            }
+            if i + 1 < lines.len() && !lines[i + 1].trim().is_empty() {
+                lines.insert(i + 1, String::new());
+                kinds.insert(i + 1, LineKind::Text);
+            }
+        }
+        i += 1;
+    }
+}
+
+/// 有序列表按层级重新编号（每个列表从其第一项的编号开始递增）
+fn renumber_lists(lines: &mut [String], kinds: &[LineKind]) {
+    let re_ordered = Regex::new(r"^(\s*)(\d+)([.)])(\s+.*)$").unwrap();
+    let re_unordered = Regex::new(r"^(\s*)[-*+]\s").unwrap();
+    // (缩进, 下一个编号)
+    let mut stack: Vec<(usize, u64)> = Vec::new();
+    let mut prev_blank = false;
+
+    for (line, kind) in lines.iter_mut().zip(kinds) {
+        if *kind != LineKind::Text {
+            stack.clear();
+            prev_blank = false;
+            continue;
+        }
+        if line.trim().is_empty() {
+            prev_blank = true;
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+
+        if let Some(caps) = re_ordered.captures(line) {
+            let number: u64 = caps[2].parse().unwrap_or(1);
+            while stack.last().is_some_and(|&(i, _)| i > indent) {
+                stack.pop();
+            }
+            let next = match stack.last_mut() {
+                Some((i, next)) if *i == indent => {
+                    let current = *next;
+                    *next += 1;
+                    current
+                }
+                _ => {
+                    stack.push((indent, number + 1));
+                    number
+                }
+            };
+            *line = format!("{}{}{}{}", &caps[1], next, &caps[3], &caps[4]);
+        } else if let Some(caps) = re_unordered.captures(line) {
+            // 同一缩进出现无序列表项：之前的有序列表结束
+            let bullet_indent = caps[1].len();
+            while stack.last().is_some_and(|&(i, _)| i >= bullet_indent) {
+                stack.pop();
+            }
+        } else if prev_blank && indent == 0 {
+            // 空行之后的非缩进内容：列表结束
+            stack.clear();
+        } else if line.trim_start().starts_with('#') {
+            stack.clear();
+        }
+        prev_blank = false;
+    }
+}
+
+/// 不参与强调标记统一的片段：行内代码、链接与图片的地址、自动链接、裸网址、行内公式与转义字符
+const EMPHASIS_ATOMS: &str =
+    r"`+[^`]*`+|\]\([^)]*\)|<[^>\s]+>|(?:https?|ftp)://[^\s<>()]+|\$[^$\n]+\$|\\[\\*_]";
+
+/// 行内的保护片段替换为私用区字符包裹的序号，处理后再还原
+fn protect_atoms(line: &str, re_atom: &Regex) -> (String, Vec<String>) {
+    let mut atoms = Vec::new();
+    let masked = re_atom
+        .replace_all(line, |c: &Captures| {
+            atoms.push(c[0].to_string());
+            format!("\u{E000}{}\u{E001}", atoms.len() - 1)
+        })
+        .to_string();
+    (masked, atoms)
+}
+
+fn restore_atoms(line: &str, atoms: &[String]) -> String {
+    let re_slot = Regex::new("\u{E000}(\\d+)\u{E001}").unwrap();
+    re_slot
+        .replace_all(line, |c: &Captures| c[1].parse::<usize>().ok().and_then(|i| atoms.get(i)).cloned().unwrap_or_default())
+        .to_string()
+}
+
+/// 统一强调标记；单词内部的 `_`（如 snake_case）不受影响
+fn normalize_emphasis(lines: &mut [String], kinds: &[LineKind], marker: EmphasisMarker) {
+    let (re_strong, re_em, strong, em) = match marker {
+        EmphasisMarker::Keep => return,
+        EmphasisMarker::Asterisk => (
+            Regex::new(r"(^|[^\w_])__([^\s_](?:[^_]*?[^\s_])?)__([^\w_]|$)").unwrap(),
+            Regex::new(r"(^|[^\w_])_([^\s_](?:[^_]*?[^\s_])?)_([^\w_]|$)").unwrap(),
+            "**",
+            "*",
+        ),
+        EmphasisMarker::Underscore => (
+            Regex::new(r"(^|[^\w*])\*\*([^\s*](?:[^*]*?[^\s*])?)\*\*([^\w*]|$)").unwrap(),
+            Regex::new(r"(^|[^\w*])\*([^\s*](?:[^*]*?[^\s*])?)\*([^\w*]|$)").unwrap(),
+            "__",
+            "_",
+        ),
+    };
+    let re_atom = Regex::new(EMPHASIS_ATOMS).unwrap();
+
+    for (line, kind) in lines.iter_mut().zip(kinds) {
+        if *kind != LineKind::Text {
+            continue;
+        }
+        // 强调可以包含代码、链接等片段，整行处理，只保护片段本身
+        let (masked, atoms) = protect_atoms(line, &re_atom);
+        let masked = re_strong.replace_all(&masked, |c: &Captures| format!("{}{}{}{}{}", &c[1], strong, &c[2], strong, &c[3]));
+        let masked = re_em.replace_all(&masked, |c: &Captures| format!("{}{}{}{}{}", &c[1], em, &c[2], em, &c[3]));
+        *line = restore_atoms(&masked, &atoms);
+    }
+}
+
+fn split_table_row(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let inner = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let inner = inner.strip_suffix('|').filter(|s| !s.ends_with('\\')).unwrap_or(inner);
+
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut in_code = false;
+    let mut escaped = false;
+    for c in inner.chars() {
+        match c {
+            '\\' if !escaped => {
+                escaped = true;
+                current.push(c);
+                continue;
+            }
+            '`' => in_code = !in_code,
+            '|' if !in_code && !escaped => {
+                cells.push(current.trim().to_string());
+                current.clear();
+                escaped = false;
+                continue;
+            }
+            _ => {}
+        }
+        escaped = false;
+        current.push(c);
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+#[derive(Clone, Copy)]
+enum Align {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+fn pad_cell(text: &str, width: usize, align: Align) -> String {
+    let gap = width.saturating_sub(display_width(text));
+    match align {
+        Align::Right => format!("{}{}", " ".repeat(gap), text),
+        Align::Center => format!("{}{}{}", " ".repeat(gap / 2), text, " ".repeat(gap - gap / 2)),
+        Align::None | Align::Left => format!("{}{}", text, " ".repeat(gap)),
+    }
+}
+
+/// 对齐表格：每列按最宽单元格补齐空格
+fn align_tables(lines: &mut [String], kinds: &[LineKind]) {
+    let re_delimiter = Regex::new(r"^\s*\|?\s*:?-+:?\s*(\|\s*:?-+:?\s*)*\|?\s*$").unwrap();
+    let mut i = 0;
+    while i + 1 < lines.len() {
+        let is_header = kinds[i] == LineKind::Text
+            && kinds[i + 1] == LineKind::Text
+            && lines[i].contains('|')
+            && re_delimiter.is_match(&lines[i + 1])
+            && lines[i + 1].contains('-');
+        if !is_header {
+            i += 1;
+            continue;
+        }
+
+        let mut end = i + 2;
+        while end < lines.len() && kinds[end] == LineKind::Text && lines[end].contains('|') && !lines[end].trim().is_empty() {
+            end += 1;
+        }
+
+        let rows: Vec<Vec<String>> = lines[i..end].iter().map(|l| split_table_row(l)).collect();
+        let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        let aligns: Vec<Align> = (0..columns)
+            .map(|c| {
+                let spec = rows[1].get(c).map(|s| s.trim()).unwrap_or("");
+                match (spec.starts_with(':'), spec.ends_with(':')) {
+                    (true, true) => Align::Center,
+                    (true, false) => Align::Left,
+                    (false, true) => Align::Right,
+                    _ => Align::None,
+                }
+            })
+            .collect();
+        let widths: Vec<usize> = (0..columns)
+            .map(|c| {
+                rows.iter()
+                    .enumerate()
+                    .filter(|(r, _)| *r != 1)
+                    .map(|(_, row)| row.get(c).map(|s| display_width(s)).unwrap_or(0))
+                    .max()
+                    .unwrap_or(0)
+                    .max(3)
+            })
+            .collect();
+
+        for (r, row) in rows.iter().enumerate() {
+            let cells: Vec<String> = (0..columns)
+                .map(|c| {
+                    if r == 1 {
+                        let w = widths[c];
+                        match aligns[c] {
+                            Align::None => "-".repeat(w),
+                            Align::Left => format!(":{}", "-".repeat(w - 1)),
+                            Align::Right => format!("{}:", "-".repeat(w - 1)),
+                            Align::Center => format!(":{}:", "-".repeat(w - 2)),
+                        }
+                    } else {
+                        pad_cell(row.get(c).map(|s| s.as_str()).unwrap_or(""), widths[c], aligns[c])
+                    }
+                })
+                .collect();
+            lines[i + r] = format!("| {} |", cells.join(" | "));
+        }
+        i = end;
+    }
+}
+
+/// 位于行首时会被解析为块级结构的词：列表标记、标题、引用、分隔线与代码围栏
+fn starts_block(token: &str) -> bool {
+    let re_marker = Regex::new(r"^(?:[-+*]|#{1,6}|\d{1,9}[.)]|=+|-+|\*+|_{3,})$|^>|^(?:`{3,}|~{3,})").unwrap();
+    re_marker.is_match(token)
+}
+
+/// 转义块级标记，使其按普通文本解析（`1.` → `1\.`，`-` → `\-`）
+fn escape_block_marker(token: &str) -> String {
+    if token.starts_with(|c: char| c.is_ascii_digit()) {
+        let split = token.len() - 1;
+        format!("{}\\{}", &token[..split], &token[split..])
+    } else {
+        format!("\\{}", token)
+    }
+}
+
+/// 按行宽折行；中日韩字符之间可以断行，标点不出现在行首，行内代码/链接/公式不拆开，
+/// 续行不以列表、标题、引用等块级标记开头（提前一个词断行，无法提前时转义标记）
+fn wrap_lines(lines: &mut Vec<String>, kinds: &mut Vec<LineKind>, width: usize) {
+    let re_prefix = Regex::new(r"^((?:\s*>)*\s*(?:[-*+]\s+(?:\[[ xX]\]\s+)?|\d+[.)]\s+)?)").unwrap();
+    let re_token = Regex::new(&format!(
+        r"(\s*)(`+[^`]*`+|!?\[[^\]]*\]\([^)]*\)|\$[^$]+\$|<[^>\s]+>|[{cjk}]|[^\s`{cjk}]+|\S)",
+        cjk = CJK_CLASS
+    ))
+    .unwrap();
+    let re_skip = Regex::new(r"^\s*(#|\||    |\t)").unwrap();
+
+    let mut i = 0;
+    while i < lines.len() {
+        if kinds[i] != LineKind::Text || display_width(&lines[i]) <= width || re_skip.is_match(&lines[i]) {
+            i += 1;
+            continue;
+        }
+        let line = lines[i].clone();
+        let hard_break = line.ends_with("  ");
+        let prefix = re_prefix.find(&line).map(|m| m.as_str().to_string()).unwrap_or_default();
+        // 续行：引用标记保留，列表标记换成等宽空格
+        let quote_part: String = prefix.chars().take_while(|c| *c == '>' || c.is_whitespace()).collect();
+        let continuation = format!("{}{}", quote_part, " ".repeat(display_width(&prefix) - display_width(&quote_part)));
+
+        let body = &line[prefix.len()..];
+        let mut wrapped: Vec<String> = Vec::new();
+        // 当前行的前缀与（前导空格, 词）列表
+        let mut current_prefix = prefix.clone();
+        let mut current: Vec<(&str, String)> = Vec::new();
+        let render = |prefix: &str, tokens: &[(&str, String)]| {
+            let mut out = prefix.to_string();
+            for (index, (space, token)) in tokens.iter().enumerate() {
+                if index > 0 {
+                    out.push_str(space);
+                }
+                out.push_str(token);
+            }
+            out
+        };
+        for caps in re_token.captures_iter(body.trim_end()) {
+            let space = if caps[1].is_empty() { "" } else { " " };
+            let token = caps.get(2).map(|m| m.as_str()).unwrap_or("");
+            let candidate_width =
+                display_width(&render(&current_prefix, &current)) + display_width(space) + display_width(token);
+            if current.is_empty() || candidate_width <= width || is_closing_punctuation(token) {
+                current.push((space, token.to_string()));
+                continue;
+            }
+            // 在 token 之前断行；新行会以块级标记开头时，把上一个词一起移到新行
+            let mut carried = vec![(space, token.to_string())];
+            while starts_block(&carried[0].1) && current.len() > 1 {
+                carried.insert(0, current.pop().unwrap_or_default());
+            }
+            if starts_block(&carried[0].1) {
+                carried[0].1 = escape_block_marker(&carried[0].1);
+            }
+            wrapped.push(render(&current_prefix, &current).trim_end().to_string());
+            current_prefix = continuation.clone();
+            current = carried;
+        }
+        let mut last = render(&current_prefix, &current);
+        if hard_break {
+            last.push_str("  ");
+        }
+        wrapped.push(last);
+
+        let count = wrapped.len();
+        lines.splice(i..=i, wrapped);
+        kinds.splice(i..=i, std::iter::repeat_n(LineKind::Text, count));
+        i += count;
+    }
+}
+
+/// 按选项格式化 Markdown 文本
+pub fn format_with_options(markdown: &str, options: &FormatOptions) -> String {
+    // 步骤 1：统一换行符
+    let mut content = markdown.replace("\r\n", "\n");
+
+    // 步骤 2：公式块
+    if options.normalize_math {
+        content = normalize_math_blocks(&content);
+    }
+
+    // 步骤 3：逐行规则
+    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    let mut kinds = classify_lines(&lines);
+
+    if options.normalize_headings {
+        normalize_headings(&mut lines, &mut kinds);
+        pad_headings(&mut lines, &mut kinds);
+    }
+    if options.renumber_lists {
+        renumber_lists(&mut lines, &kinds);
+    }
+    normalize_emphasis(&mut lines, &kinds, options.emphasis);
+    if let Some(rules) = options.typography.filter(TypographySettings::is_enabled) {
+        for (line, kind) in lines.iter_mut().zip(&kinds) {
+            if *kind == LineKind::Text {
+                *line = typography::normalize_markdown_line(line, &rules);
+            }
+        }
+    }
+    if options.line_width > 0 {
+        wrap_lines(&mut lines, &mut kinds, options.line_width);
+    }
+    if options.align_tables {
+        align_tables(&mut lines, &kinds);
+    }
+    content = lines.join("\n");
+
+    // 步骤 4：压缩连续空行（3 个及以上换行→2 个），trim
+    let re_multi = Regex::new(r"\n{3,}").unwrap();
+    content = re_multi.replace_all(&content, "\n\n").to_string();
+    content.trim().to_string()
+}
+
+/// 文本编辑：将 `[start_line, end_line)` 行（1-indexed，半开区间，含行尾换行符）替换为 `new_text`。
+/// `start_line == end_line` 表示在该行之前插入。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextEdit {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub new_text: String,
+}
+
+/// 超过该规模（行数乘积）时不再逐行求最小差异，直接整体替换
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// 将选区扩展到完整的块：向上/向下延伸到空行，且不截断代码块、公式块等受保护区域
+fn expand_to_blocks(lines: &[String], kinds: &[LineKind], mut start: usize, mut end: usize) -> (usize, usize) {
+    let joined = |a: usize, b: usize| {
+        (!lines[a].trim().is_empty() && !lines[b].trim().is_empty())
+            || (kinds[a] == LineKind::Protected && kinds[b] == LineKind::Protected)
+    };
+    while start > 0 && joined(start - 1, start) {
+        start -= 1;
+    }
+    while end + 1 < lines.len() && joined(end, end + 1) {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// 逐行最小差异，返回 (原始起始, 原始结束, 新起始, 新结束) 的半开区间（0-indexed）
+fn diff_lines(old: &[String], new: &[String]) -> Vec<(usize, usize, usize, usize)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+    if old_mid.is_empty() && new_mid.is_empty() {
+        return Vec::new();
+    }
+    if old_mid.is_empty() || new_mid.is_empty() || old_mid.len() * new_mid.len() > MAX_DIFF_CELLS {
+        return vec![(prefix, prefix + old_mid.len(), prefix, prefix + new_mid.len())];
+    }
+
+    // LCS 表：lcs[i][j] 为 old_mid[i..] 与 new_mid[j..] 的最长公共子序列长度
+    let (n, m) = (old_mid.len(), new_mid.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut hunk_start: Option<(usize, usize)> = None;
+    while i < n || j < m {
+        if i < n && j < m && old_mid[i] == new_mid[j] {
+            if let Some((hi, hj)) = hunk_start.take() {
+                hunks.push((prefix + hi, prefix + i, prefix + hj, prefix + j));
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+        hunk_start.get_or_insert((i, j));
+        if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+    if let Some((hi, hj)) = hunk_start {
+        hunks.push((prefix + hi, prefix + n, prefix + hj, prefix + m));
+    }
+    hunks
+}
+
+/// 只格式化 `start_line..=end_line`（1-indexed）所在的块，返回最小的文本编辑列表；
+/// 选区外的内容（包括换行符风格）保持不变
+pub fn format_range(markdown: &str, start_line: usize, end_line: usize, options: &FormatOptions) -> Vec<TextEdit> {
+    let raw_lines: Vec<&str> = markdown.split_inclusive('\n').collect();
+    if raw_lines.is_empty() || start_line == 0 || start_line > end_line || start_line > raw_lines.len() {
+        return Vec::new();
+    }
+    let eol = if markdown.contains("\r\n") { "\r\n" } else { "\n" };
+    let lines: Vec<String> = raw_lines
+        .iter()
+        .map(|l| l.trim_end_matches('\n').trim_end_matches('\r').to_string())
+        .collect();
+    let kinds = classify_lines(&lines);
+    let (start, end) = expand_to_blocks(&lines, &kinds, start_line - 1, end_line.min(lines.len()) - 1);
+
+    // 格式化器会去掉首尾空行，这里原样保留
+    let region = &lines[start..=end];
+    let leading = region.iter().take_while(|l| l.trim().is_empty()).count();
+    if leading == region.len() {
+        return Vec::new();
+    }
+    let trailing = region.iter().rev().take_while(|l| l.trim().is_empty()).count();
+    let formatted = format_with_options(&region[leading..region.len() - trailing].join("\n"), options);
+    let mut new_lines: Vec<String> = region[..leading].to_vec();
+    new_lines.extend(formatted.lines().map(str::to_string));
+    new_lines.extend(region[region.len() - trailing..].iter().cloned());
+
+    let ends_without_newline = !markdown.ends_with('\n');
+    diff_lines(region, &new_lines)
+        .into_iter()
+        .map(|(old_start, old_end, new_start, new_end)| {
+            let mut new_text: String = new_lines[new_start..new_end].iter().map(|l| format!("{}{}", l, eol)).collect();
+            // 替换到文件末尾且原文件末尾没有换行时，不额外补换行
+            if ends_without_newline && start + old_end == lines.len() && new_text.ends_with(eol) {
+                new_text.truncate(new_text.len() - eol.len());
+                if old_start == old_end {
+                    // 在最后一行之后插入：换行符放在前面
+                    new_text.insert_str(0, eol);
+                }
+            }
+            TextEdit {
+                start_line: start + old_start + 1,
+                end_line: start + old_end + 1,
+                new_text,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(markdown: &str, options: FormatOptions) -> String {
+        format_with_options(markdown, &options)
+    }
+
+    fn wrapped(markdown: &str, width: usize) -> String {
+        format(markdown, FormatOptions { line_width: width, ..Default::default() })
+    }
+
+    #[test]
+    fn emphasis_is_normalized_outside_protected_spans() {
+        assert_eq!(format("_a_ and __b__", FormatOptions::default()), "*a* and **b**");
+        assert_eq!(format("_see `x_y` here_", FormatOptions::default()), "*see `x_y` here*");
+        assert_eq!(format("_[link](a_b)_", FormatOptions::default()), "*[link](a_b)*");
+    }
+
+    #[test]
+    fn emphasis_leaves_urls_math_and_escapes_alone() {
+        let cases = [
+            "[x](https://example.com/_a_/b)",
+            "![img](pics/_draft_.png)",
+            "<https://example.com/_a_>",
+            "see https://example.com/_a_ now",
+            "$a_1 + b_2$ and $c_3$",
+            "\\_not emphasis\\_",
+            "snake_case_name",
+        ];
+        for case in cases {
+            assert_eq!(format(case, FormatOptions::default()), case);
+        }
+        let underscore = FormatOptions { emphasis: EmphasisMarker::Underscore, ..Default::default() };
+        assert_eq!(format("*a* $x*y*z$ \\*b\\*", underscore), "_a_ $x*y*z$ \\*b\\*");
+    }
+
+    #[test]
+    fn continuation_lines_do_not_start_with_block_markers() {
+        let markers = ["-", "+", "*", "#", ">", "1.", "2)", "---", "```"];
+        for marker in markers {
+            let line = format!("aaaa bbbb {} cccc", marker);
+            let out = wrapped(&line, 10);
+            for continuation in out.lines().skip(1) {
+                let first = continuation.split_whitespace().next().unwrap_or("");
+                assert!(!starts_block(first), "{:?} -> {:?}", line, out);
+            }
+            let words: Vec<&str> = out.split_whitespace().collect();
+            assert_eq!(words, ["aaaa", "bbbb", marker, "cccc"], "{:?}", out);
+        }
+    }
+
+    #[test]
+    fn markers_are_escaped_when_the_line_cannot_break_earlier() {
+        assert_eq!(wrapped("aaaaaaaaaa - b", 10), "aaaaaaaaaa\n\\- b");
+        assert_eq!(wrapped("aaaaaaaaaa 1. b", 10), "aaaaaaaaaa\n1\\. b");
+    }
+
+    #[test]
+    fn list_items_wrap_under_their_text() {
+        assert_eq!(wrapped("- one two three four", 10), "- one two\n  three\n  four");
+        assert_eq!(wrapped("> aaaa bbbb cccc", 10), "> aaaa\n> bbbb\n> cccc");
+    }
+
+    #[test]
+    fn cjk_text_breaks_between_characters_but_not_before_punctuation() {
+        assert_eq!(wrapped("中文中文中文，中文", 12), "中文中文中文，\n中文");
+    }
+
+    #[test]
+    fn format_range_keeps_crlf_line_endings() {
+        let markdown = "# 标题\r\n\r\n1. a\r\n1. b\r\n\r\n尾部\r\n";
+        let edits = format_range(markdown, 3, 4, &FormatOptions::default());
+        assert_eq!(edits, [TextEdit { start_line: 4, end_line: 5, new_text: "2. b\r\n".to_string() }]);
+    }
+
+    #[test]
+    fn format_range_keeps_a_missing_trailing_newline() {
+        let edits = format_range("段落\n\n#标题", 3, 3, &FormatOptions::default());
+        assert_eq!(edits, [TextEdit { start_line: 3, end_line: 4, new_text: "# 标题".to_string() }]);
+
+        let edits = format_range("a\n\n1. x\n1. y", 3, 4, &FormatOptions::default());
+        assert_eq!(edits, [TextEdit { start_line: 4, end_line: 5, new_text: "2. y".to_string() }]);
+    }
+
+    #[test]
+    fn format_range_ignores_out_of_range_selections() {
+        assert!(format_range("a\n", 0, 1, &FormatOptions::default()).is_empty());
+        assert!(format_range("a\n", 3, 4, &FormatOptions::default()).is_empty());
+        assert!(format_range("a\n\n\n", 2, 3, &FormatOptions::default()).is_empty());
+    }
+}
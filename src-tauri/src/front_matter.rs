@@ -0,0 +1,22 @@
+//! 文档开头的 YAML front matter（`---` 包围）
+
+use regex::Regex;
+use serde_yaml::Value;
+
+/// front matter 的长度（含结尾的分隔行）与其中的 YAML 内容
+pub fn split(markdown: &str) -> Option<(usize, &str)> {
+    let re_front_matter = Regex::new(r"\A---[ \t]*\r?\n((?:[\s\S]*?\r?\n)?)(?:---|\.\.\.)[ \t]*(?:\r?\n|\z)").unwrap();
+    let caps = re_front_matter.captures(markdown)?;
+    Some((caps.get(0)?.end(), caps.get(1)?.as_str()))
+}
+
+/// 解析 front matter；没有 front matter 或内容不是 YAML 映射时返回 `None`
+pub fn parse(markdown: &str) -> Option<Value> {
+    let (_, yaml) = split(markdown)?;
+    serde_yaml::from_str::<Value>(yaml).ok().filter(Value::is_mapping)
+}
+
+/// 去掉 front matter 后的正文
+pub fn body(markdown: &str) -> &str {
+    split(markdown).map(|(len, _)| &markdown[len..]).unwrap_or(markdown)
+}
@@ -0,0 +1,162 @@
+//! 缩写与术语表：
+//!  - 文档中的定义行 `*[HTML]: HyperText Markup Language`（代码块之外）与 front matter 中的
+//!    `abbreviations` 映射声明缩写，同一缩写以定义行为准
+//!  - 正文中出现的缩写转换为 `<abbr title="全称">`，代码、公式与已有的 `<abbr>` 保持不变；
+//!    以西文字母或数字开头、结尾的缩写只匹配完整的词
+//!  - 定义行渲染成的段落从正文中移除
+//!  - 导出时 front matter 中的 `glossary: true`（或以字符串作为标题）在文末生成按缩写排序的术语表
+//!
+//! 属于扩展语法，严格模式下不处理。预览由前端按 `detect_abbreviations` 的结果做同样的转换。
+
+use crate::abstract_block::is_english;
+use crate::cover::text_of;
+use crate::front_matter;
+use crate::html_util::escape_html;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Abbreviation {
+    pub term: String,
+    pub definition: String,
+}
+
+/// 文档声明的缩写：front matter 中的 `abbreviations` 与正文中的定义行，按缩写排序
+pub fn collect(markdown: &str) -> Vec<Abbreviation> {
+    let mut terms: BTreeMap<String, String> = BTreeMap::new();
+    if let Some(Value::Mapping(map)) = front_matter::parse(markdown).as_ref().and_then(|fm| fm.get("abbreviations")) {
+        for (term, definition) in map {
+            if let (Some(term), Some(definition)) = (text_of(term), text_of(definition)) {
+                terms.insert(term, definition);
+            }
+        }
+    }
+
+    let re_definition = Regex::new(r"^\*\[([^\]\n]+)\]:[ \t]*(.+)$").unwrap();
+    let mut fence: Option<&str> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        match (fence, marker) {
+            (None, Some(marker)) => fence = Some(marker),
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            (None, None) => {
+                if let Some(caps) = re_definition.captures(line.trim_end()) {
+                    let term = caps[1].trim();
+                    let definition = caps[2].trim();
+                    if !term.is_empty() && !definition.is_empty() {
+                        terms.insert(term.to_string(), definition.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    terms.into_iter().map(|(term, definition)| Abbreviation { term, definition }).collect()
+}
+
+/// 只由定义行组成的段落
+fn is_definition_paragraph(inner: &str) -> bool {
+    let re_line_break = Regex::new(r"<br\s*/?>|\n").unwrap();
+    let re_definition = Regex::new(r"^\*\[[^\]]+\]:").unwrap();
+    let mut lines = re_line_break.split(inner).map(str::trim).filter(|line| !line.is_empty()).peekable();
+    lines.peek().is_some() && lines.all(|line| re_definition.is_match(line))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// 移除定义行段落，把正文中的缩写转换为 `<abbr>`
+pub fn apply_abbreviations(html: &str, abbreviations: &[Abbreviation]) -> String {
+    if abbreviations.is_empty() {
+        return html.to_string();
+    }
+    let re_paragraph = Regex::new(r"(?s)<p>(.*?)</p>\n?").unwrap();
+    let html = re_paragraph.replace_all(html, |caps: &Captures| {
+        if is_definition_paragraph(&caps[1]) {
+            String::new()
+        } else {
+            caps[0].to_string()
+        }
+    });
+
+    // HTML 中的缩写是转义后的文本；较长的缩写优先匹配
+    let definitions: HashMap<String, &str> =
+        abbreviations.iter().map(|a| (escape_html(&a.term), a.definition.as_str())).collect();
+    let mut escaped: Vec<&String> = definitions.keys().collect();
+    escaped.sort_by_key(|term| std::cmp::Reverse(term.len()));
+    let pattern = escaped.iter().map(|term| regex::escape(term)).collect::<Vec<_>>().join("|");
+    let re_term = Regex::new(&pattern).unwrap();
+    let re_protected = Regex::new(
+        r"(?s)<pre\b.*?</pre>|<code\b.*?</code>|<math\b.*?</math>|<script\b.*?</script>|<style\b.*?</style>|<abbr\b.*?</abbr>|<[^>]*>",
+    )
+    .unwrap();
+
+    let replace = |text: &str| {
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for m in re_term.find_iter(text) {
+            let term = m.as_str();
+            let before = text[..m.start()].chars().next_back();
+            let after = text[m.end()..].chars().next();
+            let starts_word = term.chars().next().is_some_and(is_word_char);
+            let ends_word = term.chars().next_back().is_some_and(is_word_char);
+            if (starts_word && before.is_some_and(is_word_char)) || (ends_word && after.is_some_and(is_word_char)) {
+                continue;
+            }
+            out.push_str(&text[last..m.start()]);
+            out.push_str(&format!(r#"<abbr title="{}">{}</abbr>"#, escape_html(definitions[term]), term));
+            last = m.end();
+        }
+        out.push_str(&text[last..]);
+        out
+    };
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(&html) {
+        out.push_str(&replace(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&replace(&html[last..]));
+    out
+}
+
+/// front matter 中的 `glossary` 开启时在文末追加术语表，标题进入 PDF 书签
+pub fn append_glossary(html: &str, front_matter: Option<&Value>, abbreviations: &[Abbreviation]) -> String {
+    let title = match front_matter.and_then(|fm| fm.get("glossary")) {
+        Some(Value::Bool(true)) if front_matter.is_some_and(is_english) => "Glossary".to_string(),
+        Some(Value::Bool(true)) => "术语表".to_string(),
+        Some(value @ Value::String(_)) => match text_of(value) {
+            Some(title) => title,
+            None => return html.to_string(),
+        },
+        _ => return html.to_string(),
+    };
+    if abbreviations.is_empty() {
+        return html.to_string();
+    }
+
+    let mut entries: Vec<&Abbreviation> = abbreviations.iter().collect();
+    entries.sort_by_key(|a| a.term.to_lowercase());
+    let mut glossary = format!(r#"<section class="glossary"><h2 class="glossary-title">{}</h2><dl>"#, escape_html(&title));
+    for entry in entries {
+        glossary.push_str(&format!(
+            "<dt>{}</dt><dd>{}</dd>",
+            escape_html(&entry.term),
+            escape_html(&entry.definition)
+        ));
+    }
+    glossary.push_str("</dl></section>");
+    format!("{}\n{}", html, glossary)
+}
+
+/// 文档声明的缩写（供前端预览转换）
+#[tauri::command]
+pub fn detect_abbreviations(markdown: &str) -> Vec<Abbreviation> {
+    collect(markdown)
+}
@@ -0,0 +1,8 @@
+//! 内容哈希：缓存键、去重后的文件名、块 id 与 PDF 文件标识共用。
+
+use sha2::{Digest, Sha256};
+
+/// SHA-256 的十六进制小写表示
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
@@ -0,0 +1,140 @@
+//! 代码高亮：把代码块中的注释、字符串、数字与关键字包裹为带内联颜色的 `<span>`，
+//! 用于复制到 Word / 邮件等不支持样式表的场合。
+//!
+//! 按语言区分注释语法，关键字为常见语言的并集；未知语言只高亮字符串与数字。
+//!
+//! 导出页面中代码块的配色主题（背景、标题栏、高亮行与行号）由本地资源服务器以
+//! `/<token>/highlight/<主题>.css` 提供，front matter 或设置中的 `code_blocks.theme` 选择主题。
+
+use crate::html_util::escape_html;
+
+const COMMENT_COLOR: &str = "#6a737d";
+const STRING_COLOR: &str = "#032f62";
+const NUMBER_COLOR: &str = "#005cc5";
+const KEYWORD_COLOR: &str = "#d73a49";
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "and", "as", "async", "await", "break", "case", "catch", "class", "const", "continue", "def", "default",
+    "del", "do", "elif", "else", "enum", "except", "export", "extends", "false", "final", "finally", "fn", "for",
+    "from", "func", "function", "go", "if", "impl", "implements", "import", "in", "interface", "is", "lambda", "let",
+    "loop", "match", "mod", "mut", "new", "nil", "None", "not", "null", "or", "package", "pass", "private",
+    "protected", "pub", "public", "raise", "return", "select", "self", "static", "struct", "super", "switch", "this",
+    "throw", "throws", "trait", "true", "True", "False", "try", "type", "typeof", "undefined", "use", "var", "void",
+    "where", "while", "with", "yield", "then", "fi", "done", "esac", "echo", "local",
+];
+
+/// 各语言的注释语法：行注释前缀与是否支持 `/* */`
+fn comment_syntax(language: &str) -> (&'static [&'static str], bool) {
+    match language {
+        "python" | "py" | "sh" | "bash" | "shell" | "zsh" | "yaml" | "yml" | "toml" | "ruby" | "rb" | "r" | "perl"
+        | "powershell" | "ps1" | "dockerfile" | "makefile" | "ini" => (&["#"], false),
+        "sql" | "lua" | "haskell" | "hs" => (&["--"], false),
+        "lisp" | "clojure" | "scheme" | "asm" => (&[";"], false),
+        "" | "text" | "plain" | "txt" | "json" | "csv" => (&[], false),
+        "css" => (&[], true),
+        _ => (&["//"], true),
+    }
+}
+
+fn span(color: &str, text: &str) -> String {
+    format!(r#"<span style="color: {}">{}</span>"#, color, escape_html(text))
+}
+
+/// 高亮代码（`code` 为原文，未转义），返回转义后的 HTML
+pub fn highlight(code: &str, language: &str) -> String {
+    let language = language.to_ascii_lowercase();
+    let (line_comments, block_comments) = comment_syntax(&language);
+    let plain = matches!(language.as_str(), "" | "text" | "plain" | "txt");
+    let chars: Vec<char> = code.chars().collect();
+    let rest = |i: usize| chars[i..].iter().collect::<String>();
+    let starts_with = |i: usize, prefix: &str| {
+        let prefix: Vec<char> = prefix.chars().collect();
+        chars.len() >= i + prefix.len() && chars[i..i + prefix.len()] == prefix[..]
+    };
+
+    let mut out = String::with_capacity(code.len() * 2);
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        // 注释
+        if let Some(prefix) = line_comments.iter().find(|p| starts_with(i, p)) {
+            let end = chars[i..].iter().position(|&c| c == '\n').map_or(chars.len(), |n| i + n);
+            let _ = prefix;
+            out.push_str(&span(COMMENT_COLOR, &chars[i..end].iter().collect::<String>()));
+            i = end;
+            continue;
+        }
+        if block_comments && starts_with(i, "/*") {
+            let end = rest(i + 2).find("*/").map_or(chars.len(), |n| i + 2 + rest(i + 2)[..n].chars().count() + 2);
+            out.push_str(&span(COMMENT_COLOR, &chars[i..end].iter().collect::<String>()));
+            i = end;
+            continue;
+        }
+        // 字符串
+        if !plain && matches!(c, '"' | '\'' | '`') {
+            let mut end = i + 1;
+            while end < chars.len() && chars[end] != c && !(chars[end] == '\n' && c != '`') {
+                end += if chars[end] == '\\' { 2 } else { 1 };
+            }
+            let end = (end + 1).min(chars.len());
+            out.push_str(&span(STRING_COLOR, &chars[i..end].iter().collect::<String>()));
+            i = end;
+            continue;
+        }
+        // 数字
+        if !plain && c.is_ascii_digit() && (i == 0 || !(chars[i - 1].is_alphanumeric() || chars[i - 1] == '_')) {
+            let end = chars[i..]
+                .iter()
+                .position(|&c| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+                .map_or(chars.len(), |n| i + n);
+            out.push_str(&span(NUMBER_COLOR, &chars[i..end].iter().collect::<String>()));
+            i = end;
+            continue;
+        }
+        // 关键字
+        if !plain && (c.is_alphabetic() || c == '_') {
+            let end = chars[i..]
+                .iter()
+                .position(|&c| !(c.is_alphanumeric() || c == '_'))
+                .map_or(chars.len(), |n| i + n);
+            let word: String = chars[i..end].iter().collect();
+            if KEYWORDS.contains(&word.as_str()) {
+                out.push_str(&span(KEYWORD_COLOR, &word));
+            } else {
+                out.push_str(&escape_html(&word));
+            }
+            i = end;
+            continue;
+        }
+        out.push_str(&escape_html(&c.to_string()));
+        i += 1;
+    }
+    out
+}
+
+/// 代码块配色主题：名称与样式表。选择器前加 `html body`，覆盖导出页面内置的默认样式
+const THEMES: &[(&str, &str)] = &[
+    (
+        "github",
+        "html body pre { background-color: #f5f5f5; color: #1a1a1a; }
+html body .code-title { background-color: #e8e8e8; color: #57606a; }
+html body .code-line.highlighted { background-color: #fff8c5; }
+html body .line-numbers .code-line::before { color: #8c959f; }
+",
+    ),
+    (
+        "github-dark",
+        "html body pre { background-color: #0d1117; color: #e6edf3; }
+html body pre code { color: inherit; }
+html body .code-title { background-color: #161b22; color: #8b949e; }
+html body .code-line.highlighted { background-color: #3d3200; }
+html body .line-numbers .code-line::before { color: #6e7681; }
+html body pre > code.language-output { color: #c9d1d9; }
+",
+    ),
+];
+
+/// 主题的样式表，未知主题为空
+pub fn theme_css(name: &str) -> Option<&'static str> {
+    THEMES.iter().find(|(theme, _)| *theme == name).map(|(_, css)| *css)
+}
@@ -0,0 +1,138 @@
+//! 导出历史：每次成功导出 PDF 后记录源文件、输出路径、导出选项、耗时与时间，
+//! 保存在应用数据目录下的 `export_history.json`。修改 Markdown 后可以用 `reexport`
+//! 按记录中的选项重新生成同一个 PDF。
+//!
+//! 同一源文件导出到同一输出路径只保留最新的一条（沿用原来的 ID）。
+//! 加密密码不会写入历史：加密导出的记录不能直接重新导出，需要重新输入密码。
+
+use crate::diagnostics::now_ms;
+use crate::error::AppError;
+use crate::json_store::JsonStore;
+use crate::report::ExportReport;
+use crate::settings::SettingsState;
+use crate::{ExportJob, ExportOptions};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::Manager;
+
+/// 保留的记录数量
+const MAX_HISTORY_ENTRIES: usize = 50;
+
+static STORE: JsonStore = JsonStore::new("export_history.json");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRecord {
+    pub id: u64,
+    /// 源 Markdown 文件（未保存的文档为空，不能重新导出）
+    pub source_path: Option<String>,
+    pub output_path: String,
+    pub title: String,
+    /// 导出时使用的选项（不含源文档内容与密码）
+    pub options: ExportOptions,
+    /// 导出时设置了加密
+    pub protected: bool,
+    pub duration_ms: u128,
+    pub page_count: usize,
+    pub file_size: u64,
+    /// 导出完成的时间（毫秒时间戳）
+    pub exported_ms: u128,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct HistoryStore {
+    next_id: u64,
+    /// 最新的在前
+    entries: Vec<ExportRecord>,
+}
+
+/// 在锁内读取、修改并保存
+fn update_store<T>(app: &tauri::AppHandle, f: impl FnOnce(&mut HistoryStore) -> T) -> Result<T, AppError> {
+    STORE.update(app, f)
+}
+
+/// 记录一次成功的导出
+pub(crate) fn record(app: &tauri::AppHandle, job: &ExportJob, report: &ExportReport) -> Result<(), AppError> {
+    update_store(app, |store| {
+        let source_path = job.options.source_path.clone();
+        let previous = store
+            .entries
+            .iter()
+            .position(|e| e.source_path.is_some() && e.source_path == source_path && e.output_path == job.output_path)
+            .map(|index| store.entries.remove(index));
+        let id = match previous {
+            Some(previous) => previous.id,
+            None => {
+                store.next_id += 1;
+                store.next_id
+            }
+        };
+        store.entries.insert(
+            0,
+            ExportRecord {
+                id,
+                source_path,
+                output_path: job.output_path.clone(),
+                title: job.title.clone(),
+                options: job.options.clone(),
+                protected: job.options.protection.is_some(),
+                duration_ms: report.total_ms,
+                page_count: report.page_count,
+                file_size: report.file_size,
+                exported_ms: now_ms(),
+            },
+        );
+        store.entries.truncate(MAX_HISTORY_ENTRIES);
+    })
+}
+
+/// 导出历史（最新的在前）
+#[tauri::command]
+pub fn get_export_history(app: tauri::AppHandle) -> Result<Vec<ExportRecord>, AppError> {
+    Ok(STORE.load::<HistoryStore>(&app)?.entries)
+}
+
+/// 清空导出历史
+#[tauri::command]
+pub fn clear_export_history(app: tauri::AppHandle) -> Result<(), AppError> {
+    update_store(&app, |store| store.entries.clear())
+}
+
+/// 按历史记录中的选项重新导出：重新读取源文件，输出到原来的路径。
+/// `html_content` 为前端按当前内容渲染的 HTML（与普通导出一致）；省略时由后端渲染
+/// （公式保留 TeX 原文，需要公式排版时应由前端提供）。
+#[tauri::command]
+pub async fn reexport(
+    window: tauri::Window,
+    settings: tauri::State<'_, SettingsState>,
+    history_id: u64,
+    html_content: Option<String>,
+) -> Result<ExportReport, AppError> {
+    let app = window.app_handle().clone();
+    let entry = STORE.load::<HistoryStore>(&app)?.entries.into_iter().find(|e| e.id == history_id);
+    let entry = entry.ok_or_else(|| {
+        let args = [("context", "reexport".to_string()), ("id", history_id.to_string())];
+        AppError::localized("INTERNAL", "REEXPORT_NOT_FOUND", args)
+    })?;
+    if entry.protected {
+        return Err(AppError::localized("INTERNAL", "REEXPORT_PROTECTED", [("context", "reexport".to_string())]));
+    }
+    let source_path = entry
+        .source_path
+        .clone()
+        .ok_or_else(|| AppError::localized("INTERNAL", "REEXPORT_UNSAVED", [("context", "reexport".to_string())]))?;
+    let markdown = fs::read_to_string(&source_path).map_err(|e| AppError::file(&source_path, e))?;
+
+    let settings = settings.snapshot();
+    let mut options = entry.options;
+    let markdown = crate::diff::apply_compare(&markdown, options.compare_with.as_deref(), options.mode)?;
+    let markdown = crate::transforms::apply_for_export(&app, &settings, &markdown, Some(Path::new(&source_path)))?;
+    let html_content = match html_content {
+        Some(html) => html,
+        None => crate::render_source_html(&markdown, options.mode, &settings, Path::new(&source_path)),
+    };
+    options.markdown = Some(markdown);
+    tracing::info!(id = history_id, source = %source_path, "重新导出");
+    crate::run_export(&window, settings, html_content, entry.output_path, entry.title, options).await
+}
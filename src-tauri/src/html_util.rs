@@ -0,0 +1,74 @@
+//! HTML 文本处理的小工具
+
+/// 转义 HTML 特殊字符
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 只接受颜色值（`#rgb`、颜色名、`rgb()` / `rgba()`），避免写入任意样式
+pub fn safe_color(color: &str) -> Option<&str> {
+    let re_color = regex::Regex::new(r"^(#[0-9a-fA-F]{3,8}|[a-zA-Z]+|rgba?\([0-9.,%\s]+\))$").unwrap();
+    let color = color.trim();
+    re_color.is_match(color).then_some(color)
+}
+
+/// 还原代码块等处被转义的 HTML 字符
+pub fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 去掉 HTML 标签并还原实体，得到纯文本
+pub fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    unescape_html(&text)
+}
+
+/// 从 `from`（开始标签之后）起找到与之配对的 `</tag>`，返回 (内容结束位置, 结束标签之后的位置)
+pub fn find_closing_tag(html: &str, tag: &str, from: usize) -> Option<(usize, usize)> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let lower = html.to_ascii_lowercase();
+    let mut depth = 1usize;
+    let mut pos = from;
+    loop {
+        let next_close = lower[pos..].find(&close)? + pos;
+        // 只统计真正的同名开始标签（`<span>`、`<span ...>`，不含 `<spanx>`）
+        let nested_open = lower[pos..next_close]
+            .match_indices(&open)
+            .filter(|(i, _)| {
+                matches!(lower.as_bytes().get(pos + i + open.len()), Some(b'>' | b' ' | b'\t' | b'\n' | b'/'))
+            })
+            .count();
+        depth += nested_open;
+        depth -= 1;
+        if depth == 0 {
+            return Some((next_close, next_close + close.len()));
+        }
+        pos = next_close + close.len();
+    }
+}
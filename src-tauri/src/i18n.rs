@@ -0,0 +1,205 @@
+//! 消息目录：后端返回给前端的文本（目前主要是错误信息）按语言查表生成。
+//!
+//! 当前语言由设置中的 `locale` 决定，启动和保存设置时写入全局，
+//! 以便在无法访问 Tauri 状态的地方（如 `AppError` 的序列化）也能取到。
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+impl Locale {
+    fn to_u8(self) -> u8 {
+        match self {
+            Locale::ZhCn => 0,
+            Locale::EnUs => 1,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Locale::EnUs,
+            _ => Locale::ZhCn,
+        }
+    }
+}
+
+pub fn current_locale() -> Locale {
+    Locale::from_u8(CURRENT_LOCALE.load(Ordering::Relaxed))
+}
+
+pub fn set_current_locale(locale: Locale) {
+    CURRENT_LOCALE.store(locale.to_u8(), Ordering::Relaxed);
+}
+
+/// 查找消息模板，`{name}` 为占位符
+fn template(locale: Locale, key: &str) -> &'static str {
+    match (key, locale) {
+        ("FILE_READ", Locale::ZhCn) => "文件读取错误: {reason}",
+        ("FILE_READ", Locale::EnUs) => "Failed to read file: {reason}",
+        ("FILE_ACCESS", Locale::ZhCn) => "无法访问文件 {path}: {reason}",
+        ("FILE_ACCESS", Locale::EnUs) => "Cannot access file {path}: {reason}",
+        ("BROWSER_LAUNCH", Locale::ZhCn) => "浏览器启动错误: {reason}",
+        ("BROWSER_LAUNCH", Locale::EnUs) => "Failed to start browser: {reason}",
+        ("BROWSER", Locale::ZhCn) => "浏览器错误: {reason}",
+        ("BROWSER", Locale::EnUs) => "Browser error: {reason}",
+        ("BROWSER_NOT_FOUND", Locale::ZhCn) => "未找到可用的 Chrome/Edge 浏览器，已检查: {probed}",
+        ("BROWSER_NOT_FOUND", Locale::EnUs) => "No usable Chrome/Edge browser found, checked: {probed}",
+        ("PDF_GENERATION", Locale::ZhCn) => "PDF 生成错误: {reason}",
+        ("PDF_GENERATION", Locale::EnUs) => "PDF generation failed: {reason}",
+        ("SETTINGS", Locale::ZhCn) => "设置保存失败: {reason}",
+        ("SETTINGS", Locale::EnUs) => "Failed to save settings: {reason}",
+        ("DIAGNOSTICS", Locale::ZhCn) => "诊断信息收集失败: {reason}",
+        ("DIAGNOSTICS", Locale::EnUs) => "Failed to collect diagnostics: {reason}",
+        ("POLICY", Locale::ZhCn) => "组织策略文件无效（{path}）: {reason}",
+        ("POLICY", Locale::EnUs) => "Invalid organization policy file ({path}): {reason}",
+        ("CLIPBOARD", Locale::ZhCn) => "粘贴图片失败: {reason}",
+        ("CLIPBOARD", Locale::EnUs) => "Failed to paste image: {reason}",
+        ("INVALID_PATTERN", Locale::ZhCn) => "搜索表达式无效: {reason}",
+        ("INVALID_PATTERN", Locale::EnUs) => "Invalid search pattern: {reason}",
+        ("IMPORT", Locale::ZhCn) => "导入文档失败: {reason}",
+        ("IMPORT", Locale::EnUs) => "Failed to import document: {reason}",
+        ("EXTERNAL_RESOURCES", Locale::ZhCn) => "导出页面需要访问外部地址，请确认后再导出: {origins}",
+        ("EXTERNAL_RESOURCES", Locale::EnUs) => "The export page contacts external origins and needs confirmation: {origins}",
+        ("CANCELLED", Locale::ZhCn) => "导出已取消",
+        ("CANCELLED", Locale::EnUs) => "Export cancelled",
+        ("TIMEOUT", Locale::ZhCn) => "导出在 {stage} 阶段超时（超过 {seconds} 秒）",
+        ("TIMEOUT", Locale::EnUs) => "Export timed out in stage {stage} (over {seconds} s)",
+        ("PRINT_RUN_STAMP", Locale::ZhCn) => "第 {copy} 份，共 {total} 份",
+        ("PRINT_RUN_STAMP", Locale::EnUs) => "Copy {copy} of {total}",
+        ("PDFA_PROTECTED", Locale::ZhCn) => "PDF/A 不允许加密，请取消输出保护或改用其他 PDF 标准",
+        ("PDFA_PROTECTED", Locale::EnUs) => "PDF/A does not allow encryption; remove the output protection or choose another PDF standard",
+        ("CORRUPT_STORE", Locale::ZhCn) => "数据文件已损坏，请检查或删除后重试（{path}）: {reason}",
+        ("CORRUPT_STORE", Locale::EnUs) => "Data file is corrupt; check or delete it and try again ({path}): {reason}",
+        ("UNTRUSTED_CODE", Locale::ZhCn) => "文档包含可执行的代码块，确认信任后才会运行: {path}",
+        ("UNTRUSTED_CODE", Locale::EnUs) => "The document contains runnable code blocks that need to be trusted first: {path}",
+        ("INCLUDE_CYCLE", Locale::ZhCn) => "!include 循环引用或嵌套过深: {path}",
+        ("INCLUDE_CYCLE", Locale::EnUs) => "!include cycle or nesting too deep: {path}",
+        ("INCLUDE_OUTSIDE", Locale::ZhCn) => "!include 只能引用文档所在目录下的文件: {path}",
+        ("INCLUDE_OUTSIDE", Locale::EnUs) => "!include can only reference files inside the document's folder: {path}",
+        ("INCLUDE_UNSAVED", Locale::ZhCn) => "文档保存后才能使用 !include: {path}",
+        ("INCLUDE_UNSAVED", Locale::EnUs) => "Save the document before using !include: {path}",
+        ("PDF_EMPTY_PAGE", Locale::ZhCn) => "PDF 生成错误: 页面没有可导出的内容",
+        ("PDF_EMPTY_PAGE", Locale::EnUs) => "PDF generation failed: the page has no content to export",
+        ("PDF_EMPTY_SELECTION", Locale::ZhCn) => "PDF 生成错误: 选择的范围内没有可导出的内容",
+        ("PDF_EMPTY_SELECTION", Locale::EnUs) => "PDF generation failed: the selection has no content to export",
+        ("PDF_IMAGE_TOO_LONG", Locale::ZhCn) => "PDF 生成错误: 文档过长（{pixels} 像素），无法导出为单张图片，请改用按页导出或降低分辨率",
+        ("PDF_IMAGE_TOO_LONG", Locale::EnUs) => "PDF generation failed: the document is too long ({pixels} px) for a single image; export per page or lower the resolution",
+        ("PDF_INVALID_COPIES", Locale::ZhCn) => "PDF 生成错误: 份数必须在 1 到 {max} 之间",
+        ("PDF_INVALID_COPIES", Locale::EnUs) => "PDF generation failed: the number of copies must be between 1 and {max}",
+        ("PDF_REDACTION_UNCLOSED", Locale::ZhCn) => "PDF 生成错误: 涂黑标记未闭合（位置 {offset}），为避免泄露已中止导出",
+        ("PDF_REDACTION_UNCLOSED", Locale::EnUs) => "PDF generation failed: unclosed redaction marker (offset {offset}); export aborted to avoid leaking content",
+        ("PDF_MALFORMED", Locale::ZhCn) => "PDF 生成错误: 无法解析 PDF：{issue}",
+        ("PDF_MALFORMED", Locale::EnUs) => "PDF generation failed: cannot parse the PDF: {issue}",
+        ("PDF_ARCHIVE_UNSUPPORTED", Locale::ZhCn) => "PDF 生成错误: 无法转换为 PDF/A：{issue}",
+        ("PDF_ARCHIVE_UNSUPPORTED", Locale::EnUs) => "PDF generation failed: cannot convert to PDF/A: {issue}",
+        ("PDF_PROTECT_UNSUPPORTED", Locale::ZhCn) => "PDF 生成错误: 无法加密该 PDF：{issue}",
+        ("PDF_PROTECT_UNSUPPORTED", Locale::EnUs) => "PDF generation failed: cannot encrypt this PDF: {issue}",
+        ("PDF_UNTERMINATED_OBJECT", Locale::ZhCn) => "对象未结束",
+        ("PDF_UNTERMINATED_OBJECT", Locale::EnUs) => "unterminated object",
+        ("PDF_UNTERMINATED_STREAM", Locale::ZhCn) => "流未结束",
+        ("PDF_UNTERMINATED_STREAM", Locale::EnUs) => "unterminated stream",
+        ("PDF_UNTERMINATED_STRING", Locale::ZhCn) => "字符串未结束",
+        ("PDF_UNTERMINATED_STRING", Locale::EnUs) => "unterminated string",
+        ("PDF_UNTERMINATED_HEX_STRING", Locale::ZhCn) => "十六进制字符串未结束",
+        ("PDF_UNTERMINATED_HEX_STRING", Locale::EnUs) => "unterminated hex string",
+        ("PDF_MISSING_LENGTH", Locale::ZhCn) => "流缺少 /Length",
+        ("PDF_MISSING_LENGTH", Locale::EnUs) => "stream without /Length",
+        ("PDF_MISSING_TRAILER", Locale::ZhCn) => "缺少 trailer",
+        ("PDF_MISSING_TRAILER", Locale::EnUs) => "missing trailer",
+        ("PDF_MISSING_ROOT", Locale::ZhCn) => "缺少 /Root",
+        ("PDF_MISSING_ROOT", Locale::EnUs) => "missing /Root",
+        ("PDF_MISSING_CATALOG", Locale::ZhCn) => "找不到目录",
+        ("PDF_MISSING_CATALOG", Locale::EnUs) => "catalog not found",
+        ("PDF_CATALOG_NOT_DICTIONARY", Locale::ZhCn) => "目录不是字典",
+        ("PDF_CATALOG_NOT_DICTIONARY", Locale::EnUs) => "catalog is not a dictionary",
+        ("PDF_NO_OBJECTS", Locale::ZhCn) => "没有找到对象",
+        ("PDF_NO_OBJECTS", Locale::EnUs) => "no objects found",
+        ("PDF_COMPRESSED_OBJECTS", Locale::ZhCn) => "不支持对象流与交叉引用流",
+        ("PDF_COMPRESSED_OBJECTS", Locale::EnUs) => "object streams and cross-reference streams are not supported",
+        ("PDF_ALREADY_ENCRYPTED", Locale::ZhCn) => "文档已加密",
+        ("PDF_ALREADY_ENCRYPTED", Locale::EnUs) => "the document is already encrypted",
+        ("BROWSER_IMAGE_CHECK", Locale::ZhCn) => "浏览器错误: 检查图片分辨率失败: {reason}",
+        ("BROWSER_IMAGE_CHECK", Locale::EnUs) => "Browser error: failed to check image resolution: {reason}",
+        ("BROWSER_LAYOUT", Locale::ZhCn) => "浏览器错误: 页面排版失败: {reason}",
+        ("BROWSER_LAYOUT", Locale::EnUs) => "Browser error: page layout failed: {reason}",
+        ("BROWSER_SCREENSHOT", Locale::ZhCn) => "浏览器错误: 截图失败: {reason}",
+        ("BROWSER_SCREENSHOT", Locale::EnUs) => "Browser error: screenshot failed: {reason}",
+        ("BROWSER_SCREENSHOT_DATA", Locale::ZhCn) => "浏览器错误: 截图数据无效: {reason}",
+        ("BROWSER_SCREENSHOT_DATA", Locale::EnUs) => "Browser error: invalid screenshot data: {reason}",
+        ("BROWSER_INTERCEPT", Locale::ZhCn) => "浏览器错误: 启用请求拦截失败: {reason}",
+        ("BROWSER_INTERCEPT", Locale::EnUs) => "Browser error: failed to enable request interception: {reason}",
+        ("BROWSER_NEW_TAB", Locale::ZhCn) => "浏览器错误: 创建标签页失败: {reason}",
+        ("BROWSER_NEW_TAB", Locale::EnUs) => "Browser error: failed to open a tab: {reason}",
+        ("BROWSER_NAVIGATE", Locale::ZhCn) => "浏览器错误: 导航触发失败: {reason}",
+        ("BROWSER_NAVIGATE", Locale::EnUs) => "Browser error: navigation failed: {reason}",
+        ("BROWSER_NAVIGATE_WAIT", Locale::ZhCn) => "浏览器错误: 等待导航完成失败: {reason}",
+        ("BROWSER_NAVIGATE_WAIT", Locale::EnUs) => "Browser error: waiting for navigation failed: {reason}",
+        ("BROWSER_WRITE_PAGE", Locale::ZhCn) => "浏览器错误: 写入页面内容失败: {reason}",
+        ("BROWSER_WRITE_PAGE", Locale::EnUs) => "Browser error: failed to write page content: {reason}",
+        ("BROWSER_RENDER_WAIT", Locale::ZhCn) => "浏览器错误: 等待渲染完成信号失败: {reason}",
+        ("BROWSER_RENDER_WAIT", Locale::EnUs) => "Browser error: waiting for the render-complete signal failed: {reason}",
+        ("BROWSER_KATEX_LOAD", Locale::ZhCn) => "浏览器错误: 加载 KaTeX 失败: {reason}",
+        ("BROWSER_KATEX_LOAD", Locale::EnUs) => "Browser error: failed to load KaTeX: {reason}",
+        ("BROWSER_KATEX_RENDER", Locale::ZhCn) => "浏览器错误: 公式转换失败: {reason}",
+        ("BROWSER_KATEX_RENDER", Locale::EnUs) => "Browser error: formula conversion failed: {reason}",
+        ("SETTINGS_CONFIG_DIR", Locale::ZhCn) => "设置保存失败: 无法确定配置目录",
+        ("SETTINGS_CONFIG_DIR", Locale::EnUs) => "Failed to save settings: cannot determine the configuration directory",
+        ("SETTINGS_TRANSFORMS_SYNTAX", Locale::ZhCn) => "设置保存失败: transforms.toml 格式错误: {reason}",
+        ("SETTINGS_TRANSFORMS_SYNTAX", Locale::EnUs) => "Failed to save settings: transforms.toml is malformed: {reason}",
+        ("SETTINGS_UNKNOWN_TRANSFORM", Locale::ZhCn) => "设置保存失败: 没有名为 {name} 的转换",
+        ("SETTINGS_UNKNOWN_TRANSFORM", Locale::EnUs) => "Failed to save settings: no transform named {name}",
+        ("INVALID_TRANSFORM_PATTERN", Locale::ZhCn) => "转换 {name} 的正则表达式无效: {reason}",
+        ("INVALID_TRANSFORM_PATTERN", Locale::EnUs) => "Invalid regular expression in transform {name}: {reason}",
+        ("CLIPBOARD_ENCODE", Locale::ZhCn) => "粘贴图片失败: PNG 编码失败: {reason}",
+        ("CLIPBOARD_ENCODE", Locale::EnUs) => "Failed to paste image: PNG encoding failed: {reason}",
+        ("CLIPBOARD_UNSAVED", Locale::ZhCn) => "粘贴图片失败: 请先保存文档，再粘贴图片",
+        ("CLIPBOARD_UNSAVED", Locale::EnUs) => "Failed to paste image: save the document first",
+        ("CLIPBOARD_EMPTY", Locale::ZhCn) => "粘贴图片失败: 剪贴板中没有图片",
+        ("CLIPBOARD_EMPTY", Locale::EnUs) => "Failed to paste image: the clipboard has no image",
+        ("IMPORT_INVALID_DOCX", Locale::ZhCn) => "导入文档失败: 不是有效的 DOCX 文件: {reason}",
+        ("IMPORT_INVALID_DOCX", Locale::EnUs) => "Failed to import document: not a valid DOCX file: {reason}",
+        ("IMPORT_DOCX_NO_DOCUMENT", Locale::ZhCn) => "导入文档失败: DOCX 文件中缺少 word/document.xml",
+        ("IMPORT_DOCX_NO_DOCUMENT", Locale::EnUs) => "Failed to import document: word/document.xml is missing from the DOCX file",
+        ("IMPORT_DOCX_NO_BODY", Locale::ZhCn) => "导入文档失败: DOCX 文件中没有正文",
+        ("IMPORT_DOCX_NO_BODY", Locale::EnUs) => "Failed to import document: the DOCX file has no body",
+        ("IMPORT_UNSUPPORTED", Locale::ZhCn) => "导入文档失败: 不支持的文件类型: .{extension}",
+        ("IMPORT_UNSUPPORTED", Locale::EnUs) => "Failed to import document: unsupported file type: .{extension}",
+        ("REEXPORT_NOT_FOUND", Locale::ZhCn) => "找不到导出记录 {id}",
+        ("REEXPORT_NOT_FOUND", Locale::EnUs) => "Export record not found: {id}",
+        ("REEXPORT_PROTECTED", Locale::ZhCn) => "加密导出的密码未保存，请重新导出并输入密码",
+        ("REEXPORT_PROTECTED", Locale::EnUs) => "The password of an encrypted export is not stored; export again and enter the password",
+        ("REEXPORT_UNSAVED", Locale::ZhCn) => "未保存的文档无法重新导出",
+        ("REEXPORT_UNSAVED", Locale::EnUs) => "An unsaved document cannot be re-exported",
+        ("OPEN_NOT_MARKDOWN", Locale::ZhCn) => "不是 Markdown 文件: {path}",
+        ("OPEN_NOT_MARKDOWN", Locale::EnUs) => "Not a Markdown file: {path}",
+        ("CHUNK_NOT_UTF8", Locale::ZhCn) => "文件不是有效的 UTF-8 文本: {reason}",
+        ("CHUNK_NOT_UTF8", Locale::EnUs) => "The file is not valid UTF-8 text: {reason}",
+        ("RICH_COPY_INVALID_RESULT", Locale::ZhCn) => "公式转换结果无效: {reason}",
+        ("RICH_COPY_INVALID_RESULT", Locale::EnUs) => "Invalid formula conversion result: {reason}",
+        ("RICH_COPY_CLIPBOARD", Locale::ZhCn) => "写入剪贴板失败: {reason}",
+        ("RICH_COPY_CLIPBOARD", Locale::EnUs) => "Failed to write to the clipboard: {reason}",
+        ("INTERNAL", Locale::ZhCn) => "内部错误（{context}）: {reason}",
+        ("INTERNAL", Locale::EnUs) => "Internal error ({context}): {reason}",
+        (_, Locale::ZhCn) => "未知错误",
+        (_, Locale::EnUs) => "Unknown error",
+    }
+}
+
+/// 按语言生成消息，并替换模板中的占位符
+pub fn translate(locale: Locale, key: &str, args: &[(String, String)]) -> String {
+    let mut message = template(locale, key).to_string();
+    for (name, value) in args {
+        message = message.replace(&format!("{{{}}}", name), value);
+    }
+    message
+}
@@ -0,0 +1,238 @@
+//! 导出为图片：使用与 PDF 导出相同的页面（同一个无头浏览器标签页），按 A4 比例逐页截图，
+//! 或把整篇文档截为一张长图，输出 PNG / JPEG，适合做幻灯片或分享到社交媒体。
+//!
+//! 分页时尽量在顶层块（段落、表格、代码块等）之间断开，超过一页高的块才从中间截断。
+
+use crate::cancel::CancelToken;
+use crate::error::{run_blocking, AppError};
+use crate::{ExportJob, ExportOptions};
+use base64::Engine;
+use headless_chrome::protocol::cdp::{Emulation, Page};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A4 纸在 96 DPI 下的宽高（CSS 像素）
+const PAGE_WIDTH_PX: u32 = 794;
+const PAGE_HEIGHT_PX: u32 = 1123;
+/// 浏览器单张截图的最大边长（设备像素）
+const MAX_CAPTURE_PX: f64 = 16384.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageLayout {
+    /// 每页一张图片
+    #[default]
+    Pages,
+    /// 整篇文档一张长图
+    Full,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ImageOptions {
+    pub format: ImageFormat,
+    /// JPEG 质量（1–100）
+    pub quality: u32,
+    /// 输出分辨率，96 为屏幕原始大小
+    pub dpi: u32,
+    pub layout: ImageLayout,
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        ImageOptions { format: ImageFormat::Png, quality: 90, dpi: 192, layout: ImageLayout::Pages }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageExportReport {
+    pub files: Vec<String>,
+    /// 图片宽度（像素）
+    pub width: u32,
+    pub format: ImageFormat,
+}
+
+/// 计算分页位置，返回每页的 `[top, height]`（CSS 像素）
+fn slices_script(page_height: u32) -> String {
+    format!(
+        r#"(() => {{
+    const pageHeight = {page_height};
+    const total = Math.ceil(document.documentElement.scrollHeight);
+    const container = document.querySelector('.markdown-preview') || document.body;
+    const cuts = [0];
+    let start = 0;
+    for (const el of container.children) {{
+        const rect = el.getBoundingClientRect();
+        const top = Math.floor(rect.top + window.scrollY);
+        const bottom = Math.ceil(rect.bottom + window.scrollY);
+        if (bottom - start <= pageHeight) continue;
+        if (top > start) {{
+            cuts.push(top);
+            start = top;
+        }}
+        while (bottom - start > pageHeight) {{
+            start += pageHeight;
+            cuts.push(start);
+        }}
+    }}
+    const slices = [];
+    cuts.push(total);
+    for (let i = 0; i + 1 < cuts.length; i++) {{
+        if (cuts[i + 1] > cuts[i]) slices.push([cuts[i], cuts[i + 1] - cuts[i]]);
+    }}
+    return JSON.stringify(slices);
+}})()"#,
+        page_height = page_height
+    )
+}
+
+async fn run_images(
+    window: &tauri::Window,
+    job: Arc<ExportJob>,
+    images: ImageOptions,
+    cancel: &CancelToken,
+) -> Result<ImageExportReport, AppError> {
+    let scale = (images.dpi.clamp(48, 600) as f64) / 96.0;
+    let quality = images.quality.clamp(1, 100);
+
+    let output_path = Path::new(&job.output_path);
+    let dir = output_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let stem = output_path.file_stem().and_then(|s| s.to_str()).unwrap_or("document").to_string();
+
+    let mut timer = crate::diagnostics::StageTimer::new();
+    let page = crate::load_export_page(window, job.clone(), &mut timer, cancel).await?;
+    cancel.check()?;
+
+    // 按打印样式、A4 宽度排版，再计算分页
+    crate::emit_progress(window, "正在排版页面...");
+    let tab = page.tab.clone();
+    let slices_json = run_blocking("image_layout", move || {
+        tab.call_method(Emulation::SetEmulatedMedia { media: Some("print".to_string()), features: None })
+            .and_then(|_| {
+                tab.call_method(Emulation::SetDeviceMetricsOverride {
+                    width: PAGE_WIDTH_PX,
+                    height: PAGE_HEIGHT_PX,
+                    device_scale_factor: scale,
+                    mobile: false,
+                    scale: None,
+                    screen_width: None,
+                    screen_height: None,
+                    position_x: None,
+                    position_y: None,
+                    dont_set_visible_size: None,
+                    screen_orientation: None,
+                    viewport: None,
+                    display_feature: None,
+                    device_posture: None,
+                })
+            })
+            .and_then(|_| tab.evaluate(&slices_script(PAGE_HEIGHT_PX), false))
+            .map(|result| result.value.and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default())
+            .map_err(|e| AppError::browser("BROWSER_LAYOUT", e))
+    })
+    .await?;
+    let mut slices: Vec<(f64, f64)> = serde_json::from_str(&slices_json).unwrap_or_default();
+    if images.layout == ImageLayout::Full {
+        let total = slices.last().map(|(top, height)| top + height).unwrap_or(0.0);
+        if total * scale > MAX_CAPTURE_PX {
+            let pixels = ((total * scale) as u64).to_string();
+            return Err(AppError::localized("PDF_GENERATION", "PDF_IMAGE_TOO_LONG", [("pixels", pixels)]));
+        }
+        slices = vec![(0.0, total)];
+    }
+    if slices.is_empty() {
+        return Err(AppError::localized("PDF_GENERATION", "PDF_EMPTY_PAGE", []));
+    }
+
+    let format = match images.format {
+        ImageFormat::Png => Page::CaptureScreenshotFormatOption::Png,
+        ImageFormat::Jpeg => Page::CaptureScreenshotFormatOption::Jpeg,
+    };
+    let width = slices.len().to_string().len().max(3);
+    let mut files = Vec::with_capacity(slices.len());
+    for (index, (top, height)) in slices.iter().copied().enumerate() {
+        cancel.check()?;
+        crate::emit_progress(window, &format!("[{}/{}] 正在截取第 {} 页...", index + 1, slices.len(), index + 1));
+        let path = match images.layout {
+            ImageLayout::Full => dir.join(format!("{}.{}", stem, images.format.extension())),
+            ImageLayout::Pages => dir.join(format!(
+                "{}-page-{:0width$}.{}",
+                stem,
+                index + 1,
+                images.format.extension(),
+                width = width
+            )),
+        };
+        let tab = page.tab.clone();
+        let format = format.clone();
+        let capture = run_blocking("capture_image", move || {
+            let data = tab
+                .call_method(Page::CaptureScreenshot {
+                    format: Some(format),
+                    quality: (images.format == ImageFormat::Jpeg).then_some(quality),
+                    clip: Some(Page::Viewport { x: 0.0, y: top, width: PAGE_WIDTH_PX as f64, height, scale: 1.0 }),
+                    from_surface: Some(true),
+                    capture_beyond_viewport: Some(true),
+                    optimize_for_speed: None,
+                })
+                .map_err(|e| AppError::browser("BROWSER_SCREENSHOT", e))?
+                .data;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| AppError::browser("BROWSER_SCREENSHOT_DATA", e))?;
+            fs::write(&path, bytes).map_err(|e| AppError::file(&path, e))?;
+            Ok(path.to_string_lossy().to_string())
+        });
+        files.push(crate::timeouts::limit(&page.timeouts, "print", capture).await?);
+    }
+
+    Ok(ImageExportReport { files, width: (PAGE_WIDTH_PX as f64 * scale).round() as u32, format: images.format })
+}
+
+/// 把文档导出为图片：按页输出 `<名称>-page-001.png` 等，或整篇输出 `<名称>.png`，
+/// 均位于 `output_path` 所在目录
+#[tauri::command]
+pub async fn export_to_images(
+    window: tauri::Window,
+    settings: tauri::State<'_, crate::settings::SettingsState>,
+    html_content: String,
+    output_path: String,
+    title: String,
+    images: Option<ImageOptions>,
+    options: Option<ExportOptions>,
+) -> Result<ImageExportReport, AppError> {
+    let options = options.unwrap_or_default();
+    let cancel = CancelToken::register(options.export_id.clone());
+    let job = Arc::new(ExportJob {
+        html_content,
+        output_path,
+        title,
+        front_matter: options.markdown.as_deref().and_then(crate::front_matter::parse),
+        options,
+        settings: settings.snapshot(),
+    });
+    let result = run_images(&window, job, images.unwrap_or_default(), &cancel).await;
+    if let Err(e) = &result {
+        crate::diagnostics::record_error("export_to_images", e);
+    }
+    result
+}
@@ -0,0 +1,114 @@
+//! 应用数据目录下的 JSON 文件（导出历史、最近文件等）：在锁内读取、修改并保存。
+//!
+//! 写入时先写临时文件再替换，写入过程中崩溃不会损坏原来的文件。文件内容无法解析时返回错误，
+//! 原文件保持不变，不会被空数据覆盖；文件不存在时从默认值开始。
+
+use crate::error::AppError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::Manager;
+
+pub struct JsonStore {
+    file_name: &'static str,
+    /// 避免并发的命令互相覆盖
+    lock: Mutex<()>,
+}
+
+impl JsonStore {
+    pub const fn new(file_name: &'static str) -> Self {
+        JsonStore { file_name, lock: Mutex::new(()) }
+    }
+
+    fn path(&self, app: &tauri::AppHandle) -> Result<PathBuf, AppError> {
+        app.path()
+            .app_data_dir()
+            .map(|dir| dir.join(self.file_name))
+            .map_err(|e| AppError::Internal { context: self.file_name.to_string(), reason: e.to_string() })
+    }
+
+    /// 读取当前内容
+    pub fn load<T: DeserializeOwned + Default>(&self, app: &tauri::AppHandle) -> Result<T, AppError> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        read_json(&self.path(app)?)
+    }
+
+    /// 在锁内读取、修改并保存
+    pub fn update<T, R>(&self, app: &tauri::AppHandle, f: impl FnOnce(&mut T) -> R) -> Result<R, AppError>
+    where
+        T: DeserializeOwned + Serialize + Default,
+    {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let path = self.path(app)?;
+        let mut value: T = read_json(&path)?;
+        let result = f(&mut value);
+        write_json(&path, &value)?;
+        Ok(result)
+    }
+}
+
+/// 文件不存在时为默认值，无法解析时报错
+pub fn read_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, AppError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(AppError::file(path, e)),
+    };
+    serde_json::from_str(&content).map_err(|e| AppError::CorruptStore {
+        path: path.to_string_lossy().to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// 先写临时文件再替换
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::file(parent, e))?;
+    }
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::Internal { context: "json_store".to_string(), reason: e.to_string() })?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, json).map_err(|e| AppError::file(&tmp, e))?;
+    fs::rename(&tmp, path).map_err(|e| AppError::file(path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn missing_files_start_from_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let value: BTreeMap<String, u32> = read_json(&dir.path().join("missing.json")).unwrap();
+        assert!(value.is_empty());
+    }
+
+    #[test]
+    fn writes_replace_the_file_without_leaving_temporaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/store.json");
+        write_json(&path, &BTreeMap::from([("a".to_string(), 1u32)])).unwrap();
+        write_json(&path, &BTreeMap::from([("b".to_string(), 2u32)])).unwrap();
+
+        let value: BTreeMap<String, u32> = read_json(&path).unwrap();
+        assert_eq!(value, BTreeMap::from([("b".to_string(), 2)]));
+        let names: Vec<_> = fs::read_dir(path.parent().unwrap()).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(names, ["store.json"]);
+    }
+
+    #[test]
+    fn corrupt_files_are_reported_and_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.json");
+        fs::write(&path, "{\"a\": 1").unwrap();
+
+        let result: Result<BTreeMap<String, u32>, AppError> = read_json(&path);
+        assert!(matches!(result, Err(AppError::CorruptStore { .. })));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\": 1");
+    }
+}
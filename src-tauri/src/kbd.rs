@@ -0,0 +1,82 @@
+//! 按键与界面元素：技术文档、操作手册中常见的快捷键与菜单路径。
+//!
+//!  - `[[Ctrl]]+[[C]]`：按键渲染为 `<kbd>`。与 wiki 链接共用 `[[...]]`，因此只有常见按键名
+//!    （`Ctrl`、`Shift`、`Enter`、`F5`、方向键等）、单个字符，或用 `+` 与其他按键相连时才视为按键
+//!  - `文件 ▸ 导出 ▸ PDF`：用 `▸` 分隔的菜单路径，每一项为不含空格的词；
+//!    多个词组成的菜单项用不换行空格连接
+//!
+//! 属于扩展语法，代码与公式中的内容保持不变。
+
+use regex::{Captures, Regex};
+
+/// 常见按键名（不区分大小写）
+const KEY_NAMES: &[&str] = &[
+    "ctrl", "control", "alt", "option", "opt", "shift", "cmd", "command", "meta", "win", "windows", "super", "fn",
+    "enter", "return", "esc", "escape", "tab", "space", "backspace", "delete", "del", "insert", "ins", "home", "end",
+    "pageup", "pagedown", "pgup", "pgdn", "up", "down", "left", "right", "capslock", "printscreen", "prtsc",
+    "pause", "menu",
+];
+
+fn is_key_name(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    let is_function_key = lower
+        .strip_prefix('f')
+        .and_then(|n| n.parse::<u8>().ok())
+        .is_some_and(|n| (1..=24).contains(&n));
+    name.chars().count() == 1 || is_function_key || KEY_NAMES.contains(&lower.as_str())
+}
+
+/// `[[按键]]` 与 `[[按键]]+[[按键]]` 组合
+fn apply_keys(text: &str) -> String {
+    let re_combo = Regex::new(r"\[\[([^\[\]|#\n]{1,20})\]\](?:\s*\+\s*\[\[([^\[\]|#\n]{1,20})\]\])*").unwrap();
+    let re_key = Regex::new(r"\[\[([^\[\]|#\n]{1,20})\]\]").unwrap();
+    re_combo
+        .replace_all(text, |caps: &Captures| {
+            let keys: Vec<&str> = re_key.captures_iter(&caps[0]).map(|k| k.get(1).unwrap().as_str().trim()).collect();
+            // 单独一个不像按键的 `[[...]]` 是 wiki 链接
+            if keys.len() == 1 && !is_key_name(keys[0]) {
+                return caps[0].to_string();
+            }
+            keys.iter().map(|key| format!("<kbd>{}</kbd>", key)).collect::<Vec<_>>().join("+")
+        })
+        .into_owned()
+}
+
+/// `菜单 ▸ 子菜单` 路径
+fn apply_menu_paths(text: &str) -> String {
+    let re_menu = Regex::new(
+        r#"[^\s▸<>，。；：、！？,;:!?()（）"“”「」]+(?:\s*▸\s*[^\s▸<>，。；：、！？,;:!?()（）"“”「」]+)+"#,
+    )
+    .unwrap();
+    re_menu
+        .replace_all(text, |caps: &Captures| {
+            let items: Vec<String> = caps[0]
+                .split('▸')
+                .map(|item| format!(r#"<span class="menu-item">{}</span>"#, item.trim()))
+                .collect();
+            format!(r#"<span class="menu-path">{}</span>"#, items.join(r#"<span class="menu-sep">▸</span>"#))
+        })
+        .into_owned()
+}
+
+/// 把 HTML 正文中的按键与菜单路径转换为对应元素
+pub fn apply_ui_markup(html: &str) -> String {
+    if !html.contains("[[") && !html.contains('▸') {
+        return html.to_string();
+    }
+    let re_protected = Regex::new(
+        r"(?s)<pre\b.*?</pre>|<code\b.*?</code>|<kbd\b.*?</kbd>|<math\b.*?</math>|<script\b.*?</script>|<style\b.*?</style>|<[^>]*>",
+    )
+    .unwrap();
+    let replace = |text: &str| apply_menu_paths(&apply_keys(text));
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(html) {
+        out.push_str(&replace(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&replace(&html[last..]));
+    out
+}
@@ -0,0 +1,92 @@
+//! 中日文标点的避头尾（禁则）规则：
+//!  - `strict`（默认）：CSS `line-break: strict`，并在行内元素与标点之间加入不可断行的连接符，
+//!    保证逗号、句号、右括号等不出现在行首，左括号等不出现在行尾，`——`、`……` 不被拆开
+//!  - `normal`：浏览器默认规则
+//!  - `loose`：宽松规则，允许在小假名、长音符等之前换行，适合窄栏排版
+//!
+//! 设置中的 `cjk_line_break` 为默认值，front matter 中的 `cjk_line_break` 覆盖。
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineBreakRule {
+    Loose,
+    Normal,
+    #[default]
+    Strict,
+}
+
+impl LineBreakRule {
+    /// front matter 中的 `cjk_line_break` 覆盖设置中的默认值
+    pub fn resolve(default: Self, front_matter: Option<&Value>) -> Self {
+        front_matter
+            .and_then(|fm| fm.get("cjk_line_break"))
+            .and_then(|value| serde_yaml::from_value(value.clone()).ok())
+            .unwrap_or(default)
+    }
+}
+
+/// 不能出现在行首的标点
+const NO_LINE_START: &str = "，。、；：？！）】」』》〉〕］｝’”・…—ー々〜%％,.;:?!)]}";
+/// 不能出现在行尾的标点
+const NO_LINE_END: &str = "（【「『《〈〔［｛‘“([{";
+/// 不可断行的连接符（U+2060 WORD JOINER）
+const WORD_JOINER: char = '\u{2060}';
+
+/// 导出页面中的换行规则样式
+pub fn css(rule: LineBreakRule) -> String {
+    let line_break = match rule {
+        LineBreakRule::Loose => "loose",
+        LineBreakRule::Normal => "normal",
+        LineBreakRule::Strict => "strict",
+    };
+    format!(
+        ".markdown-preview {{ line-break: {}; word-break: normal; overflow-wrap: break-word; }}\n\
+         .kinsoku-pair {{ white-space: nowrap; }}\n",
+        line_break
+    )
+}
+
+/// 严格规则下，把行内元素边界两侧的标点与相邻内容连在一起（浏览器会在元素边界处换行），
+/// 并让 `——`、`……` 不被拆开；代码中的内容保持不变
+pub fn apply_kinsoku(html: &str, rule: LineBreakRule) -> String {
+    if rule != LineBreakRule::Strict {
+        return html.to_string();
+    }
+    const INLINE_TAGS: &str = "a|b|strong|em|i|u|s|del|mark|span|sup|sub|code|kbd";
+    // `</strong>，` → 连接符插在结束标签与标点之间；`（<em>` → 连接符插在标点与开始标签之间
+    let re_close = Regex::new(&format!(r"(</(?:{})>)([{}])", INLINE_TAGS, regex::escape(NO_LINE_START))).unwrap();
+    let re_open = Regex::new(&format!(r"([{}])(<(?:{})\b)", regex::escape(NO_LINE_END), INLINE_TAGS)).unwrap();
+    let re_pair = Regex::new(r"——+|…{2,}").unwrap();
+    let re_pre = Regex::new(r"(?s)<pre\b.*?</pre>").unwrap();
+    let re_tag_or_code = Regex::new(r"(?s)<code\b.*?</code>|<[^>]*>").unwrap();
+
+    let process = |segment: &str| {
+        let segment = re_close.replace_all(segment, |caps: &Captures| format!("{}{}{}", &caps[1], WORD_JOINER, &caps[2]));
+        let segment = re_open.replace_all(&segment, |caps: &Captures| format!("{}{}{}", &caps[1], WORD_JOINER, &caps[2]));
+        // 文本中的连续破折号、省略号
+        let mut out = String::with_capacity(segment.len());
+        let mut last = 0;
+        for m in re_tag_or_code.find_iter(&segment) {
+            out.push_str(&re_pair.replace_all(&segment[last..m.start()], r#"<span class="kinsoku-pair">$0</span>"#));
+            out.push_str(m.as_str());
+            last = m.end();
+        }
+        out.push_str(&re_pair.replace_all(&segment[last..], r#"<span class="kinsoku-pair">$0</span>"#));
+        out
+    };
+
+    // 代码块保持不变
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for m in re_pre.find_iter(html) {
+        out.push_str(&process(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&process(&html[last..]));
+    out
+}
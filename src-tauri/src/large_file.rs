@@ -0,0 +1,289 @@
+//! 大文件模式：超过 `LARGE_FILE_BYTES` 的文档不再整体读入与解析，而是按需读取。
+//!
+//!  - 首次访问时扫描一遍文件，建立行索引（每行的字节偏移）与安全断点（代码块、公式块之外的空行），
+//!    按文件修改时间与大小缓存
+//!  - `read_markdown_chunk` 只读取指定的行
+//!  - `parse_markdown_window` 只解析可见区域：窗口扩展到最近的安全断点，不会截断代码块或公式块，
+//!    返回的块行号为整个文件中的行号
+//!
+//! 解析模式按文件开头的 front matter 确定。
+
+use crate::error::AppError;
+use crate::parser_mode::ParserMode;
+use crate::MarkdownBlock;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// 超过该大小的文件按大文件模式打开
+pub const LARGE_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// 单次最多读取的行数
+const MAX_CHUNK_LINES: usize = 20_000;
+
+/// 读取 front matter 时最多查看的开头行数
+const HEAD_LINES: usize = 200;
+
+/// 最多缓存的行索引数量
+const MAX_CACHED_INDEXES: usize = 8;
+
+static INDEX_CACHE: Mutex<Option<HashMap<PathBuf, Arc<LineIndex>>>> = Mutex::new(None);
+
+struct LineIndex {
+    modified: Option<SystemTime>,
+    len: u64,
+    /// 每行起始的字节偏移
+    offsets: Vec<u64>,
+    /// 可以安全拆分的行（1-indexed）：代码块与公式块之外的空行
+    breaks: Vec<usize>,
+}
+
+impl LineIndex {
+    fn build(path: &Path, modified: Option<SystemTime>, len: u64) -> Result<Self, AppError> {
+        let file = File::open(path).map_err(|e| AppError::file(path, e))?;
+        let mut reader = BufReader::with_capacity(1 << 20, file);
+        let mut offsets = Vec::new();
+        let mut breaks = Vec::new();
+        let mut fence: Option<(u8, usize)> = None;
+        let mut in_math = false;
+        let mut offset = 0u64;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line).map_err(|e| AppError::file(path, e))?;
+            if read == 0 {
+                break;
+            }
+            offsets.push(offset);
+            offset += read as u64;
+
+            let trimmed = line.trim_ascii();
+            let fence_len = |c: u8| trimmed.iter().take_while(|&&b| b == c).count();
+            match fence {
+                Some((c, n)) if fence_len(c) >= n && trimmed.iter().all(|&b| b == c) => fence = None,
+                Some(_) => {}
+                None if !in_math && (fence_len(b'`') >= 3 || fence_len(b'~') >= 3) => {
+                    let c = trimmed[0];
+                    fence = Some((c, fence_len(c)));
+                }
+                None if trimmed == b"$$" => in_math = !in_math,
+                None if trimmed.is_empty() && !in_math => breaks.push(offsets.len()),
+                None => {}
+            }
+        }
+        Ok(LineIndex { modified, len, offsets, breaks })
+    }
+
+    fn line_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// `[start_line, end_line]`（1-indexed，闭区间）的字节范围
+    fn byte_range(&self, start_line: usize, end_line: usize) -> (u64, u64) {
+        let start = self.offsets[start_line - 1];
+        let end = self.offsets.get(end_line).copied().unwrap_or(self.len);
+        (start, end)
+    }
+
+    /// 把窗口扩展到最近的安全断点
+    fn expand(&self, start_line: usize, end_line: usize) -> (usize, usize) {
+        let before = self.breaks.partition_point(|&line| line <= start_line);
+        let start = if before == 0 { 1 } else { self.breaks[before - 1] };
+        let after = self.breaks.partition_point(|&line| line < end_line);
+        let end = self.breaks.get(after).copied().unwrap_or(self.line_count());
+        (start, end)
+    }
+}
+
+/// 文件的行索引：文件未变化时使用缓存；扫描文件时不持有缓存锁，其他文件的请求不必等待
+fn line_index(path: &Path) -> Result<Arc<LineIndex>, AppError> {
+    let meta = fs::metadata(path).map_err(|e| AppError::file(path, e))?;
+    let modified = meta.modified().ok();
+    let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    let cached = INDEX_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|cache| cache.get(&key).cloned())
+        .filter(|index| index.modified == modified && index.len == meta.len());
+    if let Some(index) = cached {
+        return Ok(index);
+    }
+    let index = Arc::new(LineIndex::build(path, modified, meta.len())?);
+    let mut cache = INDEX_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if cache.len() >= MAX_CACHED_INDEXES {
+        cache.clear();
+    }
+    cache.insert(key, index.clone());
+    Ok(index)
+}
+
+/// 读取 `[start_line, end_line]` 的文本（不含最后一行的换行符）
+fn read_lines(path: &Path, index: &LineIndex, start_line: usize, end_line: usize) -> Result<String, AppError> {
+    let (start, end) = index.byte_range(start_line, end_line);
+    let mut file = File::open(path).map_err(|e| AppError::file(path, e))?;
+    file.seek(SeekFrom::Start(start)).map_err(|e| AppError::file(path, e))?;
+    let mut bytes = vec![0; (end - start) as usize];
+    file.read_exact(&mut bytes).map_err(|e| AppError::file(path, e))?;
+    let mut text = String::from_utf8(bytes).map_err(|e| {
+        AppError::localized(
+            "INTERNAL",
+            "CHUNK_NOT_UTF8",
+            [("context", "read_markdown_chunk".to_string()), ("reason", e.to_string())],
+        )
+    })?;
+    if text.ends_with('\n') {
+        text.pop();
+        if text.ends_with('\r') {
+            text.pop();
+        }
+    }
+    Ok(text)
+}
+
+/// 把请求的行范围限制在文件之内；没有可读的行时返回 `None`
+fn clamp_range(index: &LineIndex, start_line: usize, count: usize) -> Option<(usize, usize)> {
+    let start = start_line.max(1);
+    if start > index.line_count() || count == 0 {
+        return None;
+    }
+    let end = (start + count.min(MAX_CHUNK_LINES) - 1).min(index.line_count());
+    Some((start, end))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkdownFileInfo {
+    pub path: String,
+    pub size: u64,
+    pub line_count: usize,
+    /// 是否应按大文件模式打开
+    pub large: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkdownChunk {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub content: String,
+    pub total_lines: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkdownWindow {
+    /// 扩展到安全断点后实际解析的行范围
+    pub start_line: usize,
+    pub end_line: usize,
+    pub blocks: Vec<MarkdownBlock>,
+    pub total_lines: usize,
+}
+
+/// 文件大小与行数，前端据此决定是否使用大文件模式
+#[tauri::command]
+pub async fn get_markdown_file_info(path: String) -> Result<MarkdownFileInfo, AppError> {
+    crate::error::run_blocking("get_markdown_file_info", move || {
+        let index = line_index(Path::new(&path))?;
+        let large = index.len > LARGE_FILE_BYTES;
+        Ok(MarkdownFileInfo { size: index.len, line_count: index.line_count(), large, path })
+    })
+    .await
+}
+
+/// 读取从 `start_line`（1-indexed）开始的 `count` 行
+#[tauri::command]
+pub async fn read_markdown_chunk(path: String, start_line: usize, count: usize) -> Result<MarkdownChunk, AppError> {
+    crate::error::run_blocking("read_markdown_chunk", move || read_chunk(Path::new(&path), start_line, count)).await
+}
+
+fn read_chunk(path: &Path, start_line: usize, count: usize) -> Result<MarkdownChunk, AppError> {
+    let index = line_index(path)?;
+    let total_lines = index.line_count();
+    let Some((start, end)) = clamp_range(&index, start_line, count) else {
+        return Ok(MarkdownChunk { start_line, end_line: start_line.saturating_sub(1), content: String::new(), total_lines });
+    };
+    let content = read_lines(path, &index, start, end)?;
+    Ok(MarkdownChunk { start_line: start, end_line: end, content, total_lines })
+}
+
+/// 只解析 `[start_line, end_line]` 附近的块（窗口扩展到安全断点），块的行号为文件中的行号
+#[tauri::command]
+pub async fn parse_markdown_window(
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    mode: Option<ParserMode>,
+) -> Result<MarkdownWindow, AppError> {
+    crate::error::run_blocking("parse_markdown_window", move || {
+        parse_window(Path::new(&path), start_line, end_line, mode)
+    })
+    .await
+}
+
+fn parse_window(
+    path: &Path,
+    start_line: usize,
+    end_line: usize,
+    mode: Option<ParserMode>,
+) -> Result<MarkdownWindow, AppError> {
+    let index = line_index(path)?;
+    let total_lines = index.line_count();
+    let Some((start, end)) = clamp_range(&index, start_line, end_line.saturating_sub(start_line) + 1) else {
+        return Ok(MarkdownWindow { start_line, end_line, blocks: Vec::new(), total_lines });
+    };
+    let (start, end) = index.expand(start, end);
+
+    let mode = match mode {
+        Some(mode) => mode,
+        None => {
+            let head = read_lines(path, &index, 1, HEAD_LINES.min(total_lines))?;
+            ParserMode::resolve(None, &head)
+        }
+    };
+    let content = read_lines(path, &index, start, end)?;
+    let offset = start - 1;
+    let blocks = crate::split_markdown_blocks(&content, mode)
+        .into_iter()
+        .map(|block| MarkdownBlock {
+            start_line: block.start_line + offset,
+            end_line: block.end_line + offset,
+            ..block
+        })
+        .collect();
+    Ok(MarkdownWindow { start_line: start, end_line: end, blocks, total_lines })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_blocks_keep_content_ids_and_file_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doc.md");
+        let markdown = "# 标题\n\n第一段\n\n```\ncode\n\n```\n\n第二段\n";
+        fs::write(&path, markdown).unwrap();
+
+        let whole = crate::split_markdown_blocks(markdown, ParserMode::default());
+        let window = parse_window(&path, 6, 6, None).unwrap();
+        assert_eq!((window.start_line, window.end_line), (4, 9));
+        assert_eq!(window.blocks.len(), 1);
+        let code = whole.iter().find(|block| block.start_line == 5).unwrap();
+        assert_eq!(window.blocks[0].id, code.id);
+        assert_eq!((window.blocks[0].start_line, window.blocks[0].end_line), (code.start_line, code.end_line));
+    }
+
+    #[test]
+    fn chunks_are_clamped_to_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("doc.md");
+        fs::write(&path, "a\nb\nc\n").unwrap();
+        let chunk = read_chunk(&path, 2, 10).unwrap();
+        assert_eq!((chunk.start_line, chunk.end_line, chunk.total_lines), (2, 3, 3));
+        assert_eq!(chunk.content, "b\nc");
+    }
+}
@@ -0,0 +1,110 @@
+//! 版式：正文分栏与纸张预设。
+//!
+//!  - `two_column`：正文按 CSS 多栏排成两栏，封面、标题区、摘要与一级标题横跨两栏；
+//!    标题不与后文分离，图片、代码块、表格与行间公式不跨栏断开。竖排时不分栏
+//!  - `booklet`：A5 纸张（A4 对折），字号与页边距相应缩小，适合打印讲义与小册子
+//!
+//! 导出选项优先，其次是 front matter 中的 `layout` / `paper`，最后是设置中的默认值。
+
+use crate::vertical::WritingMode;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageLayout {
+    #[default]
+    Single,
+    #[serde(alias = "two-column")]
+    TwoColumn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    #[default]
+    A4,
+    #[serde(alias = "a5")]
+    Booklet,
+}
+
+/// 导出选项优先，其次是 front matter 中的 `key`，最后是设置中的默认值
+fn resolve<T: Copy + serde::de::DeserializeOwned>(option: Option<T>, default: T, front_matter: Option<&Value>, key: &str) -> T {
+    option
+        .or_else(|| {
+            front_matter
+                .and_then(|fm| fm.get(key))
+                .and_then(|value| serde_yaml::from_value(value.clone()).ok())
+        })
+        .unwrap_or(default)
+}
+
+impl PageLayout {
+    pub fn resolve(option: Option<Self>, default: Self, front_matter: Option<&Value>) -> Self {
+        resolve(option, default, front_matter, "layout")
+    }
+}
+
+impl PaperSize {
+    pub fn resolve(option: Option<Self>, default: Self, front_matter: Option<&Value>) -> Self {
+        resolve(option, default, front_matter, "paper")
+    }
+
+    /// 纸张宽度与高度（英寸）
+    fn dimensions(self) -> (f64, f64) {
+        match self {
+            PaperSize::A4 => (8.27, 11.69),
+            PaperSize::Booklet => (5.83, 8.27),
+        }
+    }
+}
+
+/// 打印时左右页边距（英寸），与打印选项一致
+const SIDE_MARGIN_IN: f64 = 0.4;
+/// CSS 像素 / 英寸
+const PX_PER_IN: f64 = 96.0;
+
+/// 页面正文区域的尺寸（`--page-content-width` / `--page-content-height`，单位 px），
+/// 供需要按页排版的脚本使用；上下页边距随页眉页脚变化
+pub fn page_metrics_css(paper: PaperSize, margin_top_in: f64, margin_bottom_in: f64) -> String {
+    let (width, height) = paper.dimensions();
+    format!(
+        ":root {{ --page-content-width: {:.0}px; --page-content-height: {:.0}px; }}\n",
+        (width - 2.0 * SIDE_MARGIN_IN) * PX_PER_IN,
+        (height - margin_top_in - margin_bottom_in) * PX_PER_IN
+    )
+}
+
+const TWO_COLUMN_CSS: &str = r#"
+body { max-width: none; padding: 0; }
+.markdown-preview { column-count: 2; column-gap: 2em; column-fill: balance; }
+.cover-page, .title-block, .abstract-block, .markdown-preview > h1, .new-page { column-span: all; }
+h1, h2, h3, h4, h5, h6 { break-after: avoid; page-break-after: avoid; break-inside: avoid; }
+pre, table, figure, img, blockquote, .katex-display, .equation, mjx-container, .callout, .render-failure {
+    break-inside: avoid;
+    page-break-inside: avoid;
+}
+pre { white-space: pre-wrap; word-break: break-all; }
+img, svg { max-width: 100%; }
+.table-wrapper { overflow: hidden; }
+p { orphans: 2; widows: 2; }
+"#;
+
+const BOOKLET_CSS: &str = r#"
+@page { size: A5; }
+body { max-width: none; padding: 0 8px; font-size: 0.9em; }
+h1 { font-size: 1.7em; }
+h2 { font-size: 1.4em; }
+"#;
+
+/// 分栏与纸张样式；竖排时不分栏
+pub fn css(layout: PageLayout, paper: PaperSize, writing_mode: WritingMode) -> String {
+    let mut css = String::new();
+    if paper == PaperSize::Booklet {
+        css.push_str(BOOKLET_CSS);
+    }
+    if layout == PageLayout::TwoColumn && writing_mode == WritingMode::Horizontal {
+        css.push_str(TWO_COLUMN_CSS);
+    }
+    css
+}
@@ -1,11 +1,89 @@
 use comrak::Options as ComrakOptions;
-use headless_chrome::{Browser, LaunchOptions};
-use pulldown_cmark::{html, Options, Parser};
+use parser_mode::ParserMode;
+use pulldown_cmark::{html, Parser};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::{Emitter, Manager};
-use thiserror::Error;
+
+mod abstract_block;
+mod asset_server;
+mod assets;
+mod badges;
+mod callouts;
+#[cfg(test)]
+mod block_fuzz;
+mod browser;
+mod cancel;
+mod checker;
+mod clipboard;
+mod code_blocks;
+mod content_security;
+mod cover;
+mod data_table;
+mod decorations;
+mod diagnostics;
+mod diff;
+mod doc_import;
+mod doc_links;
+mod drafts;
+mod endnotes;
+mod equations;
+mod error;
+mod figure;
+mod fonts;
+mod formatter;
+mod front_matter;
+mod glossary;
+mod hashing;
+mod history;
+mod html_util;
+mod highlight;
+mod i18n;
+mod image_export;
+mod json_store;
+mod kbd;
+mod kinsoku;
+mod layout;
+mod large_file;
+mod line_breaks;
+mod literate;
+mod math_engine;
+mod metrics;
+mod open_file;
+mod page_breaks;
+mod parser_mode;
+mod pdf;
+mod pdf_archive;
+mod pdf_optimize;
+mod pdf_protect;
+mod policy;
+mod readiness;
+mod recent;
+mod print_run;
+mod redaction;
+mod render_failures;
+mod selection;
+mod report;
+mod rich_copy;
+mod ruby;
+mod search;
+mod settings;
+mod slug;
+mod smart_quotes;
+mod streamed_preview;
+mod table_fit;
+mod tasks;
+mod timeouts;
+mod transforms;
+mod typography;
+mod vertical;
+mod watch;
+mod watermark;
+mod workspace;
+
+pub use error::AppError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarkdownBlock {
@@ -21,46 +99,20 @@ struct ProgressPayload {
     message: String,
 }
 
-#[derive(Error, Debug)]
-pub enum AppError {
-    #[error("文件读取错误: {0}")]
-    FileReadError(#[from] std::io::Error),
-    #[error("浏览器启动错误: {0}")]
-    BrowserError(String),
-    #[error("PDF 生成错误: {0}")]
-    PdfError(String),
-}
-
-impl serde::Serialize for AppError {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        serializer.serialize_str(&self.to_string())
-    }
-}
-
 /// 读取 Markdown 文件内容
 #[tauri::command]
 fn read_markdown_file(path: &str) -> Result<String, AppError> {
-    let content = fs::read_to_string(path)?;
+    let content = fs::read_to_string(path).map_err(|e| AppError::file(path, e))?;
     Ok(content)
 }
 
-fn is_markdown_file(path: &std::path::Path) -> bool {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "md" | "markdown"))
-        .unwrap_or(false)
-}
-
-/// 获取通过命令行参数传入的 Markdown 文件路径（用于将文件拖到 exe 启动）
+/// 获取通过命令行参数传入的 Markdown 文件路径（将文件拖到 exe 上或通过文件关联启动）
 #[tauri::command]
 fn get_launch_markdown_path() -> Option<String> {
-    std::env::args_os()
-        .skip(1)
-        .map(std::path::PathBuf::from)
-        .find(|path| path.is_file() && is_markdown_file(path))
+    let cwd = std::env::current_dir().unwrap_or_default();
+    open_file::take_pending()
+        .or_else(|| open_file::markdown_arg(std::env::args_os().skip(1), &cwd))
+        .filter(|path| path.is_file())
         .map(|path| path.to_string_lossy().to_string())
 }
 
@@ -74,7 +126,8 @@ fn get_comrak_options() -> ComrakOptions<'static> {
     options.extension.math_dollars = true;
     options.extension.math_code = true;
     options.extension.front_matter_delimiter = Some("---".to_string());
-    options.parse.smart = true;
+    // 智能标点由 smart_quotes 模块按设置在渲染时处理，解析时保留原始字符
+    options.parse.smart = false;
     options.render.hardbreaks = false;
     options.render.github_pre_lang = true;
     options.render.width = 0;
@@ -133,14 +186,21 @@ struct AstNode {
     end_line: usize,     // 1-indexed
 }
 
+/// 将 Markdown 拆分为编辑器使用的块
 #[tauri::command]
-fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
+fn parse_markdown_blocks(markdown: &str, mode: Option<ParserMode>) -> Result<Vec<MarkdownBlock>, AppError> {
+    let mode = ParserMode::resolve(mode, markdown);
+    error::catch_panic("parse_markdown_blocks", || Ok(split_markdown_blocks(markdown, mode)))
+}
+
+fn split_markdown_blocks(markdown: &str, mode: ParserMode) -> Vec<MarkdownBlock> {
     use comrak::{Arena as ComrakArena, nodes::NodeValue, parse_document};
 
+    let started = Instant::now();
     let content = markdown.replace("\r\n", "\n");
     let lines: Vec<&str> = content.lines().collect();
     let arena = ComrakArena::new();
-    let options = get_comrak_options();
+    let options = parser_mode::comrak_options(mode, &content);
     let root = parse_document(&arena, &content, &options);
 
     // ---------- 第一步：用 comrak AST 收集原子节点 ----------
@@ -243,7 +303,7 @@ fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
         for (k, line) in node_lines.iter().enumerate() {
             let ltrim = line.trim();
             // 表格开始
-            let table_start_re = ltrim.starts_with("<table") && (ltrim.len() == 6 || ltrim.as_bytes().get(6).map_or(false, |&b| b == b'>' || b == b' '));
+            let table_start_re = ltrim.starts_with("<table") && (ltrim.len() == 6 || ltrim.as_bytes().get(6).is_some_and(|&b| b == b'>' || b == b' '));
             if k > current_start && table_start_re {
                 let split = split_lines_with_math(
                     node.start_line + current_start,
@@ -267,7 +327,7 @@ fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
         let rem_start = node.start_line + current_start;
         let rem_end = node.end_line;
         if rem_start <= rem_end {
-            let remaining = lines.get(current_start..node_lines.len()).unwrap_or(&[]).join("\n");
+            let remaining = node_lines.get(current_start..).unwrap_or(&[]).join("\n");
             if !remaining.contains("<table") && !remaining.contains("</table>") {
                 for (sl, el) in split_lines_with_math(rem_start, rem_end, &lines) {
                     refined.push(AstNode { node_type: "line".to_string(), start_line: sl, end_line: el });
@@ -290,7 +350,7 @@ fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
                 .unwrap_or(&[])
                 .join("\n");
             let ltrim = node_content.trim();
-            let is_table_start = ltrim.starts_with("<table") && (ltrim.len() == 6 || ltrim.as_bytes().get(6).map_or(false, |&b| b == b'>' || b == b' '));
+            let is_table_start = ltrim.starts_with("<table") && (ltrim.len() == 6 || ltrim.as_bytes().get(6).is_some_and(|&b| b == b'>' || b == b' '));
             if is_table_start {
                 let mut last_line = node.end_line;
                 let mut j = i + 1;
@@ -326,18 +386,18 @@ fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
     let mut blocks: Vec<MarkdownBlock> = Vec::new();
     let mut last_line_processed = 0usize;
 
-    for (idx, node) in atom_nodes.iter().enumerate() {
+    for node in &atom_nodes {
         let start_line = node.start_line; // 1-indexed
         let end_line = node.end_line;     // 1-indexed
 
         // 填充 gap 行
         if start_line - 1 > last_line_processed {
             let gap_nodes = split_lines_with_math(last_line_processed + 1, start_line - 1, &lines);
-            for (gidx, (gsl, gel)) in gap_nodes.iter().enumerate() {
+            for (gsl, gel) in &gap_nodes {
                 let gap_content = lines.get(gsl - 1..*gel).unwrap_or(&[]).join("\n");
                 if !gap_content.trim().is_empty() {
                     blocks.push(MarkdownBlock {
-                        id: format!("gap-{}-{}", gsl, gidx),
+                        id: String::new(),
                         content: gap_content,
                         start_line: *gsl,
                         end_line: *gel,
@@ -354,7 +414,7 @@ fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
             let block_content = lines.get(actual_start - 1..end_line).unwrap_or(&[]).join("\n");
             if !block_content.trim().is_empty() {
                 blocks.push(MarkdownBlock {
-                    id: format!("block-{}-{}", idx, actual_start),
+                    id: String::new(),
                     content: block_content,
                     start_line: actual_start,
                     end_line,
@@ -368,11 +428,11 @@ fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
     // 处理文件末尾剩余行
     if last_line_processed < lines.len() {
         let tail_nodes = split_lines_with_math(last_line_processed + 1, lines.len(), &lines);
-        for (gidx, (gsl, gel)) in tail_nodes.iter().enumerate() {
+        for (gsl, gel) in &tail_nodes {
             let gap_content = lines.get(gsl - 1..*gel).unwrap_or(&[]).join("\n");
             if !gap_content.trim().is_empty() {
                 blocks.push(MarkdownBlock {
-                    id: format!("gap-end-{}-{}", gsl, gidx),
+                    id: String::new(),
                     content: gap_content,
                     start_line: *gsl,
                     end_line: *gel,
@@ -397,7 +457,7 @@ fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
         let mut cur = blocks[k].clone();
         let mut dollar_count = count_bare_dollars(&cur.content);
         // 奇数：公式未闭合，继续吸收后续块
-        while dollar_count % 2 != 0 && k + 1 < blocks.len() {
+        while dollar_count % 2 == 1 && k + 1 < blocks.len() {
             k += 1;
             let next = &blocks[k];
             cur.content = format!("{}\n{}", cur.content, next.content);
@@ -408,66 +468,137 @@ fn parse_markdown_blocks(markdown: &str) -> Vec<MarkdownBlock> {
         fixed.push(cur);
         k += 1;
     }
-    let blocks = fixed;
 
-    blocks
+    // ---------- 第六步：生成稳定的块 id ----------
+    assign_block_ids(&mut fixed);
+    metrics::record_duration("parse", started.elapsed());
+    fixed
 }
 
-/// 格式化 Markdown 文本：
-/// 步骤：
-///  1. 统一换行符
-///  2. 将单行 $$公式$$ 展开为独立的块级公式（多行格式）
-///  3. 确保 $$ 行前后各有一个空行
-///  4. 压缩连续空行（>=3 个换行→2 个）
-///  5. trim
+/// 块 id 由内容哈希与同内容块的出现序号组成，与行号无关：
+/// 在别处插入或删除行时其余块的 id 不变，前端据此保持滚动同步与按块缓存
+fn assign_block_ids(blocks: &mut [MarkdownBlock]) {
+    let mut occurrences: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for block in blocks {
+        let hash = hashing::sha256_hex(block.content.as_bytes())[..16].to_string();
+        let occurrence = occurrences.entry(hash.clone()).or_insert(0);
+        block.id = format!("b{}-{}", hash, occurrence);
+        *occurrence += 1;
+    }
+}
+
+/// 行号（从 1 开始）所在的块；空行归入上方最近的块，文档开头的空行归入第一个块
 #[tauri::command]
-fn format_markdown(markdown: &str) -> String {
-    use regex::Regex;
+fn map_line_to_block(markdown: &str, line: usize, mode: Option<ParserMode>) -> Result<Option<MarkdownBlock>, AppError> {
+    let mode = ParserMode::resolve(mode, markdown);
+    error::catch_panic("map_line_to_block", || {
+        let blocks = split_markdown_blocks(markdown, mode);
+        let index = blocks.partition_point(|block| block.start_line <= line);
+        Ok(blocks.get(index.saturating_sub(1)).cloned())
+    })
+}
 
-    let mut content = markdown.replace("\r\n", "\n");
+/// 格式化 Markdown 文本（公式块、标题、有序列表、强调标记、折行、表格对齐），
+/// 未提供 `options` 时使用默认规则；排版规范化未指定时按设置与 front matter
+#[tauri::command]
+fn format_markdown(
+    markdown: &str,
+    options: Option<formatter::FormatOptions>,
+    settings: tauri::State<'_, settings::SettingsState>,
+) -> Result<String, AppError> {
+    let mut options = options.unwrap_or_default();
+    options.typography.get_or_insert_with(|| typography::resolve(&settings.snapshot(), markdown));
+    error::catch_panic("format_markdown", || Ok(formatter::format_with_options(markdown, &options)))
+}
 
-    // 步骤 2：将单行 $$公式$$ 展开为多行块级公式
-    let re_inline_block = Regex::new(r"\$\$([^\$\n]+?)\$\$").unwrap();
-    content = re_inline_block.replace_all(&content, "\n\n$$\n$1\n$$\n\n").to_string();
+/// 只格式化选中的行（自动扩展到完整的块），返回文本编辑列表，
+/// 编辑器据此做最小修改以保留光标位置与撤销历史
+#[tauri::command]
+fn format_markdown_range(
+    markdown: &str,
+    start_line: usize,
+    end_line: usize,
+    options: Option<formatter::FormatOptions>,
+    settings: tauri::State<'_, settings::SettingsState>,
+) -> Result<Vec<formatter::TextEdit>, AppError> {
+    let mut options = options.unwrap_or_default();
+    options.typography.get_or_insert_with(|| typography::resolve(&settings.snapshot(), markdown));
+    error::catch_panic("format_markdown_range", || {
+        Ok(formatter::format_range(markdown, start_line, end_line, &options))
+    })
+}
 
-    // 步骤 3：确保 $$ 行前后各有一个空行
-    let mut lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
-    let mut in_formula = false;
-    let mut i = 0;
-    while i < lines.len() {
-        if lines[i].trim() == "$$" {
-            if !in_formula {
-                // 公式开始：确保前面有空行
-                if i > 0 && !lines[i - 1].trim().is_empty() {
-                    lines.insert(i, String::new());
-                    i += 1; // 跳过刚插入的空行，仍处理当前 $$
-                }
-                in_formula = true;
-            } else {
-                // 公式结束：确保后面有空行
-                if i + 1 < lines.len() && !lines[i + 1].trim().is_empty() {
-                    lines.insert(i + 1, String::new());
-                }
-                in_formula = false;
-            }
-        }
-        i += 1;
-    }
-    content = lines.join("\n");
+/// 将 Markdown 转换为 HTML（用于预览）
+#[tauri::command]
+fn markdown_to_html(
+    markdown: &str,
+    mode: Option<ParserMode>,
+    settings: tauri::State<'_, settings::SettingsState>,
+) -> Result<String, AppError> {
+    let mode = ParserMode::resolve(mode, markdown);
+    let settings = settings.snapshot();
+    let line_breaks = line_breaks::LineBreaks::resolve(settings.line_breaks, markdown);
+    let smart_quotes = smart_quotes::resolve(&settings, markdown);
+    let typography = typography::resolve(&settings, markdown);
+    let callout_styles = callouts::resolve(&settings, None, front_matter::parse(markdown).as_ref());
+    error::catch_panic("markdown_to_html", || {
+        Ok(render_preview_html(markdown, mode, line_breaks, smart_quotes, &typography, &callout_styles))
+    })
+}
 
-    // 步骤 4：压缩连续空行（3 个及以上换行→2 个）
-    let re_multi = Regex::new(r"\n{3,}").unwrap();
-    content = re_multi.replace_all(&content, "\n\n").to_string();
+/// 由后端渲染源文件的 HTML（无法由前端渲染时使用，如重新导出）
+fn render_source_html(
+    markdown: &str,
+    mode: ParserMode,
+    settings: &settings::AppSettings,
+    source_path: &std::path::Path,
+) -> String {
+    let line_breaks = line_breaks::LineBreaks::resolve(settings.line_breaks, markdown);
+    let smart_quotes = smart_quotes::resolve(settings, markdown);
+    let typography = typography::resolve(settings, markdown);
+    let callout_styles = callouts::resolve(settings, Some(source_path), front_matter::parse(markdown).as_ref());
+    render_preview_html(markdown, mode, line_breaks, smart_quotes, &typography, &callout_styles)
+}
 
-    // 步骤 5：trim
-    content.trim().to_string()
+/// 预览 HTML：Markdown 渲染之后再处理依赖设置的扩展语法（提示块）
+fn render_preview_html(
+    markdown: &str,
+    mode: ParserMode,
+    line_breaks: line_breaks::LineBreaks,
+    smart_quotes: Option<smart_quotes::QuoteStyle>,
+    typography: &typography::TypographySettings,
+    callout_styles: &std::collections::BTreeMap<String, callouts::CalloutStyle>,
+) -> String {
+    let html = render_markdown_html(markdown, mode, line_breaks, smart_quotes, typography);
+    match mode {
+        ParserMode::Extended => callouts::apply_callouts(&html, callout_styles),
+        ParserMode::Strict => html,
+    }
 }
 
-/// 将 Markdown 转换为 HTML（用于预览）
-#[tauri::command]
-fn markdown_to_html(markdown: &str) -> String {
+fn render_markdown_html(
+    markdown: &str,
+    mode: ParserMode,
+    line_breaks: line_breaks::LineBreaks,
+    smart_quotes: Option<smart_quotes::QuoteStyle>,
+    typography: &typography::TypographySettings,
+) -> String {
     use regex::Regex;
 
+    let started = Instant::now();
+
+    // 严格模式：不做任何预处理与扩展语法后处理，完全按 CommonMark 规范输出
+    if mode == ParserMode::Strict {
+        let content = parser_mode::strip_declared_front_matter(markdown);
+        let parser = Parser::new_ext(content, parser_mode::pulldown_options(mode));
+        let mut html_output = String::new();
+        html::push_html(&mut html_output, parser);
+        let html_output = slug::anchor_headings(&html_output);
+        let html_output = doc_links::rewrite_document_links(&html_output, &doc_links::LinkMode::Preview, false);
+        metrics::record_duration("render", started.elapsed());
+        return html_output;
+    }
+
     // 1. 统一换行符并清理每行末尾的空白
     let mut content = markdown.replace("\r\n", "\n");
     
@@ -494,34 +625,65 @@ fn markdown_to_html(markdown: &str) -> String {
     let re_empty_block = Regex::new(r"(?m)^\s+$\n").unwrap();
     content = re_empty_block.replace_all(&content, "").to_string();
 
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-    options.insert(Options::ENABLE_TABLES);
-    options.insert(Options::ENABLE_FOOTNOTES);
-    options.insert(Options::ENABLE_TASKLISTS);
-
-    let parser = Parser::new_ext(&content, options);
+    // 段落内换行按文档的换行方式处理（硬换行或忽略中文之间的换行），智能引号按语言转换，
+    // 最后做中日文排版规范化
+    let mut options = parser_mode::pulldown_options(mode);
+    if smart_quotes.is_some() {
+        options.insert(pulldown_cmark::Options::ENABLE_SMART_PUNCTUATION);
+    }
+    let events: Vec<_> = Parser::new_ext(&content, options).collect();
+    let events = line_breaks::apply_to_events(events, line_breaks);
+    let events = match smart_quotes {
+        Some(style) => smart_quotes::apply_to_events(events, style),
+        None => events,
+    };
+    let events = typography::apply_to_events(events, typography);
     let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
+    html::push_html(&mut html_output, events.into_iter());
     
     // 5. 清理生成的 HTML 中可能存在的空标签
     html_output = html_output
         .replace("<p></p>", "")
         .replace("<p>\n</p>", "");
-        
+
+    // 6. 扩展语法后处理
+    let html_output = postprocess_html(&html_output);
+
+    // 7. 标题锚点，文档间链接改写为打开文档的深链接
+    let html_output = slug::anchor_headings(&html_output);
+    let html_output = doc_links::rewrite_document_links(&html_output, &doc_links::LinkMode::Preview, true);
+    metrics::record_duration("render", started.elapsed());
     html_output
 }
 
+/// HTML 后处理：预览和导出共用的扩展语法（图片排版、注音、数据表格等）
+fn postprocess_html(html: &str) -> String {
+    let html = page_breaks::apply_heading_markers(html);
+    let html = figure::apply_figure_attributes(&html);
+    let html = figure::apply_image_grids(&html);
+    let html = ruby::apply_ruby(&html);
+    let html = badges::apply_badges(&html);
+    let html = kbd::apply_ui_markup(&html);
+    data_table::apply_data_tables(&html)
+}
+
 /// 生成完整的 HTML 页面（用于 PDF 导出）
-fn generate_full_html(html_content: &str, title: &str, katex_css_path: &str) -> String {
+fn generate_full_html(
+    html_content: &str,
+    title: &str,
+    math_head: &str,
+    readiness: &readiness::Readiness,
+    typography_css: &str,
+    lang: &str,
+) -> String {
     format!(
         r#"<!DOCTYPE html>
-<html lang="zh-CN">
+<html lang="{lang}">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
     <title>{title}</title>
-    <link rel="stylesheet" href="{katex_css_path}">
+    {math_head}
     <style>
         * {{
             margin: 0;
@@ -587,6 +749,95 @@ fn generate_full_html(html_content: &str, title: &str, katex_css_path: &str) ->
             padding: 0;
         }}
 
+        .code-block {{
+            margin: 1em 0;
+        }}
+
+        .code-block pre {{
+            margin: 0;
+        }}
+
+        .code-title {{
+            padding: 0.4em 1em;
+            background-color: #e8e8e8;
+            border-radius: 8px 8px 0 0;
+            font-family: 'Cascadia Code', 'Fira Code', Consolas, monospace;
+            font-size: 0.85em;
+            color: #57606a;
+        }}
+
+        .code-title + pre {{
+            border-top-left-radius: 0;
+            border-top-right-radius: 0;
+        }}
+
+        .code-block.wrap pre {{
+            overflow-x: visible;
+            white-space: pre-wrap;
+            overflow-wrap: anywhere;
+        }}
+
+        .code-line {{
+            display: block;
+        }}
+
+        .code-line.highlighted {{
+            margin: 0 -1em;
+            padding: 0 1em;
+            background-color: #fff8c5;
+            -webkit-print-color-adjust: exact;
+            print-color-adjust: exact;
+        }}
+
+        .line-numbers code {{
+            counter-reset: code-line;
+        }}
+
+        .line-numbers .code-line {{
+            position: relative;
+            padding-left: 3.5em;
+        }}
+
+        .line-numbers .code-line.highlighted {{
+            padding-left: 4.5em;
+        }}
+
+        .line-numbers .code-line::before {{
+            counter-increment: code-line;
+            content: counter(code-line);
+            position: absolute;
+            left: 0;
+            width: 2.5em;
+            text-align: right;
+            color: #8c959f;
+            user-select: none;
+        }}
+
+        .line-numbers .code-line.highlighted::before {{
+            left: 1em;
+        }}
+
+        pre:has(> code.language-output) {{
+            background-color: #fafafa;
+            border-left: 3px solid #8a8886;
+            border-radius: 0 8px 8px 0;
+            margin-top: -0.5em;
+        }}
+
+        pre > code.language-output {{
+            color: #444;
+        }}
+
+        mark.redact-internal {{
+            background: none;
+            outline: 1px dashed #c00000;
+        }}
+
+        span.redacted {{
+            color: #000;
+            letter-spacing: -0.1em;
+        }}
+
         blockquote {{
             border-left: 4px solid #0078d4;
             padding-left: 1em;
@@ -620,11 +871,446 @@ fn generate_full_html(html_content: &str, title: &str, katex_css_path: &str) ->
             font-weight: 600;
         }}
 
+        table.data-table caption {{
+            caption-side: top;
+            font-weight: 600;
+            padding-bottom: 0.5em;
+        }}
+
+        table.data-table td.num {{
+            text-align: right;
+            font-variant-numeric: tabular-nums;
+        }}
+
+        table.table-split + table.table-split {{
+            margin-top: 0.5em;
+        }}
+
+        table.table-split .table-key {{
+            background-color: #fafafa;
+        }}
+
+        table.table-split th.table-key {{
+            background-color: #f5f5f5;
+        }}
+
+        .table-landscape {{
+            page-break-before: always;
+            page-break-after: always;
+        }}
+
+        .table-landscape table {{
+            margin: 0;
+        }}
+
+        .data-table-error {{
+            color: #c42b1c;
+            border-left: 4px solid #c42b1c;
+            padding: 0.5em 1em;
+            margin: 1em 0;
+            background-color: #fdf3f2;
+        }}
+
         img {{
             max-width: 100%;
             height: auto;
         }}
 
+        img.figure-align-left {{
+            display: block;
+            margin: 1em auto 1em 0;
+        }}
+
+        img.figure-align-center {{
+            display: block;
+            margin: 1em auto;
+        }}
+
+        img.figure-align-right {{
+            display: block;
+            margin: 1em 0 1em auto;
+        }}
+
+        img.figure-float-left {{
+            float: left;
+            margin: 0.3em 1.2em 0.8em 0;
+        }}
+
+        img.figure-float-right {{
+            float: right;
+            margin: 0.3em 0 0.8em 1.2em;
+        }}
+
+        img.figure-page-full {{
+            display: block;
+            width: 100%;
+            max-height: 95vh;
+            object-fit: contain;
+            margin: 0 auto;
+            page-break-before: always;
+            page-break-after: always;
+        }}
+
+        .image-grid {{
+            display: grid;
+            gap: 0.8em;
+            align-items: center;
+            margin: 1em 0;
+            page-break-inside: avoid;
+        }}
+
+        .image-grid-cell img {{
+            display: block;
+            width: 100%;
+            margin: 0;
+            float: none;
+        }}
+
+        /* 封面 */
+        .cover-page {{
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+            justify-content: center;
+            min-height: 9in;
+            text-align: center;
+            page-break-after: always;
+            break-after: page;
+        }}
+
+        .cover-logo {{
+            max-width: 40%;
+            max-height: 2in;
+            margin-bottom: 2em;
+        }}
+
+        .cover-title {{
+            font-size: 2.4em;
+            font-weight: 700;
+            line-height: 1.3;
+        }}
+
+        .cover-subtitle {{
+            margin-top: 0.6em;
+            font-size: 1.4em;
+            color: #555;
+        }}
+
+        .cover-meta {{
+            margin-top: 4em;
+            font-size: 1.1em;
+            color: #333;
+        }}
+
+        .cover-meta > div {{
+            margin: 0.3em 0;
+        }}
+
+        .title-block {{
+            margin-bottom: 1.5em;
+            text-align: center;
+        }}
+
+        .title-block-title {{
+            font-size: 2em;
+            font-weight: 700;
+            line-height: 1.3;
+        }}
+
+        .title-block-author,
+        .title-block-date {{
+            margin-top: 0.4em;
+            color: #333;
+        }}
+
+        .abstract-block {{
+            margin: 1.5em 2em 2em;
+            font-size: 0.95em;
+        }}
+
+        .abstract-title {{
+            margin-bottom: 0.4em;
+            font-weight: 700;
+            text-align: center;
+        }}
+
+        .abstract-body p {{
+            margin: 0 0 0.6em;
+            text-indent: 2em;
+        }}
+
+        .abstract-keywords {{
+            margin-top: 0.6em;
+        }}
+
+        .abstract-keywords-label {{
+            font-weight: 700;
+        }}
+
+        .callout {{
+            margin: 1em 0;
+            padding: 0.6em 1em;
+            border-left: 4px solid var(--callout-color);
+            border-radius: 4px;
+            background: color-mix(in srgb, var(--callout-color) 8%, white);
+            break-inside: avoid;
+            -webkit-print-color-adjust: exact;
+            print-color-adjust: exact;
+        }}
+
+        .callout-title {{
+            display: flex;
+            align-items: center;
+            gap: 0.4em;
+            margin-bottom: 0.3em;
+            font-weight: 700;
+            color: var(--callout-color);
+        }}
+
+        .callout-icon img,
+        .callout-icon svg {{
+            width: 1.1em;
+            height: 1.1em;
+        }}
+
+        .callout-body > :last-child {{
+            margin-bottom: 0;
+        }}
+
+        kbd {{
+            display: inline-block;
+            padding: 0.05em 0.4em;
+            border: 1px solid #d0d7de;
+            border-bottom-width: 2px;
+            border-radius: 4px;
+            background: #f6f8fa;
+            font-family: 'Cascadia Code', 'Fira Code', Consolas, monospace;
+            font-size: 0.85em;
+            line-height: 1.4;
+            white-space: nowrap;
+        }}
+
+        .menu-path {{
+            font-weight: 600;
+            white-space: nowrap;
+        }}
+
+        .menu-sep {{
+            margin: 0 0.3em;
+            color: #6e7781;
+            font-weight: 400;
+        }}
+
+        .badge {{
+            display: inline-block;
+            padding: 0.05em 0.6em;
+            border-radius: 1em;
+            background: var(--badge-color);
+            color: #fff;
+            font-size: 0.8em;
+            font-weight: 600;
+            line-height: 1.5;
+            white-space: nowrap;
+            vertical-align: 0.1em;
+            -webkit-print-color-adjust: exact;
+            print-color-adjust: exact;
+        }}
+
+        .progress {{
+            display: inline-flex;
+            align-items: center;
+            gap: 0.5em;
+            vertical-align: middle;
+        }}
+
+        .progress-track {{
+            display: inline-block;
+            width: 8em;
+            height: 0.6em;
+            border-radius: 0.3em;
+            background: #e5e7eb;
+            overflow: hidden;
+            -webkit-print-color-adjust: exact;
+            print-color-adjust: exact;
+        }}
+
+        .progress-fill {{
+            display: block;
+            height: 100%;
+            background: var(--progress-color);
+        }}
+
+        .progress-label {{
+            font-size: 0.85em;
+            color: #57606a;
+        }}
+
+        .new-page {{
+            page-break-before: always;
+            break-before: page;
+        }}
+
+        .render-failure {{
+            margin: 1em 0;
+            padding: 0.6em 1em;
+            border: 1px dashed #cf222e;
+            border-radius: 6px;
+            background: #fff5f5;
+            page-break-inside: avoid;
+            break-inside: avoid;
+            -webkit-print-color-adjust: exact;
+            print-color-adjust: exact;
+        }}
+
+        .render-failure-title {{
+            color: #cf222e;
+            font-size: 0.9em;
+            font-weight: 600;
+        }}
+
+        .render-failure pre {{
+            margin: 0.5em 0 0;
+            background: transparent;
+            white-space: pre-wrap;
+        }}
+
+        .endnotes {{
+            margin-top: 2em;
+            font-size: 0.9em;
+        }}
+
+        .endnotes-chapter {{
+            padding-top: 0.6em;
+            border-top: 1px solid #d0d7de;
+        }}
+
+        .endnotes-chapter .endnotes-title {{
+            margin-bottom: 0.4em;
+            font-weight: 700;
+        }}
+
+        .endnotes li {{
+            margin-bottom: 0.3em;
+        }}
+
+        .endnotes li p {{
+            margin: 0;
+        }}
+
+        .diff-insert,
+        .diff-delete {{
+            margin: 0.5em 0;
+            padding: 0.1em 0 0.1em 0.8em;
+            border-left: 3px solid;
+        }}
+
+        .diff-insert {{
+            background: #e6ffec;
+            border-color: #2da44e;
+        }}
+
+        .diff-delete {{
+            background: #ffebe9;
+            border-color: #cf222e;
+            color: #57606a;
+            text-decoration: line-through;
+        }}
+
+        abbr[title] {{
+            text-decoration: none;
+        }}
+
+        .glossary {{
+            margin-top: 2em;
+        }}
+
+        .glossary dt {{
+            font-weight: 700;
+        }}
+
+        .glossary dd {{
+            margin: 0 0 0.5em 2em;
+        }}
+
+        .endnote-ref,
+        .endnote-backref {{
+            text-decoration: none;
+        }}
+
+        .page-notes-source {{
+            display: none;
+        }}
+
+        .page-notes {{
+            padding-top: 0.4em;
+            border-top: 1px solid #d0d7de;
+            font-size: 0.85em;
+        }}
+
+        .page-notes ol {{
+            margin: 0;
+        }}
+
+        .page-notes-empty {{
+            padding-top: 0;
+            border-top: none;
+        }}
+
+        .watermark {{
+            position: fixed;
+            z-index: -1;
+            display: flex;
+            pointer-events: none;
+            -webkit-print-color-adjust: exact;
+            print-color-adjust: exact;
+        }}
+
+        .watermark-center {{
+            top: 0;
+            right: 0;
+            bottom: 0;
+            left: 0;
+            align-items: center;
+            justify-content: center;
+        }}
+
+        .watermark-top-left {{
+            top: 5%;
+            left: 5%;
+        }}
+
+        .watermark-top-right {{
+            top: 5%;
+            right: 5%;
+        }}
+
+        .watermark-bottom-left {{
+            bottom: 5%;
+            left: 5%;
+        }}
+
+        .watermark-bottom-right {{
+            right: 5%;
+            bottom: 5%;
+        }}
+
+        .watermark-inner {{
+            display: flex;
+            flex-direction: column;
+            align-items: center;
+        }}
+
+        .watermark-text {{
+            font-weight: bold;
+            white-space: nowrap;
+            line-height: 1.2;
+        }}
+
+        .watermark-image {{
+            max-width: none;
+            height: auto;
+        }}
+
         a {{
             color: #0078d4;
             text-decoration: none;
@@ -646,6 +1332,29 @@ fn generate_full_html(html_content: &str, title: &str, katex_css_path: &str) ->
             font-size: 1.1em;
         }}
 
+        .equation {{
+            position: relative;
+            display: flex;
+            align-items: center;
+            page-break-inside: avoid;
+        }}
+
+        .equation > :first-child {{
+            flex: 1;
+            min-width: 0;
+        }}
+
+        .equation-number {{
+            flex: none;
+            padding-left: 1em;
+            white-space: nowrap;
+        }}
+
+        .equation-ref {{
+            color: inherit;
+            text-decoration: none;
+        }}
+
         @media print {{
             body {{
                 padding: 20px;
@@ -660,10 +1369,55 @@ fn generate_full_html(html_content: &str, title: &str, katex_css_path: &str) ->
             }}
         }}
     </style>
+    <style>
+        /* 字体与排版配置 */
+{typography_css}
+    </style>
     <script>
+        // 横向表格旋转后的最大宽度（px），即 A4 纵向页面去掉页边距、页眉页脚后的高度
+        const LANDSCAPE_LENGTH_PX = 900;
+        // 缩小表格的最小比例，再小就无法阅读
+        const MIN_TABLE_SCALE = 0.4;
+
+        // 按实际排版宽度缩小或旋转宽表格（见后端 table_fit 模块）
+        function fitWideTables() {{
+            for (const table of document.querySelectorAll('table.table-fit-shrink')) {{
+                const available = table.parentElement.clientWidth;
+                if (table.scrollWidth > available) {{
+                    table.style.zoom = Math.max(available / table.scrollWidth, MIN_TABLE_SCALE);
+                }}
+            }}
+            for (const wrapper of document.querySelectorAll('.table-landscape')) {{
+                const table = wrapper.querySelector('table');
+                if (!table) continue;
+                table.style.width = 'max-content';
+                if (table.offsetWidth > LANDSCAPE_LENGTH_PX) {{
+                    table.style.width = LANDSCAPE_LENGTH_PX + 'px';
+                }}
+                // 旋转后表格的高度成为宽度，不能超过纵向页面的宽度
+                const scale = Math.max(Math.min(
+                    1,
+                    LANDSCAPE_LENGTH_PX / table.scrollWidth,
+                    wrapper.clientWidth / table.offsetHeight
+                ), MIN_TABLE_SCALE);
+                const width = table.scrollWidth * scale;
+                table.style.transformOrigin = 'top left';
+                table.style.transform = `translate(0, ${{width}}px) rotate(-90deg) scale(${{scale}})`;
+                wrapper.style.height = width + 'px';
+            }}
+        }}
+
         // 当页面完全加载并渲染完成后，添加一个带有 ID 的哨兵元素
         // 这样后端 headless_chrome 就可以精准等待，而不用固定的 sleep
-        window.addEventListener('load', () => {{
+        // （页面内容通过 DevTools 直接写入，脚本执行时文档可能已经加载完成）
+        // 渲染完成的判定条件（见后端 readiness 模块）
+        const READINESS = {readiness_config};
+{readiness_script}
+{page_notes_script}
+        async function onPageLoaded() {{
+            await waitUntilReady().catch(() => {{}});
+            fitWideTables();
+            placePageNotes();
             // 使用 double requestAnimationFrame 确保至少进行了一次完整的布局和绘制
             requestAnimationFrame(() => {{
                 requestAnimationFrame(() => {{
@@ -673,7 +1427,13 @@ fn generate_full_html(html_content: &str, title: &str, katex_css_path: &str) ->
                     document.body.appendChild(sentinel);
                 }});
             }});
-        }});
+        }}
+
+        if (document.readyState === 'complete') {{
+            onPageLoaded();
+        }} else {{
+            window.addEventListener('load', onPageLoaded);
+        }}
     </script>
 </head>
 <body>
@@ -682,176 +1442,838 @@ fn generate_full_html(html_content: &str, title: &str, katex_css_path: &str) ->
     </div>
 </body>
 </html>"#,
-        katex_css_path = katex_css_path,
+        math_head = math_head,
+        readiness_config = readiness.script_config(),
+        readiness_script = readiness::READINESS_SCRIPT,
+        page_notes_script = endnotes::PAGE_NOTES_SCRIPT,
+        typography_css = typography_css,
         title = title,
         html_content = html_content
     )
 }
 
-/// 导出为 PDF
+/// 导出为 PDF，完成后返回（并通过 `export-report` 事件发送）导出报告
 #[tauri::command]
-async fn export_to_pdf(window: tauri::Window, html_content: String, output_path: String, title: String) -> Result<(), AppError> {
-    // 在后台线程中执行，避免阻塞
-    tokio::task::spawn_blocking(move || {
-        let emit_progress = |message: &str| {
-            let _ = window.emit("export-progress", ProgressPayload { message: message.to_string() });
-        };
-
-        // 获取 KaTeX CSS 路径 (本地或 CDN 回退)
-        let app_handle = window.app_handle();
-        let katex_css_res = app_handle.path().resource_dir()
-            .map(|p| p.join("public/katex/katex.min.css"));
-            
-        let katex_css_url = match katex_css_res {
-            Ok(p) if p.exists() => {
-                let path_str = p.to_string_lossy().replace("\\", "/");
-                if path_str.starts_with('/') {
-                    format!("file://{}", path_str)
-                } else {
-                    format!("file:///{}", path_str)
-                }
-            },
-            _ => "https://cdn.jsdelivr.net/npm/katex@0.16.9/dist/katex.min.css".to_string(),
-        };
+async fn export_to_pdf(
+    window: tauri::Window,
+    settings: tauri::State<'_, settings::SettingsState>,
+    html_content: String,
+    output_path: String,
+    title: String,
+    options: Option<ExportOptions>,
+) -> Result<report::ExportReport, AppError> {
+    run_export(&window, settings.snapshot(), html_content, output_path, title, options.unwrap_or_default()).await
+}
 
-        // 生成完整的 HTML 页面
-        let full_html = generate_full_html(&html_content, &title, &katex_css_url);
+/// 执行一次 PDF 导出；成功后记入导出历史
+async fn run_export(
+    window: &tauri::Window,
+    settings: settings::AppSettings,
+    html_content: String,
+    output_path: String,
+    title: String,
+    options: ExportOptions,
+) -> Result<report::ExportReport, AppError> {
+    let cancel = cancel::CancelToken::register(options.export_id.clone());
+    let job = Arc::new(ExportJob {
+        html_content,
+        output_path,
+        title,
+        front_matter: options.markdown.as_deref().and_then(front_matter::parse),
+        options,
+        settings,
+    });
 
-        // 确定输出路径
-        let output_path_buf = std::path::Path::new(&output_path);
-        let html_path = output_path_buf.with_extension("html");
+    let mut timer = diagnostics::StageTimer::new();
+    // 取消时浏览器被直接结束，进行中的调用以浏览器错误返回，统一报告为已取消
+    let result = export_pdf(window, job.clone(), &mut timer, &cancel)
+        .await
+        .map_err(|e| if cancel.is_cancelled() { AppError::Cancelled } else { e });
+    if let Err(e) = &result {
+        diagnostics::record_error("export_to_pdf", e);
+    }
+    let timings = timer.finish(&job.output_path, result.is_ok());
+    if result.is_ok() {
+        metrics::record_ms("export", timings.total_ms as f64);
+        for stage in &timings.stages {
+            metrics::record_ms(&format!("export.{}", stage.stage), stage.duration_ms as f64);
+        }
+    }
+    diagnostics::record_export(timings.clone());
 
-        // 立即保存 HTML 文件到 PDF 同级目录
-        fs::write(&html_path, &full_html).map_err(|e| AppError::FileReadError(e))?;
-        
-        let path_str = html_path.to_string_lossy().replace("\\", "/");
-        let data_url = if path_str.starts_with('/') {
-            format!("file://{}", path_str)
-        } else {
-            format!("file:///{}", path_str)
-        };
-
-        emit_progress("[1/5] 正在启动浏览器 (Headless Chrome)...");
-
-        // 配置浏览器启动选项
-        let launch_options = LaunchOptions::default_builder()
-            .headless(true)
-            .sandbox(false)
-            .idle_browser_timeout(std::time::Duration::from_secs(3600 * 24 * 365 * 100))
-            .args(vec![
-                std::ffi::OsStr::new("--no-sandbox"),
-                std::ffi::OsStr::new("--disable-setuid-sandbox"),
-                std::ffi::OsStr::new("--disable-dev-shm-usage"),
-                std::ffi::OsStr::new("--disable-extensions"),
-                std::ffi::OsStr::new("--disable-gpu"),
-                std::ffi::OsStr::new("--disable-background-timer-throttling"),
-                std::ffi::OsStr::new("--disable-renderer-backgrounding"),
-                std::ffi::OsStr::new("--disable-backgrounding-occluded-windows"),
-                std::ffi::OsStr::new("--disable-hang-monitor"),
-            ])
-            .build()
-            .map_err(|e| AppError::BrowserError(e.to_string()))?;
-
-        // 启动浏览器
-        let browser = Browser::new(launch_options)
-            .map_err(|e| AppError::BrowserError(e.to_string()))?;
-
-        emit_progress("[2/5] 正在创建新标签页...");
-
-        // 创建新标签页
-        let tab = browser
-            .new_tab()
-            .map_err(|e| AppError::BrowserError(e.to_string()))?;
-
-        emit_progress(&format!("[3/5] 正在加载页面..."));
-
-        // 导航到 HTML 页面
-        // 触发导航
-        tab.navigate_to(&data_url)
-            .map_err(|e| AppError::BrowserError(format!("导航触发失败: {}", e)))?;
-
-        // 移除严格的超时限制，允许等待极长时间（1小时），确保大文件有足够时间渲染
-        let nav_timeout = Duration::from_secs(3600);
-        
-        tab.set_default_timeout(nav_timeout);
-        tab.wait_until_navigated()
-            .map_err(|e| AppError::BrowserError(format!("等待导航完成失败: {}", e)))?;
-        
-        emit_progress("[4/5] 正在等待数学公式动态渲染完成...");
-
-        // 等待页面完全渲染完成（前端脚本会添加 #render-complete 元素作为信号）
-        tab.wait_for_element_with_custom_timeout("#render-complete", nav_timeout)
-            .map_err(|e| AppError::BrowserError(format!("等待渲染完成信号超时: {}", e)))?;
-
-        emit_progress("[5/5] 正在生成 PDF...");
-
-        // 生成 PDF
-        let make_pdf_options = || headless_chrome::types::PrintToPdfOptions {
-            landscape: Some(false),
-            display_header_footer: Some(false),
-            print_background: Some(true),
-            scale: Some(1.0),
-            paper_width: Some(8.27),
-            paper_height: Some(11.69),
-            margin_top: Some(0.4),
-            margin_bottom: Some(0.4),
-            margin_left: Some(0.4),
-            margin_right: Some(0.4),
-            prefer_css_page_size: Some(true),
-            ..Default::default()
-        };
-
-        let mut last_err: Option<anyhow::Error> = None;
-        let mut pdf_data: Option<Vec<u8>> = None;
-
-        for attempt in 0..3 {
-            match tab.print_to_pdf(Some(make_pdf_options())) {
-                Ok(data) => {
-                    pdf_data = Some(data);
-                    break;
-                }
-                Err(e) => {
-                    last_err = Some(e);
-                    // 如果依然失败，进行重试并给一点基础时间
-                    let extra_wait = Duration::from_secs((attempt as u64) * 2 + 3);
-                    std::thread::sleep(extra_wait);
-                }
+    let export_report = report::ExportReport::new(timings, result?);
+    if let Err(e) = history::record(window.app_handle(), &job, &export_report) {
+        tracing::warn!(error = %e, "记录导出历史失败");
+    }
+    let _ = window.emit("export-report", export_report.clone());
+    Ok(export_report)
+}
+
+/// 导出选项（均可省略）。导出历史中保存除源文档内容、密码与任务 ID 之外的全部选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ExportOptions {
+    /// 解析模式，严格模式下不处理扩展语法
+    mode: ParserMode,
+    /// 内部版或涂黑版
+    profile: redaction::ExportProfile,
+    /// 源文档 Markdown，用于读取 front matter
+    #[serde(skip_serializing)]
+    markdown: Option<String>,
+    /// 源文档路径，用于解析 wiki 链接与指向其他 `.md` 文件的相对链接（未保存的文档为空）
+    source_path: Option<String>,
+    /// 每页正文下层的文字或图片水印
+    watermark: Option<watermark::Watermark>,
+    /// 缩小分辨率过高的图片以减小 PDF 体积，未指定时不优化
+    optimize: Option<pdf_optimize::ImageOptimization>,
+    /// 打开密码、权限密码与打印 / 复制等权限，生成 PDF 后加密
+    #[serde(skip_serializing)]
+    protection: Option<pdf_protect::Protection>,
+    /// 横排或竖排，未指定时按 front matter 中的 `writing_mode`
+    writing_mode: Option<vertical::WritingMode>,
+    /// 脚注或尾注（按章或文末），未指定时按 front matter 中的 `endnotes`
+    notes: Option<endnotes::NotePlacement>,
+    /// 单栏或两栏，未指定时按 front matter 中的 `layout` 与设置
+    layout: Option<layout::PageLayout>,
+    /// A4 或小册子，未指定时按 front matter 中的 `paper` 与设置
+    paper: Option<layout::PaperSize>,
+    /// 用户确认过、允许访问的外部来源（外部资源策略为 `ask` 时使用）
+    allowed_origins: Option<Vec<String>>,
+    /// 离线导出：拒绝所有外部请求
+    offline: bool,
+    /// 导出任务 ID，用于 `cancel_export`
+    #[serde(skip_serializing)]
+    export_id: Option<String>,
+    /// 页面何时算作渲染完成
+    readiness: readiness::Readiness,
+    /// 尽力导出：渲染失败的块替换为错误占位框，不中止导出
+    best_effort: bool,
+    /// 普通、带结构标签或 PDF/A，未指定时按 front matter 中的 `pdf_standard` 与设置
+    pdf_standard: Option<pdf_archive::PdfStandard>,
+    /// 旧版本文件：导出标出新增与删除内容的修订版（见 diff 模块）
+    compare_with: Option<String>,
+}
+
+/// 一次导出的参数
+struct ExportJob {
+    html_content: String,
+    output_path: String,
+    title: String,
+    options: ExportOptions,
+    /// 源文档的 front matter
+    front_matter: Option<serde_yaml::Value>,
+    /// 导出开始时的设置快照
+    settings: settings::AppSettings,
+}
+
+/// 生成好的导出页面
+struct PreparedPage {
+    full_html: String,
+    decorations: decorations::PageDecorations,
+    /// 生成 HTML 时发现的问题（如涂黑内容泄露）
+    warnings: Vec<report::ExportWarning>,
+    /// 无头浏览器可以访问的外部来源
+    network: content_security::NetworkAccess,
+    /// 尽力导出时被替换为占位框的块
+    failures: Vec<render_failures::RenderFailure>,
+}
+
+/// 已在浏览器中加载并渲染完成的导出页面，可以多次打印
+struct LoadedPage {
+    /// 持有浏览器进程，页面打印完之前不能关闭
+    _browser: browser::ManagedBrowser,
+    /// 登记在本地资源服务器上的页面，打印完之前保持可访问
+    _page: Option<asset_server::ServedPage>,
+    tab: Arc<headless_chrome::Tab>,
+    decorations: decorations::PageDecorations,
+    stats: report::PageStats,
+    timeouts: timeouts::StageTimeouts,
+    /// 打印时生成结构标签
+    tagged: bool,
+}
+
+/// 轮询渲染完成信号的间隔
+const RENDER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 本地路径转换为 `file://` 地址
+fn file_url(path: &std::path::Path) -> String {
+    let path_str = path.to_string_lossy().replace("\\", "/");
+    if path_str.starts_with('/') {
+        format!("file://{}", path_str)
+    } else {
+        format!("file:///{}", path_str)
+    }
+}
+
+/// 发送导出进度事件
+fn emit_progress(window: &tauri::Window, message: &str) {
+    tracing::info!("{}", message);
+    let _ = window.emit("export-progress", ProgressPayload { message: message.to_string() });
+}
+
+/// 文档使用的 PDF 标准；PDF/A 不允许加密，与加密选项同时使用时报错
+fn resolve_pdf_standard(job: &ExportJob) -> Result<pdf_archive::PdfStandard, AppError> {
+    let standard =
+        pdf_archive::PdfStandard::resolve(job.options.pdf_standard, job.settings.pdf_standard, job.front_matter.as_ref());
+    if standard == pdf_archive::PdfStandard::PdfA && job.options.protection.as_ref().is_some_and(|p| !p.is_noop()) {
+        return Err(AppError::ArchiveProtected);
+    }
+    Ok(standard)
+}
+
+/// 打印之后、写入文件之前的处理（导出与批量导出共用）：按标准转换为 PDF/A，再按选项加密
+async fn finish_pdf(
+    window: &tauri::Window,
+    job: &ExportJob,
+    standard: pdf_archive::PdfStandard,
+    pdf_data: Vec<u8>,
+    timer: &mut diagnostics::StageTimer,
+) -> Result<Vec<u8>, AppError> {
+    let pdf_data = match standard {
+        pdf_archive::PdfStandard::PdfA => {
+            emit_progress(window, "正在转换为 PDF/A...");
+            timer.start("archive_pdf");
+            let metadata = pdf_archive::ArchiveMetadata {
+                title: job
+                    .front_matter
+                    .as_ref()
+                    .and_then(|fm| fm.get("title"))
+                    .and_then(cover::text_of)
+                    .unwrap_or_else(|| job.title.clone()),
+                lang: pdf_archive::document_language(job.front_matter.as_ref(), &job.html_content),
+            };
+            error::run_blocking("archive_pdf", move || pdf_archive::archive(&pdf_data, &metadata)).await?
+        }
+        _ => pdf_data,
+    };
+    match job.options.protection.clone() {
+        Some(protection) => {
+            emit_progress(window, "正在加密 PDF...");
+            timer.start("protect_pdf");
+            error::run_blocking("protect_pdf", move || pdf_protect::protect(&pdf_data, &protection)).await
+        }
+        None => Ok(pdf_data),
+    }
+}
+
+/// 导出流程，各阶段耗时记录到 `timer`
+async fn export_pdf(
+    window: &tauri::Window,
+    job: Arc<ExportJob>,
+    timer: &mut diagnostics::StageTimer,
+    cancel: &cancel::CancelToken,
+) -> Result<report::RenderedPdf, AppError> {
+    let standard = resolve_pdf_standard(&job)?;
+    let page = load_export_page(window, job.clone(), timer, cancel).await?;
+
+    emit_progress(window, "[5/5] 正在生成 PDF...");
+    timer.start("print_pdf");
+    let mut pdf_data = print_page_pdf(&page, &page.decorations, cancel).await?;
+
+    // 缩小分辨率过高的图片后重新打印，结果更大时保留原始 PDF
+    let mut optimization = None;
+    if let Some(options) = &job.options.optimize {
+        emit_progress(window, "正在优化图片...");
+        timer.start("optimize_pdf");
+        let images_optimized = pdf_optimize::optimize_images(&page, options, cancel).await?;
+        let size_before = pdf_data.len() as u64;
+        if images_optimized > 0 {
+            let optimized = print_page_pdf(&page, &page.decorations, cancel).await?;
+            if optimized.len() < pdf_data.len() {
+                pdf_data = optimized;
             }
         }
+        optimization = Some(pdf_optimize::OptimizationReport {
+            size_before,
+            size_after: pdf_data.len() as u64,
+            images_optimized,
+        });
+    }
 
-        let pdf_data = pdf_data.ok_or_else(|| {
-            AppError::PdfError(format!(
-                "PDF 生成失败 (已保存 HTML 备份至 {:?}): {}",
-                html_path.file_name().unwrap_or_default(),
-                last_err
-                    .map(|e| e.to_string())
-                    .unwrap_or_else(|| "未知错误".to_string())
-            ))
-        })?;
+    // 页数按加密前的内容统计
+    let page_count = report::count_pdf_pages(&pdf_data);
+    let pdf_data = finish_pdf(window, &job, standard, pdf_data, timer).await?;
 
-        // 写入文件
-        fs::write(output_path_buf, pdf_data).map_err(|e| AppError::FileReadError(e))?;
+    // 写入文件
+    timer.start("write_pdf");
+    let file_size = pdf_data.len() as u64;
+    error::run_blocking("write_pdf", move || {
+        let output_path_buf = std::path::Path::new(&job.output_path);
+        fs::write(output_path_buf, pdf_data).map_err(|e| AppError::file(output_path_buf, e))
+    })
+    .await?;
 
-        // Clean up temp HTML
-        let _ = fs::remove_file(&html_path);
+    Ok(report::RenderedPdf { stats: page.stats, page_count, file_size, optimization })
+}
 
-        Ok(())
-    }).await.map_err(|e| AppError::PdfError(e.to_string()))?
+/// 生成完整的导出页面 HTML 与页眉页脚
+fn prepare_page(job: &ExportJob, math_assets: &math_engine::LocalAssets) -> Result<PreparedPage, AppError> {
+    // 尽力导出：渲染失败的块替换为错误占位框
+    let (html_content, failures) = if job.options.best_effort {
+        render_failures::replace_failures(&job.html_content, job.options.markdown.as_deref().unwrap_or(""))
+    } else {
+        (job.html_content.clone(), Vec::new())
+    };
+
+    // 处理扩展语法（严格模式下不处理）
+    let html_content = match job.options.mode {
+        ParserMode::Extended => postprocess_html(&html_content),
+        ParserMode::Strict => html_content,
+    };
+
+    // 涂黑标记（任何模式下都处理，涂黑版中原文不会进入 PDF）；先于标题锚点等由文字生成属性的步骤
+    let redacted = redaction::apply_redactions(&html_content, job.options.profile)?;
+    let html_content = redacted.html;
+
+    // 缩写（定义行与 front matter 中的 `abbreviations`）转换为 `<abbr>`
+    let abbreviations = match job.options.mode {
+        ParserMode::Extended => glossary::collect(job.options.markdown.as_deref().unwrap_or("")),
+        ParserMode::Strict => Vec::new(),
+    };
+    let html_content = glossary::apply_abbreviations(&html_content, &abbreviations);
+
+    // 代码块：行号、自动折行、高亮行与标题栏
+    let code_block_settings = code_blocks::CodeBlockSettings::resolve(&job.settings.code_blocks, job.front_matter.as_ref());
+    let html_content = code_blocks::apply_code_blocks(&html_content, &code_block_settings);
+    // 配色主题由本地资源服务器提供，服务器未启动时内嵌到页面中
+    let code_theme_head = code_block_settings
+        .theme
+        .as_deref()
+        .and_then(|theme| match asset_server::get().and_then(|server| server.highlight_url(theme)) {
+            Some(url) => Some(format!(r#"<link rel="stylesheet" href="{}">"#, url)),
+            None => highlight::theme_css(theme).map(|css| format!("<style>{}</style>", css)),
+        })
+        .unwrap_or_default();
+
+    // 提示块（`> [!NOTE]` 等），样式来自设置、项目配置与 front matter
+    let html_content = match job.options.mode {
+        ParserMode::Extended => {
+            let styles = callouts::resolve(
+                &job.settings,
+                job.options.source_path.as_deref().map(std::path::Path::new),
+                job.front_matter.as_ref(),
+            );
+            callouts::apply_callouts(&html_content, &styles)
+        }
+        ParserMode::Strict => html_content,
+    };
+
+    // 标题锚点：页内链接 `#...` 在 PDF 中成为可点击的跳转
+    let html_content = slug::anchor_headings(&html_content);
+
+    // 文档间链接：导出内容中的文档改写为页内锚点，其他文档改写为绝对链接（wiki 链接属于扩展语法）
+    let source_path = job.options.source_path.as_deref().map(std::path::PathBuf::from);
+    let included: Vec<std::path::PathBuf> = source_path.iter().cloned().collect();
+    let link_mode = doc_links::LinkMode::Export {
+        base_dir: source_path.as_deref().and_then(std::path::Path::parent),
+        included: &included,
+    };
+    let html_content = doc_links::rewrite_document_links(
+        &html_content,
+        &link_mode,
+        job.options.mode == ParserMode::Extended,
+    );
+
+    // 公式编号与 `\eqref` 引用（在公式引擎处理之前分配，保持引擎无关）
+    let html_content = match job.options.mode {
+        ParserMode::Extended => {
+            let numbering = equations::EquationNumbering::resolve(
+                job.settings.equation_numbering,
+                job.front_matter.as_ref(),
+            );
+            equations::number_equations(&html_content, numbering)
+        }
+        ParserMode::Strict => html_content,
+    };
+
+    // MathJax 在导出页面中排版，公式元素改写为它的定界符
+    let html_content = match job.settings.math_engine {
+        math_engine::MathEngine::Mathjax => math_engine::to_mathjax_delimiters(&html_content),
+        _ => html_content,
+    };
+
+    // 尾注：脚注移到每章末尾或文档末尾
+    let notes = endnotes::NotePlacement::resolve(job.options.notes, job.front_matter.as_ref());
+    let html_content = endnotes::apply_endnotes(&html_content, notes, job.front_matter.as_ref());
+
+    // 术语表（front matter 中的 `glossary`），位于文末注释之后
+    let html_content = glossary::append_glossary(&html_content, job.front_matter.as_ref(), &abbreviations);
+
+    // 宽表格：缩小、横向或按列拆分（`{fit=...}` 标记属于扩展语法）
+    let html_content = table_fit::apply_table_fit(
+        &html_content,
+        job.settings.table_fit,
+        job.options.mode == ParserMode::Extended,
+    );
+
+    // 摘要与关键词（front matter 中的 `abstract`、`keywords`），位于标题之下、正文之前
+    let has_cover = job.front_matter.as_ref().is_some_and(cover::cover_enabled);
+    let mut html_content = abstract_block::insert_abstract(&html_content, job.front_matter.as_ref(), has_cover);
+
+    // 组织策略：强制的页脚声明、分级横幅与封面免责声明
+    let mut decorations = decorations::PageDecorations::default();
+    if let Some(policy) = policy::active_policy(&job.settings)? {
+        policy.apply_decorations(&mut decorations);
+        if let Some(cover) = policy.cover_html() {
+            html_content = format!("{}\n{}", cover, html_content);
+        }
+    }
+
+    // 文档封面（front matter 中的 `cover`），位于策略免责声明之前
+    if let Some(cover) = job.front_matter.as_ref().and_then(|fm| cover::cover_html(fm, &job.title)) {
+        html_content = format!("{}\n{}", cover, html_content);
+    }
+
+    // 文档自身的密级横幅（front matter 中的 `classification`），与策略横幅叠加
+    if let Some(banner) = job.front_matter.as_ref().and_then(decorations::classification_banner) {
+        decorations.add_banner(banner);
+    }
+
+    // 水印
+    if let Some(mark) = job.options.watermark.as_ref().and_then(watermark::Watermark::to_html) {
+        html_content = format!("{}\n{}", mark, html_content);
+    }
+
+    // 字体：设置中的默认值，front matter 中的 `fonts` 覆盖
+    let fonts = fonts::FontSettings::resolve(&job.settings.fonts, job.front_matter.as_ref());
+
+    // 标题另起一页
+    let new_page_levels = page_breaks::resolve_levels(&job.settings.new_page_levels, job.front_matter.as_ref());
+    let html_content = page_breaks::apply_level_breaks(&html_content, &new_page_levels);
+
+    // 中日文标点避头尾
+    let line_break = kinsoku::LineBreakRule::resolve(job.settings.cjk_line_break, job.front_matter.as_ref());
+    let html_content = kinsoku::apply_kinsoku(&html_content, line_break);
+
+    // 竖排
+    let writing_mode = vertical::WritingMode::resolve(job.options.writing_mode, job.front_matter.as_ref());
+    let html_content = vertical::apply_tate_chu_yoko(&html_content, writing_mode);
+
+    // 分栏与纸张
+    let page_layout = layout::PageLayout::resolve(job.options.layout, job.settings.page_layout, job.front_matter.as_ref());
+    let paper = layout::PaperSize::resolve(job.options.paper, job.settings.paper_size, job.front_matter.as_ref());
+    let typography_css = format!(
+        "{}{}{}{}{}",
+        fonts::font_css(&fonts),
+        kinsoku::css(line_break),
+        vertical::css(writing_mode),
+        layout::css(page_layout, paper, writing_mode),
+        layout::page_metrics_css(paper, decorations.margin_top(), decorations.margin_bottom())
+    );
+
+    // 生成完整的 HTML 页面
+    let full_html = generate_full_html(
+        &html_content,
+        &job.title,
+        &format!("{}{}", math_engine::head_html(job.settings.math_engine, math_assets), code_theme_head),
+        &job.options.readiness,
+        &typography_css,
+        &pdf_archive::document_language(job.front_matter.as_ref(), &job.html_content),
+    );
+
+    // 外部资源：按设置允许、要求确认或离线
+    let network = content_security::resolve_access(
+        &full_html,
+        job.settings.external_resources,
+        job.options.allowed_origins.as_deref(),
+        job.options.offline,
+    )?;
+
+    Ok(PreparedPage { full_html, decorations, warnings: redacted.warnings, network, failures })
+}
+
+/// 导出页面的基准目录：源文档所在目录，未保存的文档为 PDF 所在目录
+fn page_base_dir(job: &ExportJob) -> std::path::PathBuf {
+    let dir = job
+        .options
+        .source_path
+        .as_deref()
+        .and_then(|path| std::path::Path::new(path).parent())
+        .or_else(|| std::path::Path::new(&job.output_path).parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
+
+/// 预览面板中正在显示的导出页面，生成新页面时注销旧页面
+static PREVIEW_PAGE: std::sync::Mutex<Option<asset_server::ServedPage>> = std::sync::Mutex::new(None);
+
+/// 生成导出页面并登记到本地资源服务器，返回地址：预览面板与无头浏览器加载同一种页面。
+/// 未保存的文档没有基准目录，服务器未启动时都返回空
+#[tauri::command]
+async fn preview_export_page(
+    settings: tauri::State<'_, settings::SettingsState>,
+    html_content: String,
+    title: String,
+    options: Option<ExportOptions>,
+) -> Result<Option<String>, AppError> {
+    let Some(server) = asset_server::get() else { return Ok(None) };
+    let options = options.unwrap_or_default();
+    let Some(source_path) = options.source_path.clone() else { return Ok(None) };
+    let job = ExportJob {
+        html_content,
+        output_path: source_path,
+        title,
+        front_matter: options.markdown.as_deref().and_then(front_matter::parse),
+        options,
+        settings: settings.snapshot(),
+    };
+    let base_dir = page_base_dir(&job);
+    let math_assets = math_engine::LocalAssets {
+        katex_css: server.katex_url("katex.min.css"),
+        mathjax_script: server.mathjax_url(math_engine::MATHJAX_SCRIPT),
+    };
+    let prepared = error::run_blocking("prepare_html", move || prepare_page(&job, &math_assets)).await?;
+    let page = server.serve_page(prepared.full_html, &base_dir);
+    let url = page.url.clone();
+    *PREVIEW_PAGE.lock().unwrap_or_else(|e| e.into_inner()) = Some(page);
+    Ok(Some(url))
+}
+
+/// 生成 HTML、启动浏览器并加载页面，等待渲染完成。
+/// 每次 DevTools 调用在阻塞线程池中单独执行，阶段之间与等待期间不占用线程，并检查是否已取消。
+async fn load_export_page(
+    window: &tauri::Window,
+    job: Arc<ExportJob>,
+    timer: &mut diagnostics::StageTimer,
+    cancel: &cancel::CancelToken,
+) -> Result<LoadedPage, AppError> {
+    let emit_progress = |message: &str| emit_progress(window, message);
+
+    timer.start("prepare_html");
+
+    // 页面的基准目录：源文档所在目录（未保存的文档为 PDF 所在目录），文档中的相对路径按此解析
+    let base_dir = page_base_dir(&job);
+
+    // 本地 KaTeX CSS 与 MathJax 脚本（不存在时使用 CDN）：优先由本地资源服务器提供，否则使用 file:// 路径
+    let bundled = |relative: &str| {
+        window.app_handle().path().resource_dir()
+            .map(|p| p.join("public").join(relative))
+            .ok()
+            .filter(|p| p.exists())
+            .map(|p| file_url(&p))
+    };
+    let server = asset_server::get();
+    let math_assets = match server {
+        Some(server) => math_engine::LocalAssets {
+            katex_css: server.katex_url("katex.min.css"),
+            mathjax_script: server.mathjax_url(math_engine::MATHJAX_SCRIPT),
+        },
+        None => math_engine::LocalAssets {
+            katex_css: bundled("katex/katex.min.css"),
+            mathjax_script: bundled(&format!("mathjax/{}", math_engine::MATHJAX_SCRIPT)),
+        },
+    };
+
+    let max_wait = job.options.readiness.max_wait();
+    let stage_timeouts = job.settings.timeouts.clone();
+    let tagged =
+        pdf_archive::PdfStandard::resolve(job.options.pdf_standard, job.settings.pdf_standard, job.front_matter.as_ref())
+            .tagged();
+    let prepared = error::run_blocking("prepare_html", move || prepare_page(&job, &math_assets)).await?;
+    cancel.check()?;
+
+    // 页面登记到本地资源服务器，无头浏览器与预览加载同一套地址
+    let served_page = server.map(|server| server.serve_page(prepared.full_html.clone(), &base_dir));
+
+    emit_progress("[1/5] 正在启动浏览器 (Headless Chrome)...");
+    timer.start("launch_browser");
+
+    // 启动浏览器
+    let browser = {
+        let budgets = stage_timeouts.clone();
+        let launch = error::run_blocking("launch_browser", move || browser::launch_headless_browser(&budgets));
+        timeouts::limit(&stage_timeouts, "navigation", launch).await?
+    };
+    browser::attach_export(&browser, cancel.id());
+    cancel.check()?;
+
+    emit_progress("[2/5] 正在创建新标签页...");
+    timer.start("new_tab");
+
+    // 创建新标签页
+    let tab = {
+        let browser = browser.clone();
+        let new_tab = error::run_blocking("new_tab", move || {
+            browser.new_tab().map_err(|e| AppError::browser("BROWSER_NEW_TAB", e))
+        });
+        timeouts::limit(&stage_timeouts, "navigation", new_tab).await?
+    };
+    tab.set_default_timeout(stage_timeouts.budget("navigation"));
+
+    // 限制外部请求：只允许确认过的来源，离线时全部拒绝
+    let blocked_requests = match prepared.network {
+        content_security::NetworkAccess::Unrestricted => None,
+        content_security::NetworkAccess::Restricted(allowed) => {
+            let tab = tab.clone();
+            Some(error::run_blocking("install_guard", move || content_security::install_guard(&tab, allowed)).await?)
+        }
+    };
+    cancel.check()?;
+
+    emit_progress("[3/5] 正在加载页面...");
+    timer.start("navigate");
+
+    // 有本地资源服务器时直接导航到登记的页面；否则先导航到输出目录，使页面处于 file:// 源下
+    // （才能加载本地图片与 KaTeX 样式），再把生成的 HTML 直接写入该页面。都不在磁盘上生成临时文件
+    {
+        let tab = tab.clone();
+        let page_url = served_page.as_ref().map(|page| page.url.clone());
+        let base_url = format!("{}/", file_url(&base_dir).trim_end_matches('/'));
+        let navigate = error::run_blocking("navigate", move || {
+            tab.navigate_to(page_url.as_deref().unwrap_or(&base_url))
+                .map_err(|e| AppError::browser("BROWSER_NAVIGATE", e))?;
+            tab.wait_until_navigated()
+                .map_err(|e| AppError::browser("BROWSER_NAVIGATE_WAIT", e))?;
+            if page_url.is_none() {
+                tab.call_method(headless_chrome::protocol::cdp::Page::SetDocumentContent {
+                    frame_id: tab.get_target_id().clone(),
+                    html: prepared.full_html,
+                })
+                .map_err(|e| AppError::browser("BROWSER_WRITE_PAGE", e))?;
+            }
+            Ok(())
+        });
+        timeouts::limit(&stage_timeouts, "navigation", navigate).await?;
+    }
+
+    emit_progress("[4/5] 正在等待数学公式动态渲染完成...");
+    timer.start("render");
+
+    // 等待页面完全渲染完成（前端脚本在满足就绪条件后添加 #render-complete 元素作为信号）
+    let ready = wait_for_render_complete(&tab, cancel, max_wait, &stage_timeouts).await?;
+
+    // 统计图片、公式数量，检查缺失的图片与无效的页内链接
+    let mut stats = {
+        let tab = tab.clone();
+        error::run_blocking("page_stats", move || Ok(report::collect_page_stats(&tab))).await?
+    };
+    stats.warnings.extend(prepared.warnings);
+    stats.failures = prepared.failures;
+    if let Some(blocked) = blocked_requests {
+        let blocked = blocked.lock().unwrap_or_else(|e| e.into_inner());
+        stats.warnings.extend(blocked.iter().map(|url| report::ExportWarning {
+            kind: "blocked_request".to_string(),
+            detail: format!("已拒绝访问外部地址: {}", url),
+        }));
+    }
+    if !ready {
+        stats.warnings.push(report::ExportWarning {
+            kind: "readiness_timeout".to_string(),
+            detail: format!("页面在 {} 毫秒内未满足就绪条件，已直接打印", max_wait.unwrap_or_default().as_millis()),
+        });
+    }
+    for warning in &stats.warnings {
+        tracing::warn!(kind = %warning.kind, detail = %warning.detail, "导出警告");
+    }
+
+    Ok(LoadedPage {
+        _browser: browser,
+        _page: served_page,
+        tab,
+        decorations: prepared.decorations,
+        stats,
+        timeouts: stage_timeouts,
+        tagged,
+    })
+}
+
+/// 页面当前的阶段：`done`（已添加渲染完成信号）、`assets` 或 `math`（见 readiness 模块）
+const RENDER_STAGE_SCRIPT: &str =
+    "document.getElementById('render-complete') ? 'done' : (document.documentElement.dataset.exportStage || 'assets')";
+
+/// 轮询渲染完成信号，直到出现、被取消或当前阶段超出预算；超过 `max_wait` 时不再等待，返回 `false`
+async fn wait_for_render_complete(
+    tab: &Arc<headless_chrome::Tab>,
+    cancel: &cancel::CancelToken,
+    max_wait: Option<Duration>,
+    stage_timeouts: &timeouts::StageTimeouts,
+) -> Result<bool, AppError> {
+    let started = Instant::now();
+    let mut stage = "assets".to_string();
+    let mut stage_started = Instant::now();
+    loop {
+        let tab = tab.clone();
+        let poll = error::run_blocking("render", move || {
+            tab.evaluate(RENDER_STAGE_SCRIPT, false)
+                .map(|result| result.value.and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default())
+                .map_err(|e| AppError::browser("BROWSER_RENDER_WAIT", e))
+        });
+        let current = timeouts::limit(stage_timeouts, &stage, poll).await?;
+        if current == "done" {
+            return Ok(true);
+        }
+        if !current.is_empty() && current != stage {
+            tracing::info!(stage = %current, "页面进入新阶段");
+            stage = current;
+            stage_started = Instant::now();
+        }
+        cancel.check()?;
+        if max_wait.is_some_and(|limit| started.elapsed() > limit) {
+            tracing::warn!("等待就绪超时，直接打印");
+            return Ok(false);
+        }
+        let budget = stage_timeouts.budget(&stage);
+        if stage_started.elapsed() > budget {
+            return Err(timeouts::timeout_error(&stage, budget));
+        }
+        tokio::time::sleep(RENDER_POLL_INTERVAL).await;
+    }
+}
+
+/// 把已加载的页面打印为 PDF（失败时重试），`decorations` 为本次打印使用的页眉页脚
+async fn print_page_pdf(
+    page: &LoadedPage,
+    decorations: &decorations::PageDecorations,
+    cancel: &cancel::CancelToken,
+) -> Result<Vec<u8>, AppError> {
+    let make_pdf_options = || headless_chrome::types::PrintToPdfOptions {
+        landscape: Some(false),
+        display_header_footer: Some(!decorations.is_empty()),
+        header_template: Some(decorations.header_template()),
+        footer_template: Some(decorations.footer_template()),
+        print_background: Some(true),
+        scale: Some(1.0),
+        paper_width: Some(8.27),
+        paper_height: Some(11.69),
+        margin_top: Some(decorations.margin_top()),
+        margin_bottom: Some(decorations.margin_bottom()),
+        margin_left: Some(0.4),
+        margin_right: Some(0.4),
+        prefer_css_page_size: Some(true),
+        // 按标题生成 PDF 书签，与页内锚点使用同一组标题
+        generate_document_outline: Some(true),
+        // 无障碍与归档输出：标题、段落、列表与表格的结构标签
+        generate_tagged_pdf: Some(page.tagged),
+        ..Default::default()
+    };
+
+    let mut last_err: Option<String> = None;
+
+    for attempt in 0..3 {
+        cancel.check()?;
+        let tab = page.tab.clone();
+        let options = make_pdf_options();
+        let print = error::run_blocking("print_pdf", move || {
+            Ok(tab.print_to_pdf(Some(options)).map_err(|e| e.to_string()))
+        });
+        // 打印超时不重试：同一页面再次打印通常同样会卡住
+        let result = timeouts::limit(&page.timeouts, "print", print).await?;
+        match result {
+            Ok(data) => return Ok(data),
+            Err(e) => {
+                tracing::warn!(attempt, "PDF 生成失败，准备重试: {}", e);
+                last_err = Some(e);
+                // 如果依然失败，进行重试并给一点基础时间
+                let extra_wait = Duration::from_secs((attempt as u64) * 2 + 3);
+                tokio::time::sleep(extra_wait).await;
+            }
+        }
+    }
+
+    Err(AppError::PdfError(last_err.unwrap_or_default()))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    error::install_panic_hook();
+
     tauri::Builder::default()
+        // 单实例插件须最先注册：再次启动时把参数转交给已运行的实例
+        .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+            open_file::handle_second_instance(app, args, cwd);
+        }))
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .setup(|app| {
+            if let Some(guard) = diagnostics::init_logging(app.handle()) {
+                app.manage(guard);
+            }
+            let app_settings = settings::load(app.handle());
+            i18n::set_current_locale(app_settings.locale);
+            app.manage(settings::SettingsState(Mutex::new(app_settings)));
+            browser::cleanup_orphans();
+            asset_server::start(app.path().resource_dir().ok().map(|dir| dir.join("public")));
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             read_markdown_file,
             get_launch_markdown_path,
             markdown_to_html,
             export_to_pdf,
+            preview_export_page,
             parse_markdown_blocks,
-            format_markdown
+            map_line_to_block,
+            format_markdown,
+            format_markdown_range,
+            settings::get_settings,
+            settings::update_settings,
+            diagnostics::collect_diagnostics,
+            literate::run_literate_blocks,
+            literate::trust_literate_document,
+            metrics::get_performance_metrics,
+            checker::check_document,
+            parser_mode::detect_parser_mode,
+            clipboard::save_clipboard_image,
+            assets::package_document,
+            assets::analyze_document,
+            doc_links::resolve_document_link,
+            selection::select_markdown,
+            print_run::export_print_run,
+            image_export::export_to_images,
+            cancel::cancel_export,
+            fonts::list_system_fonts,
+            line_breaks::detect_line_breaks,
+            smart_quotes::detect_smart_quotes,
+            typography::detect_typography,
+            glossary::detect_abbreviations,
+            transforms::list_transforms,
+            transforms::set_transform_enabled,
+            transforms::reorder_transforms,
+            transforms::apply_transforms,
+            diff::diff_markdown,
+            diff::track_changes,
+            asset_server::get_asset_base_url,
+            drafts::autosave_draft,
+            drafts::list_recovered_drafts,
+            drafts::discard_draft,
+            recent::add_recent_file,
+            recent::get_recent_files,
+            recent::pin_recent_file,
+            recent::remove_recent_file,
+            recent::get_file_session,
+            recent::save_file_session,
+            history::get_export_history,
+            history::clear_export_history,
+            history::reexport,
+            watch::watch_export,
+            watch::stop_watch_export,
+            callouts::get_callout_styles,
+            large_file::get_markdown_file_info,
+            large_file::read_markdown_chunk,
+            large_file::parse_markdown_window,
+            streamed_preview::render_document_streamed,
+            search::search_document,
+            search::replace_all,
+            tasks::toggle_task,
+            doc_import::import_document,
+            rich_copy::copy_as_rich_html,
+            workspace::validate_workspace
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|#[cfg_attr(not(target_os = "macos"), allow(unused_variables))] app, event| {
+            if let tauri::RunEvent::Exit = event {
+                browser::kill_all();
+            }
+            // macOS 的 Finder 不通过命令行参数传入文件，而是发送打开事件
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = event {
+                for path in urls.iter().filter_map(|url| url.to_file_path().ok()) {
+                    // 冷启动时前端尚未监听事件，同时记录下来供启动时读取
+                    open_file::set_pending(path.clone());
+                    open_file::open_in_app(app, &path);
+                }
+            }
+        });
 }
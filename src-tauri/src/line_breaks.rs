@@ -0,0 +1,97 @@
+//! 段落内单个换行的处理方式：
+//!  - `soft`（默认，CommonMark 行为）：换行视为空格
+//!  - `hard`：每个换行都换行显示（`<br>`）
+//!  - `cjk`：两侧都是中日文字符时忽略换行、不插入空格，其余情况视为空格，适合按句换行书写的中文文档
+//!
+//! 设置中的 `line_breaks` 为默认值，文档可以在 front matter 中用 `hardbreaks` 覆盖：
+//!
+//! ```yaml
+//! ---
+//! hardbreaks: cjk   # 或 true / false
+//! ---
+//! ```
+//!
+//! 严格模式（纯 CommonMark）下不处理。
+
+use crate::front_matter;
+use crate::settings::SettingsState;
+use pulldown_cmark::Event;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineBreaks {
+    #[default]
+    Soft,
+    Hard,
+    Cjk,
+}
+
+impl LineBreaks {
+    /// front matter 中的 `hardbreaks`：`true` / `false` / `cjk`
+    pub fn declared(markdown: &str) -> Option<LineBreaks> {
+        match front_matter::parse(markdown)?.get("hardbreaks")? {
+            Value::Bool(true) => Some(LineBreaks::Hard),
+            Value::Bool(false) => Some(LineBreaks::Soft),
+            Value::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "cjk" => Some(LineBreaks::Cjk),
+                "true" | "hard" => Some(LineBreaks::Hard),
+                "false" | "soft" => Some(LineBreaks::Soft),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// 文档声明的方式，未声明时使用 `default`
+    pub fn resolve(default: LineBreaks, markdown: &str) -> LineBreaks {
+        LineBreaks::declared(markdown).unwrap_or(default)
+    }
+}
+
+/// 中日文字符（汉字、假名与全角标点）；韩文以空格分词，不在此列
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{303F}'   // CJK 标点
+        | '\u{3040}'..='\u{30FF}' // 平假名、片假名
+        | '\u{3400}'..='\u{4DBF}' // 扩展 A
+        | '\u{4E00}'..='\u{9FFF}' // 基本汉字
+        | '\u{F900}'..='\u{FAFF}' // 兼容汉字
+        | '\u{FF00}'..='\u{FFEF}' // 全角字符
+        | '\u{20000}'..='\u{2FFFF}')
+}
+
+/// 按方式改写 pulldown-cmark 事件中的软换行
+pub fn apply_to_events<'a>(events: Vec<Event<'a>>, mode: LineBreaks) -> Vec<Event<'a>> {
+    if mode == LineBreaks::Soft {
+        return events;
+    }
+    let mut out: Vec<Event<'a>> = Vec::with_capacity(events.len());
+    for (idx, event) in events.iter().enumerate() {
+        match event {
+            Event::SoftBreak if mode == LineBreaks::Hard => out.push(Event::HardBreak),
+            Event::SoftBreak => {
+                let before = match out.last() {
+                    Some(Event::Text(text)) => text.chars().last(),
+                    _ => None,
+                };
+                let after = match events.get(idx + 1) {
+                    Some(Event::Text(text)) => text.chars().next(),
+                    _ => None,
+                };
+                if !(before.is_some_and(is_cjk) && after.is_some_and(is_cjk)) {
+                    out.push(Event::SoftBreak);
+                }
+            }
+            _ => out.push(event.clone()),
+        }
+    }
+    out
+}
+
+/// 文档的换行方式（供前端预览与导出时处理换行）
+#[tauri::command]
+pub fn detect_line_breaks(markdown: &str, settings: tauri::State<'_, SettingsState>) -> LineBreaks {
+    LineBreaks::resolve(settings.snapshot().line_breaks, markdown)
+}
@@ -0,0 +1,519 @@
+//! 文学化编程模式：执行标注了 `{run}` 的代码块，并把标准输出嵌入到代码块下方
+//!
+//! ````markdown
+//! ```python {run}
+//! print(1 + 1)
+//! ```
+//! ````
+//!
+//! 该模式默认关闭，需要在设置中开启并配置解释器。执行时的限制：
+//!  - 每个文档第一次运行前需要用户确认信任；信任按文档路径与所有 `{run}` 代码块的哈希记录，
+//!    代码块有任何改动都需要重新确认
+//!  - 只运行设置中配置过的语言
+//!  - 每个代码块在新建的临时目录中运行，清空环境变量（仅保留 PATH 等必要项），stdin 为空
+//!  - 超时后结束整个进程树，输出超过上限时截断
+//!
+//! 结果按 (语言, 解释器, 代码) 的哈希缓存在应用缓存目录中，代码不变时不会重复执行；
+//! 可用 `{run cache=false}` 对单个代码块关闭缓存。
+
+use crate::error::AppError;
+use crate::hashing::sha256_hex;
+use crate::json_store::JsonStore;
+use crate::metrics;
+use crate::settings::SettingsState;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterpreterConfig {
+    /// 可执行文件，如 `python3`、`sh`
+    pub command: String,
+    /// 位于脚本路径之前的额外参数
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 脚本文件扩展名
+    pub extension: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LiterateSettings {
+    /// 是否在导出时执行 `{run}` 代码块
+    pub enabled: bool,
+    /// 语言 → 解释器
+    pub interpreters: HashMap<String, InterpreterConfig>,
+    /// 单个代码块的最长执行时间（秒）
+    pub timeout_secs: u64,
+    /// 嵌入输出的最大字节数
+    pub max_output_bytes: usize,
+    /// 是否同时嵌入 stderr
+    pub include_stderr: bool,
+}
+
+impl Default for LiterateSettings {
+    fn default() -> Self {
+        let python = if cfg!(windows) { "python" } else { "python3" };
+        let mut interpreters = HashMap::new();
+        interpreters.insert(
+            "python".to_string(),
+            InterpreterConfig { command: python.to_string(), args: Vec::new(), extension: "py".to_string() },
+        );
+        interpreters.insert(
+            "sh".to_string(),
+            InterpreterConfig { command: "sh".to_string(), args: Vec::new(), extension: "sh".to_string() },
+        );
+        LiterateSettings {
+            enabled: false,
+            interpreters,
+            timeout_secs: 10,
+            max_output_bytes: 64 * 1024,
+            include_stderr: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockFailure {
+    /// 代码块起始行（1-indexed）
+    pub line: usize,
+    pub language: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiterateResult {
+    /// 嵌入了输出的 Markdown
+    pub markdown: String,
+    pub executed: usize,
+    pub cached: usize,
+    pub failures: Vec<BlockFailure>,
+}
+
+/// 运行时需要保留的环境变量，其余全部清除
+const PRESERVED_ENV: &[&str] = &["PATH", "SYSTEMROOT", "SystemRoot", "TEMP", "TMP", "LANG"];
+
+/// 结束子进程及其派生的所有进程
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // 子进程在独立的进程组中启动，组 ID 即其 PID
+        let _ = Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", child.id())])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &child.id().to_string()])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.kill();
+}
+
+/// 子进程是否已退出。Unix 上不回收子进程：退出后它仍是僵尸进程，PID 与进程组 ID 不会被复用，
+/// 此时结束整个进程组不会误伤无关进程
+fn has_exited(child: &mut Child) -> std::io::Result<bool> {
+    #[cfg(unix)]
+    {
+        // SAFETY: `info` 由 waitid 填写；WNOWAIT 只查询状态，子进程仍由 `child.wait()` 回收
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let flags = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+        if unsafe { libc::waitid(libc::P_PID, child.id() as libc::id_t, &mut info, flags) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(unsafe { info.si_pid() } != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        child.try_wait().map(|status| status.is_some())
+    }
+}
+
+/// 读完整个输出流，只保留前 `limit` 字节；超出的部分继续读取并丢弃，子进程不会因管道写满而阻塞
+fn read_limited(mut reader: impl Read, limit: usize) -> Vec<u8> {
+    let mut kept = Vec::new();
+    let mut chunk = [0u8; 8192];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => {
+                let room = limit.saturating_sub(kept.len());
+                kept.extend_from_slice(&chunk[..n.min(room)]);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        }
+    }
+    kept
+}
+
+fn run_block(config: &InterpreterConfig, code: &str, settings: &LiterateSettings) -> Result<String, String> {
+    // 每次执行都使用新建的私有目录，结束后（包括出错返回时）自动删除
+    let work_dir = tempfile::Builder::new().prefix("md2pdf-run-").tempdir().map_err(|e| e.to_string())?;
+    let script = work_dir.path().join(format!("block.{}", config.extension));
+    fs::write(&script, code).map_err(|e| e.to_string())?;
+
+    let mut command = Command::new(&config.command);
+    command
+        .args(&config.args)
+        .arg(&script)
+        .current_dir(work_dir.path())
+        .env_clear()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    for key in PRESERVED_ENV {
+        if let Ok(value) = std::env::var(key) {
+            command.env(key, value);
+        }
+    }
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = command.spawn().map_err(|e| format!("无法启动 {}: {}", config.command, e))?;
+    let deadline = Instant::now() + Duration::from_secs(settings.timeout_secs);
+
+    // 在独立线程中读取输出，避免管道写满导致子进程阻塞
+    let limit = settings.max_output_bytes + 1;
+    let stdout = child.stdout.take().map(|out| std::thread::spawn(move || read_limited(out, limit)));
+    let stderr = child.stderr.take().map(|err| std::thread::spawn(move || read_limited(err, limit)));
+
+    loop {
+        if has_exited(&mut child).map_err(|e| e.to_string())? {
+            break;
+        }
+        if Instant::now() >= deadline {
+            kill_tree(&mut child);
+            let _ = child.wait();
+            return Err(format!("执行超时（{} 秒）", settings.timeout_secs));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    // 脚本留在后台的进程会一直占用输出管道；主进程尚未回收，进程组仍属于本次执行
+    #[cfg(unix)]
+    kill_tree(&mut child);
+    let status = child.wait().map_err(|e| e.to_string())?;
+
+    let stdout = stdout.and_then(|h| h.join().ok()).unwrap_or_default();
+    let stderr = stderr.and_then(|h| h.join().ok()).unwrap_or_default();
+
+    let mut output = String::from_utf8_lossy(&stdout).to_string();
+    if settings.include_stderr && !stderr.is_empty() {
+        output.push_str(&String::from_utf8_lossy(&stderr));
+    }
+    if output.len() > settings.max_output_bytes {
+        let mut cut = settings.max_output_bytes;
+        while !output.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        output.truncate(cut);
+        output.push_str("\n… (输出已截断)");
+    }
+
+    if !status.success() && !settings.include_stderr {
+        let reason = String::from_utf8_lossy(&stderr).trim().to_string();
+        return Err(format!("退出码 {:?}: {}", status.code(), reason));
+    }
+    Ok(output)
+}
+
+pub(crate) fn cache_dir(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path().app_cache_dir().ok().map(|dir| dir.join("literate"))
+}
+
+/// 标注了 `{run}` 的代码块
+struct RunBlock {
+    /// 起始围栏所在行（0-indexed）
+    open: usize,
+    /// 闭合围栏所在行
+    close: usize,
+    indent: String,
+    language: String,
+    use_cache: bool,
+}
+
+fn find_run_blocks(lines: &[&str]) -> Vec<RunBlock> {
+    let re_open = Regex::new(r"^(\s{0,3})(`{3,}|~{3,})\s*([\w+#.-]+)\s*\{([^}]*)\}\s*$").unwrap();
+
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(caps) = re_open.captures(lines[i]) else {
+            i += 1;
+            continue;
+        };
+        let attrs: Vec<&str> = caps[4].split_whitespace().collect();
+        if !attrs.contains(&"run") {
+            i += 1;
+            continue;
+        }
+
+        // 寻找闭合围栏
+        let fence = &caps[2];
+        let Some(close) = (i + 1..lines.len()).find(|&j| {
+            let t = lines[j].trim();
+            t.starts_with(fence) && t.chars().all(|c| c == fence.chars().next().unwrap_or('`'))
+        }) else {
+            i += 1;
+            continue;
+        };
+
+        blocks.push(RunBlock {
+            open: i,
+            close,
+            indent: caps[1].to_string(),
+            language: caps[3].to_ascii_lowercase(),
+            use_cache: !attrs.contains(&"cache=false"),
+        });
+        i = close + 1;
+    }
+    blocks
+}
+
+/// 所有 `{run}` 代码块（语言与代码）的哈希，没有可执行的代码块时为 None
+pub fn fingerprint(markdown: &str) -> Option<String> {
+    let content = markdown.replace("\r\n", "\n");
+    let lines: Vec<&str> = content.lines().collect();
+    let blocks = find_run_blocks(&lines);
+    if blocks.is_empty() {
+        return None;
+    }
+    let mut material = String::new();
+    for block in &blocks {
+        material.push_str(&block.language);
+        material.push('\0');
+        material.push_str(&lines[block.open + 1..block.close].join("\n"));
+        material.push('\0');
+    }
+    Some(sha256_hex(material.as_bytes()))
+}
+
+/// 执行 Markdown 中所有 `{run}` 代码块并嵌入输出
+pub fn execute_blocks(markdown: &str, settings: &LiterateSettings, cache_dir: Option<&PathBuf>) -> LiterateResult {
+    let re_backticks = Regex::new(r"`+").unwrap();
+
+    let content = markdown.replace("\r\n", "\n");
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut result = LiterateResult { markdown: String::new(), executed: 0, cached: 0, failures: Vec::new() };
+
+    let mut next = 0;
+    for RunBlock { open, close, indent, language, use_cache } in find_run_blocks(&lines) {
+        out.extend(lines[next..=close].iter().map(|l| l.to_string()));
+        next = close + 1;
+        let code = lines[open + 1..close].join("\n");
+        let start_line = open + 1;
+
+        let Some(config) = settings.interpreters.get(&language) else {
+            result.failures.push(BlockFailure {
+                line: start_line,
+                language: language.clone(),
+                reason: "未配置该语言的解释器".to_string(),
+            });
+            continue;
+        };
+
+        let hash = sha256_hex(format!("{}\0{}\0{}\0{}", language, config.command, config.args.join(" "), code).as_bytes());
+        let cache_file = cache_dir.map(|dir| dir.join(format!("{}.txt", hash)));
+
+        let cached = if use_cache {
+            cache_file.as_ref().and_then(|f| fs::read_to_string(f).ok())
+        } else {
+            None
+        };
+
+        if use_cache && cache_file.is_some() {
+            metrics::record_cache("literate", cached.is_some());
+        }
+
+        let output = match cached {
+            Some(output) => {
+                result.cached += 1;
+                output
+            }
+            None => match run_block(config, &code, settings) {
+                Ok(output) => {
+                    result.executed += 1;
+                    if let Some(file) = &cache_file {
+                        if let Some(parent) = file.parent() {
+                            let _ = fs::create_dir_all(parent);
+                        }
+                        let _ = fs::write(file, &output);
+                    }
+                    output
+                }
+                Err(reason) => {
+                    tracing::warn!(line = start_line, %language, "代码块执行失败: {}", reason);
+                    result.failures.push(BlockFailure { line: start_line, language, reason: reason.clone() });
+                    format!("[执行失败] {}", reason)
+                }
+            },
+        };
+
+        // 输出使用比内容中最长反引号串更长的围栏，避免被提前闭合
+        let longest = re_backticks
+            .find_iter(&output)
+            .map(|m| m.as_str().len())
+            .max()
+            .unwrap_or(0);
+        let output_fence = "`".repeat(longest.max(2) + 1);
+        out.push(String::new());
+        out.push(format!("{}{}output", indent, output_fence));
+        out.extend(output.trim_end_matches('\n').lines().map(|l| format!("{}{}", indent, l)));
+        out.push(format!("{}{}", indent, output_fence));
+    }
+
+    out.extend(lines[next..].iter().map(|l| l.to_string()));
+    result.markdown = out.join("\n");
+    result
+}
+
+/// 用户确认过的文档：文档路径（未保存的文档为空）→ 确认时代码块的哈希
+static TRUST_STORE: JsonStore = JsonStore::new("literate_trust.json");
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct TrustStore {
+    documents: HashMap<String, String>,
+}
+
+fn trust_key(source_path: Option<&Path>) -> String {
+    source_path.map(|p| p.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// 文档包含 `{run}` 代码块且用户没有确认过当前这些代码时返回 `UntrustedCode`
+pub(crate) fn ensure_trusted(app: &tauri::AppHandle, source_path: Option<&Path>, markdown: &str) -> Result<(), AppError> {
+    let Some(fingerprint) = fingerprint(markdown) else {
+        return Ok(());
+    };
+    let key = trust_key(source_path);
+    let store: TrustStore = TRUST_STORE.load(app)?;
+    if store.documents.get(&key) == Some(&fingerprint) {
+        return Ok(());
+    }
+    Err(AppError::UntrustedCode { path: key, fingerprint })
+}
+
+/// 记录用户信任文档当前的代码块（`fingerprint` 来自 `UntrustedCode` 错误）
+#[tauri::command]
+pub fn trust_literate_document(
+    app: tauri::AppHandle,
+    source_path: Option<String>,
+    fingerprint: String,
+) -> Result<(), AppError> {
+    let key = trust_key(source_path.as_deref().map(Path::new));
+    TRUST_STORE.update(&app, |store: &mut TrustStore| {
+        store.documents.insert(key, fingerprint);
+    })
+}
+
+/// 文学化模式：执行 `{run}` 代码块并返回嵌入输出后的 Markdown；未开启时原样返回。
+/// 用户尚未信任文档的代码块时返回 `UntrustedCode`，确认后调用 `trust_literate_document` 再重试
+#[tauri::command]
+pub async fn run_literate_blocks(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    markdown: String,
+    source_path: Option<String>,
+) -> Result<LiterateResult, AppError> {
+    let settings = state.snapshot().literate;
+    if !settings.enabled {
+        return Ok(LiterateResult { markdown, executed: 0, cached: 0, failures: Vec::new() });
+    }
+    ensure_trusted(&app, source_path.as_deref().map(Path::new), &markdown)?;
+    let cache_dir = cache_dir(&app);
+    tokio::task::spawn_blocking(move || {
+        crate::error::catch_panic("run_literate_blocks", || {
+            Ok(execute_blocks(&markdown, &settings, cache_dir.as_ref()))
+        })
+    })
+    .await
+    .map_err(|e| crate::error::join_error("run_literate_blocks", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "# 标题\n\n```sh {run}\necho hi\n```\n\n正文\n";
+
+    #[test]
+    fn fingerprint_covers_only_runnable_code() {
+        assert_eq!(fingerprint("```sh\necho hi\n```\n"), None);
+        let base = fingerprint(DOC).unwrap();
+        assert_eq!(fingerprint(&DOC.replace("正文", "改过的正文")), Some(base.clone()));
+        assert_eq!(fingerprint(&DOC.replace("\n", "\r\n")), Some(base.clone()));
+        assert_ne!(fingerprint(&DOC.replace("echo hi", "echo bye")), Some(base));
+    }
+
+    #[test]
+    fn blocks_without_interpreters_are_reported() {
+        let settings = LiterateSettings { interpreters: HashMap::new(), ..Default::default() };
+        let result = execute_blocks(DOC, &settings, None);
+        assert_eq!(result.markdown, DOC.trim_end());
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(result.failures[0].line, 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn output_is_embedded_after_the_block() {
+        let result = execute_blocks(DOC, &LiterateSettings::default(), None);
+        assert!(result.failures.is_empty(), "{:?}", result.failures);
+        assert_eq!(result.executed, 1);
+        assert!(result.markdown.contains("```\n\n```output\nhi\n```\n\n正文"), "{}", result.markdown);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeouts_kill_background_processes_too() {
+        let settings = LiterateSettings { timeout_secs: 1, ..Default::default() };
+        let started = Instant::now();
+        let result = execute_blocks("```sh {run}\nsleep 30 &\nsleep 30\n```\n", &settings, None);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(result.failures.len(), 1);
+        assert!(result.failures[0].reason.contains("超时"));
+    }
+
+    #[test]
+    fn read_limited_drains_the_whole_stream() {
+        let mut data = std::io::Cursor::new(vec![b'x'; 100_000]);
+        assert_eq!(read_limited(&mut data, 10).len(), 10);
+        assert_eq!(data.position(), 100_000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn chatty_blocks_are_truncated() {
+        let settings = LiterateSettings { max_output_bytes: 1024, ..Default::default() };
+        let started = Instant::now();
+        let result = execute_blocks("```sh {run}
+head -c 5000000 /dev/zero | tr '\\0' x
+echo done >&2
+```
+", &settings, None);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert!(result.failures.is_empty(), "{:?}", result.failures);
+        assert!(result.markdown.contains("(输出已截断)"), "{}", result.markdown);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn each_run_uses_a_fresh_directory_that_is_removed() {
+        let result = execute_blocks("```sh {run}\npwd\nls\n```\n", &LiterateSettings::default(), None);
+        let output = result.markdown.lines().skip_while(|l| !l.ends_with("output")).nth(1).unwrap().to_string();
+        assert!(output.contains("md2pdf-run-"), "{}", output);
+        assert!(!Path::new(&output).exists());
+    }
+}
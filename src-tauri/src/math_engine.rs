@@ -0,0 +1,104 @@
+//! 公式引擎：`katex`（默认，前端渲染时已生成 KaTeX HTML）、`mathjax`（导出页面中由 MathJax 排版，
+//! 支持 mhchem、更完整的 `align` 等环境）或 `none`（保留 TeX 原文）。
+//!
+//! 使用 MathJax 或 `none` 时前端不运行 rehype-katex，公式以 `math-inline` / `math-display`
+//! 元素保留 TeX 原文，这里把它们改写为 MathJax 的定界符。
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+const KATEX_CDN_CSS: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.9/dist/katex.min.css";
+/// 含全部 TeX 扩展（mhchem 等）的 SVG 输出版本，打印时不依赖 Web 字体；
+/// 安装依赖时从 npm 包复制到 `public/mathjax/`（见 scripts/vendor-mathjax.mjs），随应用分发
+pub const MATHJAX_SCRIPT: &str = "tex-svg-full.js";
+const MATHJAX_CDN_SCRIPT: &str = "https://cdn.jsdelivr.net/npm/mathjax@3/es5/tex-svg-full.js";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MathEngine {
+    #[default]
+    Katex,
+    Mathjax,
+    None,
+}
+
+/// 随应用分发的公式资源地址（本地资源服务器或 file://）；不存在时使用 CDN
+#[derive(Debug, Clone, Default)]
+pub struct LocalAssets {
+    pub katex_css: Option<String>,
+    pub mathjax_script: Option<String>,
+}
+
+/// 导出页面 `<head>` 中公式相关的样式与脚本
+pub fn head_html(engine: MathEngine, assets: &LocalAssets) -> String {
+    match engine {
+        MathEngine::Katex => format!(
+            r#"<link rel="stylesheet" href="{}">"#,
+            assets.katex_css.as_deref().unwrap_or(KATEX_CDN_CSS)
+        ),
+        // 脚本加载成功或失败时记录在 data-state 上，渲染完成信号据此等待 MathJax.startup.promise
+        // 或标记公式引擎不可用（见 readiness 模块）；公式编号由 equations 模块分配，MathJax 不再自动编号
+        MathEngine::Mathjax => format!(
+            r#"<script>
+        window.MathJax = {{
+            tex: {{ inlineMath: [['\\(', '\\)']], displayMath: [['\\[', '\\]']], tags: 'none' }},
+            svg: {{ fontCache: 'global' }},
+            startup: {{ typeset: true }}
+        }};
+    </script>
+    <script id="mathjax-script" async src="{}"
+        onload="this.dataset.state = 'loaded'" onerror="this.dataset.state = 'failed'"></script>"#,
+            assets.mathjax_script.as_deref().unwrap_or(MATHJAX_CDN_SCRIPT)
+        ),
+        MathEngine::None => String::new(),
+    }
+}
+
+/// 把前端输出的公式元素改写为 MathJax 定界符（TeX 原文保持转义状态，由浏览器还原）
+pub fn to_mathjax_delimiters(html: &str) -> String {
+    let re_display =
+        Regex::new(r#"(?s)<pre><code class="language-math math-display">(.*?)</code></pre>"#).unwrap();
+    let re_inline =
+        Regex::new(r#"(?s)<code class="language-math math-inline">(.*?)</code>|<span class="math math-inline">(.*?)</span>"#)
+            .unwrap();
+    let html = re_display.replace_all(html, |caps: &regex::Captures| {
+        format!(r#"<div class="math-display">\[{}\]</div>"#, caps[1].trim())
+    });
+    re_inline
+        .replace_all(&html, |caps: &regex::Captures| {
+            let tex = caps.get(1).or_else(|| caps.get(2)).map_or("", |m| m.as_str());
+            format!(r#"<span class="math-inline">\({}\)</span>"#, tex)
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mathjax_prefers_the_bundled_script() {
+        let assets = LocalAssets {
+            katex_css: None,
+            mathjax_script: Some("http://127.0.0.1:1/t/mathjax/tex-svg-full.js".to_string()),
+        };
+        let head = head_html(MathEngine::Mathjax, &assets);
+        assert!(head.contains(r#"src="http://127.0.0.1:1/t/mathjax/tex-svg-full.js""#));
+        assert!(!head.contains(MATHJAX_CDN_SCRIPT));
+    }
+
+    #[test]
+    fn mathjax_falls_back_to_the_cdn_and_reports_load_state() {
+        let head = head_html(MathEngine::Mathjax, &LocalAssets::default());
+        assert!(head.contains(MATHJAX_CDN_SCRIPT));
+        assert!(head.contains(r#"id="mathjax-script""#));
+        assert!(head.contains("onerror=\"this.dataset.state = 'failed'\""));
+    }
+
+    #[test]
+    fn katex_uses_the_bundled_stylesheet() {
+        let assets = LocalAssets { katex_css: Some("katex/katex.min.css".to_string()), mathjax_script: None };
+        assert_eq!(head_html(MathEngine::Katex, &assets), r#"<link rel="stylesheet" href="katex/katex.min.css">"#);
+        assert!(head_html(MathEngine::None, &assets).is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+//! 性能指标：后端各环节耗时的滚动统计与缓存命中率，供 `get_performance_metrics` 查询
+
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 每项指标保留的最近样本数
+const WINDOW_SIZE: usize = 200;
+
+static TIMINGS: Mutex<BTreeMap<String, VecDeque<f64>>> = Mutex::new(BTreeMap::new());
+static CACHES: Mutex<BTreeMap<String, (u64, u64)>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimingStats {
+    pub name: String,
+    pub count: usize,
+    pub avg_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub p95_ms: f64,
+    pub last_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub name: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PerformanceMetrics {
+    pub window_size: usize,
+    pub timings: Vec<TimingStats>,
+    pub caches: Vec<CacheStats>,
+}
+
+/// 记录一次耗时
+pub fn record_duration(name: &str, duration: Duration) {
+    record_ms(name, duration.as_secs_f64() * 1000.0);
+}
+
+/// 记录一次耗时（毫秒）
+pub fn record_ms(name: &str, ms: f64) {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        let samples = timings.entry(name.to_string()).or_default();
+        if samples.len() >= WINDOW_SIZE {
+            samples.pop_front();
+        }
+        samples.push_back(ms);
+    }
+}
+
+/// 记录一次缓存访问
+pub fn record_cache(name: &str, hit: bool) {
+    if let Ok(mut caches) = CACHES.lock() {
+        let entry = caches.entry(name.to_string()).or_insert((0, 0));
+        if hit {
+            entry.0 += 1;
+        } else {
+            entry.1 += 1;
+        }
+    }
+}
+
+fn summarize(name: &str, samples: &VecDeque<f64>) -> TimingStats {
+    let mut sorted: Vec<f64> = samples.iter().copied().collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let count = sorted.len();
+    let p95_index = ((count as f64) * 0.95).ceil() as usize;
+    TimingStats {
+        name: name.to_string(),
+        count,
+        avg_ms: sorted.iter().sum::<f64>() / count.max(1) as f64,
+        min_ms: sorted.first().copied().unwrap_or(0.0),
+        max_ms: sorted.last().copied().unwrap_or(0.0),
+        p95_ms: sorted.get(p95_index.saturating_sub(1)).copied().unwrap_or(0.0),
+        last_ms: samples.back().copied().unwrap_or(0.0),
+    }
+}
+
+/// 获取性能指标（解析、渲染、导出各阶段耗时的滚动统计，以及缓存命中率）
+#[tauri::command]
+pub fn get_performance_metrics() -> PerformanceMetrics {
+    let timings = TIMINGS
+        .lock()
+        .map(|timings| timings.iter().map(|(name, samples)| summarize(name, samples)).collect())
+        .unwrap_or_default();
+    let caches = CACHES
+        .lock()
+        .map(|caches| {
+            caches
+                .iter()
+                .map(|(name, &(hits, misses))| CacheStats {
+                    name: name.clone(),
+                    hits,
+                    misses,
+                    hit_rate: if hits + misses == 0 { 0.0 } else { hits as f64 / (hits + misses) as f64 },
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    PerformanceMetrics { window_size: WINDOW_SIZE, timings, caches }
+}
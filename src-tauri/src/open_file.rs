@@ -0,0 +1,104 @@
+//! 文件关联与“打开方式”：双击 `.md` / `.markdown` 文件、拖到程序图标上或从命令行传入路径时打开该文件。
+//!
+//! 程序只运行一个实例：已在运行时再次启动，新实例把命令行参数转交给已有实例后退出，
+//! 由已有实例读取文件并向前端发送 `open-file` 事件（macOS 上 Finder 通过打开事件传入文件）。
+
+use crate::error::AppError;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+/// 前端就绪之前收到的打开请求（macOS 冷启动时打开事件早于页面加载）
+static PENDING_OPEN: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenFilePayload {
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenFileErrorPayload {
+    pub path: String,
+    pub message: String,
+}
+
+pub fn is_markdown_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "md" | "markdown"))
+        .unwrap_or(false)
+}
+
+/// 命令行参数中的第一个 Markdown 文件路径（不检查是否存在）；相对路径按启动时的工作目录解析
+pub fn markdown_arg<I>(args: I, cwd: &Path) -> Option<PathBuf>
+where
+    I: IntoIterator,
+    I::Item: Into<OsString>,
+{
+    args.into_iter()
+        .map(|arg| PathBuf::from(arg.into()))
+        .filter(|path| !path.to_string_lossy().starts_with('-'))
+        .map(|path| if path.is_relative() { cwd.join(path) } else { path })
+        .find(|path| is_markdown_file(path))
+}
+
+/// 校验并读取要打开的文件：必须是已存在的 Markdown 文件
+fn read_markdown(path: &Path) -> Result<OpenFilePayload, AppError> {
+    if !is_markdown_file(path) {
+        return Err(AppError::localized(
+            "INTERNAL",
+            "OPEN_NOT_MARKDOWN",
+            [("context", "open_file".to_string()), ("path", path.display().to_string())],
+        ));
+    }
+    let path = fs::canonicalize(path).map_err(|e| AppError::file(path, e))?;
+    let content = fs::read_to_string(&path).map_err(|e| AppError::file(&path, e))?;
+    Ok(OpenFilePayload { path: path.to_string_lossy().to_string(), content })
+}
+
+fn focus_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// 读取文件并通知前端打开，同时把主窗口带到前台；文件无效时发送 `open-file-error`
+pub fn open_in_app(app: &tauri::AppHandle, path: &Path) {
+    focus_main_window(app);
+    match read_markdown(path) {
+        Ok(payload) => {
+            tracing::info!(path = %payload.path, "通过文件关联打开文件");
+            let _ = app.emit("open-file", payload);
+        }
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "无法打开文件");
+            let _ = app.emit(
+                "open-file-error",
+                OpenFileErrorPayload { path: path.to_string_lossy().to_string(), message: e.to_string() },
+            );
+        }
+    }
+}
+
+/// 记录启动时收到的文件，由前端通过 `get_launch_markdown_path` 取走
+#[cfg(target_os = "macos")]
+pub fn set_pending(path: PathBuf) {
+    *PENDING_OPEN.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+}
+
+pub fn take_pending() -> Option<PathBuf> {
+    PENDING_OPEN.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// 再次启动时（单实例插件转交的参数与工作目录）：打开传入的文件，没有文件时只激活窗口
+pub fn handle_second_instance(app: &tauri::AppHandle, args: Vec<String>, cwd: String) {
+    match markdown_arg(args.into_iter().skip(1), Path::new(&cwd)) {
+        Some(path) => open_in_app(app, &path),
+        None => focus_main_window(app),
+    }
+}
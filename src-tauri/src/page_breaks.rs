@@ -0,0 +1,82 @@
+//! 标题另起一页：导出时在指定的标题之前强制分页，不必在每个标题前手写分页标记。
+//!
+//!  - 按级别：设置中的 `new_page_levels`（如 `[1]` 表示每个一级标题另起一页）为默认值，
+//!    front matter 中的 `new_page` 覆盖，可写 `[1, 2]`、`1`、`h1` 或 `false`
+//!  - 单个标题：标题末尾的 `{newpage}` 标记（属于扩展语法）
+//!
+//! 文档开头的标题不会产生空白页（浏览器忽略第一页之前的分页）。
+
+use regex::{Captures, Regex};
+use serde_yaml::Value;
+
+/// 另起一页的标题 class
+const NEW_PAGE_CLASS: &str = "new-page";
+
+/// front matter 中的 `new_page` 覆盖设置中的默认级别
+pub fn resolve_levels(default: &[u8], front_matter: Option<&Value>) -> Vec<u8> {
+    let level_of = |value: &Value| -> Option<u8> {
+        let level = match value {
+            Value::Number(n) => u8::try_from(n.as_u64()?).ok()?,
+            Value::String(s) => s.trim().trim_start_matches(['h', 'H']).parse().ok()?,
+            _ => return None,
+        };
+        (1..=6).contains(&level).then_some(level)
+    };
+    match front_matter.and_then(|fm| fm.get("new_page")) {
+        None => default.iter().copied().filter(|l| (1..=6).contains(l)).collect(),
+        Some(Value::Bool(false)) | Some(Value::Null) => Vec::new(),
+        Some(Value::Sequence(items)) => items.iter().filter_map(level_of).collect(),
+        Some(value) => level_of(value).into_iter().collect(),
+    }
+}
+
+/// 给开始标签加上 class（已有 class 时追加）
+fn add_class(attrs: &str, class: &str) -> String {
+    let re_class = Regex::new(r#"(\sclass\s*=\s*")([^"]*)""#).unwrap();
+    if re_class.is_match(attrs) {
+        re_class
+            .replace(attrs, |caps: &Captures| {
+                if caps[2].split_whitespace().any(|c| c == class) {
+                    caps[0].to_string()
+                } else {
+                    format!(r#"{}{} {}""#, &caps[1], &caps[2], class)
+                }
+            })
+            .into_owned()
+    } else {
+        format!(r#"{} class="{}""#, attrs, class)
+    }
+}
+
+/// 标题末尾的 `{newpage}`：去掉标记并让该标题另起一页
+pub fn apply_heading_markers(html: &str) -> String {
+    if !html.contains("newpage") {
+        return html.to_string();
+    }
+    let re_marker = Regex::new(r"(?s)<h([1-6])((?:\s[^>]*)?)>(.*?)\s*\{\s*\.?newpage\s*\}\s*</h([1-6])>").unwrap();
+    re_marker
+        .replace_all(html, |caps: &Captures| {
+            if caps[1] != caps[4] {
+                return caps[0].to_string();
+            }
+            format!("<h{}{}>{}</h{}>", &caps[1], add_class(&caps[2], NEW_PAGE_CLASS), &caps[3], &caps[1])
+        })
+        .into_owned()
+}
+
+/// 指定级别的标题另起一页
+pub fn apply_level_breaks(html: &str, levels: &[u8]) -> String {
+    if levels.is_empty() {
+        return html.to_string();
+    }
+    let re_heading = Regex::new(r"<h([1-6])((?:\s[^>]*)?)>").unwrap();
+    re_heading
+        .replace_all(html, |caps: &Captures| {
+            let level: u8 = caps[1].parse().unwrap_or(0);
+            if !levels.contains(&level) {
+                return caps[0].to_string();
+            }
+            format!("<h{}{}>", &caps[1], add_class(&caps[2], NEW_PAGE_CLASS))
+        })
+        .into_owned()
+}
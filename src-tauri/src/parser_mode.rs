@@ -0,0 +1,89 @@
+//! 解析模式：默认启用 GFM 表格、脚注、公式等扩展；严格模式只按 CommonMark 规范解析，
+//! 不启用任何扩展与智能标点，用于和只接受标准 CommonMark 的系统交换文档。
+//!
+//! 每个文档可以在 front matter 中声明模式：
+//!
+//! ```yaml
+//! ---
+//! markdown: commonmark
+//! ---
+//! ```
+
+use crate::front_matter;
+use comrak::Options as ComrakOptions;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParserMode {
+    /// GFM 及本应用的扩展语法
+    #[default]
+    Extended,
+    /// 纯 CommonMark
+    Strict,
+}
+
+impl ParserMode {
+    /// 调用方未指定时使用文档 front matter 中声明的模式
+    pub fn resolve(requested: Option<ParserMode>, markdown: &str) -> ParserMode {
+        requested.or_else(|| declared_mode(markdown)).unwrap_or_default()
+    }
+}
+
+/// front matter 中 `markdown` 字段声明的模式：`commonmark`/`strict` 或 `gfm`/`extended`
+pub fn declared_mode(markdown: &str) -> Option<ParserMode> {
+    let value = front_matter::parse(markdown)?;
+    match value.get("markdown")?.as_str()?.trim().to_ascii_lowercase().as_str() {
+        "commonmark" | "strict" => Some(ParserMode::Strict),
+        "gfm" | "extended" => Some(ParserMode::Extended),
+        _ => None,
+    }
+}
+
+/// 去掉声明了解析模式的 front matter（严格模式下它不属于正文）
+pub fn strip_declared_front_matter(markdown: &str) -> &str {
+    match declared_mode(markdown) {
+        Some(_) => front_matter::body(markdown),
+        None => markdown,
+    }
+}
+
+/// comrak 解析选项；严格模式下仅在文档用 front matter 声明了模式时识别 front matter
+pub fn comrak_options(mode: ParserMode, markdown: &str) -> ComrakOptions<'static> {
+    match mode {
+        ParserMode::Extended => {
+            let mut options = crate::get_comrak_options();
+            options.render.hardbreaks =
+                crate::line_breaks::LineBreaks::declared(markdown) == Some(crate::line_breaks::LineBreaks::Hard);
+            options
+        }
+        ParserMode::Strict => {
+            let mut options = ComrakOptions::default();
+            options.render.unsafe_ = true;
+            if declared_mode(markdown).is_some() {
+                options.extension.front_matter_delimiter = Some("---".to_string());
+            }
+            options
+        }
+    }
+}
+
+/// pulldown-cmark 解析选项
+pub fn pulldown_options(mode: ParserMode) -> pulldown_cmark::Options {
+    use pulldown_cmark::Options;
+
+    let mut options = Options::empty();
+    if mode == ParserMode::Extended {
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+        options.insert(Options::ENABLE_TABLES);
+        options.insert(Options::ENABLE_FOOTNOTES);
+        options.insert(Options::ENABLE_TASKLISTS);
+    }
+    options
+}
+
+/// 检测文档的解析模式（供前端决定预览与导出时是否启用扩展插件）
+#[tauri::command]
+pub fn detect_parser_mode(markdown: &str) -> ParserMode {
+    ParserMode::resolve(None, markdown)
+}
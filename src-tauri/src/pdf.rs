@@ -0,0 +1,134 @@
+//! Chrome 输出的 PDF 的底层读写：间接对象、十六进制字符串。
+//! 加密（`pdf_protect`）与 PDF/A 改写（`pdf_archive`）共用。
+
+use crate::error::{AppError, PdfIssue, PdfOperation};
+use regex::bytes::Regex;
+
+fn malformed(issue: PdfIssue) -> AppError {
+    AppError::PdfStructure { operation: PdfOperation::Parse, issue }
+}
+
+pub(crate) fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+pub(crate) fn unhex(text: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = text
+        .iter()
+        .filter_map(|c| (*c as char).to_digit(16).map(|d| d as u8))
+        .collect();
+    digits.chunks(2).map(|pair| (pair[0] << 4) | pair.get(1).copied().unwrap_or(0)).collect()
+}
+
+/// 间接对象：编号、代号与 `obj` / `endobj` 之间的内容
+pub(crate) struct PdfObject<'a> {
+    pub number: u32,
+    pub generation: u16,
+    pub body: &'a [u8],
+}
+
+pub(crate) fn parse_objects(data: &[u8]) -> Result<Vec<PdfObject<'_>>, AppError> {
+    let re_obj = Regex::new(r"(?m)(?:^|[\r\n])(\d+)\s+(\d+)\s+obj\b").unwrap();
+    let re_length = Regex::new(r"/Length\s+(\d+)(?:\s+(\d+)\s+R)?").unwrap();
+    let mut objects = Vec::new();
+    let mut pos = 0;
+    while let Some(caps) = re_obj.captures_at(data, pos) {
+        let whole = caps.get(0).unwrap();
+        let number = std::str::from_utf8(&caps[1]).ok().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let generation = std::str::from_utf8(&caps[2]).ok().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let body_start = whole.end();
+        // 流数据中可能出现 `endobj`，先按 `/Length` 跳过流
+        let stream_at = find(data, b"stream", body_start);
+        let endobj_at = find(data, b"endobj", body_start).ok_or_else(|| malformed(PdfIssue::UnterminatedObject))?;
+        let search_from = match stream_at {
+            Some(stream_at) if stream_at < endobj_at && !data[..stream_at].ends_with(b"end") => {
+                let dict = &data[body_start..stream_at];
+                let length = re_length
+                    .captures(dict)
+                    .filter(|c| c.get(2).is_none())
+                    .and_then(|c| std::str::from_utf8(&c[1]).ok()?.parse::<usize>().ok());
+                match length {
+                    Some(length) => stream_data_start(data, stream_at) + length,
+                    None => find(data, b"endstream", stream_at).ok_or_else(|| malformed(PdfIssue::UnterminatedStream))?,
+                }
+            }
+            _ => body_start,
+        };
+        let end = find(data, b"endobj", search_from).ok_or_else(|| malformed(PdfIssue::UnterminatedObject))?;
+        objects.push(PdfObject { number, generation, body: &data[body_start..end] });
+        pos = end + b"endobj".len();
+    }
+    Ok(objects)
+}
+
+pub(crate) fn find(data: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    data.get(from..)?.windows(needle.len()).position(|w| w == needle).map(|p| p + from)
+}
+
+/// `stream` 关键字之后（跳过行尾）流数据的起始位置
+pub(crate) fn stream_data_start(data: &[u8], stream_at: usize) -> usize {
+    let mut start = stream_at + b"stream".len();
+    if data.get(start) == Some(&b'\r') {
+        start += 1;
+    }
+    if data.get(start) == Some(&b'\n') {
+        start += 1;
+    }
+    start
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const SAMPLE_CONTENT: &[u8] = b"BT /F1 12 Tf (Hello) Tj ET";
+
+    /// 与 Chrome 输出结构相同的最小文档：目录、页面树、一页、内容流与文档信息
+    pub(crate) fn sample_pdf() -> Vec<u8> {
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        pdf.extend_from_slice(b"1 0 obj\n<< /Type /Catalog /Pages 2 0 R >>\nendobj\n");
+        pdf.extend_from_slice(b"2 0 obj\n<< /Type /Pages /Kids [3 0 R] /Count 1 >>\nendobj\n");
+        pdf.extend_from_slice(b"3 0 obj\n<< /Type /Page /Parent 2 0 R /MediaBox [0 0 200 200] /Contents 4 0 R >>\nendobj\n");
+        pdf.extend_from_slice(format!("4 0 obj\n<< /Length {} >>\nstream\n", SAMPLE_CONTENT.len()).as_bytes());
+        pdf.extend_from_slice(SAMPLE_CONTENT);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n");
+        pdf.extend_from_slice(b"5 0 obj\n<< /Title (Test \\(1\\)) /Producer (Skia/PDF m120) /CreationDate (D:20240102030405+08'00') >>\nendobj\n");
+        pdf.extend_from_slice(b"trailer\n<< /Size 6 /Root 1 0 R /Info 5 0 R /ID [<0011AABB> <0011AABB>] >>\n%%EOF\n");
+        pdf
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        assert_eq!(hex(&[0x00, 0xAB, 0x7F]), "00AB7F");
+        assert_eq!(unhex(b"00ab 7F"), [0x00, 0xAB, 0x7F]);
+        // 奇数个数字时最后一位补 0
+        assert_eq!(unhex(b"ABC"), [0xAB, 0xC0]);
+    }
+
+    #[test]
+    fn objects_are_split_at_their_own_endobj() {
+        let pdf = sample_pdf();
+        let objects = parse_objects(&pdf).unwrap();
+        let numbers: Vec<u32> = objects.iter().map(|o| o.number).collect();
+        assert_eq!(numbers, [1, 2, 3, 4, 5]);
+        assert_eq!(objects[0].body, b"\n<< /Type /Catalog /Pages 2 0 R >>\n");
+    }
+
+    #[test]
+    fn stream_data_may_contain_endobj() {
+        let data = b"endobj endstream";
+        let mut pdf = format!("1 0 obj\n<< /Length {} >>\nstream\n", data.len()).into_bytes();
+        pdf.extend_from_slice(data);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n2 0 obj\n(x)\nendobj\n");
+        let objects = parse_objects(&pdf).unwrap();
+        assert_eq!(objects.len(), 2);
+        let stream = objects[0].body;
+        let start = stream_data_start(stream, find(stream, b"stream", 0).unwrap());
+        assert_eq!(&stream[start..start + data.len()], data);
+    }
+
+    #[test]
+    fn unterminated_objects_are_errors() {
+        assert!(parse_objects(b"1 0 obj\n<< /A 1 >>\n").is_err());
+    }
+}
@@ -0,0 +1,432 @@
+//! 无障碍与归档输出：
+//!  - `tagged`：Chrome 生成带结构标签的 PDF（标题、段落、列表、表格等），文档语言写入 `/Lang`
+//!  - `pdf_a`：在带标签的 PDF 上再做一次 PDF/A-2b 改写：追加 XMP 元数据（`pdfaid`、标题、语言）、
+//!    sRGB 输出意图，目录中声明语言并让阅读器显示文档标题，文档信息与 XMP 保持一致
+//!
+//! 导出选项优先，其次是 front matter 中的 `pdf_standard`，最后是设置中的默认值。
+//! 文档语言取 front matter 中的 `lang` / `language`，未声明时按内容判断。
+//! PDF/A 不允许加密，与输出保护不能同时使用；改写只声明一致性并补齐元数据，不做完整校验。
+
+use crate::error::{AppError, PdfIssue, PdfOperation};
+use crate::html_util::escape_html;
+use crate::pdf::{find, hex, parse_objects, unhex};
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PdfStandard {
+    /// 普通 PDF（不带结构标签）
+    #[default]
+    Standard,
+    /// 带结构标签，供屏幕阅读器使用
+    Tagged,
+    /// 带结构标签的 PDF/A-2b
+    #[serde(alias = "pdfa", alias = "pdf-a")]
+    PdfA,
+}
+
+impl PdfStandard {
+    /// 导出选项优先，其次是 front matter 中的 `pdf_standard`，最后是设置中的默认值
+    pub fn resolve(option: Option<Self>, default: Self, front_matter: Option<&Value>) -> Self {
+        option
+            .or_else(|| {
+                front_matter
+                    .and_then(|fm| fm.get("pdf_standard"))
+                    .and_then(|value| serde_yaml::from_value(value.clone()).ok())
+            })
+            .unwrap_or(default)
+    }
+
+    /// 是否让 Chrome 生成结构标签
+    pub fn tagged(self) -> bool {
+        self != PdfStandard::Standard
+    }
+}
+
+/// 文档语言（BCP 47）：front matter 中的 `lang` / `language`，未声明或格式不对时按内容判断
+pub fn document_language(front_matter: Option<&Value>, content: &str) -> String {
+    let re_tag = regex::Regex::new(r"^[A-Za-z]{2,3}(?:-[A-Za-z0-9]{1,8})*$").unwrap();
+    let declared = front_matter
+        .and_then(|fm| fm.get("lang").or_else(|| fm.get("language")))
+        .and_then(Value::as_str)
+        .map(|lang| lang.trim().replace('_', "-"))
+        .filter(|lang| re_tag.is_match(lang));
+    if let Some(lang) = declared {
+        return lang;
+    }
+    if content.chars().any(|c| matches!(c, '\u{3040}'..='\u{30FF}')) {
+        "ja".to_string()
+    } else if content.chars().any(|c| matches!(c, '\u{AC00}'..='\u{D7AF}')) {
+        "ko".to_string()
+    } else if content.chars().any(crate::typography::is_cjk) {
+        "zh-CN".to_string()
+    } else {
+        "en".to_string()
+    }
+}
+
+/// 写入 PDF/A 元数据的内容
+pub struct ArchiveMetadata {
+    pub title: String,
+    pub lang: String,
+}
+
+fn unsupported(issue: PdfIssue) -> AppError {
+    AppError::PdfStructure { operation: PdfOperation::Archive, issue }
+}
+
+// ---------------------------------------------------------------------------
+// sRGB 输出意图
+// ---------------------------------------------------------------------------
+
+/// sRGB IEC61966-2.1 显示器配置文件（ICC v4.2，480 字节，带精确的参数化色调曲线），
+/// 来自 Compact ICC Profiles 项目，以 CC0 发布
+const SRGB_ICC_PROFILE: &[u8] = include_bytes!("../assets/sRGB-v4.icc");
+
+// ---------------------------------------------------------------------------
+// 元数据
+// ---------------------------------------------------------------------------
+
+/// PDF 日期 `D:YYYYMMDDHHmmSS+HH'mm'` 转换为 XMP 日期
+fn xmp_date(pdf_date: &str) -> Option<String> {
+    let re_date = regex::Regex::new(
+        r"^D:(\d{4})(\d{2})?(\d{2})?(\d{2})?(\d{2})?(\d{2})?(?:([Zz])|([+-])(\d{2})'?(\d{2})?'?)?$",
+    )
+    .unwrap();
+    let caps = re_date.captures(pdf_date.trim())?;
+    let part = |i: usize, default: &'static str| caps.get(i).map_or(default, |m| m.as_str());
+    let zone = if caps.get(7).is_some() {
+        "Z".to_string()
+    } else if let Some(sign) = caps.get(8) {
+        format!("{}{}:{}", sign.as_str(), part(9, "00"), part(10, "00"))
+    } else {
+        String::new()
+    };
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}{}",
+        part(1, "0000"),
+        part(2, "01"),
+        part(3, "01"),
+        part(4, "00"),
+        part(5, "00"),
+        part(6, "00"),
+        zone
+    ))
+}
+
+/// 文档信息中的字面字符串（只取不含转义的简单值）
+fn info_string(info: &[u8], key: &str) -> Option<String> {
+    let re_value = Regex::new(&format!(r"/{}\s*\(([^()\\]*)\)", key)).unwrap();
+    let value = re_value.captures(info)?;
+    Some(String::from_utf8_lossy(&value[1]).to_string())
+}
+
+/// UTF-16BE 文本字符串
+fn text_string(text: &str) -> String {
+    let bytes: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+    format!("<FEFF{}>", hex(&bytes))
+}
+
+struct DocumentInfo {
+    title: String,
+    lang: String,
+    producer: String,
+    created: Option<String>,
+    modified: Option<String>,
+}
+
+const CREATOR: &str = "md2pdf";
+
+impl DocumentInfo {
+    /// 文档信息字典，与 XMP 中的同名项一致
+    fn dictionary(&self) -> String {
+        let mut dict = format!(
+            "<< /Title {} /Creator ({}) /Producer ({})",
+            text_string(&self.title),
+            CREATOR,
+            self.producer
+        );
+        if let Some(created) = &self.created {
+            dict.push_str(&format!(" /CreationDate ({})", created));
+        }
+        if let Some(modified) = &self.modified {
+            dict.push_str(&format!(" /ModDate ({})", modified));
+        }
+        dict.push_str(" >>");
+        dict
+    }
+
+    fn xmp(&self) -> String {
+        let mut dates = String::new();
+        if let Some(created) = self.created.as_deref().and_then(xmp_date) {
+            dates.push_str(&format!("   <xmp:CreateDate>{}</xmp:CreateDate>\n", created));
+        }
+        if let Some(modified) = self.modified.as_deref().and_then(xmp_date) {
+            dates.push_str(&format!("   <xmp:ModifyDate>{}</xmp:ModifyDate>\n", modified));
+        }
+        format!(
+            r#"<?xpacket begin="{bom}" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/"
+    xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:pdf="http://ns.adobe.com/pdf/1.3/">
+   <pdfaid:part>2</pdfaid:part>
+   <pdfaid:conformance>B</pdfaid:conformance>
+   <dc:format>application/pdf</dc:format>
+   <dc:title><rdf:Alt><rdf:li xml:lang="x-default">{title}</rdf:li></rdf:Alt></dc:title>
+   <dc:language><rdf:Bag><rdf:li>{lang}</rdf:li></rdf:Bag></dc:language>
+   <xmp:CreatorTool>{creator}</xmp:CreatorTool>
+{dates}   <pdf:Producer>{producer}</pdf:Producer>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#,
+            bom = '\u{FEFF}',
+            title = escape_html(&self.title),
+            lang = escape_html(&self.lang),
+            creator = CREATOR,
+            dates = dates,
+            producer = escape_html(&self.producer),
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PDF 改写
+// ---------------------------------------------------------------------------
+
+/// 目录字典：去掉将被替换的项，追加元数据、输出意图、语言与阅读器选项
+fn rewrite_catalog(body: &[u8], lang: &str, metadata: u32, intent: u32) -> Result<Vec<u8>, AppError> {
+    let re_replaced = Regex::new(
+        r"/(?:Metadata\s+\d+\s+\d+\s+R|OutputIntents\s*(?:\[[^\]]*\]|\d+\s+\d+\s+R)|Lang\s*(?:\([^)]*\)|<[^>]*>)|ViewerPreferences\s*(?:<<[^>]*>>|\d+\s+\d+\s+R))",
+    )
+    .unwrap();
+    let body = re_replaced.replace_all(body, &b""[..]);
+    let end = body.windows(2).rposition(|w| w == b">>").ok_or_else(|| unsupported(PdfIssue::CatalogNotDictionary))?;
+    let mut entries = format!(
+        " /Metadata {} 0 R /OutputIntents [{} 0 R] /Lang ({}) /ViewerPreferences << /DisplayDocTitle true >>",
+        metadata, intent, lang
+    );
+    // 带标签的文档需要声明 `/MarkInfo`
+    if find(&body, b"/StructTreeRoot", 0).is_some() && find(&body, b"/MarkInfo", 0).is_none() {
+        entries.push_str(" /MarkInfo << /Marked true >>");
+    }
+    let mut out = body[..end].to_vec();
+    out.extend_from_slice(entries.as_bytes());
+    out.extend_from_slice(&body[end..]);
+    Ok(out)
+}
+
+/// 把 Chrome 生成的 PDF 改写为 PDF/A-2b
+pub fn archive(pdf: &[u8], metadata: &ArchiveMetadata) -> Result<Vec<u8>, AppError> {
+    let trailer_at = pdf.windows(7).rposition(|w| w == b"trailer").ok_or_else(|| unsupported(PdfIssue::MissingTrailer))?;
+    let trailer = &pdf[trailer_at..];
+    if find(pdf, b"/Encrypt", 0).is_some() {
+        return Err(AppError::ArchiveProtected);
+    }
+    let re_ref = |key: &str| Regex::new(&format!(r"/{}\s+(\d+)\s+\d+\s+R", key)).unwrap();
+    let number_of = |key: &str| -> Option<u32> {
+        re_ref(key).captures(trailer).and_then(|c| std::str::from_utf8(&c[1]).ok()?.parse().ok())
+    };
+    let root = number_of("Root").ok_or_else(|| unsupported(PdfIssue::MissingRoot))?;
+    let info = number_of("Info");
+    let id = Regex::new(r"/ID\s*\[\s*<([0-9A-Fa-f\s]*)>")
+        .unwrap()
+        .captures(trailer)
+        .map(|c| unhex(&c[1]))
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| {
+            let digest = crate::hashing::sha256_hex(pdf);
+            unhex(&digest.as_bytes()[..32])
+        });
+
+    let objects = parse_objects(pdf)?;
+    if objects.is_empty() {
+        return Err(unsupported(PdfIssue::NoObjects));
+    }
+    if objects.iter().any(|o| find(o.body, b"/ObjStm", 0).is_some() || find(o.body, b"/XRef", 0).is_some()) {
+        return Err(unsupported(PdfIssue::CompressedObjects));
+    }
+    let catalog = objects.iter().rev().find(|o| o.number == root).ok_or_else(|| unsupported(PdfIssue::MissingCatalog))?;
+    let old_info = info.and_then(|info| objects.iter().rev().find(|o| o.number == info)).map(|o| o.body).unwrap_or(b"");
+
+    let document = DocumentInfo {
+        title: metadata.title.clone(),
+        lang: metadata.lang.clone(),
+        producer: info_string(old_info, "Producer").unwrap_or_else(|| "Skia/PDF".to_string()),
+        created: info_string(old_info, "CreationDate"),
+        modified: info_string(old_info, "ModDate"),
+    };
+
+    let next = objects.iter().map(|o| o.number).max().unwrap_or(0) + 1;
+    let (metadata_number, profile_number, intent_number) = (next, next + 1, next + 2);
+    let info_number = info.unwrap_or(next + 3);
+
+    let header_end = find(pdf, b"\n", 0).map(|p| p + 1).unwrap_or(0);
+    let mut out = pdf[..header_end].to_vec();
+    out.extend_from_slice(b"%\xE2\xE3\xCF\xD3\n");
+    let mut offsets: HashMap<u32, (usize, u16)> = HashMap::new();
+    let mut write_object = |out: &mut Vec<u8>, number: u32, generation: u16, body: &[u8]| {
+        offsets.insert(number, (out.len(), generation));
+        out.extend_from_slice(format!("{} {} obj", number, generation).as_bytes());
+        out.extend_from_slice(body);
+        out.extend_from_slice(b"endobj\n");
+    };
+    for object in &objects {
+        if object.number == root {
+            let body = rewrite_catalog(catalog.body, &metadata.lang, metadata_number, intent_number)?;
+            write_object(&mut out, object.number, object.generation, &body);
+        } else if object.number != info_number {
+            write_object(&mut out, object.number, object.generation, object.body);
+        }
+    }
+
+    let xmp = document.xmp();
+    let mut body = format!("\n<< /Type /Metadata /Subtype /XML /Length {} >>\nstream\n", xmp.len()).into_bytes();
+    body.extend_from_slice(xmp.as_bytes());
+    body.extend_from_slice(b"\nendstream\n");
+    write_object(&mut out, metadata_number, 0, &body);
+
+    let mut body = format!("\n<< /N 3 /Length {} >>\nstream\n", SRGB_ICC_PROFILE.len()).into_bytes();
+    body.extend_from_slice(SRGB_ICC_PROFILE);
+    body.extend_from_slice(b"\nendstream\n");
+    write_object(&mut out, profile_number, 0, &body);
+
+    let intent = format!(
+        "\n<< /Type /OutputIntent /S /GTS_PDFA1 /OutputConditionIdentifier (sRGB IEC61966-2.1) /Info (sRGB IEC61966-2.1) /DestOutputProfile {} 0 R >>\n",
+        profile_number
+    );
+    write_object(&mut out, intent_number, 0, intent.as_bytes());
+    write_object(&mut out, info_number, 0, format!("\n{}\n", document.dictionary()).as_bytes());
+
+    let xref_at = out.len();
+    let size = offsets.keys().max().copied().unwrap_or(0) + 1;
+    out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", size).as_bytes());
+    for number in 1..size {
+        match offsets.get(&number) {
+            Some((offset, generation)) => out.extend_from_slice(format!("{:010} {:05} n \n", offset, generation).as_bytes()),
+            None => out.extend_from_slice(b"0000000000 65535 f \n"),
+        }
+    }
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root {} {} R /Info {} 0 R /ID [<{}> <{}>] >>\nstartxref\n{}\n%%EOF\n",
+            size,
+            root,
+            catalog.generation,
+            info_number,
+            hex(&id),
+            hex(&id),
+            xref_at
+        )
+        .as_bytes(),
+    );
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::tests::sample_pdf;
+
+    fn metadata() -> ArchiveMetadata {
+        ArchiveMetadata { title: "报告 <1>".to_string(), lang: "zh-CN".to_string() }
+    }
+
+    fn object(pdf: &[u8], number: u32) -> Vec<u8> {
+        parse_objects(pdf).unwrap().into_iter().find(|o| o.number == number).unwrap().body.to_vec()
+    }
+
+    #[test]
+    fn srgb_profile_is_a_valid_icc_header() {
+        let profile = SRGB_ICC_PROFILE;
+        assert_eq!(u32::from_be_bytes(profile[..4].try_into().unwrap()) as usize, profile.len());
+        assert_eq!(&profile[12..16], b"mntr");
+        assert_eq!(&profile[16..20], b"RGB ");
+        assert_eq!(&profile[20..24], b"XYZ ");
+        assert_eq!(&profile[36..40], b"acsp");
+        // PDF/A-2 接受 ICC.1:2004-10（v4.2）及更早的版本
+        assert!(profile[8] <= 4);
+    }
+
+    #[test]
+    fn catalog_declares_metadata_intent_and_language() {
+        let out = archive(&sample_pdf(), &metadata()).unwrap();
+        let catalog = String::from_utf8(object(&out, 1)).unwrap();
+        assert!(catalog.contains("/Type /Catalog /Pages 2 0 R"));
+        assert!(catalog.contains("/Metadata 6 0 R"));
+        assert!(catalog.contains("/OutputIntents [8 0 R]"));
+        assert!(catalog.contains("/Lang (zh-CN)"));
+        assert!(catalog.contains("/DisplayDocTitle true"));
+
+        let intent = String::from_utf8(object(&out, 8)).unwrap();
+        assert!(intent.contains("/S /GTS_PDFA1"));
+        assert!(intent.contains("/DestOutputProfile 7 0 R"));
+        let profile = object(&out, 7);
+        assert!(find(&profile, SRGB_ICC_PROFILE, 0).is_some());
+    }
+
+    #[test]
+    fn xmp_matches_the_document_information() {
+        let out = archive(&sample_pdf(), &metadata()).unwrap();
+        let xmp = String::from_utf8(object(&out, 6)).unwrap();
+        assert!(xmp.contains("/Type /Metadata /Subtype /XML"));
+        assert!(xmp.contains("<pdfaid:part>2</pdfaid:part>"));
+        assert!(xmp.contains("<pdfaid:conformance>B</pdfaid:conformance>"));
+        assert!(xmp.contains("<rdf:li xml:lang=\"x-default\">报告 &lt;1&gt;</rdf:li>"));
+        assert!(xmp.contains("<pdf:Producer>Skia/PDF m120</pdf:Producer>"));
+        assert!(xmp.contains("<xmp:CreateDate>2024-01-02T03:04:05+08:00</xmp:CreateDate>"));
+
+        // 文档信息沿用原来的对象编号，标题为 UTF-16BE，日期与 XMP 一致
+        let info = String::from_utf8(object(&out, 5)).unwrap();
+        assert!(info.contains(&format!("/Title {}", text_string("报告 <1>"))));
+        assert!(info.contains("/Producer (Skia/PDF m120)"));
+        assert!(info.contains("/CreationDate (D:20240102030405+08'00')"));
+    }
+
+    #[test]
+    fn trailer_and_xref_point_at_the_rewritten_objects() {
+        let out = archive(&sample_pdf(), &metadata()).unwrap();
+        let trailer_at = find(&out, b"trailer", 0).unwrap();
+        let trailer = String::from_utf8_lossy(&out[trailer_at..]).to_string();
+        assert!(trailer.contains("/Size 9 /Root 1 0 R /Info 5 0 R /ID [<0011AABB> <0011AABB>]"));
+
+        let xref_at: usize = trailer.split("startxref\n").nth(1).unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(out[xref_at..].starts_with(b"xref\n0 9\n"));
+        let entries: Vec<&str> = std::str::from_utf8(&out[xref_at..trailer_at]).unwrap().lines().skip(3).collect();
+        for (number, entry) in (1..).zip(entries) {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(out[offset..].starts_with(format!("{} 0 obj", number).as_bytes()), "{}", number);
+        }
+    }
+
+    #[test]
+    fn encrypted_input_is_rejected() {
+        let mut pdf = sample_pdf();
+        pdf.extend_from_slice(b"trailer\n<< /Root 1 0 R /Encrypt 9 0 R >>\n");
+        assert!(matches!(archive(&pdf, &metadata()), Err(AppError::ArchiveProtected)));
+    }
+
+    #[test]
+    fn language_comes_from_front_matter_or_content() {
+        let fm: Value = serde_yaml::from_str("lang: en_GB").unwrap();
+        assert_eq!(document_language(Some(&fm), "中文"), "en-GB");
+        assert_eq!(document_language(None, "ひらがな"), "ja");
+        assert_eq!(document_language(None, "中文"), "zh-CN");
+        assert_eq!(document_language(None, "plain"), "en");
+    }
+
+    #[test]
+    fn option_overrides_front_matter_and_default() {
+        let fm: Value = serde_yaml::from_str("pdf_standard: pdf-a").unwrap();
+        assert_eq!(PdfStandard::resolve(None, PdfStandard::Standard, Some(&fm)), PdfStandard::PdfA);
+        assert_eq!(PdfStandard::resolve(Some(PdfStandard::Tagged), PdfStandard::Standard, Some(&fm)), PdfStandard::Tagged);
+        assert_eq!(PdfStandard::resolve(None, PdfStandard::Tagged, None), PdfStandard::Tagged);
+    }
+}
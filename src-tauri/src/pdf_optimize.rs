@@ -0,0 +1,162 @@
+//! PDF 体积优化：打印前把分辨率高于目标 DPI 的图片在页面中缩小并重新压缩（不透明图片为 JPEG，
+//! 带透明通道的为 PNG），再重新打印，并报告优化前后的文件大小。
+//!
+//! 本地与远程图片由后端读取后以 data URL 交给页面处理，避免 `file://` 图片污染画布而无法导出。
+//! 矢量图（SVG）不处理；优化后反而更大时保留原始 PDF。
+
+use crate::cancel::CancelToken;
+use crate::error::{run_blocking, AppError};
+use crate::LoadedPage;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageOptimization {
+    /// 图片在页面上的最高分辨率，超过时缩小
+    pub max_dpi: u32,
+    /// 重新压缩为 JPEG 时的质量（1–100）
+    pub quality: u32,
+}
+
+impl Default for ImageOptimization {
+    fn default() -> Self {
+        ImageOptimization { max_dpi: 150, quality: 80 }
+    }
+}
+
+/// 优化结果，随导出报告返回
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizationReport {
+    pub size_before: u64,
+    pub size_after: u64,
+    /// 缩小或重新压缩的图片数量
+    pub images_optimized: usize,
+}
+
+/// 需要缩小的图片：页面中的序号、地址与目标像素尺寸
+#[derive(Debug, Deserialize)]
+struct Candidate {
+    index: usize,
+    src: String,
+    width: u32,
+    height: u32,
+}
+
+/// 找出像素尺寸超过 `maxDpi` 下显示尺寸的图片
+fn candidates_script(max_dpi: u32) -> String {
+    format!(
+        r#"(() => {{
+    const ratio = {max_dpi} / 96;
+    const result = [];
+    Array.from(document.images).forEach((img, index) => {{
+        const src = img.currentSrc || img.src;
+        if (!img.complete || img.naturalWidth === 0 || /\.svg(\?|#|$)|^data:image\/svg/i.test(src)) return;
+        const rect = img.getBoundingClientRect();
+        if (rect.width === 0 || rect.height === 0) return;
+        const width = Math.ceil(rect.width * ratio);
+        const height = Math.ceil(rect.height * ratio);
+        if (img.naturalWidth > width * 1.05) result.push({{ index, src, width, height }});
+    }});
+    return JSON.stringify(result);
+}})()"#,
+        max_dpi = max_dpi
+    )
+}
+
+/// 在画布上缩小一张图片并替换页面中的图片，返回是否替换
+fn downsample_script(candidate: &Candidate, data_url: &str, quality: u32) -> String {
+    format!(
+        r#"(async () => {{
+    const target = document.images[{index}];
+    if (!target) return false;
+    const source = new Image();
+    source.src = {data_url};
+    await source.decode();
+    const canvas = document.createElement('canvas');
+    canvas.width = {width};
+    canvas.height = {height};
+    const ctx = canvas.getContext('2d');
+    ctx.imageSmoothingQuality = 'high';
+    ctx.drawImage(source, 0, 0, canvas.width, canvas.height);
+    const pixels = ctx.getImageData(0, 0, canvas.width, canvas.height).data;
+    let opaque = true;
+    for (let i = 3; i < pixels.length; i += 4) {{
+        if (pixels[i] < 255) {{ opaque = false; break; }}
+    }}
+    const url = opaque ? canvas.toDataURL('image/jpeg', {quality} / 100) : canvas.toDataURL('image/png');
+    if (url.length >= {data_url}.length) return false;
+    // 固定显示尺寸，缩小后的图片不改变排版
+    const rect = target.getBoundingClientRect();
+    target.style.width = rect.width + 'px';
+    target.style.height = rect.height + 'px';
+    target.removeAttribute('srcset');
+    target.src = url;
+    await target.decode();
+    return true;
+}})()"#,
+        index = candidate.index,
+        data_url = serde_json::to_string(data_url).unwrap_or_default(),
+        width = candidate.width.max(1),
+        height = candidate.height.max(1),
+        quality = quality
+    )
+}
+
+/// 读取图片并转换为 data URL；已经是 data URL 时原样返回
+fn load_data_url(src: &str) -> Result<String, String> {
+    let Some((data, extension)) = crate::assets::fetch_asset(src, Path::new(""))? else {
+        return Ok(src.to_string());
+    };
+    let mime = match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg".to_string(),
+        other => format!("image/{}", other),
+    };
+    Ok(format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(data)))
+}
+
+/// 缩小页面中分辨率过高的图片，返回处理的图片数量；单张图片失败时跳过
+pub async fn optimize_images(
+    page: &LoadedPage,
+    options: &ImageOptimization,
+    cancel: &CancelToken,
+) -> Result<usize, AppError> {
+    let max_dpi = options.max_dpi.clamp(72, 600);
+    let quality = options.quality.clamp(1, 100);
+
+    let tab = page.tab.clone();
+    let candidates_json = run_blocking("optimize_images", move || {
+        tab.evaluate(&candidates_script(max_dpi), false)
+            .map(|result| result.value.and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default())
+            .map_err(|e| AppError::browser("BROWSER_IMAGE_CHECK", e))
+    })
+    .await?;
+    let candidates: Vec<Candidate> = serde_json::from_str(&candidates_json).unwrap_or_default();
+
+    let mut optimized = 0;
+    for candidate in candidates {
+        cancel.check()?;
+        let tab = page.tab.clone();
+        let replace = run_blocking("optimize_images", move || {
+            let data_url = match load_data_url(&candidate.src) {
+                Ok(url) => url,
+                Err(e) => {
+                    tracing::warn!(src = %candidate.src, "读取图片失败，跳过优化: {}", e);
+                    return Ok(false);
+                }
+            };
+            match tab.evaluate(&downsample_script(&candidate, &data_url, quality), true) {
+                Ok(result) => Ok(result.value.and_then(|v| v.as_bool()).unwrap_or(false)),
+                Err(e) => {
+                    tracing::warn!(src = %candidate.src, "缩小图片失败，跳过优化: {}", e);
+                    Ok(false)
+                }
+            }
+        });
+        if crate::timeouts::limit(&page.timeouts, "print", replace).await? {
+            optimized += 1;
+        }
+    }
+    Ok(optimized)
+}
@@ -0,0 +1,526 @@
+//! PDF 加密与权限：在 Chrome 生成 PDF 之后，用标准安全处理程序（AES-256，修订版 6）加密，
+//! 设置打开密码（用户密码）、权限密码（所有者密码）以及打印、复制、修改、注释权限。
+//!
+//! 只处理 Chrome 输出的传统交叉引用表结构：逐个改写间接对象（加密其中的字符串与流），
+//! 追加加密字典，重新生成交叉引用表与 trailer。修订版 6 属于 PDF 2.0，文件头随之改为 `%PDF-2.0`。
+
+use crate::error::{AppError, PdfIssue, PdfOperation};
+use crate::pdf::{find, hex, parse_objects, stream_data_start, unhex};
+use aes::cipher::block_padding::{NoPadding, Pkcs7};
+use aes::cipher::{BlockEncrypt, BlockEncryptMut, KeyInit, KeyIvInit};
+use regex::bytes::Regex;
+use serde::Deserialize;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::collections::HashMap;
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+type Aes256CbcEnc = cbc::Encryptor<aes::Aes256>;
+
+/// 输出保护选项；两个密码都为空且所有权限都允许时不加密
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Protection {
+    /// 打开文档需要的密码，为空表示无需密码即可打开
+    pub user_password: Option<String>,
+    /// 修改权限需要的密码，为空时随机生成（权限无法被解除）
+    pub owner_password: Option<String>,
+    pub allow_print: bool,
+    /// 高质量打印（不允许时只能以低分辨率打印）
+    pub allow_high_quality_print: bool,
+    pub allow_copy: bool,
+    pub allow_modify: bool,
+    pub allow_annotate: bool,
+}
+
+impl Default for Protection {
+    fn default() -> Self {
+        Protection {
+            user_password: None,
+            owner_password: None,
+            allow_print: true,
+            allow_high_quality_print: true,
+            allow_copy: true,
+            allow_modify: true,
+            allow_annotate: true,
+        }
+    }
+}
+
+impl Protection {
+    pub(crate) fn is_noop(&self) -> bool {
+        self.user_password.as_deref().unwrap_or("").is_empty()
+            && self.owner_password.as_deref().unwrap_or("").is_empty()
+            && self.allow_print
+            && self.allow_high_quality_print
+            && self.allow_copy
+            && self.allow_modify
+            && self.allow_annotate
+    }
+
+    /// 加密字典中的 `/P`：第 7、8 位与 13–32 位必须为 1
+    fn permission_flags(&self) -> i32 {
+        let mut flags: u32 = 0xFFFF_F0C0;
+        if self.allow_print {
+            flags |= 1 << 2;
+            if self.allow_high_quality_print {
+                flags |= 1 << 11;
+            }
+        }
+        if self.allow_modify {
+            flags |= (1 << 3) | (1 << 10);
+        }
+        if self.allow_copy {
+            flags |= (1 << 4) | (1 << 9);
+        } else {
+            // 辅助功能读取始终允许
+            flags |= 1 << 9;
+        }
+        if self.allow_annotate {
+            flags |= (1 << 5) | (1 << 8);
+        }
+        flags as i32
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 标准安全处理程序（修订版 6）
+// ---------------------------------------------------------------------------
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], AppError> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| AppError::Internal { context: "pdf_protect".to_string(), reason: e.to_string() })?;
+    Ok(bytes)
+}
+
+/// 密码按 UTF-8 编码，最多 127 字节（未做 SASLprep 规范化，ASCII 以外的密码按原样使用）
+fn password_bytes(password: &str) -> &[u8] {
+    let mut end = password.len().min(127);
+    while !password.is_char_boundary(end) {
+        end -= 1;
+    }
+    &password.as_bytes()[..end]
+}
+
+/// 算法 2.B：密码哈希；计算所有者密码相关的值时 `user_entry` 为 48 字节的 `/U`，否则为空
+fn hash_r6(password: &[u8], salt: &[u8], user_entry: &[u8]) -> [u8; 32] {
+    let mut k = Sha256::new().chain_update(password).chain_update(salt).chain_update(user_entry).finalize().to_vec();
+    let mut round = 0;
+    loop {
+        let mut k1 = Vec::with_capacity(64 * (password.len() + k.len() + user_entry.len()));
+        for _ in 0..64 {
+            k1.extend_from_slice(password);
+            k1.extend_from_slice(&k);
+            k1.extend_from_slice(user_entry);
+        }
+        // k1 的长度是 64 的倍数，无需填充
+        let e = Aes128CbcEnc::new_from_slices(&k[..16], &k[16..32])
+            .expect("密钥与 IV 长度固定")
+            .encrypt_padded_vec_mut::<NoPadding>(&k1);
+        // 前 16 字节作为大端整数模 3，等于各字节之和模 3
+        k = match e[..16].iter().map(|&b| b as u32).sum::<u32>() % 3 {
+            0 => Sha256::digest(&e).to_vec(),
+            1 => Sha384::digest(&e).to_vec(),
+            _ => Sha512::digest(&e).to_vec(),
+        };
+        round += 1;
+        if round >= 64 && e[e.len() - 1] as usize <= round - 32 {
+            break;
+        }
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&k[..32]);
+    hash
+}
+
+/// 用中间密钥以全零 IV 加密文件密钥（`/UE`、`/OE`）
+fn wrap_file_key(intermediate: &[u8; 32], file_key: &[u8; 32]) -> Vec<u8> {
+    Aes256CbcEnc::new(intermediate.into(), &[0u8; 16].into()).encrypt_padded_vec_mut::<NoPadding>(file_key)
+}
+
+/// 加密字典中与密码相关的值
+struct SecurityEntries {
+    owner: Vec<u8>,
+    user: Vec<u8>,
+    owner_key: Vec<u8>,
+    user_key: Vec<u8>,
+    perms: Vec<u8>,
+}
+
+/// 算法 8–10：`/U`、`/UE`、`/O`、`/OE` 与 `/Perms`
+fn security_entries(
+    user_password: &[u8],
+    owner_password: &[u8],
+    permissions: i32,
+    file_key: &[u8; 32],
+) -> Result<SecurityEntries, AppError> {
+    // 验证盐与密钥盐各 8 字节
+    let user_salts = random_bytes::<16>()?;
+    let mut user = hash_r6(user_password, &user_salts[..8], &[]).to_vec();
+    user.extend_from_slice(&user_salts);
+    let user_key = wrap_file_key(&hash_r6(user_password, &user_salts[8..], &[]), file_key);
+
+    let owner_salts = random_bytes::<16>()?;
+    let mut owner = hash_r6(owner_password, &owner_salts[..8], &user).to_vec();
+    owner.extend_from_slice(&owner_salts);
+    let owner_key = wrap_file_key(&hash_r6(owner_password, &owner_salts[8..], &user), file_key);
+
+    // 权限的副本，防止 `/P` 被篡改：P（小端）、全 1、加密元数据、"adb" 与 4 个随机字节
+    let mut block = [0u8; 16];
+    block[..4].copy_from_slice(&permissions.to_le_bytes());
+    block[4..8].fill(0xFF);
+    block[8] = b'T';
+    block[9..12].copy_from_slice(b"adb");
+    block[12..].copy_from_slice(&random_bytes::<4>()?);
+    let mut block = block.into();
+    aes::Aes256::new(file_key.into()).encrypt_block(&mut block);
+
+    Ok(SecurityEntries { owner, user, owner_key, user_key, perms: block.to_vec() })
+}
+
+/// 加密字符串或流数据：随机 IV 在前，PKCS#7 填充
+fn aes_encrypt(file_key: &[u8; 32], data: &[u8]) -> Result<Vec<u8>, AppError> {
+    let iv = random_bytes::<16>()?;
+    let mut out = iv.to_vec();
+    out.extend(Aes256CbcEnc::new(file_key.into(), &iv.into()).encrypt_padded_vec_mut::<Pkcs7>(data));
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// PDF 改写
+// ---------------------------------------------------------------------------
+
+fn unsupported(issue: PdfIssue) -> AppError {
+    AppError::PdfStructure { operation: PdfOperation::Protect, issue }
+}
+
+/// 解析从 `start`（`(` 之后）开始的字面字符串，返回内容与 `)` 之后的位置
+fn read_literal_string(data: &[u8], start: usize) -> Result<(Vec<u8>, usize), AppError> {
+    let mut out = Vec::new();
+    let mut depth = 1;
+    let mut i = start;
+    while i < data.len() {
+        let c = data[i];
+        match c {
+            b'\\' => {
+                i += 1;
+                let Some(&next) = data.get(i) else { break };
+                match next {
+                    b'n' => out.push(b'\n'),
+                    b'r' => out.push(b'\r'),
+                    b't' => out.push(b'\t'),
+                    b'b' => out.push(0x08),
+                    b'f' => out.push(0x0C),
+                    b'0'..=b'7' => {
+                        let mut value = 0u32;
+                        let mut len = 0;
+                        while len < 3 && matches!(data.get(i), Some(b'0'..=b'7')) {
+                            value = value * 8 + (data[i] - b'0') as u32;
+                            i += 1;
+                            len += 1;
+                        }
+                        out.push(value as u8);
+                        continue;
+                    }
+                    // 行尾续行
+                    b'\r' => {
+                        if data.get(i + 1) == Some(&b'\n') {
+                            i += 1;
+                        }
+                    }
+                    b'\n' => {}
+                    other => out.push(other),
+                }
+            }
+            b'(' => {
+                depth += 1;
+                out.push(c);
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((out, i + 1));
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+    Err(unsupported(PdfIssue::UnterminatedString))
+}
+
+/// 加密对象内容中的字符串与流数据；间接的 `/Length` 从 `lengths` 中查找
+fn encrypt_body(body: &[u8], key: &[u8; 32], lengths: &HashMap<u32, usize>) -> Result<Vec<u8>, AppError> {
+    let re_length = Regex::new(r"/Length\s+(\d+)(?:\s+(\d+)\s+R)?").unwrap();
+    let mut out = Vec::with_capacity(body.len());
+    let mut i = 0;
+    while i < body.len() {
+        match body[i] {
+            b'(' => {
+                let (content, next) = read_literal_string(body, i + 1)?;
+                out.extend_from_slice(format!("<{}>", hex(&aes_encrypt(key, &content)?)).as_bytes());
+                i = next;
+            }
+            b'<' if body.get(i + 1) == Some(&b'<') => {
+                out.extend_from_slice(b"<<");
+                i += 2;
+            }
+            b'<' => {
+                let end = find(body, b">", i).ok_or_else(|| unsupported(PdfIssue::UnterminatedHexString))?;
+                let content = unhex(&body[i + 1..end]);
+                out.extend_from_slice(format!("<{}>", hex(&aes_encrypt(key, &content)?)).as_bytes());
+                i = end + 1;
+            }
+            b'%' => {
+                while i < body.len() && !matches!(body[i], b'\r' | b'\n') {
+                    out.push(body[i]);
+                    i += 1;
+                }
+            }
+            b's' if body[i..].starts_with(b"stream") && !body[..i].ends_with(b"end") => {
+                let length = re_length.captures(&out).and_then(|caps| {
+                    let value: usize = std::str::from_utf8(&caps[1]).ok()?.parse().ok()?;
+                    match caps.get(2) {
+                        Some(_) => lengths.get(&(value as u32)).copied(),
+                        None => Some(value),
+                    }
+                });
+                let start = stream_data_start(body, i);
+                let length = match length {
+                    Some(length) => length,
+                    None => {
+                        find(body, b"endstream", start).ok_or_else(|| unsupported(PdfIssue::UnterminatedStream))? - start
+                    }
+                };
+                let end = (start + length).min(body.len());
+                let encrypted = aes_encrypt(key, &body[start..end])?;
+                // 加密后长度改变：`/Length`（包括间接引用）改写为加密后的长度
+                if !re_length.is_match(&out) {
+                    return Err(unsupported(PdfIssue::MissingLength));
+                }
+                out = re_length.replace(&out[..], format!("/Length {}", encrypted.len()).as_bytes()).into_owned();
+                out.extend_from_slice(&body[i..start]);
+                out.extend_from_slice(&encrypted);
+                i = end;
+            }
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// 加密 PDF；`protection` 不要求任何保护时原样返回
+pub fn protect(pdf: &[u8], protection: &Protection) -> Result<Vec<u8>, AppError> {
+    if protection.is_noop() {
+        return Ok(pdf.to_vec());
+    }
+
+    let trailer_at = pdf.windows(7).rposition(|w| w == b"trailer").ok_or_else(|| unsupported(PdfIssue::MissingTrailer))?;
+    let trailer = &pdf[trailer_at..];
+    if find(pdf, b"/Encrypt", 0).is_some() {
+        return Err(unsupported(PdfIssue::AlreadyEncrypted));
+    }
+    let re_ref = |key: &str| Regex::new(&format!(r"/{}\s+(\d+\s+\d+\s+R)", key)).unwrap();
+    let root = re_ref("Root")
+        .captures(trailer)
+        .map(|c| String::from_utf8_lossy(&c[1]).to_string())
+        .ok_or_else(|| unsupported(PdfIssue::MissingRoot))?;
+    let info = re_ref("Info").captures(trailer).map(|c| String::from_utf8_lossy(&c[1]).to_string());
+    let id = Regex::new(r"/ID\s*\[\s*<([0-9A-Fa-f\s]*)>")
+        .unwrap()
+        .captures(trailer)
+        .map(|c| unhex(&c[1]))
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| {
+            let digest = crate::hashing::sha256_hex(pdf);
+            unhex(&digest.as_bytes()[..32])
+        });
+
+    // 随机的文件密钥与加密字典
+    let owner_password = match protection.owner_password.as_deref().filter(|p| !p.is_empty()) {
+        Some(password) => password.to_string(),
+        None => hex(&random_bytes::<16>()?),
+    };
+    let permissions = protection.permission_flags();
+    let key = random_bytes::<32>()?;
+    let entries = security_entries(
+        password_bytes(protection.user_password.as_deref().unwrap_or("")),
+        password_bytes(&owner_password),
+        permissions,
+        &key,
+    )?;
+
+    let objects = parse_objects(pdf)?;
+    if objects.is_empty() {
+        return Err(unsupported(PdfIssue::NoObjects));
+    }
+    if objects.iter().any(|o| find(o.body, b"/ObjStm", 0).is_some() || find(o.body, b"/XRef", 0).is_some()) {
+        return Err(unsupported(PdfIssue::CompressedObjects));
+    }
+    // 间接的流长度
+    let lengths: HashMap<u32, usize> = objects
+        .iter()
+        .filter_map(|o| Some((o.number, std::str::from_utf8(o.body).ok()?.trim().parse().ok()?)))
+        .collect();
+
+    let mut out = b"%PDF-2.0\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets: HashMap<u32, (usize, u16)> = HashMap::new();
+    for object in &objects {
+        let encrypted = encrypt_body(object.body, &key, &lengths)?;
+        offsets.insert(object.number, (out.len(), object.generation));
+        out.extend_from_slice(format!("{} {} obj", object.number, object.generation).as_bytes());
+        out.extend_from_slice(&encrypted);
+        out.extend_from_slice(b"endobj\n");
+    }
+
+    let encrypt_number = objects.iter().map(|o| o.number).max().unwrap_or(0) + 1;
+    offsets.insert(encrypt_number, (out.len(), 0));
+    out.extend_from_slice(
+        format!(
+            "{} 0 obj\n<< /Filter /Standard /V 5 /R 6 /Length 256 \
+             /CF << /StdCF << /Type /CryptFilter /CFM /AESV3 /AuthEvent /DocOpen /Length 32 >> >> \
+             /StmF /StdCF /StrF /StdCF /O <{}> /U <{}> /OE <{}> /UE <{}> /P {} /Perms <{}> >>\nendobj\n",
+            encrypt_number,
+            hex(&entries.owner),
+            hex(&entries.user),
+            hex(&entries.owner_key),
+            hex(&entries.user_key),
+            permissions,
+            hex(&entries.perms)
+        )
+        .as_bytes(),
+    );
+
+    let xref_at = out.len();
+    let size = encrypt_number + 1;
+    out.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", size).as_bytes());
+    for number in 1..size {
+        match offsets.get(&number) {
+            Some((offset, generation)) => out.extend_from_slice(format!("{:010} {:05} n \n", offset, generation).as_bytes()),
+            None => out.extend_from_slice(b"0000000000 65535 f \n"),
+        }
+    }
+    let info = info.map(|info| format!(" /Info {}", info)).unwrap_or_default();
+    out.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root {}{} /Encrypt {} 0 R /ID [<{}> <{}>] >>\nstartxref\n{}\n%%EOF\n",
+            size,
+            root,
+            info,
+            encrypt_number,
+            hex(&id),
+            hex(&id),
+            xref_at
+        )
+        .as_bytes(),
+    );
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdf::tests::{sample_pdf, SAMPLE_CONTENT as CONTENT};
+    use aes::cipher::{BlockDecrypt, BlockDecryptMut};
+
+    type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+
+    fn entry(pdf: &[u8], key: &str) -> Vec<u8> {
+        let re = Regex::new(&format!(r"/{}\s*<([0-9A-F]+)>", key)).unwrap();
+        unhex(&re.captures(pdf).unwrap()[1])
+    }
+
+    fn unwrap_file_key(intermediate: &[u8; 32], wrapped: &[u8]) -> Vec<u8> {
+        Aes256CbcDec::new(intermediate.into(), &[0u8; 16].into()).decrypt_padded_vec_mut::<NoPadding>(wrapped).unwrap()
+    }
+
+    fn aes_decrypt(file_key: &[u8], data: &[u8]) -> Vec<u8> {
+        let (iv, data) = data.split_at(16);
+        Aes256CbcDec::new_from_slices(file_key, iv).unwrap().decrypt_padded_vec_mut::<Pkcs7>(data).unwrap()
+    }
+
+    /// 参考值由独立的实现（Python hashlib 与 cryptography）按 ISO 32000-2 算法 2.B 计算
+    #[test]
+    fn password_hash_matches_reference_values() {
+        let salt: Vec<u8> = (0..8).collect();
+        assert_eq!(hex(&hash_r6(b"user", &salt, &[])), "731758C09C8B0160A34721D18BDD24220ABADA0070AA3F05B8103FD5B8D05F17");
+        let salt: Vec<u8> = (8..16).collect();
+        let user_entry: Vec<u8> = (0..48).collect();
+        assert_eq!(
+            hex(&hash_r6("密码".as_bytes(), &salt, &user_entry)),
+            "79BE79DB790A856631DD7345FFDD0AAB78EF88E48E1DAFD884E416C8BA9493CD"
+        );
+    }
+
+    #[test]
+    fn passwords_are_truncated_on_char_boundaries() {
+        assert_eq!(password_bytes("abc"), b"abc");
+        let long = "密".repeat(50);
+        assert_eq!(password_bytes(&long).len(), 126);
+    }
+
+    #[test]
+    fn permission_flags_set_reserved_bits() {
+        let flags = Protection::default().permission_flags() as u32;
+        assert_eq!(flags & 0xFFFF_F0C0, 0xFFFF_F0C0);
+        let locked = Protection { allow_copy: false, allow_print: false, ..Default::default() };
+        let flags = locked.permission_flags() as u32;
+        assert_eq!(flags & (1 << 4), 0);
+        assert_eq!(flags & (1 << 2), 0);
+        assert_ne!(flags & (1 << 9), 0);
+    }
+
+    #[test]
+    fn unprotected_documents_are_unchanged() {
+        let pdf = sample_pdf();
+        assert_eq!(protect(&pdf, &Protection::default()).unwrap(), pdf);
+    }
+
+    #[test]
+    fn encrypted_documents_open_with_either_password() {
+        let protection = Protection {
+            user_password: Some("打开".to_string()),
+            owner_password: Some("owner".to_string()),
+            allow_copy: false,
+            ..Default::default()
+        };
+        let out = protect(&sample_pdf(), &protection).unwrap();
+        assert!(out.starts_with(b"%PDF-2.0\n"));
+        assert!(find(&out, b"/V 5 /R 6", 0).is_some());
+
+        // 用户密码通过 `/U` 验证，并由 `/UE` 得到文件密钥
+        let user = entry(&out, "U");
+        assert_eq!(user.len(), 48);
+        let password = password_bytes("打开");
+        assert_eq!(hash_r6(password, &user[32..40], &[]), user[..32]);
+        assert_ne!(hash_r6(b"wrong", &user[32..40], &[]), user[..32]);
+        let file_key = unwrap_file_key(&hash_r6(password, &user[40..48], &[]), &entry(&out, "UE"));
+
+        // 所有者密码通过 `/O` 验证，`/OE` 得到同一个密钥
+        let owner = entry(&out, "O");
+        assert_eq!(hash_r6(b"owner", &owner[32..40], &user), owner[..32]);
+        assert_eq!(unwrap_file_key(&hash_r6(b"owner", &owner[40..48], &user), &entry(&out, "OE")), file_key);
+
+        // `/Perms` 用文件密钥解密后与 `/P` 一致
+        let mut perms = <[u8; 16]>::try_from(entry(&out, "Perms")).unwrap().into();
+        aes::Aes256::new_from_slice(&file_key).unwrap().decrypt_block(&mut perms);
+        assert_eq!(perms[..4], protection.permission_flags().to_le_bytes());
+        assert_eq!(&perms[9..12], b"adb");
+
+        // 流与字符串解密后与原文一致，`/Length` 为加密后的长度
+        let objects = parse_objects(&out).unwrap();
+        let stream = objects.iter().find(|o| o.number == 4).unwrap().body;
+        let length: usize = std::str::from_utf8(&Regex::new(r"/Length (\d+)").unwrap().captures(stream).unwrap()[1])
+            .unwrap()
+            .parse()
+            .unwrap();
+        let start = stream_data_start(stream, find(stream, b"stream", 0).unwrap());
+        assert_eq!(aes_decrypt(&file_key, &stream[start..start + length]), CONTENT);
+        let info = objects.iter().find(|o| o.number == 5).unwrap().body;
+        assert_eq!(aes_decrypt(&file_key, &entry(info, "Title")), b"Test (1)");
+    }
+}
@@ -0,0 +1,81 @@
+//! 组织策略文件：路径在设置中指定（`policy_path`），导出时强制注入页脚声明、分级横幅与封面免责声明，
+//! 单个文档无法覆盖。文件为 JSON 或 YAML：
+//!
+//! ```yaml
+//! footer_text: 本文件仅供内部使用，未经授权不得外传
+//! banner:
+//!   text: Internal Use Only
+//!   background: "#1f4e79"
+//! cover_disclaimer: |
+//!   本文件包含公司保密信息……
+//! ```
+
+use crate::decorations::{Banner, PageDecorations};
+use crate::error::AppError;
+use crate::html_util::escape_html;
+use crate::settings::AppSettings;
+use serde::Deserialize;
+use std::fs;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct OrgPolicy {
+    /// 每页页脚的声明文字
+    pub footer_text: Option<String>,
+    /// 每页顶部与底部的分级横幅
+    pub banner: Option<Banner>,
+    /// 封面免责声明（单独一页，位于正文之前）
+    pub cover_disclaimer: Option<String>,
+}
+
+/// 读取策略文件（JSON 是 YAML 的子集，统一按 YAML 解析）
+pub fn load_policy(path: &str) -> Result<OrgPolicy, AppError> {
+    let content = fs::read_to_string(path).map_err(|e| AppError::PolicyError {
+        path: path.to_string(),
+        reason: e.to_string(),
+    })?;
+    serde_yaml::from_str(&content).map_err(|e| AppError::PolicyError {
+        path: path.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// 设置中配置了策略文件时读取它；读取失败时导出中止，避免在缺少强制声明的情况下生成文件
+pub fn active_policy(settings: &AppSettings) -> Result<Option<OrgPolicy>, AppError> {
+    match settings.policy_path.as_deref().map(str::trim) {
+        Some(path) if !path.is_empty() => load_policy(path).map(Some),
+        _ => Ok(None),
+    }
+}
+
+impl OrgPolicy {
+    /// 把页脚声明与横幅加入页面装饰
+    pub fn apply_decorations(&self, decorations: &mut PageDecorations) {
+        if let Some(banner) = &self.banner {
+            if !banner.text.trim().is_empty() {
+                decorations.add_banner(banner.clone());
+            }
+        }
+        if let Some(footer) = &self.footer_text {
+            decorations
+                .footer_lines
+                .extend(footer.lines().map(str::trim).filter(|l| !l.is_empty()).map(str::to_string));
+        }
+    }
+
+    /// 封面免责声明页；使用内联 `!important` 样式，文档中的 CSS 无法将其隐藏
+    pub fn cover_html(&self) -> Option<String> {
+        let disclaimer = self.cover_disclaimer.as_deref()?.trim();
+        if disclaimer.is_empty() {
+            return None;
+        }
+        let paragraphs: String = disclaimer
+            .split("\n\n")
+            .map(|p| format!("<p>{}</p>", escape_html(p.trim()).replace('\n', "<br>")))
+            .collect();
+        Some(format!(
+            r#"<section class="policy-disclaimer" style="display:block !important;visibility:visible !important;opacity:1 !important;page-break-after:always !important;break-after:page !important;padding-top:35vh !important;text-align:center !important;">{}</section>"#,
+            paragraphs
+        ))
+    }
+}
@@ -0,0 +1,131 @@
+//! 印次编号：同一文档批量导出多份，每份在页脚打印份号（如“第 007 份，共 250 份”），
+//! 并生成列出所有输出文件及其 SHA-256 的清单，便于分发登记与核对。
+//!
+//! 页面只加载、渲染一次，各份之间只有页脚不同；每份与单份导出一样按设置转换为 PDF/A 并加密。
+
+use crate::cancel::CancelToken;
+use crate::error::{run_blocking, AppError};
+use crate::i18n;
+use crate::{ExportJob, ExportOptions};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 单次批量导出的份数上限
+const MAX_COPIES: usize = 9999;
+/// 份号至少补齐到 3 位
+const MIN_NUMBER_WIDTH: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrintRunCopy {
+    /// 份号（从 1 开始）
+    pub copy: usize,
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrintRunManifest {
+    pub title: String,
+    pub total: usize,
+    pub copies: Vec<PrintRunCopy>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrintRunReport {
+    pub manifest_path: String,
+    pub manifest: PrintRunManifest,
+}
+
+/// 按当前语言生成份号文字，份号补零到与总数相同的位数
+fn stamp_text(copy: usize, total: usize) -> String {
+    let width = total.to_string().len().max(MIN_NUMBER_WIDTH);
+    i18n::translate(
+        i18n::current_locale(),
+        "PRINT_RUN_STAMP",
+        &[
+            ("copy".to_string(), format!("{:0width$}", copy, width = width)),
+            ("total".to_string(), total.to_string()),
+        ],
+    )
+}
+
+async fn run_print(
+    window: &tauri::Window,
+    job: Arc<ExportJob>,
+    copies: usize,
+    cancel: &CancelToken,
+) -> Result<PrintRunReport, AppError> {
+    if copies == 0 || copies > MAX_COPIES {
+        return Err(AppError::localized("PDF_GENERATION", "PDF_INVALID_COPIES", [("max", MAX_COPIES.to_string())]));
+    }
+
+    let output_path = Path::new(&job.output_path);
+    let dir = output_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let stem = output_path.file_stem().and_then(|s| s.to_str()).unwrap_or("document").to_string();
+    let width = copies.to_string().len().max(MIN_NUMBER_WIDTH);
+    let standard = crate::resolve_pdf_standard(&job)?;
+
+    let mut timer = crate::diagnostics::StageTimer::new();
+    let page = crate::load_export_page(window, job.clone(), &mut timer, cancel).await?;
+
+    let mut entries = Vec::with_capacity(copies);
+    for copy in 1..=copies {
+        crate::emit_progress(window, &format!("[{}/{}] 正在生成第 {} 份 PDF...", copy, copies, copy));
+        let mut decorations = page.decorations.clone();
+        decorations.footer_lines.push(stamp_text(copy, copies));
+        let pdf_data = crate::print_page_pdf(&page, &decorations, cancel).await?;
+        let pdf_data = crate::finish_pdf(window, &job, standard, pdf_data, &mut timer).await?;
+
+        let path = dir.join(format!("{}-copy-{:0width$}.pdf", stem, copy, width = width));
+        let entry = run_blocking("export_print_run", move || {
+            fs::write(&path, &pdf_data).map_err(|e| AppError::file(&path, e))?;
+            Ok(PrintRunCopy {
+                copy,
+                path: path.to_string_lossy().to_string(),
+                sha256: crate::hashing::sha256_hex(&pdf_data),
+                size: pdf_data.len() as u64,
+            })
+        })
+        .await?;
+        entries.push(entry);
+    }
+
+    let manifest = PrintRunManifest { title: job.title.clone(), total: copies, copies: entries };
+    let manifest_path = dir.join(format!("{}-manifest.json", stem));
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| AppError::PdfError(e.to_string()))?;
+    fs::write(&manifest_path, json).map_err(|e| AppError::file(&manifest_path, e))?;
+
+    Ok(PrintRunReport { manifest_path: manifest_path.to_string_lossy().to_string(), manifest })
+}
+
+/// 批量导出 `copies` 份带份号的 PDF：输出为 `<名称>-copy-001.pdf` 等，清单为 `<名称>-manifest.json`，
+/// 均位于 `output_path` 所在目录
+#[tauri::command]
+pub async fn export_print_run(
+    window: tauri::Window,
+    settings: tauri::State<'_, crate::settings::SettingsState>,
+    html_content: String,
+    output_path: String,
+    title: String,
+    copies: usize,
+    options: Option<ExportOptions>,
+) -> Result<PrintRunReport, AppError> {
+    let options = options.unwrap_or_default();
+    let cancel = CancelToken::register(options.export_id.clone());
+    let job = Arc::new(ExportJob {
+        html_content,
+        output_path,
+        title,
+        front_matter: options.markdown.as_deref().and_then(crate::front_matter::parse),
+        options,
+        settings: settings.snapshot(),
+    });
+    let result = run_print(&window, job, copies, &cancel).await;
+    if let Err(e) = &result {
+        crate::diagnostics::record_error("export_print_run", e);
+    }
+    result
+}
@@ -0,0 +1,120 @@
+//! 渲染完成的判定策略：导出页面在满足所有条件后才添加 `#render-complete` 信号，后端随后打印。
+//!
+//! - `fonts_ready`：`document.fonts.ready`，Web 字体全部加载完成
+//! - `images_complete`：所有图片加载并解码完成（加载失败的图片也视为完成，由导出报告给出警告）
+//! - `network_idle`：连续 `network_idle_ms` 毫秒内没有新的资源加载完成
+//! - `selector`：页面中出现指定元素（如异步绘制的图表）
+//! - `max_wait_ms`：超过该时间仍未就绪时不再等待，直接打印并在导出报告中给出警告
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessCheck {
+    NetworkIdle,
+    FontsReady,
+    ImagesComplete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Readiness {
+    pub checks: Vec<ReadinessCheck>,
+    pub selector: Option<String>,
+    pub network_idle_ms: u64,
+    pub max_wait_ms: Option<u64>,
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Readiness {
+            checks: vec![ReadinessCheck::FontsReady, ReadinessCheck::ImagesComplete],
+            selector: None,
+            network_idle_ms: 500,
+            max_wait_ms: None,
+        }
+    }
+}
+
+impl Readiness {
+    /// 超过后直接打印的等待时间
+    pub fn max_wait(&self) -> Option<Duration> {
+        self.max_wait_ms.map(Duration::from_millis)
+    }
+
+    /// 嵌入导出页面脚本的配置（JSON，已转义 `</`）
+    pub fn script_config(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string()).replace("</", "<\\/")
+    }
+}
+
+/// 导出页面中按配置等待就绪的脚本（`READINESS` 为 `script_config` 的内容）
+pub const READINESS_SCRIPT: &str = r#"
+        // 轮询直到条件成立
+        function waitFor(predicate) {
+            return new Promise(resolve => {
+                const tick = () => predicate() ? resolve() : setTimeout(tick, 50);
+                tick();
+            });
+        }
+
+        // 连续 idleMs 毫秒没有新的资源加载完成
+        function networkIdle(idleMs) {
+            let last = performance.now();
+            new PerformanceObserver(() => {
+                last = performance.now();
+            }).observe({ type: 'resource', buffered: true });
+            return waitFor(() => performance.now() - last >= idleMs);
+        }
+
+        // 等待所有图片加载并解码（失败的图片不阻塞）
+        function imagesComplete() {
+            return Promise.all(Array.from(document.images).map(img => {
+                const loaded = img.complete ? Promise.resolve() : new Promise(resolve => {
+                    img.addEventListener('load', resolve, { once: true });
+                    img.addEventListener('error', resolve, { once: true });
+                });
+                return loaded.then(() => img.decode ? img.decode().catch(() => {}) : undefined);
+            }));
+        }
+
+        // 当前所处的阶段（assets / math），后端据此使用对应的超时预算
+        function setStage(stage) {
+            document.documentElement.dataset.exportStage = stage;
+        }
+
+        async function waitUntilReady() {
+            const checks = READINESS.checks || [];
+            if (checks.includes('fonts_ready') && document.fonts) {
+                await document.fonts.ready;
+            }
+            if (checks.includes('images_complete')) {
+                await imagesComplete();
+            }
+            if (READINESS.selector) {
+                await waitFor(() => {
+                    try {
+                        return document.querySelector(READINESS.selector) !== null;
+                    } catch (e) {
+                        return true; // 无效的选择器不阻塞导出
+                    }
+                });
+            }
+            if (checks.includes('network_idle')) {
+                await networkIdle(READINESS.network_idle_ms || 500);
+            }
+            // 使用 MathJax 时先等待脚本加载结束，再等待首次排版完成；
+            // 脚本未能加载时标记出来，由导出报告给出警告（公式保留为 TeX 原文）
+            const mathjax = document.getElementById('mathjax-script');
+            if (mathjax) {
+                setStage('math');
+                await waitFor(() => mathjax.dataset.state);
+                if (window.MathJax && MathJax.startup && MathJax.startup.promise) {
+                    await MathJax.startup.promise;
+                } else {
+                    document.documentElement.dataset.mathEngineFailed = 'true';
+                }
+            }
+        }
+"#;
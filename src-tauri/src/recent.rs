@@ -0,0 +1,156 @@
+//! 最近打开的文件与每个文件的会话状态（滚动位置、光标所在行、主题），
+//! 保存在应用数据目录下的 `recent.json`，重新打开文档时恢复到上次离开的位置。
+//!
+//! 固定的条目不会被挤出列表；读取列表时重新检查文件是否存在，不存在的未固定条目被移除。
+
+use crate::diagnostics::now_ms;
+use crate::error::AppError;
+use crate::json_store::JsonStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 未固定条目的最大数量
+const MAX_RECENT_FILES: usize = 20;
+
+static STORE: JsonStore = JsonStore::new("recent.json");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    /// 最后一次打开的时间（毫秒时间戳）
+    pub opened_ms: u128,
+    pub pinned: bool,
+    /// 读取列表时文件是否存在
+    #[serde(default = "default_exists")]
+    pub exists: bool,
+}
+
+fn default_exists() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileSession {
+    /// 编辑区最上方可见的区块序号
+    pub scroll_block: usize,
+    /// 光标所在行（1-indexed）
+    pub cursor_line: Option<usize>,
+    /// 用户为该文件选择的主题
+    pub theme: Option<String>,
+    pub updated_ms: u128,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RecentStore {
+    files: Vec<RecentFile>,
+    /// 按文件路径保存的会话状态
+    sessions: HashMap<String, FileSession>,
+}
+
+impl RecentStore {
+    /// 打开文件：移到列表最前，超出数量的未固定条目及其会话被移除
+    fn touch(&mut self, path: &str) {
+        let pinned = self.files.iter().any(|f| f.path == path && f.pinned);
+        self.files.retain(|f| f.path != path);
+        self.files.insert(0, RecentFile { path: path.to_string(), opened_ms: now_ms(), pinned, exists: true });
+
+        let mut unpinned = 0;
+        let mut dropped = Vec::new();
+        self.files.retain(|f| {
+            if f.pinned {
+                return true;
+            }
+            unpinned += 1;
+            if unpinned > MAX_RECENT_FILES {
+                dropped.push(f.path.clone());
+                return false;
+            }
+            true
+        });
+        for path in dropped {
+            self.sessions.remove(&path);
+        }
+    }
+
+    /// 重新检查文件是否存在：移除不存在的未固定条目，固定条目保留并标记
+    fn revalidate(&mut self) {
+        for file in &mut self.files {
+            file.exists = Path::new(&file.path).is_file();
+        }
+        let missing: Vec<String> = self.files.iter().filter(|f| !f.exists && !f.pinned).map(|f| f.path.clone()).collect();
+        self.files.retain(|f| f.exists || f.pinned);
+        for path in missing {
+            self.sessions.remove(&path);
+        }
+    }
+
+    /// 固定的条目在前，其余按打开时间排序
+    fn sorted_files(&self) -> Vec<RecentFile> {
+        let mut files = self.files.clone();
+        files.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.opened_ms.cmp(&a.opened_ms)));
+        files
+    }
+}
+
+/// 在锁内读取、修改并保存
+fn update_store<T>(app: &tauri::AppHandle, f: impl FnOnce(&mut RecentStore) -> T) -> Result<T, AppError> {
+    STORE.update(app, f)
+}
+
+/// 记录打开的文件，返回更新后的列表
+#[tauri::command]
+pub fn add_recent_file(app: tauri::AppHandle, path: String) -> Result<Vec<RecentFile>, AppError> {
+    update_store(&app, |store| {
+        store.touch(&path);
+        store.sorted_files()
+    })
+}
+
+/// 最近打开的文件（固定的在前），同时移除已不存在的未固定条目
+#[tauri::command]
+pub fn get_recent_files(app: tauri::AppHandle) -> Result<Vec<RecentFile>, AppError> {
+    update_store(&app, |store| {
+        store.revalidate();
+        store.sorted_files()
+    })
+}
+
+/// 固定或取消固定条目
+#[tauri::command]
+pub fn pin_recent_file(app: tauri::AppHandle, path: String, pinned: bool) -> Result<Vec<RecentFile>, AppError> {
+    update_store(&app, |store| {
+        if let Some(file) = store.files.iter_mut().find(|f| f.path == path) {
+            file.pinned = pinned;
+        }
+        store.sorted_files()
+    })
+}
+
+/// 从列表中移除条目及其会话状态
+#[tauri::command]
+pub fn remove_recent_file(app: tauri::AppHandle, path: String) -> Result<Vec<RecentFile>, AppError> {
+    update_store(&app, |store| {
+        store.files.retain(|f| f.path != path);
+        store.sessions.remove(&path);
+        store.sorted_files()
+    })
+}
+
+/// 文件上次的会话状态
+#[tauri::command]
+pub fn get_file_session(app: tauri::AppHandle, path: String) -> Result<Option<FileSession>, AppError> {
+    Ok(STORE.load::<RecentStore>(&app)?.sessions.get(&path).cloned())
+}
+
+/// 保存文件的会话状态（只保存最近文件列表中的文件）
+#[tauri::command]
+pub fn save_file_session(app: tauri::AppHandle, path: String, session: FileSession) -> Result<(), AppError> {
+    update_store(&app, |store| {
+        if store.files.iter().any(|f| f.path == path) {
+            store.sessions.insert(path, FileSession { updated_ms: now_ms(), ..session });
+        }
+    })
+}
@@ -0,0 +1,223 @@
+//! 涂黑标记：`~~redact:内容~~` 或 `<span class="redact">内容</span>`。
+//!
+//! - 内部版（`internal`）：保留原文，以虚线框标出
+//! - 涂黑版（`redacted`）：原文从 HTML 中删除，按原文长度替换为实心方块，PDF 中不含被涂黑的文字
+//!   （不是用样式遮盖，无法通过复制或文本提取还原）；标题 id、页内链接等由文字生成的属性中的原文一并替换
+
+use crate::error::AppError;
+use crate::checker::percent_decode;
+use crate::html_util::{escape_html, find_closing_tag, strip_tags, unescape_html};
+use crate::report::ExportWarning;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportProfile {
+    /// 内部版：显示被标记的原文
+    #[default]
+    Internal,
+    /// 涂黑版：删除被标记的原文
+    Redacted,
+}
+
+/// 方块数量上限，避免过长的涂黑内容撑破排版
+const MAX_BLOCK_CHARS: usize = 40;
+
+/// 少于该字数的涂黑内容容易误匹配，不做泄露检查与属性清理
+const MIN_MATCH_CHARS: usize = 3;
+
+/// 由文字生成、可能带出原文的属性；`href` 只处理页内链接
+const TEXT_ATTRIBUTES: &[&str] = &["id", "name", "title", "alt", "aria-label", "href"];
+
+pub struct RedactionResult {
+    pub html: String,
+    pub warnings: Vec<ExportWarning>,
+}
+
+/// 一处涂黑标记：整个元素的范围与内容的范围
+struct Marker {
+    start: usize,
+    content_start: usize,
+    content_end: usize,
+    end: usize,
+}
+
+/// 按出现顺序找出所有最外层的涂黑标记
+fn find_markers(html: &str) -> Result<Vec<Marker>, AppError> {
+    let re_open = Regex::new(
+        r#"(?i)<(del|s)>\s*redact:|<(span)\b[^>]*\bclass\s*=\s*["'](?:[^"']*\s)?redact(?:\s[^"']*)?["'][^>]*>"#,
+    )
+    .unwrap();
+    let mut markers: Vec<Marker> = Vec::new();
+    for caps in re_open.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        if markers.last().is_some_and(|m| whole.start() < m.end) {
+            continue; // 嵌套在上一个标记内部
+        }
+        let tag = caps.get(1).or(caps.get(2)).unwrap().as_str().to_ascii_lowercase();
+        let (content_end, end) = find_closing_tag(html, &tag, whole.end()).ok_or_else(|| {
+            AppError::localized("PDF_GENERATION", "PDF_REDACTION_UNCLOSED", [("offset", whole.start().to_string())])
+        })?;
+        markers.push(Marker {
+            start: whole.start(),
+            content_start: whole.end(),
+            content_end,
+            end,
+        });
+    }
+    Ok(markers)
+}
+
+/// 把属性值中的涂黑内容（原文或其 slug，不区分大小写）替换为 `redacted`
+fn scrub_attributes(html: &str, secrets: &[Regex]) -> String {
+    if secrets.is_empty() {
+        return html.to_string();
+    }
+    let re_tag = Regex::new(r"<[A-Za-z][^>]*>").unwrap();
+    let re_attr = Regex::new(r#"(\s([\w:-]+)\s*=\s*)(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    re_tag
+        .replace_all(html, |tag: &regex::Captures| {
+            re_attr
+                .replace_all(&tag[0], |attr: &regex::Captures| {
+                    let name = attr[2].to_ascii_lowercase();
+                    let raw = attr.get(3).or(attr.get(4)).unwrap().as_str();
+                    if !TEXT_ATTRIBUTES.contains(&name.as_str()) || (name == "href" && !raw.starts_with('#')) {
+                        return attr[0].to_string();
+                    }
+                    let value = if name == "href" { percent_decode(&unescape_html(raw)) } else { unescape_html(raw) };
+                    let mut scrubbed = value.clone();
+                    for secret in secrets {
+                        scrubbed = secret.replace_all(&scrubbed, "redacted").into_owned();
+                    }
+                    if scrubbed == value {
+                        attr[0].to_string()
+                    } else {
+                        format!(r#"{}"{}""#, &attr[1], escape_html(&scrubbed))
+                    }
+                })
+                .into_owned()
+        })
+        .into_owned()
+}
+
+/// 按导出配置处理涂黑标记
+pub fn apply_redactions(html: &str, profile: ExportProfile) -> Result<RedactionResult, AppError> {
+    let markers = find_markers(html)?;
+    if markers.is_empty() {
+        return Ok(RedactionResult { html: html.to_string(), warnings: Vec::new() });
+    }
+
+    let secrets: Vec<String> = match profile {
+        ExportProfile::Internal => Vec::new(),
+        ExportProfile::Redacted => markers
+            .iter()
+            .map(|m| strip_tags(&html[m.content_start..m.content_end]).trim().to_string())
+            .collect(),
+    };
+    let patterns: Vec<Regex> = secrets
+        .iter()
+        .filter(|secret| secret.chars().count() >= MIN_MATCH_CHARS)
+        .flat_map(|secret| [secret.clone(), crate::slug::slugify(secret)])
+        .filter(|text| !text.is_empty())
+        .map(|text| Regex::new(&format!("(?i){}", regex::escape(&text))).unwrap())
+        .collect();
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for marker in &markers {
+        out.push_str(&scrub_attributes(&html[last..marker.start], &patterns));
+        let content = &html[marker.content_start..marker.content_end];
+        match profile {
+            ExportProfile::Internal => {
+                out.push_str(r#"<mark class="redact-internal">"#);
+                out.push_str(content);
+                out.push_str("</mark>");
+            }
+            ExportProfile::Redacted => {
+                let text = strip_tags(content);
+                let count = text.trim().chars().count().clamp(3, MAX_BLOCK_CHARS);
+                out.push_str(r#"<span class="redacted" aria-label="redacted">"#);
+                out.push_str(&"\u{2588}".repeat(count));
+                out.push_str("</span>");
+            }
+        }
+        last = marker.end;
+    }
+    out.push_str(&scrub_attributes(&html[last..], &patterns));
+
+    // 同样的文字在文档其他位置仍以明文出现时给出警告（不在警告中重复原文）
+    let mut warnings = Vec::new();
+    if profile == ExportProfile::Redacted {
+        let visible = strip_tags(&out);
+        for (idx, secret) in secrets.iter().enumerate() {
+            let leaked = visible.contains(secret.as_str()) || out.contains(&escape_html(secret));
+            if secret.chars().count() >= MIN_MATCH_CHARS && leaked {
+                warnings.push(ExportWarning {
+                    kind: "redaction_leak".to_string(),
+                    detail: format!("第 {} 处涂黑内容在文档其他位置仍以明文出现", idx + 1),
+                });
+            }
+        }
+    }
+    Ok(RedactionResult { html: out, warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact(html: &str) -> RedactionResult {
+        apply_redactions(html, ExportProfile::Redacted).unwrap()
+    }
+
+    #[test]
+    fn internal_profile_keeps_the_text() {
+        let result = apply_redactions("<p>a <del>redact:Falcon</del></p>", ExportProfile::Internal).unwrap();
+        assert_eq!(result.html, r#"<p>a <mark class="redact-internal">Falcon</mark></p>"#);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn redacted_text_is_removed() {
+        let result = redact(r#"<p>代号 <del>redact:Falcon</del> 与 <span class="note redact">Osprey <b>X</b></span></p>"#);
+        assert!(!result.html.contains("Falcon") && !result.html.contains("Osprey"), "{}", result.html);
+        assert_eq!(result.html.matches(r#"<span class="redacted""#).count(), 2);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn redacted_text_is_removed_from_attributes_and_ids() {
+        let html = concat!(
+            r##"<h2 id="project-redactfalcon">Project <del>redact:Falcon</del></h2>"##,
+            r##"<p><a href="#project-redactfalcon" title="About FALCON">link</a><img src="falcon.png" alt="falcon"></p>"##,
+        );
+        let result = redact(html);
+        assert!(result.html.contains(r#"id="project-redactredacted""#), "{}", result.html);
+        assert!(result.html.contains(r##"href="#project-redactredacted""##), "{}", result.html);
+        assert!(result.html.contains(r#"title="About redacted""#), "{}", result.html);
+        assert!(result.html.contains(r#"alt="redacted""#), "{}", result.html);
+        // 外部地址不改写
+        assert!(result.html.contains(r#"src="falcon.png""#), "{}", result.html);
+    }
+
+    #[test]
+    fn heading_anchors_built_after_redaction_do_not_leak() {
+        let result = redact("<h2>Project <del>redact:Falcon</del></h2>");
+        let anchored = crate::slug::anchor_headings(&result.html);
+        assert!(!anchored.to_lowercase().contains("falcon"), "{}", anchored);
+    }
+
+    #[test]
+    fn warns_when_the_text_appears_elsewhere() {
+        let result = redact("<p><del>redact:Falcon</del></p><p>Falcon again</p>");
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].kind, "redaction_leak");
+    }
+
+    #[test]
+    fn rejects_unclosed_markers() {
+        let error = apply_redactions("<p><del>redact:Falcon</p>", ExportProfile::Redacted).err().unwrap();
+        assert_eq!(error.code(), "PDF_GENERATION");
+    }
+}
@@ -0,0 +1,137 @@
+//! 尽力导出：渲染失败的块（错误的公式、无法解析的内容）替换为错误占位框，列出对应的源码行，
+//! 其余内容照常导出，失败项记录在导出报告中。
+//!
+//! 失败的块有两种来源：
+//!  - KaTeX 无法解析的公式（`<span class="katex-error">`）：替换其所在的段落、列表项或表格单元格
+//!  - 前端整体渲染失败后逐块渲染时，无法渲染的块输出的
+//!    `<div class="render-failure" data-start-line=".." data-end-line=".." data-error=".."></div>` 标记
+//!
+//! 未开启尽力导出时不做处理：公式错误以红色原文显示，整体渲染失败时中止导出。
+
+use crate::html_util::{escape_html, find_closing_tag, strip_tags, unescape_html};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderFailure {
+    /// `math` | `block`
+    pub kind: String,
+    /// 源码中的行范围（1-indexed，含两端）；无法定位时为 0
+    pub start_line: usize,
+    pub end_line: usize,
+    pub message: String,
+}
+
+/// 源码中第一次出现 `text` 的位置所在的段落（连续的非空行）
+fn locate(markdown: &str, text: &str) -> Option<(usize, usize)> {
+    let offset = markdown.find(text.trim()).filter(|_| !text.trim().is_empty())?;
+    let lines: Vec<&str> = markdown.lines().collect();
+    let first = markdown[..offset].matches('\n').count();
+    let last = first + text.trim().matches('\n').count();
+    let mut start = first;
+    while start > 0 && !lines[start - 1].trim().is_empty() {
+        start -= 1;
+    }
+    let mut end = last.min(lines.len().saturating_sub(1));
+    while end + 1 < lines.len() && !lines[end + 1].trim().is_empty() {
+        end += 1;
+    }
+    Some((start + 1, end + 1))
+}
+
+/// 错误占位框：错误信息与对应的源码行
+fn placeholder(failure: &RenderFailure, markdown: &str) -> String {
+    let title = if failure.start_line == 0 {
+        format!("渲染失败：{}", failure.message)
+    } else if failure.start_line == failure.end_line {
+        format!("第 {} 行渲染失败：{}", failure.start_line, failure.message)
+    } else {
+        format!("第 {}-{} 行渲染失败：{}", failure.start_line, failure.end_line, failure.message)
+    };
+    let source: Vec<&str> = if failure.start_line == 0 {
+        Vec::new()
+    } else {
+        markdown.lines().skip(failure.start_line - 1).take(failure.end_line + 1 - failure.start_line).collect()
+    };
+    let source_html = if source.is_empty() {
+        String::new()
+    } else {
+        format!("<pre>{}</pre>", escape_html(&source.join("\n")))
+    };
+    format!(
+        r#"<div class="render-failure"><div class="render-failure-title">{}</div>{}</div>"#,
+        escape_html(&title),
+        source_html
+    )
+}
+
+/// 包含 `[start, end)` 的最内层块级元素（段落、列表项、表格单元格、`div` 与标题）的范围
+fn enclosing_block(html: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    let re_open = Regex::new(r"(?i)<(p|li|td|th|div|h[1-6])\b[^>]*>").unwrap();
+    re_open
+        .captures_iter(&html[..start])
+        .filter_map(|caps| {
+            let open = caps.get(0).unwrap();
+            let tag = caps[1].to_ascii_lowercase();
+            let (_, close_end) = find_closing_tag(html, &tag, open.end())?;
+            (close_end >= end).then_some((open.start(), close_end))
+        })
+        .last()
+}
+
+/// 替换渲染失败的块，返回处理后的 HTML 与失败项（按在文档中的顺序）
+pub fn replace_failures(html: &str, markdown: &str) -> (String, Vec<RenderFailure>) {
+    let re_marker = Regex::new(r#"<div class="render-failure"([^>]*)></div>"#).unwrap();
+    let re_attr = Regex::new(r#"data-([a-z-]+)="([^"]*)""#).unwrap();
+    let re_katex_error = Regex::new(r#"(?s)<span class="katex-error"(?:\s+title="([^"]*)")?[^>]*>(.*?)</span>"#).unwrap();
+
+    // (替换范围, 失败项)
+    let mut replacements: Vec<((usize, usize), RenderFailure)> = Vec::new();
+    for caps in re_marker.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        let attr = |name: &str| {
+            re_attr
+                .captures_iter(&caps[1])
+                .find(|a| &a[1] == name)
+                .map(|a| unescape_html(&a[2]))
+                .unwrap_or_default()
+        };
+        let start_line = attr("start-line").parse().unwrap_or(0);
+        let failure = RenderFailure {
+            kind: "block".to_string(),
+            start_line,
+            end_line: attr("end-line").parse().unwrap_or(start_line),
+            message: attr("error"),
+        };
+        replacements.push(((whole.start(), whole.end()), failure));
+    }
+    for caps in re_katex_error.captures_iter(html) {
+        let whole = caps.get(0).unwrap();
+        let range = enclosing_block(html, whole.start(), whole.end()).unwrap_or((whole.start(), whole.end()));
+        // 同一块中的多个错误只替换一次
+        if replacements.iter().any(|((s, e), _)| *s <= range.0 && range.1 <= *e) {
+            continue;
+        }
+        let message = caps.get(1).map(|m| unescape_html(m.as_str())).unwrap_or_else(|| "公式解析失败".to_string());
+        let tex = strip_tags(&caps[2]);
+        let (start_line, end_line) = locate(markdown, &tex).unwrap_or((0, 0));
+        replacements.push((range, RenderFailure { kind: "math".to_string(), start_line, end_line, message }));
+    }
+    replacements.sort_by_key(|((start, _), _)| *start);
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    let mut failures = Vec::new();
+    for ((start, end), failure) in replacements {
+        if start < last {
+            continue;
+        }
+        out.push_str(&html[last..start]);
+        out.push_str(&placeholder(&failure, markdown));
+        tracing::warn!(line = failure.start_line, message = %failure.message, "块渲染失败，已替换为占位框");
+        failures.push(failure);
+        last = end;
+    }
+    out.push_str(&html[last..]);
+    (out, failures)
+}
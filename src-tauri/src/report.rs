@@ -0,0 +1,108 @@
+//! 导出报告：各阶段耗时、PDF 页数与大小、图片与公式数量，以及渲染过程中发现的问题
+
+use crate::diagnostics::{ExportTimings, StageTiming};
+use crate::pdf_optimize::OptimizationReport;
+use crate::render_failures::RenderFailure;
+use headless_chrome::Tab;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportWarning {
+    /// 问题类型：`missing_image`、`unresolved_reference` 等
+    pub kind: String,
+    pub detail: String,
+}
+
+/// 页面渲染完成后从浏览器中统计到的信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageStats {
+    pub image_count: usize,
+    pub math_count: usize,
+    pub warnings: Vec<ExportWarning>,
+    /// 尽力导出时被替换为占位框的块（由生成 HTML 时填入，不来自页面脚本）
+    #[serde(default)]
+    pub failures: Vec<RenderFailure>,
+}
+
+/// 导出流程的产出（不含耗时）
+#[derive(Debug, Clone, Default)]
+pub struct RenderedPdf {
+    pub stats: PageStats,
+    pub page_count: usize,
+    pub file_size: u64,
+    /// 图片优化前后的大小，未启用优化时为空
+    pub optimization: Option<OptimizationReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportReport {
+    pub output_path: String,
+    pub total_ms: u128,
+    pub stages: Vec<StageTiming>,
+    pub page_count: usize,
+    pub file_size: u64,
+    pub image_count: usize,
+    pub math_count: usize,
+    pub warnings: Vec<ExportWarning>,
+    /// 尽力导出时渲染失败、已替换为占位框的块
+    pub failures: Vec<RenderFailure>,
+    pub optimization: Option<OptimizationReport>,
+}
+
+impl ExportReport {
+    pub fn new(timings: ExportTimings, rendered: RenderedPdf) -> Self {
+        ExportReport {
+            output_path: timings.output_path,
+            total_ms: timings.total_ms,
+            stages: timings.stages,
+            page_count: rendered.page_count,
+            file_size: rendered.file_size,
+            image_count: rendered.stats.image_count,
+            math_count: rendered.stats.math_count,
+            warnings: rendered.stats.warnings,
+            failures: rendered.stats.failures,
+            optimization: rendered.optimization,
+        }
+    }
+}
+
+/// 在页面中统计图片、公式，并检查加载失败的图片、无法解析的页内链接与未能加载的公式引擎
+const PAGE_STATS_SCRIPT: &str = r##"
+(() => {
+    const warnings = [];
+    const images = Array.from(document.images);
+    for (const img of images) {
+        if (!img.complete || img.naturalWidth === 0) {
+            warnings.push({ kind: 'missing_image', detail: img.getAttribute('src') || '' });
+        }
+    }
+    for (const a of document.querySelectorAll('a[href^="#"]')) {
+        const id = decodeURIComponent(a.getAttribute('href').slice(1));
+        if (id && !document.getElementById(id) && !document.getElementsByName(id).length) {
+            warnings.push({ kind: 'unresolved_reference', detail: '#' + id });
+        }
+    }
+    if (document.documentElement.dataset.mathEngineFailed) {
+        const src = document.getElementById('mathjax-script')?.getAttribute('src') || '';
+        warnings.push({ kind: 'math_engine_unavailable', detail: 'MathJax 未能加载，公式以 TeX 原文输出：' + src });
+    }
+    const mathCount = document.querySelectorAll('.katex, mjx-container').length;
+    return JSON.stringify({ image_count: images.length, math_count: mathCount, warnings });
+})()
+"##;
+
+/// 收集页面统计信息；失败时返回空统计，不影响导出
+pub fn collect_page_stats(tab: &Tab) -> PageStats {
+    tab.evaluate(PAGE_STATS_SCRIPT, false)
+        .ok()
+        .and_then(|result| result.value)
+        .and_then(|value| value.as_str().map(str::to_string))
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// 统计 PDF 页数（Chrome 生成的 PDF 中每页都有一个 `/Type /Page` 对象）
+pub fn count_pdf_pages(pdf: &[u8]) -> usize {
+    let re_page = regex::bytes::Regex::new(r"/Type\s*/Page[^s]").unwrap();
+    re_page.find_iter(pdf).count()
+}
@@ -0,0 +1,277 @@
+//! 复制为富文本：把文档（或选中的块）渲染为带内联样式的 HTML，同时放入纯文本，
+//! 粘贴到 Word、Outlook 或邮件中时保留标题、表格、代码高亮与公式。
+//!
+//!  - 扩展语法与导出一致（提示块、徽章、按键等）
+//!  - 代码块按语言高亮（见 `highlight` 模块），颜色写成内联样式
+//!  - 公式在无头浏览器中用随应用分发的 KaTeX 转换为 MathML（Word 粘贴后为可编辑公式）；
+//!    找不到 KaTeX 或浏览器时保留 TeX 原文，并在结果中说明
+//!  - 表格、引用、代码等常用元素加上内联样式，不依赖样式表
+
+use crate::error::AppError;
+use crate::html_util::{escape_html, strip_tags, unescape_html};
+use crate::parser_mode::ParserMode;
+use crate::selection::BlockSelection;
+use crate::settings::SettingsState;
+use crate::{callouts, front_matter, highlight, line_breaks, smart_quotes, typography};
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+const MONO_FONTS: &str = "Consolas, 'Courier New', monospace";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RichCopyResult {
+    /// 转换为 MathML 的公式数量
+    pub formulas: usize,
+    pub code_blocks: usize,
+    /// 无法转换的内容（如缺少公式引擎时保留原文的公式）
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct MathItem {
+    tex: String,
+    display: bool,
+}
+
+/// 正文中的 `$$...$$` 与 `$...$` 替换为占位元素，返回公式列表（代码中的内容保持不变）
+fn extract_math(html: &str) -> (String, Vec<MathItem>) {
+    let re_protected = Regex::new(r"(?s)<pre\b.*?</pre>|<code\b.*?</code>|<[^>]*>").unwrap();
+    let re_math = Regex::new(r"(?s)\$\$(.+?)\$\$|\$([^\s$](?:[^$\n]*?[^\s$\\])?)\$").unwrap();
+    let mut items = Vec::new();
+    let mut replace = |text: &str| {
+        re_math
+            .replace_all(text, |caps: &Captures| {
+                let m = caps.get(0).unwrap();
+                let escaped = text[..m.start()].ends_with('\\');
+                let price = caps.get(2).is_some() && text[m.end()..].starts_with(|c: char| c.is_ascii_digit());
+                if escaped || price {
+                    return caps[0].to_string();
+                }
+                let (tex, display) = match caps.get(1) {
+                    Some(tex) => (tex.as_str(), true),
+                    None => (&caps[2], false),
+                };
+                items.push(MathItem { tex: unescape_html(tex.trim()), display });
+                format!(r#"<span data-math="{}"></span>"#, items.len() - 1)
+            })
+            .into_owned()
+    };
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(html) {
+        out.push_str(&replace(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&replace(&html[last..]));
+    // 单独成段的行间公式不再包在段落中
+    let re_display_paragraph = Regex::new(r#"<p>\s*(<span data-math="\d+"></span>)\s*</p>"#).unwrap();
+    let out = re_display_paragraph.replace_all(&out, "$1").into_owned();
+    (out, items)
+}
+
+/// 在无头浏览器中用 KaTeX 把公式转换为 MathML
+fn render_mathml(katex_js: &str, items: &[MathItem]) -> Result<Vec<Option<String>>, AppError> {
+    let browser = crate::browser::launch_headless_browser(&crate::timeouts::StageTimeouts::default())?;
+    let tab = browser.new_tab().map_err(|e| AppError::browser("BROWSER_NEW_TAB", e))?;
+    tab.evaluate(katex_js, false)
+        .map_err(|e| AppError::browser("BROWSER_KATEX_LOAD", e))?;
+    let items_json = serde_json::to_string(items).unwrap_or_else(|_| "[]".to_string());
+    let script = format!(
+        r#"JSON.stringify({}.map(m => {{
+            try {{ return katex.renderToString(m.tex, {{ output: 'mathml', displayMode: m.display, throwOnError: true }}); }}
+            catch (e) {{ return null; }}
+        }}))"#,
+        items_json
+    );
+    let result = tab
+        .evaluate(&script, false)
+        .map_err(|e| AppError::browser("BROWSER_KATEX_RENDER", e))?;
+    let json = result.value.and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+    serde_json::from_str(&json).map_err(|e| {
+        AppError::localized(
+            "INTERNAL",
+            "RICH_COPY_INVALID_RESULT",
+            [("context", "copy_as_rich_html".to_string()), ("reason", e.to_string())],
+        )
+    })
+}
+
+/// 代码块高亮并加上内联样式
+fn highlight_code_blocks(html: &str) -> (String, usize) {
+    let re_pre =
+        Regex::new(r#"(?s)<pre><code(?: class="language-([^"\s]*)[^"]*")?>(.*?)</code></pre>"#).unwrap();
+    let mut count = 0;
+    let html = re_pre
+        .replace_all(html, |caps: &Captures| {
+            count += 1;
+            let language = caps.get(1).map_or("", |m| m.as_str());
+            let code = unescape_html(&caps[2]);
+            format!(
+                r#"<pre style="background: #f6f8fa; padding: 8pt; border: 1px solid #d0d7de; font-family: {}; font-size: 9.5pt; white-space: pre-wrap;"><code>{}</code></pre>"#,
+                MONO_FONTS,
+                highlight::highlight(code.trim_end_matches('\n'), language)
+            )
+        })
+        .into_owned();
+    (html, count)
+}
+
+/// 给开始标签加上内联样式（已有 style 时放在前面，原样式优先）
+fn add_style(html: &str, tag: &str, style: &str) -> String {
+    let re_tag = Regex::new(&format!(r#"<{}((?:\s[^>]*)?)>"#, tag)).unwrap();
+    let re_style = Regex::new(r#"\sstyle="([^"]*)""#).unwrap();
+    re_tag
+        .replace_all(html, |caps: &Captures| {
+            let attrs = &caps[1];
+            let attrs = if re_style.is_match(attrs) {
+                re_style.replace(attrs, |s: &Captures| format!(r#" style="{} {}""#, style, &s[1])).into_owned()
+            } else {
+                format!(r#"{} style="{}""#, attrs, style)
+            };
+            format!("<{}{}>", tag, attrs)
+        })
+        .into_owned()
+}
+
+/// 常用元素的内联样式（代码块已单独处理）
+fn inline_styles(html: &str) -> String {
+    let rules = [
+        ("table", "border-collapse: collapse;"),
+        ("th", "border: 1px solid #d0d7de; padding: 4pt 8pt; background: #f6f8fa;"),
+        ("td", "border: 1px solid #d0d7de; padding: 4pt 8pt;"),
+        ("blockquote", "margin: 0 0 0 4pt; padding-left: 10pt; border-left: 3pt solid #d0d7de; color: #57606a;"),
+        ("code", "background: #f0f0f0; padding: 0 2pt; font-family: Consolas, 'Courier New', monospace;"),
+        ("kbd", "border: 1px solid #d0d7de; padding: 0 3pt; font-family: Consolas, 'Courier New', monospace;"),
+    ];
+    // 代码块中的 <code> 不加行内代码样式
+    let re_pre = Regex::new(r"(?s)<pre\b.*?</pre>").unwrap();
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    let style_all = |fragment: &str| rules.iter().fold(fragment.to_string(), |acc, (tag, style)| add_style(&acc, tag, style));
+    for m in re_pre.find_iter(html) {
+        out.push_str(&style_all(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&style_all(&html[last..]));
+    out
+}
+
+/// 纯文本：块级元素之间换行，表格单元格之间用制表符
+fn plain_text(html: &str) -> String {
+    let re_breaks = Regex::new(r"(?i)<br\s*/?>|</(?:p|h[1-6]|li|tr|pre|blockquote|div|table)>").unwrap();
+    let re_cells = Regex::new(r"(?i)</t[hd]>\s*").unwrap();
+    let re_math = Regex::new(r#"(?s)<annotation encoding="application/x-tex">(.*?)</annotation>"#).unwrap();
+    let re_mathml = Regex::new(r"(?s)<math\b.*?</math>").unwrap();
+    // 公式的纯文本为 TeX 原文
+    let html = re_mathml.replace_all(html, |caps: &Captures| {
+        re_math.captures(&caps[0]).map(|tex| tex[1].to_string()).unwrap_or_default()
+    });
+    let html = re_cells.replace_all(&html, "\t");
+    let html = re_breaks.replace_all(&html, "$0\n");
+    let text = strip_tags(&html);
+    let re_blank = Regex::new(r"\n{3,}").unwrap();
+    re_blank.replace_all(text.trim(), "\n\n").into_owned()
+}
+
+/// 渲染为适合粘贴的 HTML；`katex_js` 为 KaTeX 脚本内容（缺少时保留公式原文）
+fn render_rich_html(
+    markdown: &str,
+    settings: &crate::settings::AppSettings,
+    source_path: Option<&str>,
+    katex_js: Option<&str>,
+) -> (String, RichCopyResult) {
+    let mode = ParserMode::resolve(None, markdown);
+    let line_breaks = line_breaks::LineBreaks::resolve(settings.line_breaks, markdown);
+    let smart_quotes = smart_quotes::resolve(settings, markdown);
+    let typography = typography::resolve(settings, markdown);
+    let callout_styles = callouts::resolve(settings, source_path.map(Path::new), front_matter::parse(markdown).as_ref());
+    let html = crate::render_preview_html(markdown, mode, line_breaks, smart_quotes, &typography, &callout_styles);
+
+    let mut warnings = Vec::new();
+    let (html, items) = extract_math(&html);
+    let rendered = match (items.is_empty(), katex_js) {
+        (true, _) => Vec::new(),
+        (false, None) => {
+            warnings.push("找不到 KaTeX，公式保留为 TeX 原文".to_string());
+            Vec::new()
+        }
+        (false, Some(js)) => render_mathml(js, &items).unwrap_or_else(|e| {
+            warnings.push(format!("公式转换失败，保留为 TeX 原文: {}", e));
+            Vec::new()
+        }),
+    };
+    let re_placeholder = Regex::new(r#"<span data-math="(\d+)"></span>"#).unwrap();
+    let mut formulas = 0;
+    let html = re_placeholder
+        .replace_all(&html, |caps: &Captures| {
+            let index: usize = caps[1].parse().unwrap_or(0);
+            let item = &items[index];
+            match rendered.get(index).cloned().flatten() {
+                Some(mathml) if item.display => {
+                    formulas += 1;
+                    format!("<p>{}</p>", mathml)
+                }
+                Some(mathml) => {
+                    formulas += 1;
+                    mathml
+                }
+                None if item.display => format!("<p>$${}$$</p>", escape_html(&item.tex)),
+                None => format!("${}$", escape_html(&item.tex)),
+            }
+        })
+        .into_owned();
+
+    let (html, code_blocks) = highlight_code_blocks(&html);
+    let html = inline_styles(&html);
+    let html = format!(
+        r#"<div style="font-family: 'Segoe UI', 'Microsoft YaHei', 'PingFang SC', sans-serif; font-size: 11pt; line-height: 1.5;">{}</div>"#,
+        html
+    );
+    (html, RichCopyResult { formulas, code_blocks, warnings })
+}
+
+/// 渲染文档（或选中的块）并把 HTML 与纯文本同时放入剪贴板
+#[tauri::command]
+pub async fn copy_as_rich_html(
+    app: tauri::AppHandle,
+    settings: tauri::State<'_, SettingsState>,
+    markdown: String,
+    selection: Option<BlockSelection>,
+    source_path: Option<String>,
+) -> Result<RichCopyResult, AppError> {
+    let settings = settings.snapshot();
+    let katex_path: Option<PathBuf> = app
+        .path()
+        .resource_dir()
+        .map(|dir| dir.join("public/katex/katex.min.js"))
+        .ok()
+        .filter(|path| path.exists());
+
+    crate::error::run_blocking("copy_as_rich_html", move || {
+        let markdown = match &selection {
+            Some(selection) => crate::selection::select_blocks(&markdown, selection)?.markdown,
+            None => markdown,
+        };
+        let katex_js = katex_path.and_then(|path| std::fs::read_to_string(path).ok());
+        let (html, result) = render_rich_html(&markdown, &settings, source_path.as_deref(), katex_js.as_deref());
+        let text = plain_text(&html);
+
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_html(html, Some(text)))
+            .map_err(|e| {
+                AppError::localized(
+                    "INTERNAL",
+                    "RICH_COPY_CLIPBOARD",
+                    [("context", "copy_as_rich_html".to_string()), ("reason", e.to_string())],
+                )
+            })?;
+        tracing::info!(formulas = result.formulas, code_blocks = result.code_blocks, "已复制为富文本");
+        Ok(result)
+    })
+    .await
+}
@@ -0,0 +1,68 @@
+//! 注音（ruby）语法：`{汉字|hàn zì}` 转换为 `<ruby>` 元素，用于拼音、假名等标注。
+//!
+//!  - `{漢字|かん|じ}`：注音用 `|` 分隔且数量与字数相同时逐字标注
+//!  - `{汉字|hàn zì}`：注音用空格分隔且数量与字数相同时同样逐字标注
+//!  - 其他情况整组标注
+//!
+//! 属于扩展语法，代码与公式中的内容保持不变。
+
+use regex::{Captures, Regex};
+
+/// `<rt>` 两侧加上 `<rp>` 括号，不支持注音的阅读器中显示为 `汉(hàn)`
+fn ruby_pair(base: &str, annotation: &str) -> String {
+    format!("{}<rp>(</rp><rt>{}</rt><rp>)</rp>", base, annotation)
+}
+
+/// 单个注音标记转换为 `<ruby>` 元素
+fn ruby_html(base: &str, annotation: &str) -> String {
+    let chars: Vec<char> = base.chars().collect();
+    let by_pipe: Vec<&str> = annotation.split('|').map(str::trim).collect();
+    let by_space: Vec<&str> = annotation.split_whitespace().collect();
+    // 数量不一致时把 `|` 视为普通分隔，整组标注
+    let whole = by_pipe.join(" ");
+    let per_char = if by_pipe.len() > 1 {
+        (by_pipe.len() == chars.len()).then_some(by_pipe)
+    } else {
+        (chars.len() > 1 && by_space.len() == chars.len()).then_some(by_space)
+    };
+
+    let inner = match per_char {
+        Some(parts) => chars.iter().zip(parts).map(|(c, rt)| ruby_pair(&c.to_string(), rt)).collect(),
+        None => ruby_pair(base, &whole),
+    };
+    format!("<ruby>{}</ruby>", inner)
+}
+
+/// 把 HTML 正文中的 `{文字|注音}` 转换为 `<ruby>` 元素
+pub fn apply_ruby(html: &str) -> String {
+    if !html.contains('|') {
+        return html.to_string();
+    }
+    let re_protected = Regex::new(
+        r"(?s)<pre\b.*?</pre>|<code\b.*?</code>|<math\b.*?</math>|<script\b.*?</script>|<style\b.*?</style>|<ruby\b.*?</ruby>|<[^>]*>",
+    )
+    .unwrap();
+    let re_ruby = Regex::new(r"\{([^{}|\n]+)\|([^{}\n]+)\}").unwrap();
+    let replace = |text: &str| {
+        re_ruby
+            .replace_all(text, |caps: &Captures| {
+                let base = caps[1].trim();
+                let annotation = caps[2].trim();
+                if base.is_empty() || annotation.is_empty() {
+                    return caps[0].to_string();
+                }
+                ruby_html(base, annotation)
+            })
+            .into_owned()
+    };
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(html) {
+        out.push_str(&replace(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&replace(&html[last..]));
+    out
+}
@@ -0,0 +1,299 @@
+//! 文档内全文搜索与全部替换：在后端搜索整篇 Markdown，大文件中也不会卡住界面。
+//!
+//! 支持区分大小写、全字匹配与正则表达式。匹配位置以行、列表示（行从 1 开始，
+//! 列为行内 UTF-16 偏移、从 1 开始，与前端字符串下标一致）；全部替换返回按行的文本编辑列表，
+//! 编辑器据此做最小修改以保留光标位置与撤销历史。
+
+use crate::error::AppError;
+use crate::formatter::TextEdit;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+/// 最多返回的匹配数量
+const MAX_MATCHES: usize = 10_000;
+
+/// 上下文中匹配前后各保留的字符数
+const CONTEXT_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    /// 匹配前后不能紧挨字母、数字或下划线
+    pub whole_word: bool,
+    /// 查询按正则表达式解析，替换文本中可用 `$1`、`${name}` 引用捕获组
+    pub regex: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    /// 匹配所在行中位于匹配之前的文字
+    pub before: String,
+    pub matched: String,
+    /// 匹配结束所在行中位于匹配之后的文字
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub matches: Vec<SearchMatch>,
+    /// 匹配数超过上限，只返回了前一部分
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplaceResult {
+    pub edits: Vec<TextEdit>,
+    pub replaced: usize,
+}
+
+fn build_regex(query: &str, options: &SearchOptions) -> Result<Regex, AppError> {
+    let pattern = if options.regex { query.to_string() } else { regex::escape(query) };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(true)
+        .build()
+        .map_err(|e| AppError::InvalidPattern(e.to_string()))
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// 全字匹配：匹配两侧都不是单词字符
+fn at_word_boundary(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back().is_none_or(|c| !is_word_char(c));
+    let after = text[end..].chars().next().is_none_or(|c| !is_word_char(c));
+    before && after
+}
+
+/// 符合选项的匹配（跳过空匹配）
+fn find_matches<'r, 't>(
+    text: &'t str,
+    re: &'r Regex,
+    options: &SearchOptions,
+) -> impl Iterator<Item = regex::Captures<'t>> + use<'r, 't> {
+    let whole_word = options.whole_word;
+    re.captures_iter(text).filter(move |caps| {
+        let m = caps.get(0).unwrap();
+        !m.is_empty() && (!whole_word || at_word_boundary(text, m.start(), m.end()))
+    })
+}
+
+/// 行首的字节偏移
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { starts }
+    }
+
+    /// 字节偏移所在的行（从 0 开始）
+    fn line_of(&self, offset: usize) -> usize {
+        self.starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// 行的字节范围（不含行尾换行符）
+    fn line_range(&self, text: &str, line: usize) -> (usize, usize) {
+        let start = self.starts[line];
+        let end = self.starts.get(line + 1).map(|next| next - 1).unwrap_or(text.len());
+        let end = if text[start..end].ends_with('\r') { end - 1 } else { end };
+        (start, end)
+    }
+
+    /// (行, 列)，均从 1 开始，列按 UTF-16 计数
+    fn position(&self, text: &str, offset: usize) -> (usize, usize) {
+        let line = self.line_of(offset);
+        let column = text[self.starts[line]..offset].encode_utf16().count() + 1;
+        (line + 1, column)
+    }
+}
+
+fn last_chars(text: &str, count: usize) -> String {
+    let skip = text.chars().count().saturating_sub(count);
+    text.chars().skip(skip).collect()
+}
+
+pub fn search(markdown: &str, query: &str, options: &SearchOptions) -> Result<SearchResult, AppError> {
+    if query.is_empty() {
+        return Ok(SearchResult { matches: Vec::new(), truncated: false });
+    }
+    let re = build_regex(query, options)?;
+    let index = LineIndex::new(markdown);
+
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    for caps in find_matches(markdown, &re, options) {
+        if matches.len() == MAX_MATCHES {
+            truncated = true;
+            break;
+        }
+        let m = caps.get(0).unwrap();
+        let (line, column) = index.position(markdown, m.start());
+        let (end_line, end_column) = index.position(markdown, m.end());
+        let (line_start, _) = index.line_range(markdown, line - 1);
+        let (_, line_end) = index.line_range(markdown, index.line_of(m.end()));
+        matches.push(SearchMatch {
+            line,
+            column,
+            end_line,
+            end_column,
+            before: last_chars(&markdown[line_start..m.start()], CONTEXT_CHARS),
+            matched: m.as_str().to_string(),
+            after: markdown[m.end()..line_end.max(m.end())].chars().take(CONTEXT_CHARS).collect(),
+        });
+    }
+    Ok(SearchResult { matches, truncated })
+}
+
+pub fn replace(markdown: &str, query: &str, replacement: &str, options: &SearchOptions) -> Result<ReplaceResult, AppError> {
+    if query.is_empty() {
+        return Ok(ReplaceResult { edits: Vec::new(), replaced: 0 });
+    }
+    let re = build_regex(query, options)?;
+    let index = LineIndex::new(markdown);
+
+    // 相邻的匹配涉及同一行时合并为一个编辑：(起始行, 结束行, 该范围内的匹配)
+    let mut groups: Vec<(usize, usize, Vec<regex::Captures>)> = Vec::new();
+    for caps in find_matches(markdown, &re, options) {
+        let m = caps.get(0).unwrap();
+        let first = index.line_of(m.start());
+        let last = index.line_of(m.end() - 1);
+        match groups.last_mut() {
+            Some((_, end, group)) if first <= *end => {
+                *end = (*end).max(last);
+                group.push(caps);
+            }
+            _ => groups.push((first, last, vec![caps])),
+        }
+    }
+
+    let mut replaced = 0;
+    let mut edits = Vec::with_capacity(groups.len());
+    for (first, last, group) in groups {
+        let start = index.starts[first];
+        let end = index.starts.get(last + 1).copied().unwrap_or(markdown.len());
+        let mut new_text = String::with_capacity(end - start);
+        let mut pos = start;
+        for caps in &group {
+            let m = caps.get(0).unwrap();
+            new_text.push_str(&markdown[pos..m.start()]);
+            if options.regex {
+                caps.expand(replacement, &mut new_text);
+            } else {
+                new_text.push_str(replacement);
+            }
+            pos = m.end();
+        }
+        new_text.push_str(&markdown[pos..end]);
+        replaced += group.len();
+        edits.push(TextEdit { start_line: first + 1, end_line: last + 2, new_text });
+    }
+    Ok(ReplaceResult { edits, replaced })
+}
+
+/// 在整篇文档中搜索
+#[tauri::command]
+pub fn search_document(markdown: String, query: String, options: Option<SearchOptions>) -> Result<SearchResult, AppError> {
+    let options = options.unwrap_or_default();
+    crate::error::catch_panic("search_document", || search(&markdown, &query, &options))
+}
+
+/// 全部替换，返回按行的文本编辑列表
+#[tauri::command]
+pub fn replace_all(
+    markdown: String,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+) -> Result<ReplaceResult, AppError> {
+    let options = options.unwrap_or_default();
+    crate::error::catch_panic("replace_all", || replace(&markdown, &query, &replacement, &options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(case_sensitive: bool, whole_word: bool, regex: bool) -> SearchOptions {
+        SearchOptions { case_sensitive, whole_word, regex }
+    }
+
+    /// 按行把编辑应用到原文
+    fn apply(markdown: &str, edits: &[TextEdit]) -> String {
+        let lines: Vec<&str> = markdown.split_inclusive('\n').collect();
+        let mut out = String::new();
+        let mut next = 0;
+        for edit in edits {
+            out.push_str(&lines[next..edit.start_line - 1].concat());
+            out.push_str(&edit.new_text);
+            next = edit.end_line - 1;
+        }
+        out.push_str(&lines[next.min(lines.len())..].concat());
+        out
+    }
+
+    #[test]
+    fn reports_utf16_columns_and_context() {
+        let markdown = "第一行\r\n😀 Foo bar foo\n";
+        let result = search(markdown, "foo", &options(false, false, false)).unwrap();
+        let found: Vec<(usize, usize, usize)> = result.matches.iter().map(|m| (m.line, m.column, m.end_column)).collect();
+        assert_eq!(found, [(2, 4, 7), (2, 12, 15)]);
+        assert_eq!(result.matches[0].before, "😀 ");
+        assert_eq!(result.matches[0].after, " bar foo");
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn honours_case_and_whole_word() {
+        let markdown = "foo Foo food _foo foo_bar";
+        assert_eq!(search(markdown, "foo", &options(true, false, false)).unwrap().matches.len(), 4);
+        assert_eq!(search(markdown, "foo", &options(false, true, false)).unwrap().matches.len(), 2);
+        assert!(search(markdown, "", &options(false, false, false)).unwrap().matches.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_regex() {
+        let error = search("text", "(", &options(false, false, true)).unwrap_err();
+        assert_eq!(error.code(), "INVALID_PATTERN");
+        // 非正则模式下按字面搜索
+        assert_eq!(search("a(b", "(", &options(false, false, false)).unwrap().matches.len(), 1);
+    }
+
+    #[test]
+    fn replaces_with_minimal_line_edits() {
+        let markdown = "keep\nfoo and foo\r\nkeep\nfoo\n";
+        let result = replace(markdown, "foo", "bar", &options(false, false, false)).unwrap();
+        assert_eq!(result.replaced, 3);
+        let ranges: Vec<(usize, usize)> = result.edits.iter().map(|e| (e.start_line, e.end_line)).collect();
+        assert_eq!(ranges, [(2, 3), (4, 5)]);
+        assert_eq!(apply(markdown, &result.edits), "keep\nbar and bar\r\nkeep\nbar\n");
+    }
+
+    #[test]
+    fn merges_multiline_matches_and_expands_captures() {
+        let markdown = "a1\nb2\nc3";
+        let result = replace(markdown, r"(\w)(\d)\n(\w)", "$2$1-$3", &options(true, false, true)).unwrap();
+        assert_eq!(result.replaced, 1);
+        assert_eq!(apply(markdown, &result.edits), "1a-b2\nc3");
+
+        // 两个匹配共用第 2 行，合并为一个编辑
+        let result = replace(markdown, r"\d\n\w", "-", &options(true, false, true)).unwrap();
+        let ranges: Vec<(usize, usize)> = result.edits.iter().map(|e| (e.start_line, e.end_line)).collect();
+        assert_eq!(ranges, [(1, 4)]);
+        assert_eq!(apply(markdown, &result.edits), "a--3");
+
+        // 以换行结尾的匹配不涉及下一行
+        let result = replace(markdown, r"\d\n", "", &options(true, false, true)).unwrap();
+        assert_eq!(result.edits.len(), 2);
+        assert_eq!(apply(markdown, &result.edits), "abc3");
+    }
+}
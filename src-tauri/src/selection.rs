@@ -0,0 +1,221 @@
+//! 部分导出：按行范围、块 ID 或章节截取文档。
+//!
+//! 截取以块为单位（`split_markdown_blocks` 的块模型），公式、表格、代码块不会被截断；
+//! 文档的 front matter 始终保留，以便密级横幅、封面等仍然生效；选中内容引用的链接定义
+//! （`[id]: url`）与脚注定义（`[^1]: …`）附在末尾，引用不会变成普通文本。
+
+use crate::error::AppError;
+use crate::parser_mode::ParserMode;
+use crate::MarkdownBlock;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BlockSelection {
+    /// 与 `start_line..=end_line`（1-indexed）有重叠的块
+    Lines { start_line: usize, end_line: usize },
+    /// 指定 ID 的块
+    Blocks { ids: Vec<String> },
+    /// `line` 所在的块；若为标题，则一直到下一个同级或更高级标题之前
+    Section { line: usize },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectedMarkdown {
+    pub markdown: String,
+    /// 选中内容在原文中的起止行
+    pub start_line: usize,
+    pub end_line: usize,
+    pub block_count: usize,
+}
+
+/// 标题级别（ATX 或 Setext），不是标题时返回 `None`
+fn heading_level(block: &MarkdownBlock) -> Option<usize> {
+    if block.block_type != "heading" && block.block_type != "line" {
+        return None;
+    }
+    let first = block.content.lines().next()?.trim_start();
+    let hashes = first.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) && first[hashes..].chars().next().is_none_or(char::is_whitespace) {
+        return Some(hashes);
+    }
+    if block.block_type == "heading" {
+        let underline = block.content.lines().last()?.trim();
+        if underline.starts_with('=') {
+            return Some(1);
+        }
+        if underline.starts_with('-') {
+            return Some(2);
+        }
+    }
+    None
+}
+
+/// 选中块的下标
+fn selected_indices(blocks: &[MarkdownBlock], selection: &BlockSelection) -> Vec<usize> {
+    match selection {
+        BlockSelection::Lines { start_line, end_line } => blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.end_line >= *start_line && b.start_line <= *end_line)
+            .map(|(i, _)| i)
+            .collect(),
+        BlockSelection::Blocks { ids } => {
+            let ids: HashSet<&str> = ids.iter().map(String::as_str).collect();
+            blocks
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| ids.contains(b.id.as_str()))
+                .map(|(i, _)| i)
+                .collect()
+        }
+        BlockSelection::Section { line } => {
+            let Some(start) = blocks.iter().position(|b| b.start_line <= *line && *line <= b.end_line) else {
+                return Vec::new();
+            };
+            let Some(level) = heading_level(&blocks[start]) else {
+                return vec![start];
+            };
+            let end = blocks[start + 1..]
+                .iter()
+                .position(|b| heading_level(b).is_some_and(|l| l <= level))
+                .map(|offset| start + 1 + offset)
+                .unwrap_or(blocks.len());
+            (start..end).collect()
+        }
+    }
+}
+
+/// 未选中部分中的链接定义与脚注定义：(引用形式的小写，如 `[id]`、`[^1]`) → 定义原文
+fn definitions(blocks: &[MarkdownBlock], selected: &[usize]) -> Vec<(String, String)> {
+    let re_link = Regex::new(r"^ {0,3}(\[[^\]^][^\]]*\]):\s*\S").unwrap();
+    let re_footnote = Regex::new(r"^ {0,3}(\[\^[^\]]+\]):").unwrap();
+    let mut found = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        if selected.contains(&i) {
+            continue;
+        }
+        match block.block_type.as_str() {
+            "footnoteDefinition" => {
+                if let Some(caps) = re_footnote.captures(&block.content) {
+                    found.push((caps[1].to_lowercase(), block.content.clone()));
+                }
+            }
+            "code" | "math" | "html" | "yaml" | "toml" => {}
+            _ => found.extend(
+                block
+                    .content
+                    .lines()
+                    .filter_map(|line| re_link.captures(line).map(|caps| (caps[1].to_lowercase(), line.to_string()))),
+            ),
+        }
+    }
+    found
+}
+
+/// 按选择截取文档；相邻的块保留原文中的间隔，不相邻的部分之间用空行分隔
+pub fn select_blocks(markdown: &str, selection: &BlockSelection) -> Result<SelectedMarkdown, AppError> {
+    let content = markdown.replace("\r\n", "\n");
+    let lines: Vec<&str> = content.lines().collect();
+    let mode = ParserMode::resolve(None, &content);
+    let blocks = crate::split_markdown_blocks(&content, mode);
+
+    let mut indices = selected_indices(&blocks, selection);
+    if indices.is_empty() {
+        return Err(AppError::localized("PDF_GENERATION", "PDF_EMPTY_SELECTION", []));
+    }
+    let (start_line, end_line) = (blocks[indices[0]].start_line, blocks[*indices.last().unwrap()].end_line);
+    let block_count = indices.len();
+
+    // front matter 始终保留
+    if let Some(front) = blocks.iter().position(|b| b.block_type == "yaml") {
+        if !indices.contains(&front) {
+            indices.insert(0, front);
+        }
+    }
+
+    // 合并为连续的行区间
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (pos, &idx) in indices.iter().enumerate() {
+        let block = &blocks[idx];
+        let adjacent = pos > 0 && indices[pos - 1] + 1 == idx;
+        match ranges.last_mut() {
+            Some(last) if adjacent => last.1 = block.end_line,
+            _ => ranges.push((block.start_line, block.end_line)),
+        }
+    }
+    let mut parts: Vec<String> = ranges
+        .iter()
+        .map(|&(start, end)| lines.get(start - 1..end).unwrap_or(&[]).join("\n"))
+        .collect();
+
+    // 附上被引用的定义；脚注定义中可能再引用其他定义，直到不再有新的引用
+    let mut pending = definitions(&blocks, &indices);
+    let mut referenced = parts.join("\n").to_lowercase();
+    while let Some(pos) = pending.iter().position(|(label, _)| referenced.contains(label.as_str())) {
+        let (label, definition) = pending.remove(pos);
+        pending.retain(|(other, _)| *other != label);
+        referenced.push('\n');
+        referenced.push_str(&definition.to_lowercase());
+        parts.push(definition);
+    }
+
+    Ok(SelectedMarkdown {
+        markdown: parts.join("\n\n"),
+        start_line,
+        end_line,
+        block_count,
+    })
+}
+
+/// 截取文档的一部分用于导出（如只打印一章或当前选中的块）
+#[tauri::command]
+pub fn select_markdown(markdown: String, selection: BlockSelection) -> Result<SelectedMarkdown, AppError> {
+    crate::error::catch_panic("select_markdown", || select_blocks(&markdown, &selection))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "---\ntitle: 报告\n---\n\n# 第一章\n\n见 [手册][Manual] 与脚注[^a]。\n\n# 第二章\n\n\
+                       其他内容[^b]。\n\n[manual]: https://example.com/manual\n[unused]: https://example.com\n\n\
+                       [^a]: 脚注 A，参见[^c]。\n\n[^b]: 脚注 B。\n\n[^c]: 脚注 C。\n";
+
+    fn chapter_one() -> SelectedMarkdown {
+        let line = DOC.lines().position(|l| l == "# 第一章").unwrap() + 1;
+        select_blocks(DOC, &BlockSelection::Section { line }).unwrap()
+    }
+
+    #[test]
+    fn keeps_front_matter_and_the_section() {
+        let selected = chapter_one();
+        assert!(selected.markdown.starts_with("---\ntitle: 报告\n---"));
+        assert!(selected.markdown.contains("# 第一章"));
+        assert!(!selected.markdown.contains("# 第二章"));
+        assert_eq!(selected.block_count, 2);
+    }
+
+    #[test]
+    fn carries_referenced_link_definitions() {
+        let selected = chapter_one();
+        assert!(selected.markdown.contains("[manual]: https://example.com/manual"));
+        assert!(!selected.markdown.contains("[unused]:"));
+    }
+
+    #[test]
+    fn carries_referenced_footnotes_transitively() {
+        let selected = chapter_one();
+        assert!(selected.markdown.contains("[^a]: 脚注 A"));
+        assert!(selected.markdown.contains("[^c]: 脚注 C。"));
+        assert!(!selected.markdown.contains("[^b]:"));
+    }
+
+    #[test]
+    fn empty_selection_is_an_error() {
+        let selection = BlockSelection::Blocks { ids: vec!["missing".to_string()] };
+        assert!(matches!(select_blocks(DOC, &selection), Err(e) if e.code() == "PDF_GENERATION"));
+    }
+}
@@ -0,0 +1,144 @@
+//! 应用设置：保存在应用配置目录下的 `settings.json`
+
+use crate::callouts::CalloutStyle;
+use crate::code_blocks::CodeBlockSettings;
+use crate::content_security::ExternalResourcePolicy;
+use crate::equations::EquationNumbering;
+use crate::error::AppError;
+use crate::fonts::FontSettings;
+use crate::i18n::{self, Locale};
+use crate::kinsoku::LineBreakRule;
+use crate::layout::{PageLayout, PaperSize};
+use crate::line_breaks::LineBreaks;
+use crate::literate::LiterateSettings;
+use crate::math_engine::MathEngine;
+use crate::pdf_archive::PdfStandard;
+use crate::smart_quotes::QuoteStyle;
+use crate::table_fit::TableFit;
+use crate::timeouts::StageTimeouts;
+use crate::transforms::TransformSettings;
+use crate::typography::TypographySettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// 界面与错误信息语言
+    pub locale: Locale,
+    /// 文学化模式：导出时执行 `{run}` 代码块
+    pub literate: LiterateSettings,
+    /// 组织策略文件路径（见 `policy` 模块），导出时强制应用
+    pub policy_path: Option<String>,
+    /// 未标注 `{fit=...}` 的宽表格的默认分页策略
+    pub table_fit: TableFit,
+    /// 导出使用的字体、字号与行高（front matter 中的 `fonts` 可以覆盖）
+    pub fonts: FontSettings,
+    /// 代码块的行号与自动折行（front matter 中的 `code_blocks` 可以覆盖，围栏属性可以逐块覆盖）
+    pub code_blocks: CodeBlockSettings,
+    /// 导出时的公式引擎
+    pub math_engine: MathEngine,
+    /// 行间公式的自动编号范围（front matter 中的 `equation_numbering` 可以覆盖）
+    pub equation_numbering: EquationNumbering,
+    /// 段落内换行的默认处理方式（front matter 中的 `hardbreaks` 可以覆盖）
+    pub line_breaks: LineBreaks,
+    /// 中日文标点避头尾的默认规则（front matter 中的 `cjk_line_break` 可以覆盖）
+    pub cjk_line_break: LineBreakRule,
+    /// 导出时另起一页的标题级别，如 `[1]` 表示每个一级标题另起一页（front matter 中的 `new_page` 可以覆盖）
+    pub new_page_levels: Vec<u8>,
+    /// 正文单栏或两栏（front matter 中的 `layout` 可以覆盖）
+    pub page_layout: PageLayout,
+    /// 纸张：A4 或小册子（A5，front matter 中的 `paper` 可以覆盖）
+    pub paper_size: PaperSize,
+    /// 普通 PDF、带结构标签的 PDF 或 PDF/A（front matter 中的 `pdf_standard` 可以覆盖）
+    pub pdf_standard: PdfStandard,
+    /// 是否启用智能标点（弯引号、破折号与省略号，front matter 中的 `smart_quotes` 可以覆盖）
+    pub smart_punctuation: bool,
+    /// 智能引号的默认样式（front matter 中的 `smart_quotes` 或 `lang` 可以覆盖）
+    pub quote_style: QuoteStyle,
+    /// 中日文排版规范化：中西文间空格、全角标点与半角字母数字（front matter 中的 `typography` 可以覆盖）
+    pub typography: TypographySettings,
+    /// 提示块各类型的图标、颜色与标题覆盖，也可声明新的类型（项目配置与 front matter 可以再覆盖）
+    pub callouts: BTreeMap<String, CalloutStyle>,
+    /// 导出时是否允许无头浏览器访问外部地址（CDN、远程图片等）：允许、先询问或离线
+    pub external_resources: ExternalResourcePolicy,
+    /// 导出各阶段的超时时间
+    pub timeouts: StageTimeouts,
+    /// 导出前 Markdown 预处理管线的顺序与启用状态（自定义转换见 `transforms.toml`）
+    pub transforms: TransformSettings,
+}
+
+pub struct SettingsState(pub Mutex<AppSettings>);
+
+impl SettingsState {
+    pub fn snapshot(&self) -> AppSettings {
+        self.0.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+fn settings_path(app: &tauri::AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join("settings.json"))
+}
+
+/// 读取设置文件；不存在或损坏时使用默认值
+pub fn load(app: &tauri::AppHandle) -> AppSettings {
+    settings_path(app)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(), AppError> {
+    let path = settings_path(app)
+        .ok_or_else(|| AppError::localized("SETTINGS", "SETTINGS_CONFIG_DIR", []))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::file(parent, e))?;
+    }
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| AppError::SettingsError(e.to_string()))?;
+    fs::write(&path, content).map_err(|e| AppError::file(&path, e))?;
+    Ok(())
+}
+
+/// 修改当前设置并持久化（供单项设置的命令使用）
+pub(crate) fn update(
+    app: &tauri::AppHandle,
+    state: &SettingsState,
+    f: impl FnOnce(&mut AppSettings),
+) -> Result<AppSettings, AppError> {
+    let mut settings = state.snapshot();
+    f(&mut settings);
+    save(app, &settings)?;
+    if let Ok(mut current) = state.0.lock() {
+        *current = settings.clone();
+    }
+    Ok(settings)
+}
+
+/// 获取当前设置
+#[tauri::command]
+pub fn get_settings(state: tauri::State<'_, SettingsState>) -> AppSettings {
+    state.snapshot()
+}
+
+/// 更新并持久化设置
+#[tauri::command]
+pub fn update_settings(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    settings: AppSettings,
+) -> Result<AppSettings, AppError> {
+    save(&app, &settings)?;
+    i18n::set_current_locale(settings.locale);
+    if let Ok(mut current) = state.0.lock() {
+        *current = settings.clone();
+    }
+    Ok(settings)
+}
@@ -0,0 +1,95 @@
+//! 标题锚点 slug 生成（GitHub 风格），重复的 slug 依次追加 `-1`、`-2`。
+//!
+//! 文档检查、导出页面的标题锚点与 PDF 书签都使用这里的规则，保证 `[跳转](#标题)` 在各处指向同一个标题。
+
+use crate::checker::percent_decode;
+use crate::html_util::{escape_html, strip_tags, unescape_html};
+use regex::{Captures, Regex};
+use std::collections::{HashMap, HashSet};
+
+/// 将标题文本转换为 slug：转小写，去掉标点，空白替换为 `-`，保留中日韩等非 ASCII 字母数字
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() {
+            slug.push('-');
+        }
+    }
+    slug
+}
+
+/// 为同一文档内的多个标题生成唯一 slug
+#[derive(Debug, Default)]
+pub struct Slugger {
+    seen: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub fn new() -> Self {
+        Slugger::default()
+    }
+
+    /// 返回唯一 slug，以及该 slug 的基础形式此前是否已出现过
+    pub fn slug_with_duplicate(&mut self, text: &str) -> (String, bool) {
+        let base = slugify(text);
+        let count = self.seen.entry(base.clone()).or_insert(0);
+        let duplicate = *count > 0;
+        let slug = if duplicate { format!("{}-{}", base, count) } else { base.clone() };
+        *count += 1;
+        if duplicate {
+            self.seen.entry(slug.clone()).or_insert(1);
+        }
+        (slug, duplicate)
+    }
+
+    pub fn slug(&mut self, text: &str) -> String {
+        self.slug_with_duplicate(text).0
+    }
+}
+
+/// 为 HTML 中没有 `id` 的标题添加锚点，并把写法不同（大小写、空格、标点）的页内链接 `#...`
+/// 统一为对应标题的 slug；已有 `id` 的标题保持不变
+pub fn anchor_headings(html: &str) -> String {
+    let re_heading = Regex::new(r"(?s)<h([1-6])((?:\s[^>]*)?)>(.*?)</h[1-6]>").unwrap();
+    let re_id = Regex::new(r#"(?:^|\s)id\s*=\s*"([^"]*)""#).unwrap();
+    let mut slugger = Slugger::new();
+    let mut heading_ids = HashSet::new();
+
+    let html = re_heading.replace_all(html, |caps: &Captures| {
+        if let Some(id) = re_id.captures(&caps[2]) {
+            heading_ids.insert(unescape_html(&id[1]));
+            return caps[0].to_string();
+        }
+        let slug = slugger.slug(&strip_tags(&caps[3]));
+        if slug.is_empty() {
+            return caps[0].to_string();
+        }
+        let tag = format!(r#"<h{} id="{}"{}>"#, &caps[1], escape_html(&slug), &caps[2]);
+        heading_ids.insert(slug);
+        format!("{}{}</h{}>", tag, &caps[3], &caps[1])
+    });
+
+    // 页内链接：已有同名元素时保持不变，否则按 slug 规则匹配标题
+    let all_ids: HashSet<String> = Regex::new(r#"\sid\s*=\s*"([^"]*)""#)
+        .unwrap()
+        .captures_iter(&html)
+        .map(|caps| unescape_html(&caps[1]))
+        .collect();
+    let re_link = Regex::new(r##"href="#([^"]+)""##).unwrap();
+    re_link
+        .replace_all(&html, |caps: &Captures| {
+            let fragment = percent_decode(&unescape_html(&caps[1]));
+            if all_ids.contains(&fragment) {
+                return caps[0].to_string();
+            }
+            let slug = slugify(&fragment);
+            if heading_ids.contains(&slug) {
+                format!(r##"href="#{}""##, escape_html(&slug))
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .to_string()
+}
@@ -0,0 +1,118 @@
+//! 智能标点：把直引号转换为弯引号，`--`、`---`、`...` 转换为短破折号、长破折号与省略号。
+//! 引号样式按语言选择：
+//!  - `english`（默认）：“双引号” ‘单引号’
+//!  - `german`：„双引号“ ‚单引号‘
+//!  - `french`：« 双引号 » ‹ 单引号 ›（引号内侧加不换行窄空格）
+//!
+//! 设置中的 `smart_punctuation` 与 `quote_style` 为默认值；front matter 中的 `smart_quotes`
+//! （`true` / `false` / 样式名）覆盖，未声明时按 `lang` 选择样式。严格模式下不处理。
+
+use crate::front_matter;
+use crate::settings::{AppSettings, SettingsState};
+use pulldown_cmark::{CowStr, Event};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteStyle {
+    #[default]
+    English,
+    German,
+    French,
+}
+
+/// 法语引号内侧的不换行窄空格
+const NARROW_NBSP: char = '\u{202F}';
+
+impl QuoteStyle {
+    /// 语言代码对应的样式（`de-CH` 等按主语言处理）
+    fn from_lang(lang: &str) -> Option<QuoteStyle> {
+        match lang.split(['-', '_']).next()?.trim().to_ascii_lowercase().as_str() {
+            "en" => Some(QuoteStyle::English),
+            "de" => Some(QuoteStyle::German),
+            "fr" => Some(QuoteStyle::French),
+            _ => None,
+        }
+    }
+
+    /// 双引号开、闭与单引号开、闭（法语引号内侧带窄空格）
+    fn marks(self) -> [String; 4] {
+        match self {
+            QuoteStyle::English => ["“", "”", "‘", "’"].map(str::to_string),
+            QuoteStyle::German => ["„", "“", "‚", "‘"].map(str::to_string),
+            QuoteStyle::French => [
+                format!("«{}", NARROW_NBSP),
+                format!("{}»", NARROW_NBSP),
+                format!("‹{}", NARROW_NBSP),
+                format!("{}›", NARROW_NBSP),
+            ],
+        }
+    }
+}
+
+/// 文档使用的智能引号样式，`None` 表示不启用智能标点
+pub fn resolve(settings: &AppSettings, markdown: &str) -> Option<QuoteStyle> {
+    let fm = front_matter::parse(markdown);
+    let declared = fm.as_ref().and_then(|fm| fm.get("smart_quotes"));
+    let enabled = match declared {
+        Some(Value::Bool(enabled)) => *enabled,
+        Some(Value::String(_)) => true,
+        _ => settings.smart_punctuation,
+    };
+    if !enabled {
+        return None;
+    }
+    let style = declared
+        .and_then(|value| serde_yaml::from_value(value.clone()).ok())
+        .or_else(|| fm.as_ref()?.get("lang")?.as_str().and_then(QuoteStyle::from_lang))
+        .unwrap_or(settings.quote_style);
+    Some(style)
+}
+
+/// 改写 pulldown-cmark 智能标点生成的引号（解析器把每个引号作为单独的文本事件输出）。
+/// 单闭引号与撇号都是 `’`：开引号之后、后面不紧跟字母数字的第一个 `’` 视为闭引号。
+pub fn apply_to_events(events: Vec<Event<'_>>, style: QuoteStyle) -> Vec<Event<'_>> {
+    if style == QuoteStyle::English {
+        return events;
+    }
+    let [open_double, close_double, open_single, close_single] = style.marks();
+    let mut single_open = false;
+    let mut out = Vec::with_capacity(events.len());
+    let mut iter = events.into_iter().peekable();
+    while let Some(event) = iter.next() {
+        let quote = match &event {
+            Event::Text(text) if text.chars().count() == 1 => text.chars().next(),
+            _ => None,
+        };
+        let replacement = match quote {
+            Some('“') => Some(&open_double),
+            Some('”') => Some(&close_double),
+            Some('‘') => {
+                single_open = true;
+                Some(&open_single)
+            }
+            Some('’') if single_open => {
+                let in_word = matches!(iter.peek(), Some(Event::Text(next)) if next.starts_with(char::is_alphanumeric));
+                if in_word {
+                    None
+                } else {
+                    single_open = false;
+                    Some(&close_single)
+                }
+            }
+            _ => None,
+        };
+        out.push(match replacement {
+            Some(mark) => Event::Text(CowStr::from(mark.clone())),
+            None => event,
+        });
+    }
+    out
+}
+
+/// 文档的智能引号样式（供前端预览与导出时转换引号），`null` 表示不启用
+#[tauri::command]
+pub fn detect_smart_quotes(markdown: &str, settings: tauri::State<'_, SettingsState>) -> Option<QuoteStyle> {
+    resolve(&settings.snapshot(), markdown)
+}
@@ -0,0 +1,110 @@
+//! 分批渲染预览：数千个块的文档不再等待一整段 HTML，而是按块分组渲染，
+//! 每组完成后发送 `preview-chunk` 事件，前端逐步显示。
+//!
+//! 分组沿用 `parse_markdown_blocks` 的块边界，公式块、表格等不会被拆开。
+//! 各组独立渲染，因此脚注与链接引用定义只在所在的组内生效。
+//! 新的渲染开始后，进行中的旧渲染在当前组完成后停止。
+
+use crate::error::AppError;
+use crate::parser_mode::ParserMode;
+use crate::settings::SettingsState;
+use crate::{callouts, front_matter, line_breaks, smart_quotes, typography};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::Emitter;
+
+/// 每组最多的块数
+const BLOCKS_PER_CHUNK: usize = 200;
+
+/// 每组最多的字符数（超过时提前结束该组）
+const CHARS_PER_CHUNK: usize = 64 * 1024;
+
+/// 最近一次渲染的序号，旧的渲染发现序号变化后停止
+static LATEST_RENDER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewChunk {
+    /// 前端传入的渲染 ID，用于丢弃过期的事件
+    pub render_id: String,
+    pub index: usize,
+    pub total: usize,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub html: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamedRender {
+    pub render_id: String,
+    pub chunks: usize,
+    /// 被更新的渲染取代，未发送全部分组
+    pub superseded: bool,
+}
+
+/// 按块边界把文档分成若干组，返回每组的行范围（1-indexed，闭区间），覆盖全部行
+fn chunk_ranges(markdown: &str, mode: ParserMode) -> Vec<(usize, usize)> {
+    let blocks = crate::split_markdown_blocks(markdown, mode);
+    let line_count = markdown.lines().count();
+    let mut ranges = Vec::new();
+    let mut start = 1;
+    let mut count = 0;
+    let mut chars = 0;
+    for block in &blocks {
+        count += 1;
+        chars += block.content.len();
+        if count >= BLOCKS_PER_CHUNK || chars >= CHARS_PER_CHUNK {
+            ranges.push((start, block.end_line));
+            start = block.end_line + 1;
+            count = 0;
+            chars = 0;
+        }
+    }
+    if start <= line_count || ranges.is_empty() {
+        ranges.push((start, line_count.max(start)));
+    }
+    ranges
+}
+
+/// 分批渲染预览，每组通过 `preview-chunk` 事件发送
+#[tauri::command]
+pub async fn render_document_streamed(
+    window: tauri::Window,
+    settings: tauri::State<'_, SettingsState>,
+    markdown: String,
+    mode: Option<ParserMode>,
+    render_id: String,
+) -> Result<StreamedRender, AppError> {
+    let generation = LATEST_RENDER.fetch_add(1, Ordering::SeqCst) + 1;
+    let settings = settings.snapshot();
+
+    crate::error::run_blocking("render_document_streamed", move || {
+        let mode = ParserMode::resolve(mode, &markdown);
+        let line_breaks = line_breaks::LineBreaks::resolve(settings.line_breaks, &markdown);
+        let smart_quotes = smart_quotes::resolve(&settings, &markdown);
+        let typography = typography::resolve(&settings, &markdown);
+        let callout_styles = callouts::resolve(&settings, None, front_matter::parse(&markdown).as_ref());
+
+        let content = markdown.replace("\r\n", "\n");
+        let lines: Vec<&str> = content.lines().collect();
+        let ranges = chunk_ranges(&content, mode);
+        let total = ranges.len();
+
+        for (index, &(start_line, end_line)) in ranges.iter().enumerate() {
+            if LATEST_RENDER.load(Ordering::SeqCst) != generation {
+                return Ok(StreamedRender { render_id, chunks: index, superseded: true });
+            }
+            let mut chunk = lines.get(start_line - 1..end_line).unwrap_or(&[]).join("\n");
+            // 后续分组开头的 `---` 是分隔线，不能被当作 front matter
+            if index > 0 {
+                chunk.insert(0, '\n');
+            }
+            let html = crate::render_preview_html(&chunk, mode, line_breaks, smart_quotes, &typography, &callout_styles);
+            let _ = window.emit(
+                "preview-chunk",
+                PreviewChunk { render_id: render_id.clone(), index, total, start_line, end_line, html },
+            );
+        }
+        Ok(StreamedRender { render_id, chunks: total, superseded: false })
+    })
+    .await
+}
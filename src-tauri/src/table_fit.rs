@@ -0,0 +1,256 @@
+//! 宽表格的分页策略：在表格前一段（空一行）写 `{fit=shrink}`、`{fit=landscape}` 或
+//! `{fit=split key=1 columns=5}`，未标注的表格使用设置中的 `table_fit`。
+//!
+//! - `shrink`：表格超出页宽时整体缩小（由导出页面中的脚本按实际宽度计算缩放比例）
+//! - `landscape`：表格单独占一页并旋转 90°，以页面高度作为表格宽度
+//! - `split`：按列拆分为多张页宽以内的表格，每张都重复前 `key` 列（默认 1 列）；
+//!   `columns` 指定每张表格的数据列数，省略时按单元格文字宽度估算
+//!
+//! `landscape`、`split` 只对估算宽度超出页宽的表格生效，显式标注的 `landscape` 除外。
+
+use crate::figure::parse_attributes;
+use crate::html_util::{find_closing_tag, strip_tags};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFit {
+    /// 不处理，超出页宽的部分被裁掉
+    #[default]
+    None,
+    Shrink,
+    Landscape,
+    Split,
+}
+
+impl TableFit {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "none" | "clip" => Some(TableFit::None),
+            "shrink" => Some(TableFit::Shrink),
+            "landscape" | "rotate" => Some(TableFit::Landscape),
+            "split" => Some(TableFit::Split),
+            _ => None,
+        }
+    }
+}
+
+/// 导出页面正文的可用宽度（px），与模板中 A4 纸、打印样式的边距一致
+const PAGE_CONTENT_PX: f64 = 680.0;
+/// 半角字符的估算宽度（px），全角字符按两倍计算
+const CHAR_PX: f64 = 8.0;
+/// 单元格左右内边距与边框（px）
+const CELL_PADDING_PX: f64 = 34.0;
+/// 单列按文字估算的宽度上限（半角字符数），更长的内容会换行
+const MAX_COLUMN_CHARS: usize = 40;
+
+#[derive(Debug, Clone)]
+struct TableSpec {
+    fit: TableFit,
+    /// 拆分时重复的前几列
+    key_columns: usize,
+    /// 拆分时每张表格的数据列数，`None` 为自动
+    columns: Option<usize>,
+    /// 是否由属性显式指定
+    explicit: bool,
+}
+
+impl TableSpec {
+    fn new(fit: TableFit) -> Self {
+        TableSpec { fit, key_columns: 1, columns: None, explicit: false }
+    }
+
+    /// 解析 `{fit=... key=... columns=...}`；没有可识别的 `fit` 时返回 `None`
+    fn from_attributes(raw: &str) -> Option<Self> {
+        let attrs = parse_attributes(raw);
+        let fit = attrs.iter().find(|(key, _)| key == "fit").and_then(|(_, value)| TableFit::parse(value))?;
+        let number = |name: &str| -> Option<usize> {
+            attrs.iter().find(|(key, _)| key == name).and_then(|(_, value)| value.parse().ok())
+        };
+        Some(TableSpec {
+            fit,
+            key_columns: number("key").unwrap_or(1),
+            columns: number("columns").filter(|&n| n > 0),
+            explicit: true,
+        })
+    }
+}
+
+/// 表格中的一行：是否位于表头，以及各单元格的完整 HTML
+struct Row {
+    header: bool,
+    cells: Vec<String>,
+}
+
+/// 拆出表格的开始标签、标题与各行；含合并单元格的表格无法按列拆分，返回 `None`
+fn parse_table(table: &str) -> Option<(&str, Option<&str>, Vec<Row>)> {
+    let re_open = Regex::new(r"(?i)^<table\b[^>]*>").unwrap();
+    let re_caption = Regex::new(r"(?is)<caption\b.*?</caption>").unwrap();
+    let re_row = Regex::new(r"(?is)<tr\b[^>]*>(.*?)</tr>").unwrap();
+    let re_cell = Regex::new(r"(?is)<(th|td)\b([^>]*)>.*?</(?:th|td)>").unwrap();
+
+    let open = re_open.find(table)?.as_str();
+    let caption = re_caption.find(table).map(|m| m.as_str());
+    let lower = table.to_ascii_lowercase();
+    let thead_end = lower.find("</thead>");
+
+    let mut rows = Vec::new();
+    for row in re_row.captures_iter(table) {
+        let mut cells = Vec::new();
+        for cell in re_cell.captures_iter(&row[1]) {
+            let attrs = cell[2].to_ascii_lowercase();
+            if attrs.contains("colspan") || attrs.contains("rowspan") {
+                return None;
+            }
+            cells.push(cell[0].to_string());
+        }
+        let start = row.get(0).unwrap().start();
+        let header = thead_end.map_or(cells.iter().all(|c| c[..3].eq_ignore_ascii_case("<th")), |end| start < end);
+        rows.push(Row { header, cells });
+    }
+    (!rows.is_empty()).then_some((open, caption, rows))
+}
+
+/// 按单元格文字估算各列宽度（px）
+fn column_widths(rows: &[Row]) -> Vec<f64> {
+    let count = rows.iter().map(|r| r.cells.len()).max().unwrap_or(0);
+    (0..count)
+        .map(|col| {
+            let chars = rows
+                .iter()
+                .filter_map(|r| r.cells.get(col))
+                .map(|cell| strip_tags(cell).trim().width().min(MAX_COLUMN_CHARS))
+                .max()
+                .unwrap_or(0);
+            chars as f64 * CHAR_PX + CELL_PADDING_PX
+        })
+        .collect()
+}
+
+/// 给开始标签追加 class
+fn add_class(tag: &str, class: &str) -> String {
+    let re_class = Regex::new(r#"(?i)\bclass\s*=\s*"([^"]*)""#).unwrap();
+    if re_class.is_match(tag) {
+        re_class.replace(tag, |caps: &regex::Captures| format!(r#"class="{} {}""#, &caps[1], class)).to_string()
+    } else {
+        let end = tag.trim_end_matches('>').trim_end_matches('/').len();
+        format!(r#"{} class="{}"{}"#, &tag[..end], class, &tag[end..])
+    }
+}
+
+/// 把非键列分组，每组与键列一起不超过页宽（至少一列）
+fn split_columns(widths: &[f64], key_columns: usize, columns: Option<usize>) -> Vec<Vec<usize>> {
+    let data: Vec<usize> = (key_columns..widths.len()).collect();
+    if let Some(per_chunk) = columns {
+        return data.chunks(per_chunk).map(<[usize]>::to_vec).collect();
+    }
+    let key_width: f64 = widths[..key_columns].iter().sum();
+    let mut chunks: Vec<Vec<usize>> = Vec::new();
+    let mut current: Vec<usize> = Vec::new();
+    let mut width = key_width;
+    for col in data {
+        if !current.is_empty() && width + widths[col] > PAGE_CONTENT_PX {
+            chunks.push(std::mem::take(&mut current));
+            width = key_width;
+        }
+        width += widths[col];
+        current.push(col);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// 按列拆分表格，每张都带上键列；无法或无需拆分时返回 `None`
+fn split_table(table: &str, spec: &TableSpec) -> Option<String> {
+    let (open, caption, rows) = parse_table(table)?;
+    let widths = column_widths(&rows);
+    let key_columns = spec.key_columns.min(widths.len().saturating_sub(1));
+    let chunks = split_columns(&widths, key_columns, spec.columns);
+    if chunks.len() < 2 {
+        return None;
+    }
+
+    let mut out = String::new();
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let class = if idx == 0 { "table-split" } else { "table-split table-split-continued" };
+        out.push_str(&add_class(open, class));
+        if idx == 0 {
+            out.push_str(caption.unwrap_or(""));
+        }
+        for header in [true, false] {
+            let section: String = rows
+                .iter()
+                .filter(|r| r.header == header)
+                .map(|row| {
+                    let keys = row.cells.iter().take(key_columns).map(|cell| {
+                        let end = cell.find('>').map_or(0, |i| i + 1);
+                        format!("{}{}", add_class(&cell[..end], "table-key"), &cell[end..])
+                    });
+                    let data = chunk.iter().filter_map(|&col| row.cells.get(col).cloned());
+                    format!("<tr>{}</tr>\n", keys.chain(data).collect::<String>())
+                })
+                .collect();
+            if !section.is_empty() {
+                let tag = if header { "thead" } else { "tbody" };
+                out.push_str(&format!("<{tag}>\n{section}</{tag}>\n", tag = tag, section = section));
+            }
+        }
+        out.push_str("</table>\n");
+    }
+    Some(out)
+}
+
+/// 估算宽度是否超出页宽
+fn is_wide(table: &str) -> bool {
+    parse_table(table).is_some_and(|(_, _, rows)| column_widths(&rows).iter().sum::<f64>() > PAGE_CONTENT_PX)
+}
+
+fn fit_table(table: &str, spec: &TableSpec) -> String {
+    match spec.fit {
+        TableFit::None => table.to_string(),
+        TableFit::Shrink => {
+            let end = table.find('>').map_or(0, |i| i + 1);
+            format!("{}{}", add_class(&table[..end], "table-fit-shrink"), &table[end..])
+        }
+        TableFit::Landscape if spec.explicit || is_wide(table) => {
+            format!("<div class=\"table-landscape\">{}</div>", table)
+        }
+        TableFit::Split => split_table(table, spec).unwrap_or_else(|| table.to_string()),
+        TableFit::Landscape => table.to_string(),
+    }
+}
+
+/// 按表格前的 `{fit=...}` 标记（`attributes` 为 false 时忽略）或默认策略处理所有表格
+pub fn apply_table_fit(html: &str, default_fit: TableFit, attributes: bool) -> String {
+    let re_open = Regex::new(r"(?i)<table\b[^>]*>").unwrap();
+    let re_marker = Regex::new(r"<p>\s*\{([^{}\n]*)\}\s*</p>\s*$").unwrap();
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for open in re_open.find_iter(html) {
+        if open.start() < last {
+            continue; // 嵌套在上一张表格中
+        }
+        let Some((_, end)) = find_closing_tag(html, "table", open.end()) else {
+            continue;
+        };
+        let before = &html[last..open.start()];
+        let marker = re_marker
+            .captures(before)
+            .filter(|_| attributes)
+            .and_then(|caps| Some((caps.get(0)?.start(), TableSpec::from_attributes(&caps[1])?)));
+        let (prefix_end, spec) = match marker {
+            Some((start, spec)) => (start, spec),
+            None => (before.len(), TableSpec::new(default_fit)),
+        };
+        out.push_str(&before[..prefix_end]);
+        out.push_str(&fit_table(&html[open.start()..end], &spec));
+        last = end;
+    }
+    out.push_str(&html[last..]);
+    out
+}
@@ -0,0 +1,67 @@
+//! 任务列表勾选：在预览中点击复选框时切换源码中对应行的 `- [ ]` / `- [x]`。
+//!
+//! 列表项可以任意嵌套，也可以位于引用块中；代码块、公式块与 HTML 块中的行不做修改。
+//! 返回只涉及该行的文本编辑，编辑器据此做最小修改以保留光标位置与撤销历史。
+
+use crate::error::AppError;
+use crate::formatter::{classify_lines, LineKind, TextEdit};
+use regex::Regex;
+
+/// 切换第 `line` 行（1-indexed）的任务状态；该行不是任务列表项时返回 `None`
+pub fn toggle(markdown: &str, line: usize) -> Option<TextEdit> {
+    let raw_lines: Vec<&str> = markdown.split_inclusive('\n').collect();
+    if line == 0 || line > raw_lines.len() {
+        return None;
+    }
+    let lines: Vec<String> = raw_lines
+        .iter()
+        .map(|l| l.trim_end_matches('\n').trim_end_matches('\r').to_string())
+        .collect();
+    if classify_lines(&lines)[line - 1] == LineKind::Protected {
+        return None;
+    }
+
+    let re_task = Regex::new(r"^(\s*(?:>\s?\s*)*(?:[-*+]|\d{1,9}[.)])[ \t]+\[)([ xX])(\](?:[ \t]|$))").unwrap();
+    let text = &lines[line - 1];
+    let caps = re_task.captures(text)?;
+    let mark = if &caps[2] == " " { "x" } else { " " };
+    let eol = &raw_lines[line - 1][text.len()..];
+    let new_text = format!("{}{}{}{}{}", &caps[1], mark, &caps[3], &text[caps.get(0).unwrap().end()..], eol);
+    Some(TextEdit { start_line: line, end_line: line + 1, new_text })
+}
+
+/// 切换任务列表项的勾选状态，返回该行的文本编辑
+#[tauri::command]
+pub fn toggle_task(markdown: String, line: usize) -> Result<Option<TextEdit>, AppError> {
+    crate::error::catch_panic("toggle_task", || Ok(toggle(&markdown, line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toggled(markdown: &str, line: usize) -> Option<String> {
+        toggle(markdown, line).map(|edit| {
+            assert_eq!((edit.start_line, edit.end_line), (line, line + 1));
+            edit.new_text
+        })
+    }
+
+    #[test]
+    fn toggles_task_items() {
+        let markdown = "- [ ] todo\n  * [x] done\r\n> 1. [X] quoted\n- [ ]\n";
+        assert_eq!(toggled(markdown, 1).as_deref(), Some("- [x] todo\n"));
+        assert_eq!(toggled(markdown, 2).as_deref(), Some("  * [ ] done\r\n"));
+        assert_eq!(toggled(markdown, 3).as_deref(), Some("> 1. [ ] quoted\n"));
+        assert_eq!(toggled(markdown, 4).as_deref(), Some("- [x]\n"));
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        let markdown = "text [ ] here\n- [ ]no space\n```\n- [ ] in code\n```\n- [ ] last";
+        for line in [0, 1, 2, 4, 7] {
+            assert_eq!(toggled(markdown, line), None, "line {line}");
+        }
+        assert_eq!(toggled(markdown, 6).as_deref(), Some("- [x] last"));
+    }
+}
@@ -0,0 +1,67 @@
+//! 导出各阶段的超时预算：导航、资源加载（图片、字体等）、公式排版与打印分别计时，
+//! 某一阶段卡住时尽快以带阶段名的错误结束，而不是等待一个统一的超长超时。
+
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageTimeouts {
+    /// 启动浏览器、打开页面并写入内容
+    pub navigation_secs: u64,
+    /// 页面加载与就绪条件（图片、字体、网络空闲等）
+    pub assets_secs: u64,
+    /// 公式排版（MathJax）
+    pub math_secs: u64,
+    /// 每次打印 PDF
+    pub print_secs: u64,
+}
+
+impl Default for StageTimeouts {
+    fn default() -> Self {
+        StageTimeouts {
+            navigation_secs: 60,
+            assets_secs: 300,
+            math_secs: 600,
+            print_secs: 600,
+        }
+    }
+}
+
+impl StageTimeouts {
+    /// 阶段名对应的预算；`navigation`、`assets`、`math`、`print`
+    pub fn budget(&self, stage: &str) -> Duration {
+        let secs = match stage {
+            "navigation" => self.navigation_secs,
+            "math" => self.math_secs,
+            "print" => self.print_secs,
+            _ => self.assets_secs,
+        };
+        Duration::from_secs(secs.max(1))
+    }
+
+    /// 浏览器的空闲超时：最长的阶段预算再留一分钟余量
+    pub fn idle_timeout(&self) -> Duration {
+        let longest = self.navigation_secs.max(self.assets_secs).max(self.math_secs).max(self.print_secs);
+        Duration::from_secs(longest + 60)
+    }
+}
+
+/// 超时的错误
+pub fn timeout_error(stage: &str, budget: Duration) -> AppError {
+    AppError::StageTimeout { stage: stage.to_string(), seconds: budget.as_secs() }
+}
+
+/// 在 `stage` 的预算内等待 `future` 完成
+pub async fn limit<T>(
+    timeouts: &StageTimeouts,
+    stage: &str,
+    future: impl Future<Output = Result<T, AppError>>,
+) -> Result<T, AppError> {
+    let budget = timeouts.budget(stage);
+    tokio::time::timeout(budget, future)
+        .await
+        .map_err(|_| timeout_error(stage, budget))?
+}
@@ -0,0 +1,589 @@
+//! Markdown 预处理管线：导出前按顺序对源文本应用一组具名转换，便于加入文档约定而无需修改应用。
+//!
+//! 内置转换：
+//!  - `include`：单独一行的 `!include 路径` 展开为被引用文件的正文（去掉其 front matter），
+//!    路径相对于引用它的文件，可以嵌套，循环引用时报错。被引用的文件（解析符号链接后）
+//!    必须位于导出文档所在目录之下，否则报错，避免文档把任意本地文件带进 PDF；未保存的文档不能引用
+//!  - `alerts`：`:::note 标题` … `:::` 容器转换为 `> [!NOTE] 标题` 提示块；只处理 GitHub 的五种提示类型，
+//!    `:::gallery` 等其他容器保持原样
+//!  - `wiki_links`：`[[文档#标题|文字]]` 转换为指向 `.md` 文件的标准链接（严格模式下也能跳转），默认关闭
+//!  - `heading_numbers`：标题前加章节编号（`1`、`1.1`…）；全文只有一个一级标题时从二级标题开始编号，
+//!    以 `{-}` 结尾的标题不编号。编号改变标题锚点，默认关闭
+//!
+//! 自定义转换写在配置目录下的 `transforms.toml` 中：
+//!
+//! ```toml
+//! [[transform]]
+//! name = "todo"
+//! description = "TODO 标记加粗"
+//! pattern = "TODO\\((\\w+)\\)"
+//! replacement = "**TODO**（$1）"
+//! # enabled = true    默认启用
+//! # skip_code = true  默认不改动代码块
+//! ```
+//!
+//! 顺序与启用状态保存在设置的 `transforms` 中。除 `include` 外，代码块中的内容保持不变。
+//! 转换作用于整篇文档，只用于导出（含监视导出、重新导出与导出预览）；编辑器旁的分块预览
+//! 显示源文本，界面的预处理菜单中有相应说明。
+
+use crate::doc_links;
+use crate::error::{AppError, IncludeFailure};
+use crate::front_matter;
+use crate::settings::{self, AppSettings, SettingsState};
+use crate::slug::slugify;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+/// `!include` 的最大嵌套层数
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// 内置转换：名称、说明与默认是否启用
+const BUILTINS: [(&str, &str, bool); 4] = [
+    ("include", "展开 `!include 路径` 为被引用文件的内容", true),
+    ("alerts", "`:::note` 容器转换为 `> [!NOTE]` 提示块", true),
+    ("wiki_links", "`[[文档|文字]]` 转换为标准 Markdown 链接", false),
+    ("heading_numbers", "标题前加章节编号", false),
+];
+
+/// 设置中的管线配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformSettings {
+    /// 转换的顺序；未列出的内置转换与自定义转换依次排在后面
+    pub order: Vec<String>,
+    /// 用户修改过的启用状态，未列出时按各转换的默认值
+    pub enabled: BTreeMap<String, bool>,
+}
+
+/// `transforms.toml` 中的自定义正则替换
+#[derive(Debug, Clone, Deserialize)]
+struct RegexRule {
+    name: String,
+    #[serde(default)]
+    description: String,
+    pattern: String,
+    #[serde(default)]
+    replacement: String,
+    #[serde(default = "default_true")]
+    enabled: bool,
+    #[serde(default = "default_true")]
+    skip_code: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TransformFile {
+    #[serde(default)]
+    transform: Vec<RegexRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransformInfo {
+    pub name: String,
+    pub description: String,
+    pub builtin: bool,
+    pub enabled: bool,
+}
+
+/// 读取 `transforms.toml`；文件不存在时没有自定义转换
+fn load_rules(app: &tauri::AppHandle) -> Result<Vec<RegexRule>, AppError> {
+    let Ok(dir) = app.path().app_config_dir() else { return Ok(Vec::new()) };
+    let path = dir.join("transforms.toml");
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AppError::file(&path, e)),
+    };
+    let file: TransformFile = toml::from_str(&content)
+        .map_err(|e| AppError::localized("SETTINGS", "SETTINGS_TRANSFORMS_SYNTAX", [("reason", e.to_string())]))?;
+    Ok(file.transform)
+}
+
+/// 管线中的转换（按执行顺序）
+fn pipeline(settings: &TransformSettings, rules: &[RegexRule]) -> Vec<TransformInfo> {
+    let mut all: Vec<TransformInfo> = BUILTINS
+        .iter()
+        .map(|(name, description, enabled)| TransformInfo {
+            name: name.to_string(),
+            description: description.to_string(),
+            builtin: true,
+            enabled: *enabled,
+        })
+        .collect();
+    for rule in rules {
+        if all.iter().any(|t| t.name == rule.name) {
+            tracing::warn!(name = %rule.name, "自定义转换与已有转换重名，已忽略");
+            continue;
+        }
+        all.push(TransformInfo {
+            name: rule.name.clone(),
+            description: rule.description.clone(),
+            builtin: false,
+            enabled: rule.enabled,
+        });
+    }
+    for transform in &mut all {
+        if let Some(enabled) = settings.enabled.get(&transform.name) {
+            transform.enabled = *enabled;
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(all.len());
+    for name in &settings.order {
+        if let Some(index) = all.iter().position(|t| &t.name == name) {
+            ordered.push(all.remove(index));
+        }
+    }
+    ordered.extend(all);
+    ordered
+}
+
+// ---------------------------------------------------------------------------
+// 内置转换
+// ---------------------------------------------------------------------------
+
+/// 围栏代码块的开始或结束标记
+fn fence_marker(line: &str) -> Option<&'static str> {
+    let trimmed = line.trim_start();
+    ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m))
+}
+
+/// 逐行跟踪是否位于围栏代码块中（围栏行本身算作代码）
+#[derive(Default)]
+struct Fences {
+    open: Option<&'static str>,
+}
+
+impl Fences {
+    fn in_code(&mut self, line: &str) -> bool {
+        match (self.open, fence_marker(line)) {
+            (None, Some(marker)) => {
+                self.open = Some(marker);
+                true
+            }
+            (Some(open), Some(marker)) if open == marker => {
+                self.open = None;
+                true
+            }
+            (open, _) => open.is_some(),
+        }
+    }
+}
+
+/// 对代码块之外的文本逐段应用 `f`
+fn map_text(markdown: &str, mut f: impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut fences = Fences::default();
+    let mut text = String::new();
+    for line in markdown.split_inclusive('\n') {
+        if fences.in_code(line) {
+            out.push_str(&f(&text));
+            text.clear();
+            out.push_str(line);
+        } else {
+            text.push_str(line);
+        }
+    }
+    out.push_str(&f(&text));
+    out
+}
+
+/// `!include` 的解析范围：被引用的文件必须位于 `root`（导出文档所在目录）之下
+struct IncludeScope {
+    root: PathBuf,
+    stack: Vec<PathBuf>,
+}
+
+impl IncludeScope {
+    /// `source_path` 为导出的文档；未保存的文档没有范围
+    fn for_document(source_path: Option<&Path>) -> Option<IncludeScope> {
+        let source = fs::canonicalize(source_path?).ok()?;
+        let root = source.parent()?.to_path_buf();
+        Some(IncludeScope { root, stack: vec![source] })
+    }
+}
+
+/// `scope` 为空（未保存的文档）时遇到 `!include` 报错
+fn expand_includes(markdown: &str, base_dir: &Path, scope: &mut Option<IncludeScope>) -> Result<String, AppError> {
+    let re_include = Regex::new(r#"^!include\s+["<]?([^">]+?)[">]?\s*$"#).unwrap();
+    let mut out = String::with_capacity(markdown.len());
+    let mut fences = Fences::default();
+    for line in markdown.split_inclusive('\n') {
+        let caps = (!fences.in_code(line)).then(|| re_include.captures(line.trim_end())).flatten();
+        let Some(caps) = caps else {
+            out.push_str(line);
+            continue;
+        };
+        let target = caps[1].trim();
+        let Some(include) = scope.as_mut() else {
+            return Err(AppError::IncludeError { path: target.to_string(), failure: IncludeFailure::UnsavedDocument });
+        };
+        let path = base_dir.join(target);
+        let path = fs::canonicalize(&path).map_err(|e| AppError::file(&path, e))?;
+        let path_text = path.to_string_lossy().to_string();
+        if !path.starts_with(&include.root) {
+            return Err(AppError::IncludeError { path: path_text, failure: IncludeFailure::OutsideDocument });
+        }
+        if include.stack.contains(&path) || include.stack.len() > MAX_INCLUDE_DEPTH {
+            return Err(AppError::IncludeError { path: path_text, failure: IncludeFailure::Cycle });
+        }
+        let content = fs::read_to_string(&path).map_err(|e| AppError::file(&path, e))?.replace("\r\n", "\n");
+        include.stack.push(path.clone());
+        let expanded = expand_includes(front_matter::body(&content), path.parent().unwrap_or(base_dir), scope)?;
+        if let Some(include) = scope.as_mut() {
+            include.stack.pop();
+        }
+        out.push_str(expanded.trim_end_matches('\n'));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn convert_alerts(markdown: &str) -> String {
+    let re_open = Regex::new(r"^:::\s*(?i:(note|tip|important|warning|caution))(?:[ \t]+(.*?))?\s*$").unwrap();
+    let mut out = String::with_capacity(markdown.len());
+    let mut fences = Fences::default();
+    let mut in_alert = false;
+    for line in markdown.split_inclusive('\n') {
+        let code = fences.in_code(line);
+        let content = line.trim_end_matches(['\n', '\r']);
+        if !code && !in_alert {
+            if let Some(caps) = re_open.captures(content) {
+                in_alert = true;
+                let title = caps.get(2).map_or("", |m| m.as_str());
+                out.push_str(format!("> [!{}] {}", caps[1].to_ascii_uppercase(), title).trim_end());
+                out.push('\n');
+                continue;
+            }
+        } else if !code && content.trim() == ":::" {
+            in_alert = false;
+            continue;
+        }
+        if in_alert {
+            out.push_str(if content.is_empty() { ">" } else { "> " });
+            out.push_str(content);
+            out.push('\n');
+        } else {
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+fn convert_wiki_links(markdown: &str, base_dir: &Path) -> String {
+    let re_protected = Regex::new(r"`+[^`]*`+").unwrap();
+    let re_wiki = Regex::new(r"\[\[([^\[\]|#\n]*)(?:#([^\[\]|\n]*))?(?:\|([^\[\]\n]+))?\]\]").unwrap();
+    let replace = |text: &str| {
+        re_wiki
+            .replace_all(text, |caps: &Captures| {
+                let document = caps[1].trim();
+                let heading = caps.get(2).map(|m| m.as_str().trim()).filter(|h| !h.is_empty());
+                let target = if document.is_empty() {
+                    Some(String::new())
+                } else {
+                    doc_links::resolve_target(document, base_dir).map(|path| {
+                        path.strip_prefix(base_dir).unwrap_or(&path).to_string_lossy().replace('\\', "/")
+                    })
+                };
+                let Some(target) = target.filter(|t| !t.is_empty() || heading.is_some()) else {
+                    return caps[0].to_string();
+                };
+                let label = caps.get(3).map(|m| m.as_str().trim().to_string()).unwrap_or_else(|| match heading {
+                    Some(heading) if document.is_empty() => heading.to_string(),
+                    Some(heading) => format!("{} › {}", document, heading),
+                    None => document.to_string(),
+                });
+                let anchor = heading.map(|h| format!("#{}", slugify(h))).unwrap_or_default();
+                format!("[{}](<{}{}>)", label, target, anchor)
+            })
+            .into_owned()
+    };
+    map_text(markdown, |text| {
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for m in re_protected.find_iter(text) {
+            out.push_str(&replace(&text[last..m.start()]));
+            out.push_str(m.as_str());
+            last = m.end();
+        }
+        out.push_str(&replace(&text[last..]));
+        out
+    })
+}
+
+fn number_headings(markdown: &str) -> String {
+    let re_heading = Regex::new(r"^(#{1,6})[ \t]+(.*?)[ \t]*$").unwrap();
+    let re_unnumbered = Regex::new(r"[ \t]*\{-\}$").unwrap();
+    let body = front_matter::body(markdown);
+    let front = &markdown[..markdown.len() - body.len()];
+
+    let mut fences = Fences::default();
+    let levels: Vec<usize> = body
+        .lines()
+        .filter(|line| !fences.in_code(line))
+        .filter_map(|line| re_heading.captures(line).map(|c| c[1].len()))
+        .collect();
+    let first = if levels.iter().filter(|&&l| l == 1).count() == 1 { 2 } else { 1 };
+
+    let mut counters = [0usize; 6];
+    let mut fences = Fences::default();
+    let mut out = String::from(front);
+    for line in body.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let caps = (!fences.in_code(line)).then(|| re_heading.captures(content)).flatten();
+        let Some(caps) = caps else {
+            out.push_str(line);
+            continue;
+        };
+        let level = caps[1].len();
+        let text = &caps[2];
+        let newline = &line[content.len()..];
+        if re_unnumbered.is_match(text) {
+            out.push_str(&format!("{} {}{}", &caps[1], re_unnumbered.replace(text, ""), newline));
+            continue;
+        }
+        if level < first {
+            out.push_str(line);
+            continue;
+        }
+        counters[level - 1] += 1;
+        counters[level..].iter_mut().for_each(|c| *c = 0);
+        let number: Vec<String> = counters[first - 1..level].iter().map(|n| n.to_string()).collect();
+        out.push_str(&format!("{} {} {}{}", &caps[1], number.join("."), text, newline));
+    }
+    out
+}
+
+/// 对代码块之外的文本应用自定义正则替换
+fn apply_rule(markdown: &str, rule: &RegexRule) -> Result<String, AppError> {
+    let re = Regex::new(&rule.pattern)
+        .map_err(|e| {
+            let args = [("name", rule.name.clone()), ("reason", e.to_string())];
+            AppError::localized("INVALID_PATTERN", "INVALID_TRANSFORM_PATTERN", args)
+        })?;
+    if rule.skip_code {
+        Ok(map_text(markdown, |text| re.replace_all(text, rule.replacement.as_str()).into_owned()))
+    } else {
+        Ok(re.replace_all(markdown, rule.replacement.as_str()).into_owned())
+    }
+}
+
+/// 按管线依次应用启用的转换；`source_path` 为空（未保存的文档）时 `!include` 报错，wiki 链接保持原样
+fn apply(
+    markdown: &str,
+    source_path: Option<&Path>,
+    settings: &TransformSettings,
+    rules: &[RegexRule],
+) -> Result<String, AppError> {
+    let base_dir = source_path.and_then(Path::parent);
+    let mut markdown = markdown.replace("\r\n", "\n");
+    for transform in pipeline(settings, rules).into_iter().filter(|t| t.enabled) {
+        markdown = match (transform.name.as_str(), base_dir) {
+            ("include", _) => {
+                let mut scope = IncludeScope::for_document(source_path);
+                let base_dir = scope.as_ref().map_or(Path::new(""), |scope| scope.root.as_path()).to_path_buf();
+                expand_includes(&markdown, &base_dir, &mut scope)?
+            }
+            ("alerts", _) => convert_alerts(&markdown),
+            ("wiki_links", Some(base_dir)) => convert_wiki_links(&markdown, base_dir),
+            ("wiki_links", None) => markdown,
+            ("heading_numbers", _) => number_headings(&markdown),
+            (name, _) => match rules.iter().find(|r| r.name == name) {
+                Some(rule) => apply_rule(&markdown, rule)?,
+                None => markdown,
+            },
+        };
+    }
+    Ok(markdown)
+}
+
+/// 按设置与 `transforms.toml` 处理一篇源文档（监视导出与重新导出使用）
+pub(crate) fn apply_for_export(
+    app: &tauri::AppHandle,
+    settings: &AppSettings,
+    markdown: &str,
+    source_path: Option<&Path>,
+) -> Result<String, AppError> {
+    apply(markdown, source_path, &settings.transforms, &load_rules(app)?)
+}
+
+/// 管线中的转换（按执行顺序）
+#[tauri::command]
+pub fn list_transforms(app: tauri::AppHandle, state: tauri::State<'_, SettingsState>) -> Result<Vec<TransformInfo>, AppError> {
+    Ok(pipeline(&state.snapshot().transforms, &load_rules(&app)?))
+}
+
+/// 启用或停用一个转换
+#[tauri::command]
+pub fn set_transform_enabled(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    name: String,
+    enabled: bool,
+) -> Result<Vec<TransformInfo>, AppError> {
+    let rules = load_rules(&app)?;
+    if !pipeline(&state.snapshot().transforms, &rules).iter().any(|t| t.name == name) {
+        return Err(AppError::localized("SETTINGS", "SETTINGS_UNKNOWN_TRANSFORM", [("name", name.to_string())]));
+    }
+    let updated = settings::update(&app, &state, |settings| {
+        settings.transforms.enabled.insert(name, enabled);
+    })?;
+    Ok(pipeline(&updated.transforms, &rules))
+}
+
+/// 调整转换的顺序：`order` 中的转换排在前面，其余保持原来的相对顺序
+#[tauri::command]
+pub fn reorder_transforms(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    order: Vec<String>,
+) -> Result<Vec<TransformInfo>, AppError> {
+    let rules = load_rules(&app)?;
+    let current: Vec<String> = pipeline(&state.snapshot().transforms, &rules).into_iter().map(|t| t.name).collect();
+    let mut new_order: Vec<String> = Vec::with_capacity(current.len());
+    for name in order.into_iter().chain(current.iter().cloned()) {
+        if current.contains(&name) && !new_order.contains(&name) {
+            new_order.push(name);
+        }
+    }
+    let updated = settings::update(&app, &state, |settings| settings.transforms.order = new_order)?;
+    Ok(pipeline(&updated.transforms, &rules))
+}
+
+/// 导出前处理源文档
+#[tauri::command]
+pub fn apply_transforms(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    markdown: String,
+    source_path: Option<String>,
+) -> Result<String, AppError> {
+    apply_for_export(&app, &state.snapshot(), &markdown, source_path.as_deref().map(Path::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled(names: &[&str]) -> TransformSettings {
+        TransformSettings {
+            order: Vec::new(),
+            enabled: BUILTINS.iter().map(|(name, _, _)| (name.to_string(), names.contains(name))).collect(),
+        }
+    }
+
+    fn rule(name: &str, pattern: &str, replacement: &str) -> RegexRule {
+        RegexRule {
+            name: name.to_string(),
+            description: String::new(),
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            enabled: true,
+            skip_code: true,
+        }
+    }
+
+    #[test]
+    fn includes_expand_relative_to_the_including_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("parts")).unwrap();
+        fs::write(dir.path().join("parts/a.md"), "---\ntitle: A\n---\nA 的正文\n!include b.md\n").unwrap();
+        fs::write(dir.path().join("parts/b.md"), "B 的正文\n").unwrap();
+        let main = dir.path().join("main.md");
+        let markdown = "# 主文档\n!include parts/a.md\n```\n!include parts/a.md\n```\n";
+        fs::write(&main, markdown).unwrap();
+
+        let out = apply(markdown, Some(&main), &enabled(&["include"]), &[]).unwrap();
+        assert_eq!(out, "# 主文档\nA 的正文\nB 的正文\n```\n!include parts/a.md\n```\n");
+    }
+
+    #[test]
+    fn include_cycles_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.md"), "!include b.md\n").unwrap();
+        fs::write(dir.path().join("b.md"), "!include a.md\n").unwrap();
+        let main = dir.path().join("a.md");
+
+        let error = apply("!include b.md\n", Some(&main), &enabled(&["include"]), &[]).unwrap_err();
+        assert!(matches!(error, AppError::IncludeError { failure: IncludeFailure::Cycle, .. }));
+    }
+
+    #[test]
+    fn includes_outside_the_document_folder_are_rejected() {
+        let outer = tempfile::tempdir().unwrap();
+        fs::write(outer.path().join("secret.txt"), "secret").unwrap();
+        fs::create_dir(outer.path().join("doc")).unwrap();
+        let main = outer.path().join("doc/main.md");
+        fs::write(&main, "").unwrap();
+
+        for target in ["../secret.txt", &outer.path().join("secret.txt").to_string_lossy()] {
+            let markdown = format!("!include {}\n", target);
+            let error = apply(&markdown, Some(&main), &enabled(&["include"]), &[]).unwrap_err();
+            assert!(matches!(error, AppError::IncludeError { failure: IncludeFailure::OutsideDocument, .. }));
+        }
+    }
+
+    #[test]
+    fn unsaved_documents_cannot_include() {
+        let error = apply("!include notes.md\n", None, &enabled(&["include"]), &[]).unwrap_err();
+        assert!(matches!(error, AppError::IncludeError { failure: IncludeFailure::UnsavedDocument, .. }));
+        assert_eq!(apply("正文\n", None, &enabled(&["include"]), &[]).unwrap(), "正文\n");
+    }
+
+    #[test]
+    fn alerts_become_callouts_outside_code() {
+        let markdown = ":::warning 小心\n第一行\n\n第二行\n:::\n```\n:::note\n```\n";
+        assert_eq!(
+            convert_alerts(markdown),
+            "> [!WARNING] 小心\n> 第一行\n>\n> 第二行\n```\n:::note\n```\n"
+        );
+    }
+
+    #[test]
+    fn alerts_leave_other_containers_alone() {
+        let markdown = ":::gallery\n![a](a.png)\n![b](b.png)\n:::\n:::Tip\n提示\n:::\n:::notes\n:::\n";
+        assert_eq!(
+            convert_alerts(markdown),
+            ":::gallery\n![a](a.png)\n![b](b.png)\n:::\n> [!TIP]\n> 提示\n:::notes\n:::\n"
+        );
+    }
+
+    #[test]
+    fn headings_are_numbered_except_unnumbered_ones() {
+        let markdown = "# 标题\n## 背景\n### 细节\n## 附录 {-}\n## 方法\n```\n## 代码\n```\n";
+        assert_eq!(
+            number_headings(markdown),
+            "# 标题\n## 1 背景\n### 1.1 细节\n## 附录\n## 2 方法\n```\n## 代码\n```\n"
+        );
+    }
+
+    #[test]
+    fn rules_skip_fenced_code() {
+        let markdown = "TODO(甲)\n```\nTODO(乙)\n```\n";
+        let out = apply_rule(markdown, &rule("todo", r"TODO\((\w+)\)", "**TODO**（$1）")).unwrap();
+        assert_eq!(out, "**TODO**（甲）\n```\nTODO(乙)\n```\n");
+        assert!(matches!(apply_rule(markdown, &rule("bad", "(", "")), Err(e) if e.code() == "INVALID_PATTERN"));
+    }
+
+    #[test]
+    fn pipeline_merges_saved_order_with_new_transforms() {
+        let rules = vec![rule("todo", "x", "y"), rule("alerts", "x", "y")];
+        let settings = TransformSettings {
+            order: vec!["todo".to_string(), "missing".to_string(), "heading_numbers".to_string()],
+            enabled: BTreeMap::from([("include".to_string(), false)]),
+        };
+        let pipeline = pipeline(&settings, &rules);
+        let names: Vec<&str> = pipeline.iter().map(|t| t.name.as_str()).collect();
+        // 保存的顺序在前，其余按默认顺序；与内置转换重名的自定义转换被忽略
+        assert_eq!(names, ["todo", "heading_numbers", "include", "alerts", "wiki_links"]);
+        assert!(!pipeline[2].enabled);
+        assert!(pipeline[0].enabled && !pipeline[1].enabled);
+    }
+}
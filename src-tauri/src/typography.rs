@@ -0,0 +1,224 @@
+//! 中日文排版规范化：
+//!  - `cjk_latin_spacing`：中日文与西文字母、数字之间加空格（“盘古之白”）
+//!  - `fullwidth_punctuation`：紧跟在中日文之后的半角标点 `, . ; : ! ?` 转换为全角，
+//!    内容含中日文的半角括号转换为全角括号
+//!  - `halfwidth_alphanumerics`：全角字母与数字转换为半角
+//!
+//! 设置中的 `typography` 为默认值，front matter 中的 `typography` 按字段覆盖。
+//! 格式化器在源码中应用（行内代码、链接地址、HTML 标签、网址与公式不变），
+//! 预览与导出在渲染后的文本中应用，两处使用相同的规则。严格模式下预览不处理。
+
+use crate::front_matter;
+use crate::settings::{AppSettings, SettingsState};
+use pulldown_cmark::{CowStr, Event, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TypographySettings {
+    /// 中日文与西文、数字之间加空格
+    pub cjk_latin_spacing: bool,
+    /// 中日文之后的半角标点转换为全角
+    pub fullwidth_punctuation: bool,
+    /// 全角字母与数字转换为半角
+    pub halfwidth_alphanumerics: bool,
+}
+
+impl TypographySettings {
+    pub fn is_enabled(&self) -> bool {
+        self.cjk_latin_spacing || self.fullwidth_punctuation || self.halfwidth_alphanumerics
+    }
+}
+
+/// 设置与 front matter 中 `typography` 合并后的规则
+pub fn resolve(settings: &AppSettings, markdown: &str) -> TypographySettings {
+    let mut typography = settings.typography;
+    let fm = front_matter::parse(markdown);
+    if let Some(overrides) = fm.as_ref().and_then(|fm| fm.get("typography")) {
+        let flag = |key: &str| overrides.get(key).and_then(Value::as_bool);
+        if let Some(value) = flag("cjk_latin_spacing") {
+            typography.cjk_latin_spacing = value;
+        }
+        if let Some(value) = flag("fullwidth_punctuation") {
+            typography.fullwidth_punctuation = value;
+        }
+        if let Some(value) = flag("halfwidth_alphanumerics") {
+            typography.halfwidth_alphanumerics = value;
+        }
+    }
+    typography
+}
+
+/// 中日文字符（汉字、假名，不含标点）
+pub(crate) fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
+
+fn fullwidth_of(c: char) -> Option<char> {
+    match c {
+        ',' => Some('，'),
+        '.' => Some('。'),
+        ';' => Some('；'),
+        ':' => Some('：'),
+        '!' => Some('！'),
+        '?' => Some('？'),
+        _ => None,
+    }
+}
+
+fn to_halfwidth(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'Ａ'..='Ｚ' | 'ａ'..='ｚ' | '０'..='９' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+fn to_fullwidth_punctuation(text: &str) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    // 需要转换为全角的括号位置
+    let mut paren_close: Vec<usize> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+        if c == '(' {
+            let close = chars[i + 1..].iter().position(|&c| c == '(' || c == ')').map(|n| i + 1 + n);
+            if let Some(close) = close.filter(|&close| chars[close] == ')' && chars[i + 1..close].iter().any(|&c| is_cjk(c))) {
+                out.push('（');
+                paren_close.push(close);
+                i += 1;
+                continue;
+            }
+        }
+        if c == ')' && paren_close.contains(&i) {
+            out.push('）');
+            // 转换后的括号之后按中文语境处理
+            chars[i] = '）';
+            i += 1;
+            continue;
+        }
+        // 省略号、文件扩展名与小数不转换
+        let convertible = prev.is_some_and(|p| is_cjk(p) || "）」』】》".contains(p))
+            && next.is_none_or(|n| n != c && !n.is_ascii_alphanumeric());
+        match fullwidth_of(c).filter(|_| convertible) {
+            Some(full) => {
+                out.push(full);
+                i += 1;
+                while chars.get(i).is_some_and(|c| *c == ' ') {
+                    i += 1;
+                }
+            }
+            None => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn add_spacing(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len() + 8);
+    for (i, &c) in chars.iter().enumerate() {
+        if let Some(&prev) = i.checked_sub(1).and_then(|p| chars.get(p)) {
+            let latin_after_cjk = is_cjk(prev) && c.is_ascii_alphanumeric();
+            let cjk_after_latin = (prev.is_ascii_alphanumeric() || prev == '%') && is_cjk(c);
+            if latin_after_cjk || cjk_after_latin {
+                out.push(' ');
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// 对一段纯文本应用规则
+pub fn normalize_text(text: &str, settings: &TypographySettings) -> String {
+    let mut text = text.to_string();
+    if settings.halfwidth_alphanumerics {
+        text = to_halfwidth(&text);
+    }
+    if settings.fullwidth_punctuation {
+        text = to_fullwidth_punctuation(&text);
+    }
+    if settings.cjk_latin_spacing {
+        text = add_spacing(&text);
+    }
+    text
+}
+
+/// 对一行 Markdown 源码应用规则：行内代码、链接地址、HTML 标签、网址、公式与属性标记保持不变
+pub fn normalize_markdown_line(line: &str, settings: &TypographySettings) -> String {
+    let re_protected =
+        Regex::new(r"`+[^`]*`+|\]\([^)]*\)|<[^>]+>|https?://\S+|\$\$?[^$]+\$\$?|\{[^}]*\}|\[\^[^\]]*\]").unwrap();
+    let mut out = String::with_capacity(line.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(line) {
+        out.push_str(&normalize_text(&line[last..m.start()], settings));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&normalize_text(&line[last..], settings));
+    out
+}
+
+/// 对 pulldown-cmark 的文本事件应用规则（代码块与行内公式除外）。
+/// 相邻的文本事件先合并，避免解析器在标点处拆开的文本无法整体判断。
+pub fn apply_to_events<'a>(events: Vec<Event<'a>>, settings: &TypographySettings) -> Vec<Event<'a>> {
+    if !settings.is_enabled() {
+        return events;
+    }
+    let re_math = Regex::new(r"\$\$?[^$]+\$\$?").unwrap();
+    let normalize = |text: &str| {
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for m in re_math.find_iter(text) {
+            out.push_str(&normalize_text(&text[last..m.start()], settings));
+            out.push_str(m.as_str());
+            last = m.end();
+        }
+        out.push_str(&normalize_text(&text[last..], settings));
+        out
+    };
+
+    let mut out: Vec<Event<'a>> = Vec::with_capacity(events.len());
+    let mut in_code = false;
+    let mut pending = String::new();
+    let flush = |pending: &mut String, out: &mut Vec<Event<'a>>| {
+        if !pending.is_empty() {
+            out.push(Event::Text(CowStr::from(normalize(pending))));
+            pending.clear();
+        }
+    };
+    for event in events {
+        match event {
+            Event::Text(text) if !in_code => pending.push_str(&text),
+            event => {
+                flush(&mut pending, &mut out);
+                match &event {
+                    Event::Start(Tag::CodeBlock(_)) => in_code = true,
+                    Event::End(TagEnd::CodeBlock) => in_code = false,
+                    _ => {}
+                }
+                out.push(event);
+            }
+        }
+    }
+    flush(&mut pending, &mut out);
+    out
+}
+
+/// 文档使用的排版规范化规则（供前端预览与导出时应用）
+#[tauri::command]
+pub fn detect_typography(markdown: &str, settings: tauri::State<'_, SettingsState>) -> TypographySettings {
+    resolve(&settings.snapshot(), markdown)
+}
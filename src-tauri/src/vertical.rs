@@ -0,0 +1,97 @@
+//! 竖排（直书）：导出时以 `writing-mode: vertical-rl` 排版，文字从上到下、行从右到左，
+//! 页面同样从右向左推进，适合传统样式的中文、日文文档。
+//!
+//!  - 代码块、表格、公式与图片保持横排
+//!  - 两位以内的半角数字与 `!!`、`!?` 等纵中横（`text-combine-upright`），更长的西文按顺时针旋转排列
+//!  - 注音（`<ruby>`）显示在字的右侧
+//!
+//! 导出选项中的 `writing_mode` 优先，其次是 front matter 中的 `writing_mode`，默认横排。
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WritingMode {
+    #[default]
+    #[serde(alias = "horizontal-tb")]
+    Horizontal,
+    #[serde(alias = "vertical-rl")]
+    Vertical,
+}
+
+impl WritingMode {
+    /// 导出选项优先，其次是 front matter 中的 `writing_mode`
+    pub fn resolve(option: Option<Self>, front_matter: Option<&Value>) -> Self {
+        option
+            .or_else(|| {
+                front_matter
+                    .and_then(|fm| fm.get("writing_mode"))
+                    .and_then(|value| serde_yaml::from_value(value.clone()).ok())
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// 竖排时的页面样式
+pub fn css(mode: WritingMode) -> &'static str {
+    match mode {
+        WritingMode::Horizontal => "",
+        WritingMode::Vertical => {
+            r#"
+html { writing-mode: vertical-rl; }
+body { max-width: none; max-height: 100vh; margin: 0; text-orientation: mixed; }
+h1, h2, h3, h4, h5, h6 { margin-block: 0; margin-inline: 0; padding-block: 0 0.5em; border-bottom: none; }
+p, ul, ol, blockquote { margin-block: 0 1em; }
+ul, ol { padding-left: 0; padding-block-start: 2em; }
+blockquote { border-left: none; border-block-start: 4px solid #d0d7de; padding-block-start: 1em; padding-left: 0; }
+pre, table, .table-wrapper, .katex-display, .equation, .MathJax, mjx-container, img, svg, figure, .mermaid {
+    writing-mode: horizontal-tb;
+}
+pre, table, figure, .equation { max-height: 100%; margin-block: 0 1em; }
+img { max-height: 90vh; }
+.tcy { text-combine-upright: all; }
+ruby { ruby-position: over; }
+rt { font-size: 0.5em; }
+"#
+        }
+    }
+}
+
+/// 竖排时把两位以内的半角数字与 `!!`、`!?` 等包裹为纵中横；代码、公式与标签属性保持不变
+pub fn apply_tate_chu_yoko(html: &str, mode: WritingMode) -> String {
+    if mode != WritingMode::Vertical {
+        return html.to_string();
+    }
+    let re_protected = Regex::new(
+        r#"(?s)<pre\b.*?</pre>|<code\b.*?</code>|<math\b.*?</math>|<script\b.*?</script>|<style\b.*?</style>|<[^>]*>|&[#\w]+;"#,
+    )
+    .unwrap();
+    let re_run = Regex::new(r"[0-9A-Za-z.,]+|[!?]+").unwrap();
+
+    let replace = |text: &str| {
+        re_run
+            .replace_all(text, |caps: &regex::Captures| {
+                let run = &caps[0];
+                let upright = (run.len() <= 2 && run.bytes().all(|b| b.is_ascii_digit()))
+                    || (run.chars().count() == 2 && run.chars().all(|c| c == '!' || c == '?'));
+                if upright {
+                    format!(r#"<span class="tcy">{}</span>"#, run)
+                } else {
+                    run.to_string()
+                }
+            })
+            .into_owned()
+    };
+
+    let mut out = String::with_capacity(html.len());
+    let mut last = 0;
+    for m in re_protected.find_iter(html) {
+        out.push_str(&replace(&html[last..m.start()]));
+        out.push_str(m.as_str());
+        last = m.end();
+    }
+    out.push_str(&replace(&html[last..]));
+    out
+}
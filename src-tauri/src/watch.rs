@@ -0,0 +1,214 @@
+//! 监视导出：监视 Markdown 文件及其引用的本地资源（图片、样式表、字体），文件变化后防抖，
+//! 自动按相同选项重新导出到同一个 PDF，便于在外部编辑器中编辑时“保存即导出”。
+//!
+//! 开始监视时先导出一次。每次导出后发送事件：
+//!  - `watch-export-complete`：`{ watch_id, report }`
+//!  - `watch-export-error`：`{ watch_id, error }`
+//!
+//! 编辑器常以“写入临时文件再改名”的方式保存，因此监视的是文件所在的目录，只处理涉及被监视文件的事件。
+//! HTML 由后端渲染（与 `markdown_to_html` 相同），公式保留 TeX 原文；源文本先经过预处理管线（见 `transforms` 模块），
+//! 文学化模式开启时再执行代码块。
+
+use crate::error::{run_blocking, AppError};
+use crate::settings::SettingsState;
+use crate::{assets, diff, literate, transforms, ExportOptions};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::sync::mpsc;
+
+/// 最后一次变化之后等待的时间，连续的写入只触发一次导出
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 进行中的监视，移除后监视器释放，导出任务随之结束
+static WATCHES: Mutex<BTreeMap<String, ActiveWatch>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+struct ActiveWatch {
+    source_path: PathBuf,
+    watcher: RecommendedWatcher,
+    /// 已在监视的目录
+    dirs: HashSet<PathBuf>,
+}
+
+/// 被监视的文件：Markdown 文件与存在的本地资源
+fn watched_files(source_path: &Path, markdown: &str) -> HashSet<PathBuf> {
+    let base_dir = source_path.parent().unwrap_or(Path::new(""));
+    let mut files: HashSet<PathBuf> = assets::analyze(markdown, Some(base_dir))
+        .assets
+        .into_iter()
+        .filter(|asset| !asset.remote)
+        .filter_map(|asset| asset.path)
+        .filter_map(|path| fs::canonicalize(path).ok())
+        .collect();
+    files.insert(source_path.to_path_buf());
+    files
+}
+
+/// 开始监视 `files` 所在的目录中尚未监视的部分
+fn watch_dirs(watch_id: &str, files: &HashSet<PathBuf>) {
+    let mut watches = WATCHES.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(watch) = watches.get_mut(watch_id) else { return };
+    for dir in files.iter().filter_map(|f| f.parent()) {
+        if watch.dirs.contains(dir) {
+            continue;
+        }
+        match watch.watcher.watch(dir, RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                watch.dirs.insert(dir.to_path_buf());
+            }
+            Err(e) => tracing::warn!(dir = %dir.display(), error = %e, "无法监视目录"),
+        }
+    }
+}
+
+/// 读取源文件并按选项导出一次，返回导出报告与本次引用的文件
+async fn export_once(
+    window: &tauri::Window,
+    source_path: &Path,
+    output_path: &str,
+    options: &ExportOptions,
+) -> Result<(crate::report::ExportReport, HashSet<PathBuf>), AppError> {
+    let app = window.app_handle().clone();
+    let settings = app.state::<SettingsState>().snapshot();
+    let markdown = fs::read_to_string(source_path).map_err(|e| AppError::file(source_path, e))?;
+    let files = watched_files(source_path, &markdown);
+
+    let literate_settings = settings.literate.clone();
+    let render_settings = settings.clone();
+    let path = source_path.to_path_buf();
+    let mode = options.mode;
+    let compare_with = options.compare_with.clone();
+    let (markdown, html_content) = run_blocking("watch_export", move || {
+        let markdown = diff::apply_compare(&markdown, compare_with.as_deref(), mode)?;
+        let markdown = transforms::apply_for_export(&app, &render_settings, &markdown, Some(&path))?;
+        let markdown = if literate_settings.enabled {
+            // 监视导出不能弹出确认，未信任的文档需要先在界面中导出一次并确认
+            literate::ensure_trusted(&app, Some(&path), &markdown)?;
+            literate::execute_blocks(&markdown, &literate_settings, literate::cache_dir(&app).as_ref()).markdown
+        } else {
+            markdown
+        };
+        let html = crate::render_source_html(&markdown, mode, &render_settings, &path);
+        Ok((markdown, html))
+    })
+    .await?;
+
+    let title = source_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let mut options = options.clone();
+    options.markdown = Some(markdown);
+    options.source_path = Some(source_path.to_string_lossy().to_string());
+    let report = crate::run_export(window, settings, html_content, output_path.to_string(), title, options).await?;
+    Ok((report, files))
+}
+
+async fn run_watch(
+    window: tauri::Window,
+    watch_id: String,
+    source_path: PathBuf,
+    output_path: String,
+    options: ExportOptions,
+    mut changes: mpsc::UnboundedReceiver<PathBuf>,
+) {
+    let mut files: HashSet<PathBuf> = HashSet::from([source_path.clone()]);
+    loop {
+        match export_once(&window, &source_path, &output_path, &options).await {
+            Ok((report, referenced)) => {
+                files = referenced;
+                watch_dirs(&watch_id, &files);
+                let _ = window.emit("watch-export-complete", json!({ "watch_id": watch_id, "report": report }));
+            }
+            Err(e) => {
+                tracing::warn!(watch_id = %watch_id, error = %e, "监视导出失败");
+                let _ = window.emit("watch-export-error", json!({ "watch_id": watch_id, "error": e }));
+            }
+        }
+
+        // 等待被监视文件的变化，再等到连续 DEBOUNCE 没有新的变化
+        loop {
+            let Some(path) = changes.recv().await else { return };
+            let path = fs::canonicalize(&path).unwrap_or(path);
+            if files.contains(&path) {
+                break;
+            }
+        }
+        loop {
+            match tokio::time::timeout(DEBOUNCE, changes.recv()).await {
+                Ok(Some(_)) => continue,
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+        tracing::info!(watch_id = %watch_id, "文件已变化，重新导出");
+    }
+}
+
+/// 开始监视 `path`，变化后自动导出到 `output_path`（省略时为同名的 `.pdf`），返回监视 ID。
+/// 同一文件已在监视时先停止原来的监视。
+#[tauri::command]
+pub fn watch_export(
+    window: tauri::Window,
+    path: String,
+    output_path: Option<String>,
+    options: Option<ExportOptions>,
+) -> Result<String, AppError> {
+    let source_path = fs::canonicalize(&path).map_err(|e| AppError::file(&path, e))?;
+    let output_path =
+        output_path.unwrap_or_else(|| source_path.with_extension("pdf").to_string_lossy().to_string());
+
+    let (sender, changes) = mpsc::unbounded_channel();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else { return };
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+            for path in event.paths {
+                let _ = sender.send(path);
+            }
+        }
+    })
+    .map_err(|e| AppError::Internal { context: "watch_export".to_string(), reason: e.to_string() })?;
+
+    stop_watch_export(None, Some(source_path.to_string_lossy().to_string()));
+    let watch_id = format!("watch-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    WATCHES.lock().unwrap_or_else(|e| e.into_inner()).insert(
+        watch_id.clone(),
+        ActiveWatch { source_path: source_path.clone(), watcher, dirs: HashSet::new() },
+    );
+    watch_dirs(&watch_id, &HashSet::from([source_path.clone()]));
+    tracing::info!(watch_id = %watch_id, source = %source_path.display(), output = %output_path, "开始监视导出");
+
+    tauri::async_runtime::spawn(run_watch(
+        window,
+        watch_id.clone(),
+        source_path,
+        output_path,
+        options.unwrap_or_default(),
+        changes,
+    ));
+    Ok(watch_id)
+}
+
+/// 停止监视：按监视 ID 或源文件路径；都未指定时停止所有监视。返回停止的数量
+#[tauri::command]
+pub fn stop_watch_export(watch_id: Option<String>, path: Option<String>) -> usize {
+    let path = path.map(|p| fs::canonicalize(&p).unwrap_or_else(|_| PathBuf::from(p)));
+    let mut watches = WATCHES.lock().unwrap_or_else(|e| e.into_inner());
+    let before = watches.len();
+    watches.retain(|id, watch| {
+        let matched = match (&watch_id, &path) {
+            (Some(target), _) => target == id,
+            (None, Some(path)) => &watch.source_path == path,
+            (None, None) => true,
+        };
+        if matched {
+            tracing::info!(watch_id = %id, "停止监视导出");
+        }
+        !matched
+    });
+    before - watches.len()
+}
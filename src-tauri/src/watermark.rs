@@ -0,0 +1,103 @@
+//! 水印：文字（如“草稿”“DRAFT”）或图片（如公司标志），以固定定位绘制在每一页正文的下层。
+//!
+//! `position: fixed` 的元素在 Chrome 打印时会在每一页重复出现；`z-index: -1` 使其位于
+//! 正文之下，不遮挡文字与表格。
+
+use crate::decorations::safe_color;
+use crate::html_util::escape_html;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatermarkPosition {
+    #[default]
+    Center,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl WatermarkPosition {
+    fn class(self) -> &'static str {
+        match self {
+            WatermarkPosition::Center => "watermark-center",
+            WatermarkPosition::TopLeft => "watermark-top-left",
+            WatermarkPosition::TopRight => "watermark-top-right",
+            WatermarkPosition::BottomLeft => "watermark-bottom-left",
+            WatermarkPosition::BottomRight => "watermark-bottom-right",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Watermark {
+    /// 水印文字
+    pub text: Option<String>,
+    /// 水印图片：本地路径（相对路径以 PDF 所在目录为基准）或 URL；同时指定文字时显示在文字上方
+    pub image: Option<String>,
+    /// 不透明度，0 ~ 1
+    pub opacity: f64,
+    /// 旋转角度（度），负数为逆时针
+    pub rotation: f64,
+    pub position: WatermarkPosition,
+    pub color: String,
+    /// 文字字号（pt）
+    pub font_size: f64,
+    /// 图片宽度（占页面宽度的百分比）
+    pub image_width: f64,
+}
+
+impl Default for Watermark {
+    fn default() -> Self {
+        Watermark {
+            text: None,
+            image: None,
+            opacity: 0.15,
+            rotation: -30.0,
+            position: WatermarkPosition::Center,
+            color: "#808080".to_string(),
+            font_size: 96.0,
+            image_width: 50.0,
+        }
+    }
+}
+
+impl Watermark {
+    /// 生成水印元素；没有文字也没有图片时返回 `None`
+    pub fn to_html(&self) -> Option<String> {
+        let text = self.text.as_deref().map(str::trim).filter(|t| !t.is_empty());
+        let image = self.image.as_deref().map(str::trim).filter(|i| !i.is_empty());
+        if text.is_none() && image.is_none() {
+            return None;
+        }
+
+        let mut content = String::new();
+        if let Some(image) = image {
+            let src = if Path::new(image).is_absolute() { crate::file_url(Path::new(image)) } else { image.to_string() };
+            content.push_str(&format!(
+                r#"<img class="watermark-image" src="{}" alt="" style="width: {}vw;" />"#,
+                escape_html(&src),
+                self.image_width.clamp(1.0, 100.0)
+            ));
+        }
+        if let Some(text) = text {
+            content.push_str(&format!(
+                r#"<div class="watermark-text" style="font-size: {}pt; color: {};">{}</div>"#,
+                self.font_size.clamp(6.0, 400.0),
+                safe_color(&self.color, "#808080"),
+                escape_html(text)
+            ));
+        }
+
+        Some(format!(
+            r#"<div class="watermark {}" aria-hidden="true" style="opacity: {};"><div class="watermark-inner" style="transform: rotate({}deg);">{}</div></div>"#,
+            self.position.class(),
+            self.opacity.clamp(0.0, 1.0),
+            self.rotation.clamp(-360.0, 360.0),
+            content
+        ))
+    }
+}
@@ -0,0 +1,299 @@
+//! 文件夹批量检查：对目录下的所有 Markdown 文件并行执行格式检查、链接检查与资源检查，
+//! 汇总为一份报告，可保存为 JSON（供 CI 判断是否通过）或 HTML（供人阅读）。
+//!
+//!  - 格式检查：按格式化规则会被修改的行（`unformatted`，警告）
+//!  - 链接检查：与 `check_document` 相同
+//!  - 资源检查：缺失的样式表、字体与封面图片（`missing_asset`，错误），过大的本地资源（`large_asset`，警告）
+//!
+//! 隐藏目录、`node_modules` 与 `target` 不会被扫描。存在任何错误时报告视为未通过。
+
+use crate::checker::{self, DocumentIssue};
+use crate::error::AppError;
+use crate::formatter::{self, FormatOptions};
+use crate::html_util::escape_html;
+use crate::parser_mode::ParserMode;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// 超过该大小的本地资源给出警告
+const LARGE_ASSET_BYTES: u64 = 5 * 1024 * 1024;
+/// 扫描的最大目录深度
+const MAX_DEPTH: usize = 16;
+const SKIPPED_DIRS: [&str; 2] = ["node_modules", "target"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    /// 相对于检查目录的路径（使用 `/` 分隔）
+    pub path: String,
+    pub issues: Vec<DocumentIssue>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceReport {
+    pub root: String,
+    pub file_count: usize,
+    pub error_count: usize,
+    pub warning_count: usize,
+    /// 没有任何错误
+    pub passed: bool,
+    /// 只包含存在问题的文件，按路径排序
+    pub files: Vec<FileReport>,
+}
+
+fn collect_markdown_files(dir: &Path, files: &mut Vec<PathBuf>, depth: usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(file_type) = entry.file_type() else { continue };
+        if file_type.is_dir() {
+            if depth < MAX_DEPTH && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_markdown_files(&path, files, depth + 1);
+            }
+            continue;
+        }
+        // 不跟随指向目录的符号链接，避免循环或扫描到工作区之外
+        if file_type.is_symlink() && path.is_dir() {
+            continue;
+        }
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+        if matches!(extension.as_deref(), Some("md" | "markdown")) {
+            files.push(path);
+        }
+    }
+}
+
+/// 按格式化规则会被修改的行
+fn lint(markdown: &str, options: &FormatOptions) -> Vec<DocumentIssue> {
+    let line_count = markdown.lines().count();
+    formatter::format_range(markdown, 1, line_count, options)
+        .into_iter()
+        .map(|edit| {
+            let line = edit.start_line.min(line_count.max(1));
+            let message = if edit.end_line > edit.start_line + 1 {
+                format!("第 {}-{} 行不符合格式规范", edit.start_line, edit.end_line - 1)
+            } else {
+                format!("第 {} 行不符合格式规范", line)
+            };
+            DocumentIssue::new(line, 1, "warning", "unformatted", "", message)
+        })
+        .collect()
+}
+
+/// 资源引用所在的行（找不到时为第 1 行）
+fn line_of(markdown: &str, source: &str) -> usize {
+    markdown.lines().position(|l| l.contains(source)).map_or(1, |n| n + 1)
+}
+
+/// 链接检查未覆盖的资源问题：缺失的样式表、字体与封面图片，以及过大的本地资源
+fn audit_assets(markdown: &str, base_dir: &Path, reported: &[DocumentIssue]) -> Vec<DocumentIssue> {
+    let analysis = crate::assets::analyze(markdown, Some(base_dir));
+    let mut issues = Vec::new();
+    for asset in analysis.assets {
+        if asset.remote || reported.iter().any(|i| i.target == asset.source) {
+            continue;
+        }
+        let line = line_of(markdown, &asset.source);
+        if !asset.exists {
+            let message = format!("找不到资源文件 {}", asset.source);
+            issues.push(DocumentIssue::new(line, 1, "error", "missing_asset", &asset.source, message));
+        } else if let Some(size) = asset.size.filter(|&s| s > LARGE_ASSET_BYTES) {
+            let message = format!("资源文件 {} 过大（{:.1} MB）", asset.source, size as f64 / 1024.0 / 1024.0);
+            issues.push(DocumentIssue::new(line, 1, "warning", "large_asset", &asset.source, message));
+        }
+    }
+    issues
+}
+
+fn validate_file(path: &Path, options: &FormatOptions) -> Vec<DocumentIssue> {
+    let markdown = match fs::read_to_string(path) {
+        Ok(markdown) => markdown,
+        Err(e) => {
+            return vec![DocumentIssue::new(1, 1, "error", "unreadable", "", format!("无法读取文件: {}", e))];
+        }
+    };
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let mode = ParserMode::resolve(None, &markdown);
+    let mut issues = checker::check_markdown(&markdown, Some(base_dir), mode);
+    issues.extend(audit_assets(&markdown, base_dir, &issues));
+    issues.extend(lint(&markdown, options));
+    issues.sort_by(|a, b| a.line.cmp(&b.line).then(a.column.cmp(&b.column)));
+    issues
+}
+
+/// 并行检查 `root` 下的所有 Markdown 文件
+pub fn validate(root: &Path, options: &FormatOptions) -> WorkspaceReport {
+    let mut paths = Vec::new();
+    collect_markdown_files(root, &mut paths, 0);
+    paths.sort();
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<FileReport>> = Mutex::new(Vec::new());
+    let workers = std::thread::available_parallelism().map_or(4, |n| n.get()).min(paths.len().max(1));
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(index) else { break };
+                let issues = validate_file(path, options);
+                if !issues.is_empty() {
+                    let relative = path.strip_prefix(root).unwrap_or(path);
+                    let report = FileReport { path: relative.to_string_lossy().replace('\\', "/"), issues };
+                    results.lock().unwrap_or_else(|e| e.into_inner()).push(report);
+                }
+            });
+        }
+    });
+
+    let mut files = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let count = |severity: &str| files.iter().flat_map(|f| &f.issues).filter(|i| i.severity == severity).count();
+    let error_count = count("error");
+    let warning_count = count("warning");
+    WorkspaceReport {
+        root: root.to_string_lossy().to_string(),
+        file_count: paths.len(),
+        error_count,
+        warning_count,
+        passed: error_count == 0,
+        files,
+    }
+}
+
+/// 生成可直接在浏览器中打开的 HTML 报告
+pub fn render_html(report: &WorkspaceReport) -> String {
+    let mut rows = String::new();
+    for file in &report.files {
+        for issue in &file.issues {
+            rows.push_str(&format!(
+                "<tr class=\"{}\"><td>{}:{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&issue.severity),
+                escape_html(&file.path),
+                issue.line,
+                if issue.severity == "error" { "错误" } else { "警告" },
+                escape_html(&issue.kind),
+                escape_html(&issue.message),
+            ));
+        }
+    }
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="UTF-8">
+<title>文档检查报告</title>
+<style>
+  body {{ font-family: -apple-system, "Segoe UI", "Microsoft YaHei", sans-serif; margin: 2em; color: #24292e; }}
+  .summary {{ margin-bottom: 1em; }}
+  .passed {{ color: #22863a; }}
+  .failed {{ color: #cb2431; }}
+  table {{ border-collapse: collapse; width: 100%; font-size: 14px; }}
+  th, td {{ border: 1px solid #e1e4e8; padding: 6px 10px; text-align: left; }}
+  th {{ background: #f6f8fa; }}
+  tr.error td:nth-child(2) {{ color: #cb2431; }}
+  tr.warning td:nth-child(2) {{ color: #b08800; }}
+</style>
+</head>
+<body>
+<h1>文档检查报告</h1>
+<p class="summary">目录 <code>{}</code>：共 {} 个文件，{} 个错误，{} 个警告 —
+<b class="{}">{}</b></p>
+<table>
+<thead><tr><th>位置</th><th>级别</th><th>类型</th><th>说明</th></tr></thead>
+<tbody>
+{}</tbody>
+</table>
+</body>
+</html>
+"#,
+        escape_html(&report.root),
+        report.file_count,
+        report.error_count,
+        report.warning_count,
+        if report.passed { "passed" } else { "failed" },
+        if report.passed { "通过" } else { "未通过" },
+        rows
+    )
+}
+
+/// 检查目录下的所有 Markdown 文件。提供 `report_path` 时同时保存报告：
+/// 扩展名为 `.json` 时保存为 JSON，否则保存为 HTML。未提供 `options` 时使用默认格式规则。
+#[tauri::command]
+pub async fn validate_workspace(
+    root: String,
+    report_path: Option<String>,
+    options: Option<FormatOptions>,
+) -> Result<WorkspaceReport, AppError> {
+    crate::error::run_blocking("validate_workspace", move || {
+        let root = PathBuf::from(&root);
+        if !root.is_dir() {
+            return Err(AppError::file(
+                &root,
+                std::io::Error::new(std::io::ErrorKind::NotFound, "不是有效的目录"),
+            ));
+        }
+        let report = validate(&root, &options.unwrap_or_default());
+        if let Some(report_path) = report_path {
+            let content = if report_path.to_ascii_lowercase().ends_with(".json") {
+                serde_json::to_string_pretty(&report).map_err(|e| AppError::Internal {
+                    context: "validate_workspace".to_string(),
+                    reason: e.to_string(),
+                })?
+            } else {
+                render_html(&report)
+            };
+            fs::write(&report_path, content).map_err(|e| AppError::file(&report_path, e))?;
+        }
+        tracing::info!(
+            files = report.file_count,
+            errors = report.error_count,
+            warnings = report.warning_count,
+            "文件夹检查完成"
+        );
+        Ok(report)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_markdown_files_and_skips_hidden_and_vendor_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in ["a.md", "b.MARKDOWN", "c.txt", "sub/d.md", ".git/e.md", "node_modules/f.md"] {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "# x\n").unwrap();
+        }
+        let mut files = Vec::new();
+        collect_markdown_files(dir.path(), &mut files, 0);
+        let mut names: Vec<String> =
+            files.iter().map(|f| f.strip_prefix(dir.path()).unwrap().to_string_lossy().replace('\\', "/")).collect();
+        names.sort();
+        assert_eq!(names, ["a.md", "b.MARKDOWN", "sub/d.md"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_directories_are_not_followed() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret.md"), "x").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/a.md"), "x").unwrap();
+        // 指向上级目录的循环链接与指向工作区外的链接
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("sub/loop")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("outside")).unwrap();
+
+        let mut files = Vec::new();
+        collect_markdown_files(dir.path(), &mut files, 0);
+        assert_eq!(files, [dir.path().join("sub/a.md")]);
+    }
+}
@@ -1,4 +1,5 @@
 ﻿import { useState, useEffect, useCallback, useRef } from 'react';
+import type { ClipboardEvent as ReactClipboardEvent, MouseEvent as ReactMouseEvent } from 'react';
 import {
   FluentProvider,
   webLightTheme,
@@ -8,6 +9,15 @@ import {
   Title3,
   Body1,
   Spinner,
+  Switch,
+  Select,
+  Input,
+  Menu,
+  MenuTrigger,
+  MenuPopover,
+  MenuList,
+  MenuItem,
+  MenuDivider,
   Toast,
   ToastTitle,
   ToastBody,
@@ -20,8 +30,11 @@ import {
 } from '@fluentui/react-components';
 import {
   ArrowUploadRegular,
+  ArrowImportRegular,
+  HistoryRegular,
   DocumentPdfRegular,
   DocumentRegular,
+  ImageRegular,
   CheckmarkCircleRegular,
   DismissCircleRegular,
   DeleteRegular,
@@ -30,13 +43,14 @@ import {
   SaveCopyRegular,
   ArrowUndoRegular,
   WandRegular,
+  ClipboardCodeRegular,
 } from '@fluentui/react-icons';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { open, save } from '@tauri-apps/plugin-dialog';
 import { writeTextFile } from '@tauri-apps/plugin-fs';
-import ReactMarkdown from 'react-markdown';
+import ReactMarkdown, { defaultUrlTransform } from 'react-markdown';
 import remarkMath from 'remark-math';
 import remarkGfm from 'remark-gfm';
 import rehypeKatex from 'rehype-katex';
@@ -59,6 +73,51 @@ const injectLineNumbers = (node: any) => {
   }
 };
 
+// 区块在全量内容（各区块以空行连接）中的起始行
+const blockStartLine = (blocks: { content: string }[], index: number) => {
+  let line = 1;
+  for (const block of blocks.slice(0, index)) {
+    const content = block.content.trim();
+    if (content !== '') line += content.split('\n').length + 1;
+  }
+  return line;
+};
+
+// 后端返回的文本编辑：将 [start_line, end_line) 行（1-indexed，含行尾换行符）替换为 new_text
+type RustTextEdit = { start_line: number; end_line: number; new_text: string };
+
+const applyTextEdit = (content: string, edit: RustTextEdit) => {
+  const lines = content.match(/[^\n]*\n|[^\n]+$/g) ?? [];
+  return [...lines.slice(0, edit.start_line - 1), edit.new_text, ...lines.slice(edit.end_line - 1)].join('');
+};
+
+// 去掉文档开头的 front matter（由后端读取，不作为正文导出）
+const stripFrontMatter = (markdown: string) =>
+  markdown.replace(/^---[ \t]*\r?\n(?:[\s\S]*?\r?\n)?(?:---|\.\.\.)[ \t]*(?:\r?\n|$)/, '');
+
+// 自定义 remark 插件：把代码块围栏信息串（```rust {3-5} title="main.rs"）写入 data-meta，
+// 导出时由后端处理行号、高亮行与标题栏
+const remarkCodeMeta = () => {
+  return (tree: any) => {
+    const visit = (node: any) => {
+      if (node.type === 'code' && node.meta) {
+        node.data = { ...node.data, hProperties: { ...node.data?.hProperties, dataMeta: node.meta } };
+      }
+      node.children?.forEach(visit);
+    };
+    visit(tree);
+  };
+};
+
+// KaTeX 选项：公式编号与 \eqref 引用在导出时由后端处理，这里只保证预览不报错
+const katexOptions = {
+  macros: {
+    '\\label': '\\@firstoftwo{}{#1}',
+    '\\eqref': '(\\mathrm{#1})',
+    '\\ref': '\\mathrm{#1}'
+  }
+};
+
 // 自定义 rehype 插件：处理 HTML 元素内的 LaTeX 公式
 const rehypeMathInHtml = () => {
   return (tree: any) => {
@@ -134,6 +193,646 @@ const rehypeMathInHtml = () => {
   };
 };
 
+// 段落内换行的处理方式（见后端 detect_line_breaks）：soft 视为空格，hard 换行显示，cjk 忽略中日文之间的换行
+type LineBreaks = 'soft' | 'hard' | 'cjk';
+
+// 中日文字符（汉字、假名与全角标点），与后端 line_breaks::is_cjk 一致
+const CJK_CHAR = '[\\u3000-\\u303f\\u3040-\\u30ff\\u3400-\\u4dbf\\u4e00-\\u9fff\\uf900-\\ufaff\\uff00-\\uffef\\u{20000}-\\u{2ffff}]';
+const CJK_NEWLINE = new RegExp(`(?<=${CJK_CHAR})[ \\t]*\\n[ \\t]*(?=${CJK_CHAR})`, 'gu');
+
+// 自定义 rehype 插件：按换行方式处理段落文本中的软换行（跳过代码与公式）
+const rehypeLineBreaks = (options: { mode: LineBreaks }) => {
+  return (tree: any) => {
+    if (options.mode === 'soft') return;
+    const visit = (node: any) => {
+      if (node.type === 'element' && (node.tagName === 'pre' || node.tagName === 'code')) return;
+      if (!node.children) return;
+      const newChildren: any[] = [];
+      node.children.forEach((child: any) => {
+        if (child.type !== 'text' || !child.value.includes('\n') || !child.value.trim()) {
+          visit(child);
+          newChildren.push(child);
+          return;
+        }
+        if (options.mode === 'cjk') {
+          newChildren.push({ ...child, value: child.value.replace(CJK_NEWLINE, '') });
+          return;
+        }
+        child.value.split('\n').forEach((line: string, index: number) => {
+          if (index > 0) newChildren.push({ type: 'element', tagName: 'br', properties: {}, children: [] });
+          if (line) newChildren.push({ type: 'text', value: line });
+        });
+      });
+      node.children = newChildren;
+    };
+
+    visit(tree);
+  };
+};
+
+// 提示块样式（见后端 get_callout_styles），键为类型名
+type CalloutStyle = { icon: string | null; color: string | null; title: string | null };
+
+const CALLOUT_MARKER = /^\[!([a-z][a-z0-9_-]*)\][ \t]*([^\n]*)\n?/i;
+
+// 自定义 rehype 插件：`> [!NOTE]` 等引用块渲染为带图标与标题的提示块（未知类型保持为引用块）
+const rehypeCallouts = (options: { styles: Record<string, CalloutStyle> }) => {
+  const iconNode = (icon: string) => {
+    if (icon.startsWith('<svg')) {
+      return { type: 'element', tagName: 'img', properties: { src: `data:image/svg+xml;utf8,${encodeURIComponent(icon)}`, alt: '' }, children: [] };
+    }
+    if (/[/\\.]/.test(icon)) {
+      return { type: 'element', tagName: 'img', properties: { src: icon, alt: '' }, children: [] };
+    }
+    return { type: 'text', value: icon };
+  };
+
+  return (tree: any) => {
+    const visit = (node: any) => {
+      if (!node.children) return;
+      node.children.forEach(visit);
+      if (node.type !== 'element' || node.tagName !== 'blockquote') return;
+
+      const firstPara = node.children.find((child: any) => child.type === 'element');
+      const firstText = firstPara?.tagName === 'p' ? firstPara.children[0] : null;
+      const match = firstText?.type === 'text' ? firstText.value.match(CALLOUT_MARKER) : null;
+      const style = match ? options.styles[match[1].toLowerCase()] : null;
+      if (!match || !style) return;
+
+      firstText.value = firstText.value.slice(match[0].length);
+      if (firstPara.children[0]?.type === 'element' && firstPara.children[0].tagName === 'br') firstPara.children.shift();
+      const kind = match[1].toLowerCase();
+      const title = match[2].trim() || style.title || match[1];
+      node.tagName = 'div';
+      node.properties = { className: ['callout', `callout-${kind}`], style: `--callout-color: ${style.color ?? '#0969da'}` };
+      node.children = [
+        {
+          type: 'element',
+          tagName: 'div',
+          properties: { className: ['callout-title'] },
+          children: [
+            { type: 'element', tagName: 'span', properties: { className: ['callout-icon'] }, children: style.icon ? [iconNode(style.icon)] : [] },
+            { type: 'text', value: title }
+          ]
+        },
+        { type: 'element', tagName: 'div', properties: { className: ['callout-body'] }, children: node.children }
+      ];
+    };
+
+    visit(tree);
+  };
+};
+
+// 智能引号样式（见后端 detect_smart_quotes），null 表示不启用智能标点
+type QuoteStyle = 'english' | 'german' | 'french';
+
+// 各样式的双引号开、闭与单引号开、闭（法语引号内侧带不换行窄空格）
+const QUOTE_MARKS: Record<QuoteStyle, [string, string, string, string]> = {
+  english: ['“', '”', '‘', '’'],
+  german: ['„', '“', '‚', '‘'],
+  french: ['«\u202f', '\u202f»', '‹\u202f', '\u202f›']
+};
+
+// 自定义 rehype 插件：直引号转换为所选语言的弯引号，--、---、... 转换为破折号与省略号（跳过代码与公式）
+const rehypeSmartQuotes = (options: { style: QuoteStyle | null }) => {
+  return (tree: any) => {
+    if (!options.style) return;
+    const [openDouble, closeDouble, openSingle, closeSingle] = QUOTE_MARKS[options.style];
+    // 前一个字符（跨文本节点），用于判断引号是开还是闭
+    let prev = '';
+    const opensAfter = (c: string) => c === '' || /[\s([{\u2014\u2013-]/.test(c);
+
+    const educate = (text: string) => {
+      let out = '';
+      const value = text.replace(/---/g, '—').replace(/--/g, '–').replace(/\.\.\./g, '…');
+      for (let i = 0; i < value.length; i++) {
+        const c = value[i];
+        const next = value[i + 1] ?? '';
+        if (c === '"') {
+          out += opensAfter(prev) ? openDouble : closeDouble;
+        } else if (c === "'") {
+          if (/[\p{L}\p{N}]/u.test(prev) && /[\p{L}]/u.test(next)) out += '’';
+          else out += opensAfter(prev) ? openSingle : closeSingle;
+        } else {
+          out += c;
+        }
+        prev = c;
+      }
+      return out;
+    };
+
+    const visit = (node: any) => {
+      if (node.type === 'element' && ['code', 'pre', 'script', 'style'].includes(node.tagName)) return;
+      if (node.type === 'element' && String(node.properties?.className ?? '').includes('math')) return;
+      if (node.type === 'text') {
+        node.value = educate(node.value);
+        return;
+      }
+      if (node.type === 'element' && ['p', 'li', 'td', 'th', 'h1', 'h2', 'h3', 'h4', 'h5', 'h6', 'blockquote'].includes(node.tagName)) {
+        prev = '';
+      }
+      node.children?.forEach(visit);
+    };
+
+    visit(tree);
+  };
+};
+
+// 中日文排版规范化规则（见后端 typography 模块与 detect_typography）
+interface Typography {
+  cjk_latin_spacing: boolean;
+  fullwidth_punctuation: boolean;
+  halfwidth_alphanumerics: boolean;
+}
+
+const CJK_LETTER = /[\u3040-\u30ff\u3400-\u4dbf\u4e00-\u9fff\uf900-\ufaff]|[\u{20000}-\u{2fa1f}]/u;
+const FULLWIDTH_PUNCTUATION: Record<string, string> = { ',': '，', '.': '。', ';': '；', ':': '：', '!': '！', '?': '？' };
+
+// 与后端 typography::normalize_text 相同的规则
+const normalizeTypography = (text: string, rules: Typography) => {
+  let value = text;
+  if (rules.halfwidth_alphanumerics) {
+    value = value.replace(/[Ａ-Ｚａ-ｚ０-９]/g, c => String.fromCharCode(c.charCodeAt(0) - 0xfee0));
+  }
+  if (rules.fullwidth_punctuation) {
+    const chars = Array.from(value);
+    const closeParens = new Set<number>();
+    let out = '';
+    for (let i = 0; i < chars.length; i++) {
+      const c = chars[i];
+      if (c === '(') {
+        const offset = chars.slice(i + 1).findIndex(x => x === '(' || x === ')');
+        const close = offset < 0 ? -1 : i + 1 + offset;
+        if (close > 0 && chars[close] === ')' && chars.slice(i + 1, close).some(x => CJK_LETTER.test(x))) {
+          out += '（';
+          closeParens.add(close);
+          continue;
+        }
+      }
+      if (c === ')' && closeParens.has(i)) {
+        out += '）';
+        chars[i] = '）';
+        continue;
+      }
+      const prev = chars[i - 1] ?? '';
+      const next = chars[i + 1];
+      // 省略号、文件扩展名与小数不转换
+      const convertible = (CJK_LETTER.test(prev) || (prev !== '' && '）」』】》'.includes(prev)))
+        && (next === undefined || (next !== c && !/[A-Za-z0-9]/.test(next)));
+      if (FULLWIDTH_PUNCTUATION[c] && convertible) {
+        out += FULLWIDTH_PUNCTUATION[c];
+        while (chars[i + 1] === ' ') i++;
+      } else {
+        out += c;
+      }
+    }
+    value = out;
+  }
+  if (rules.cjk_latin_spacing) {
+    value = value
+      .replace(new RegExp(`(${CJK_LETTER.source})(?=[A-Za-z0-9])`, 'gu'), '$1 ')
+      .replace(new RegExp(`([A-Za-z0-9%])(?=${CJK_LETTER.source})`, 'gu'), '$1 ');
+  }
+  return value;
+};
+
+// 自定义 rehype 插件：中日文排版规范化（跳过代码与公式，公式定界符之间的内容保持不变）
+const rehypeTypography = (options: { rules: Typography | null }) => {
+  return (tree: any) => {
+    const rules = options.rules;
+    if (!rules || !(rules.cjk_latin_spacing || rules.fullwidth_punctuation || rules.halfwidth_alphanumerics)) return;
+    const visit = (node: any) => {
+      if (node.type === 'element' && ['code', 'pre', 'kbd', 'script', 'style'].includes(node.tagName)) return;
+      if (node.type === 'element' && String(node.properties?.className ?? '').includes('math')) return;
+      if (node.type === 'text') {
+        node.value = node.value
+          .split(/(\$\$?[^$]+\$\$?)/)
+          .map((part: string, i: number) => (i % 2 === 1 ? part : normalizeTypography(part, rules)))
+          .join('');
+        return;
+      }
+      node.children?.forEach(visit);
+    };
+    visit(tree);
+  };
+};
+
+// 文档声明的缩写（见后端 glossary 模块与 detect_abbreviations）
+interface Abbreviation {
+  term: string;
+  definition: string;
+}
+
+const ABBREVIATION_DEFINITION = /^\*\[[^\]]+\]:/;
+const WORD_CHAR = /[A-Za-z0-9_]/;
+
+// 段落的纯文本，<br> 视为换行
+const paragraphText = (node: any): string => node.children?.map((child: any) => {
+  if (child.type === 'text') return child.value;
+  if (child.type === 'element' && child.tagName === 'br') return '\n';
+  return paragraphText(child);
+}).join('') ?? '';
+
+// 自定义 rehype 插件：移除缩写定义行，正文中的缩写转换为 <abbr>（与后端 glossary 模块一致）
+const rehypeAbbreviations = (options: { abbreviations: Abbreviation[] }) => {
+  return (tree: any) => {
+    const abbreviations = options.abbreviations;
+    if (abbreviations.length === 0) return;
+    const definitions = new Map(abbreviations.map(a => [a.term, a.definition]));
+    const terms = [...definitions.keys()].sort((a, b) => b.length - a.length);
+    const termRegex = new RegExp(terms.map(t => t.replace(/[.*+?^${}()|[\]\\]/g, '\\$&')).join('|'), 'g');
+
+    const isDefinitionParagraph = (node: any) => {
+      const lines = paragraphText(node).split('\n').map(line => line.trim()).filter(Boolean);
+      return lines.length > 0 && lines.every(line => ABBREVIATION_DEFINITION.test(line));
+    };
+    const visit = (node: any) => {
+      if (node.type === 'element' && ['code', 'pre', 'abbr', 'script', 'style'].includes(node.tagName)) return;
+      if (node.type === 'element' && String(node.properties?.className ?? '').includes('math')) return;
+      if (!node.children) return;
+      const newChildren: any[] = [];
+      node.children.forEach((child: any) => {
+        if (child.type === 'element' && child.tagName === 'p' && isDefinitionParagraph(child)) return;
+        if (child.type !== 'text') {
+          visit(child);
+          newChildren.push(child);
+          return;
+        }
+        const text: string = child.value;
+        let lastIndex = 0;
+        for (const match of text.matchAll(termRegex)) {
+          const term = match[0];
+          const start = match.index!;
+          const end = start + term.length;
+          // 以西文字母或数字开头、结尾的缩写只匹配完整的词
+          const before = Array.from(text.substring(0, start)).pop() ?? '';
+          const after = Array.from(text.substring(end))[0] ?? '';
+          if ((WORD_CHAR.test(term[0]) && WORD_CHAR.test(before)) || (WORD_CHAR.test(term[term.length - 1]) && WORD_CHAR.test(after))) {
+            continue;
+          }
+          if (start > lastIndex) newChildren.push({ type: 'text', value: text.substring(lastIndex, start) });
+          newChildren.push({
+            type: 'element',
+            tagName: 'abbr',
+            properties: { title: definitions.get(term) },
+            children: [{ type: 'text', value: term }]
+          });
+          lastIndex = end;
+        }
+        if (lastIndex < text.length) newChildren.push({ type: 'text', value: text.substring(lastIndex) });
+      });
+      node.children = newChildren;
+    };
+    visit(tree);
+  };
+};
+
+// 打开其他文档的深链接（由后端 resolve_document_link 解析目标路径）
+const OPEN_DOCUMENT_URL = 'md2pdf://open-document';
+
+const openDocumentUrl = (target: string, anchor?: string) =>
+  `${OPEN_DOCUMENT_URL}?target=${encodeURIComponent(target)}${anchor ? `&anchor=${encodeURIComponent(anchor)}` : ''}`;
+
+// 预览中保留深链接，其余链接按 react-markdown 的默认规则过滤
+const previewUrlTransform = (url: string) =>
+  url.startsWith(OPEN_DOCUMENT_URL) ? url : defaultUrlTransform(url);
+
+// 自定义 rehype 插件：[[目标#标题|文字]] 形式的 wiki 链接与指向 .md 文件的相对链接改写为深链接
+const rehypeDocumentLinks = () => {
+  const wikiRegex = /\[\[([^\[\]|#\n]*)(?:#([^\[\]|\n]*))?(?:\|([^\[\]\n]+))?\]\]/g;
+  const isMarkdownHref = (href: string) =>
+    !/^[a-z][a-z0-9+.-]*:/i.test(href) && /\.(md|markdown)$/i.test(href.split('#')[0]);
+
+  return (tree: any) => {
+    const visit = (node: any) => {
+      if (node.type === 'element' && node.tagName === 'a') {
+        const href = String(node.properties?.href ?? '');
+        if (isMarkdownHref(href)) {
+          const [target, anchor] = href.split('#');
+          node.properties.href = openDocumentUrl(decodeURIComponent(target), anchor);
+        }
+        return;
+      }
+      if (node.type === 'element' && (node.tagName === 'code' || node.tagName === 'pre')) return;
+      if (!node.children) return;
+
+      const newChildren: any[] = [];
+      node.children.forEach((child: any) => {
+        if (child.type !== 'text' || !child.value.includes('[[')) {
+          visit(child);
+          newChildren.push(child);
+          return;
+        }
+        let lastIndex = 0;
+        for (const match of child.value.matchAll(wikiRegex)) {
+          const [raw, target = '', heading = '', label] = match;
+          if (!target.trim() && !heading.trim()) continue;
+          if (match.index! > lastIndex) {
+            newChildren.push({ type: 'text', value: child.value.substring(lastIndex, match.index) });
+          }
+          const text = label?.trim() || (heading.trim() ? (target.trim() ? `${target.trim()} › ${heading.trim()}` : heading.trim()) : target.trim());
+          newChildren.push({
+            type: 'element',
+            tagName: 'a',
+            properties: {
+              className: ['wiki-link'],
+              href: target.trim() ? openDocumentUrl(target.trim(), heading.trim() || undefined) : `#${heading.trim()}`
+            },
+            children: [{ type: 'text', value: text }]
+          });
+          lastIndex = match.index! + raw.length;
+        }
+        if (lastIndex < child.value.length) {
+          newChildren.push({ type: 'text', value: child.value.substring(lastIndex) });
+        }
+      });
+      node.children = newChildren;
+    };
+
+    visit(tree);
+  };
+};
+
+// 自定义 rehype 插件：{汉字|hàn zì} 形式的注音转换为 <ruby>（与后端 ruby 模块一致）
+const rehypeRuby = () => {
+  const rubyRegex = /\{([^{}|\n]+)\|([^{}\n]+)\}/g;
+  const rubyPair = (base: string, annotation: string) => [
+    { type: 'text', value: base },
+    { type: 'element', tagName: 'rp', properties: {}, children: [{ type: 'text', value: '(' }] },
+    { type: 'element', tagName: 'rt', properties: {}, children: [{ type: 'text', value: annotation }] },
+    { type: 'element', tagName: 'rp', properties: {}, children: [{ type: 'text', value: ')' }] }
+  ];
+  const rubyElement = (base: string, annotation: string) => {
+    const chars = Array.from(base);
+    const byPipe = annotation.split('|').map((part) => part.trim());
+    const bySpace = annotation.split(/\s+/).filter(Boolean);
+    // 注音数量与字数相同时逐字标注，否则整组标注
+    const perChar = byPipe.length > 1
+      ? (byPipe.length === chars.length ? byPipe : null)
+      : (chars.length > 1 && bySpace.length === chars.length ? bySpace : null);
+    return {
+      type: 'element',
+      tagName: 'ruby',
+      properties: {},
+      children: perChar
+        ? chars.flatMap((char, index) => rubyPair(char, perChar[index]))
+        : rubyPair(base, byPipe.join(' '))
+    };
+  };
+
+  return (tree: any) => {
+    const visit = (node: any) => {
+      if (node.type === 'element' && ['code', 'pre', 'ruby'].includes(node.tagName)) return;
+      if (node.type === 'element' && String(node.properties?.className ?? '').includes('math')) return;
+      if (!node.children) return;
+      const newChildren: any[] = [];
+      node.children.forEach((child: any) => {
+        if (child.type !== 'text' || !child.value.includes('|')) {
+          visit(child);
+          newChildren.push(child);
+          return;
+        }
+        let lastIndex = 0;
+        for (const match of child.value.matchAll(rubyRegex)) {
+          const [raw, base, annotation] = match;
+          if (!base.trim() || !annotation.trim()) continue;
+          if (match.index! > lastIndex) {
+            newChildren.push({ type: 'text', value: child.value.substring(lastIndex, match.index) });
+          }
+          newChildren.push(rubyElement(base.trim(), annotation.trim()));
+          lastIndex = match.index! + raw.length;
+        }
+        if (lastIndex < child.value.length) {
+          newChildren.push({ type: 'text', value: child.value.substring(lastIndex) });
+        }
+      });
+      node.children = newChildren;
+    };
+
+    visit(tree);
+  };
+};
+
+// 常见按键名（与后端 kbd 模块一致），单独的 [[...]] 只有是按键名或单个字符时才视为按键，否则是 wiki 链接
+const KEY_NAMES = new Set([
+  'ctrl', 'control', 'alt', 'option', 'opt', 'shift', 'cmd', 'command', 'meta', 'win', 'windows', 'super', 'fn',
+  'enter', 'return', 'esc', 'escape', 'tab', 'space', 'backspace', 'delete', 'del', 'insert', 'ins', 'home', 'end',
+  'pageup', 'pagedown', 'pgup', 'pgdn', 'up', 'down', 'left', 'right', 'capslock', 'printscreen', 'prtsc',
+  'pause', 'menu'
+]);
+const KEY_COMBO = /\[\[([^[\]|#\n]{1,20})\]\](?:\s*\+\s*\[\[([^[\]|#\n]{1,20})\]\])*/g;
+const MENU_PATH = /[^\s▸<>，。；：、！？,;:!?()（）"“”「」]+(?:\s*▸\s*[^\s▸<>，。；：、！？,;:!?()（）"“”「」]+)+/g;
+
+const isKeyName = (name: string) => {
+  const lower = name.toLowerCase();
+  const functionKey = /^f([1-9]|1[0-9]|2[0-4])$/.test(lower);
+  return Array.from(name).length === 1 || functionKey || KEY_NAMES.has(lower);
+};
+
+// 自定义 rehype 插件：[[Ctrl]]+[[C]] 渲染为按键，`文件 ▸ 导出` 渲染为菜单路径（跳过代码与公式）
+const rehypeUiMarkup = () => {
+  const el = (tagName: string, className: string | null, children: any[]) => ({
+    type: 'element',
+    tagName,
+    properties: className ? { className: [className] } : {},
+    children
+  });
+
+  const menuNodes = (text: string): any[] => {
+    const nodes: any[] = [];
+    let lastIndex = 0;
+    for (const match of text.matchAll(MENU_PATH)) {
+      if (match.index! > lastIndex) nodes.push({ type: 'text', value: text.substring(lastIndex, match.index) });
+      const items = match[0].split('▸').map((item) => el('span', 'menu-item', [{ type: 'text', value: item.trim() }]));
+      nodes.push(el('span', 'menu-path', items.flatMap((item, index) => (
+        index === 0 ? [item] : [el('span', 'menu-sep', [{ type: 'text', value: '▸' }]), item]
+      ))));
+      lastIndex = match.index! + match[0].length;
+    }
+    if (lastIndex < text.length) nodes.push({ type: 'text', value: text.substring(lastIndex) });
+    return nodes;
+  };
+
+  const convert = (text: string): any[] => {
+    const nodes: any[] = [];
+    let lastIndex = 0;
+    for (const match of text.matchAll(KEY_COMBO)) {
+      const keys = Array.from(match[0].matchAll(/\[\[([^[\]|#\n]{1,20})\]\]/g), (key) => key[1].trim());
+      if (keys.length === 1 && !isKeyName(keys[0])) continue;
+      if (match.index! > lastIndex) nodes.push(...menuNodes(text.substring(lastIndex, match.index)));
+      keys.forEach((key, index) => {
+        if (index > 0) nodes.push({ type: 'text', value: '+' });
+        nodes.push(el('kbd', null, [{ type: 'text', value: key }]));
+      });
+      lastIndex = match.index! + match[0].length;
+    }
+    if (lastIndex < text.length) nodes.push(...menuNodes(text.substring(lastIndex)));
+    return nodes;
+  };
+
+  return (tree: any) => {
+    const visit = (node: any) => {
+      if (node.type === 'element' && ['code', 'pre', 'kbd'].includes(node.tagName)) return;
+      if (node.type === 'element' && String(node.properties?.className ?? '').includes('math')) return;
+      if (!node.children) return;
+      node.children = node.children.flatMap((child: any) => {
+        if (child.type === 'text' && (child.value.includes('[[') || child.value.includes('▸'))) {
+          return convert(child.value);
+        }
+        visit(child);
+        return [child];
+      });
+    };
+
+    visit(tree);
+  };
+};
+
+// 自定义 rehype 插件：任务列表的复选框可以点击，并记录列表项在区块中的行号（点击后由后端切换源码）
+const rehypeTaskCheckboxes = () => {
+  return (tree: any) => {
+    const visit = (node: any) => {
+      if (!node.children) return;
+      const className = node.properties?.className;
+      if (node.tagName === 'li' && (Array.isArray(className) ? className : [className]).includes('task-list-item')) {
+        const first = node.children.find((child: any) => child.type === 'element');
+        const input = first?.tagName === 'input'
+          ? first
+          : first?.tagName === 'p' ? first.children.find((child: any) => child.tagName === 'input') : undefined;
+        if (input && node.position) {
+          input.properties = { ...input.properties, disabled: false, readOnly: true, dataTaskLine: node.position.start.line };
+        }
+      }
+      node.children.forEach(visit);
+    };
+
+    visit(tree);
+  };
+};
+
+// 自定义 rehype 插件：标题末尾的 {newpage} 标记去掉后给标题加上 new-page（与后端 page_breaks 模块一致）
+const rehypeHeadingPageBreaks = () => {
+  const marker = /\s*\{\s*\.?newpage\s*\}\s*$/;
+  return (tree: any) => {
+    const visit = (node: any) => {
+      if (!node.children) return;
+      if (node.type === 'element' && /^h[1-6]$/.test(node.tagName)) {
+        const last = node.children[node.children.length - 1];
+        if (last?.type === 'text' && marker.test(last.value)) {
+          last.value = last.value.replace(marker, '');
+          const className = node.properties?.className ?? [];
+          node.properties = { ...node.properties, className: [...(Array.isArray(className) ? className : [className]), 'new-page'] };
+        }
+        return;
+      }
+      node.children.forEach(visit);
+    };
+
+    visit(tree);
+  };
+};
+
+// 徽章与进度条的预设颜色（与后端 badges 模块一致）
+const BADGE_PALETTE: Record<string, string> = {
+  gray: '#6e7781',
+  grey: '#6e7781',
+  blue: '#0969da',
+  green: '#1a7f37',
+  yellow: '#bf8700',
+  orange: '#d9730d',
+  red: '#cf222e',
+  purple: '#8250df'
+};
+const SAFE_COLOR = /^(#[0-9a-fA-F]{3,8}|[a-zA-Z]+|rgba?\([0-9.,%\s]+\))$/;
+const BADGE_MARKER = /\[(badge|progress):\s*([^\]|\n]+?)\s*(?:\|\s*([^\]|\n]+?)\s*)?\]/g;
+
+const badgeColor = (name: string | undefined, fallback: string) => {
+  if (!name) return fallback;
+  const color = BADGE_PALETTE[name.trim().toLowerCase()] ?? name.trim();
+  return SAFE_COLOR.test(color) ? color : fallback;
+};
+
+// 自定义 rehype 插件：`[badge: 文字|颜色]` 与 `[progress: 70%]` 渲染为徽章与进度条（跳过代码与公式）
+// 自定义 rehype 插件：图片的相对路径按本地资源服务器上的文档目录解析（见后端 asset_server 模块）
+const rehypeAssetBase = (options: { base: string | null }) => {
+  return (tree: any) => {
+    if (!options.base) return;
+    const visit = (node: any) => {
+      if (node.type === 'element' && node.tagName === 'img') {
+        const src = String(node.properties?.src ?? '');
+        if (src && !/^([a-z][a-z0-9+.-]*:|\/\/|#)/i.test(src)) {
+          node.properties.src = new URL(src, options.base!).href;
+        }
+      }
+      node.children?.forEach(visit);
+    };
+    visit(tree);
+  };
+};
+
+const rehypeBadges = () => {
+  const markerNode = (kind: string, value: string, color: string | undefined) => {
+    if (kind === 'badge') {
+      return {
+        type: 'element',
+        tagName: 'span',
+        properties: { className: ['badge'], style: `--badge-color: ${badgeColor(color, '#6e7781')}` },
+        children: [{ type: 'text', value: value.trim() }]
+      };
+    }
+    const parsed = parseFloat(value.replace('%', ''));
+    if (Number.isNaN(parsed)) return null;
+    const percent = Math.min(100, Math.max(0, parsed));
+    return {
+      type: 'element',
+      tagName: 'span',
+      properties: { className: ['progress'], role: 'progressbar', ariaValueNow: percent, style: `--progress-color: ${badgeColor(color, '#0969da')}` },
+      children: [
+        {
+          type: 'element',
+          tagName: 'span',
+          properties: { className: ['progress-track'] },
+          children: [{ type: 'element', tagName: 'span', properties: { className: ['progress-fill'], style: `width: ${percent}%` }, children: [] }]
+        },
+        { type: 'element', tagName: 'span', properties: { className: ['progress-label'] }, children: [{ type: 'text', value: `${percent}%` }] }
+      ]
+    };
+  };
+
+  return (tree: any) => {
+    const visit = (node: any) => {
+      if (node.type === 'element' && ['code', 'pre'].includes(node.tagName)) return;
+      if (node.type === 'element' && String(node.properties?.className ?? '').includes('math')) return;
+      if (!node.children) return;
+      const newChildren: any[] = [];
+      node.children.forEach((child: any) => {
+        if (child.type !== 'text' || !/\[(badge|progress):/.test(child.value)) {
+          visit(child);
+          newChildren.push(child);
+          return;
+        }
+        let lastIndex = 0;
+        for (const match of child.value.matchAll(BADGE_MARKER)) {
+          const [raw, kind, value, color] = match;
+          const element = markerNode(kind, value, color);
+          if (!element) continue;
+          if (match.index! > lastIndex) {
+            newChildren.push({ type: 'text', value: child.value.substring(lastIndex, match.index) });
+          }
+          newChildren.push(element);
+          lastIndex = match.index! + raw.length;
+        }
+        if (lastIndex < child.value.length) {
+          newChildren.push({ type: 'text', value: child.value.substring(lastIndex) });
+        }
+      });
+      node.children = newChildren;
+    };
+
+    visit(tree);
+  };
+};
+
 const useStyles = makeStyles({
   root: {
     display: 'flex',
@@ -257,6 +956,12 @@ const useStyles = makeStyles({
     ...shorthands.padding('24px'),
     backgroundColor: tokens.colorNeutralBackground1,
   },
+  exportPreviewFrame: {
+    width: '100%',
+    height: '100%',
+    ...shorthands.border('0'),
+    backgroundColor: '#ffffff',
+  },
   emptyState: {
     display: 'flex',
     flexDirection: 'column',
@@ -314,6 +1019,128 @@ const useStyles = makeStyles({
   },
 });
 
+// 后端错误序列化为 { code, message, details }，这里取出本地化后的 message
+interface BackendError {
+  code: string;
+  message: string;
+  details?: Record<string, unknown>;
+}
+
+const formatError = (error: unknown): string => {
+  if (error && typeof error === 'object' && 'message' in error) {
+    return (error as BackendError).message;
+  }
+  return String(error);
+};
+
+// 调用导出命令：外部资源策略为“询问”且页面会访问外部地址时，列出这些来源请用户确认，
+// 确认后只允许访问这些来源，否则离线导出（不加载外部资源）
+const invokeExport = async <T,>(command: string, args: { options: Record<string, unknown> } & Record<string, unknown>): Promise<T> => {
+  try {
+    return await invoke<T>(command, args);
+  } catch (error) {
+    if ((error as BackendError)?.code !== 'EXTERNAL_RESOURCES') throw error;
+    const origins = ((error as BackendError).details?.origins as string[] | undefined) ?? [];
+    const allow = window.confirm(`导出页面将访问以下外部地址：\n${origins.join('\n')}\n\n选择“确定”允许访问，选择“取消”离线导出（不加载外部资源）。`);
+    const network = allow ? { allowed_origins: origins } : { offline: true };
+    return invoke<T>(command, { ...args, options: { ...args.options, ...network } });
+  }
+};
+
+// 文学化模式：执行 {run} 代码块并返回嵌入输出后的 Markdown。文档的代码块尚未被信任时
+// 先请用户确认，确认后记录信任并重试；拒绝则不执行代码块，按原文导出
+const runLiterate = async (markdown: string, sourcePath: string | null): Promise<string> => {
+  try {
+    return (await invoke<{ markdown: string }>('run_literate_blocks', { markdown, sourcePath })).markdown;
+  } catch (error) {
+    if ((error as BackendError)?.code !== 'UNTRUSTED_CODE') throw error;
+    const trust = window.confirm('文档包含 {run} 代码块，导出时会在本机执行这些代码，只应运行来源可信的文档。\n\n选择“确定”信任并执行，选择“取消”不执行代码块。');
+    if (!trust) return markdown;
+    const fingerprint = (error as BackendError).details?.fingerprint as string;
+    await invoke('trust_literate_document', { sourcePath, fingerprint });
+    return (await invoke<{ markdown: string }>('run_literate_blocks', { markdown, sourcePath })).markdown;
+  }
+};
+
+// 导出报告（export_to_pdf 的返回值）
+interface ExportReport {
+  output_path: string;
+  total_ms: number;
+  stages: { stage: string; duration_ms: number }[];
+  page_count: number;
+  file_size: number;
+  image_count: number;
+  math_count: number;
+  warnings: { kind: string; detail: string }[];
+  failures: { kind: string; start_line: number; end_line: number; message: string }[];
+  optimization: { size_before: number; size_after: number; images_optimized: number } | null;
+}
+
+// 崩溃恢复的草稿（见后端 drafts 模块）
+interface RecoveredDraft {
+  doc_id: string;
+  content: string;
+  updated_ms: number;
+}
+
+// 最近打开的文件（见后端 recent 模块）
+interface RecentFile {
+  path: string;
+  opened_ms: number;
+  pinned: boolean;
+  exists: boolean;
+}
+
+// 导出前的预处理转换（见后端 transforms 模块），按执行顺序
+interface TransformInfo {
+  name: string;
+  description: string;
+  builtin: boolean;
+  enabled: boolean;
+}
+
+// 导出历史记录（见后端 history 模块）
+interface ExportRecord {
+  id: number;
+  source_path: string | null;
+  output_path: string;
+  title: string;
+  options: { mode: ParserMode; best_effort: boolean; compare_with?: string | null };
+  protected: boolean;
+  duration_ms: number;
+  page_count: number;
+  file_size: number;
+  exported_ms: number;
+}
+
+// 文件的会话状态：上次最上方可见的区块等
+interface FileSession {
+  scroll_block: number;
+  cursor_line: number | null;
+  theme: string | null;
+}
+
+// 滚动停止多久后保存会话状态
+const SESSION_SAVE_DELAY_MS = 1000;
+
+// 停止编辑多久后自动保存草稿
+const AUTOSAVE_DELAY_MS = 3000;
+
+// 解析模式：strict 为纯 CommonMark（不启用 GFM、公式等扩展）
+type ParserMode = 'extended' | 'strict';
+// 脚注保持原样，或转换为页底注释、按章 / 文末的尾注
+type NotePlacement = 'footnotes' | 'page' | 'chapter' | 'document';
+// 正文分栏与纸张预设（见后端 layout 模块）
+type PageLayout = 'single' | 'two_column';
+type PaperSize = 'a4' | 'booklet';
+type PdfStandard = 'standard' | 'tagged' | 'pdf_a';
+
+// 部分导出的选择方式（见后端 select_markdown）
+type BlockSelection =
+  | { kind: 'lines'; start_line: number; end_line: number }
+  | { kind: 'blocks'; ids: string[] }
+  | { kind: 'section'; line: number };
+
 interface MarkdownBlock {
   id: string;
   content: string;
@@ -326,9 +1153,45 @@ function App() {
   const [markdownContent, setMarkdownContent] = useState('');
   const [markdownBlocks, setMarkdownBlocks] = useState<MarkdownBlock[]>([]);
   const [currentFile, setCurrentFile] = useState<string | null>(null);
+  // 未保存文档的草稿 ID（`untitled:<uuid>`），另存为文件后换成新的
+  const [untitledId, setUntitledId] = useState(() => `untitled:${crypto.randomUUID()}`);
+  const [recentFiles, setRecentFiles] = useState<RecentFile[]>([]);
+  const [exportHistory, setExportHistory] = useState<ExportRecord[]>([]);
+  const [transforms, setTransforms] = useState<TransformInfo[]>([]);
+  // 当前文档目录在本地资源服务器上的地址，预览中的相对图片路径按此解析
+  const [assetBaseUrl, setAssetBaseUrl] = useState<string | null>(null);
+  // 导出预览：右侧面板加载本地资源服务器上的导出页面（与无头浏览器加载的页面相同）
+  const [exportPreview, setExportPreview] = useState(false);
+  const [exportPreviewUrl, setExportPreviewUrl] = useState<string | null>(null);
   const [isDirty, setIsDirty] = useState(false);
   const [isLoading, setIsLoading] = useState(false);
   const [loadingMessage, setLoadingMessage] = useState('');
+  const [parserMode, setParserMode] = useState<ParserMode>('extended');
+  const [lineBreaks, setLineBreaks] = useState<LineBreaks>('soft');
+  const [quoteStyle, setQuoteStyle] = useState<QuoteStyle | null>(null);
+  const [typography, setTypography] = useState<Typography | null>(null);
+  const [abbreviations, setAbbreviations] = useState<Abbreviation[]>([]);
+  const [calloutStyles, setCalloutStyles] = useState<Record<string, CalloutStyle>>({});
+  const [redactedExport, setRedactedExport] = useState(false);
+  const [draftExport, setDraftExport] = useState(false);
+  const [verticalExport, setVerticalExport] = useState(false);
+  const [bestEffortExport, setBestEffortExport] = useState(false);
+  // 监视导出的 ID（见后端 watch 模块），未监视时为空
+  const [watchId, setWatchId] = useState<string | null>(null);
+  // 未选择时按 front matter 中的 `endnotes`
+  const [notePlacement, setNotePlacement] = useState<NotePlacement | ''>('');
+  // 未选择时按 front matter 中的 `layout` / `paper` 与设置
+  const [pageLayout, setPageLayout] = useState<PageLayout | ''>('');
+  const [paperSize, setPaperSize] = useState<PaperSize | ''>('');
+  // 未选择时按 front matter 中的 `pdf_standard` 与设置；PDF/A 不能与打开密码同时使用
+  const [pdfStandard, setPdfStandard] = useState<PdfStandard | ''>('');
+  // 修订导出对比的旧版本文件（见后端 diff 模块）
+  const [compareWith, setCompareWith] = useState<string | null>(null);
+  const [optimizeExport, setOptimizeExport] = useState(false);
+  // 导出 PDF 的打开密码，为空时不加密
+  const [exportPassword, setExportPassword] = useState('');
+  // 进行中的导出任务 ID（用于取消）
+  const [activeExportId, setActiveExportId] = useState<string | null>(null);
   const styles = useStyles();
   const toasterId = useId('toaster');
   const { dispatchToast } = useToastController(toasterId);
@@ -347,6 +1210,49 @@ function App() {
     };
   }, []);
 
+  useEffect(() => {
+    invoke<string | null>('get_asset_base_url', { path: currentFile })
+      .then(setAssetBaseUrl)
+      .catch(() => setAssetBaseUrl(null));
+  }, [currentFile]);
+
+  // 读取预处理管线
+  useEffect(() => {
+    invoke<TransformInfo[]>('list_transforms').then(setTransforms).catch(() => setTransforms([]));
+  }, []);
+
+  // 读取导出历史，每次导出成功后刷新
+  useEffect(() => {
+    const refresh = () => invoke<ExportRecord[]>('get_export_history').then(setExportHistory).catch(() => {});
+    refresh();
+    const pending = listen('export-report', refresh);
+    return () => {
+      pending.then(unlisten => unlisten());
+    };
+  }, []);
+
+  // 根据文档 front matter 中的 markdown 字段确定解析模式
+  useEffect(() => {
+    invoke<ParserMode>('detect_parser_mode', { markdown: markdownContent })
+      .then(setParserMode)
+      .catch(() => setParserMode('extended'));
+    invoke<LineBreaks>('detect_line_breaks', { markdown: markdownContent })
+      .then(setLineBreaks)
+      .catch(() => setLineBreaks('soft'));
+    invoke<QuoteStyle | null>('detect_smart_quotes', { markdown: markdownContent })
+      .then(setQuoteStyle)
+      .catch(() => setQuoteStyle(null));
+    invoke<Typography>('detect_typography', { markdown: markdownContent })
+      .then(setTypography)
+      .catch(() => setTypography(null));
+    invoke<Abbreviation[]>('detect_abbreviations', { markdown: markdownContent })
+      .then(setAbbreviations)
+      .catch(() => setAbbreviations([]));
+    invoke<Record<string, CalloutStyle>>('get_callout_styles', { markdown: markdownContent, sourcePath: currentFile })
+      .then(setCalloutStyles)
+      .catch(() => setCalloutStyles({}));
+  }, [markdownContent, currentFile]);
+
   // 解析 Markdown 内容为分块
   const parseMarkdownToBlocks = useCallback(async (content: string): Promise<MarkdownBlock[]> => {
     if (!content) return [];
@@ -370,6 +1276,9 @@ function App() {
   const isProgrammatic = useRef(false);
   const calibrationTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
   const lastLeftTopIndex = useRef(0);
+  // 会话状态保存在当前文件下（滚动回调中读取）
+  const currentFileRef = useRef<string | null>(null);
+  const sessionSaveTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
   const lastRightTopIndex = useRef(0);
 
   // 处理块内容修改
@@ -430,6 +1339,15 @@ function App() {
     if (newTopIndex === lastLeftTopIndex.current) return;
     lastLeftTopIndex.current = newTopIndex;
 
+    // 记录滚动位置，重新打开文件时恢复
+    if (sessionSaveTimer.current) clearTimeout(sessionSaveTimer.current);
+    sessionSaveTimer.current = setTimeout(() => {
+      const path = currentFileRef.current;
+      if (!path) return;
+      const session: FileSession = { scroll_block: newTopIndex, cursor_line: null, theme: null };
+      invoke('save_file_session', { path, session }).catch(() => {});
+    }, SESSION_SAVE_DELAY_MS);
+
     // 只有当左侧是活跃面板时才同步右侧
     if (activePane.current !== 'left' || isProgrammatic.current) return;
 
@@ -527,13 +1445,45 @@ function App() {
     );
   }, [dispatchToast]);
 
+  // 点击预览中的任务复选框：切换源码中对应的 `- [ ]` / `- [x]`
+  const handleTaskToggle = useCallback(async (index: number, e: ReactMouseEvent) => {
+    const target = e.target as HTMLElement;
+    const line = target instanceof HTMLInputElement ? Number(target.dataset.taskLine) : NaN;
+    if (!line) return;
+    e.preventDefault();
+
+    const block = markdownBlocks[index];
+    try {
+      const edit = await invoke<RustTextEdit | null>('toggle_task', { markdown: block.content, line });
+      if (edit) handleBlockChange(index, applyTextEdit(block.content, edit));
+    } catch (error) {
+      showErrorToast(`切换任务状态失败: ${formatError(error)}`);
+    }
+  }, [markdownBlocks, handleBlockChange, showErrorToast]);
+
+  // 粘贴剪贴板中的图片：保存到文档旁的 assets/ 目录，并在光标处插入图片链接
+  const handleImagePaste = useCallback(async (index: number, e: ReactClipboardEvent<HTMLTextAreaElement>) => {
+    const hasImage = Array.from(e.clipboardData.items).some(item => item.type.startsWith('image/'));
+    if (!hasImage || !currentFile) return;
+    e.preventDefault();
+    const textarea = e.currentTarget;
+    const { selectionStart, selectionEnd, value } = textarea;
+    try {
+      const link = await invoke<string>('save_clipboard_image', { docPath: currentFile });
+      handleBlockChange(index, value.slice(0, selectionStart) + link + value.slice(selectionEnd));
+    } catch (error) {
+      showErrorToast(`粘贴图片失败: ${formatError(error)}`);
+    }
+  }, [currentFile, handleBlockChange, showErrorToast]);
+
   // 判断是否为 Markdown 文件路径
   const isMarkdownPath = useCallback((path: string) => {
     return /\.(md|markdown)$/i.test(path);
   }, []);
 
   // 统一按路径加载 Markdown
-  const loadMarkdownFromPath = useCallback(async (path: string, skipDirtyConfirm = false) => {
+  // `preloaded` 为后端已读取的内容（通过文件关联打开时），省去再次读取
+  const loadMarkdownFromPath = useCallback(async (path: string, skipDirtyConfirm = false, preloaded?: string) => {
     if (!isMarkdownPath(path)) {
       showErrorToast('仅支持导入 Markdown 文件（.md / .markdown）');
       return false;
@@ -542,13 +1492,15 @@ function App() {
     if (!skipDirtyConfirm && isDirty) {
       const confirm = await window.confirm('当前文件有未保存的更改，确定要打开新文件吗？（更改将丢失）');
       if (!confirm) return false;
+      // 放弃的未命名文档不再提示恢复
+      if (!currentFile) invoke('discard_draft', { docId: untitledId }).catch(() => {});
     }
 
     try {
       setIsLoading(true);
       setLoadingMessage('正在读取文件...');
 
-      const content = await invoke<string>('read_markdown_file', { path });
+      const content = preloaded ?? await invoke<string>('read_markdown_file', { path });
       setMarkdownContent(content);
 
       setLoadingMessage('正在解析文档结构...');
@@ -558,16 +1510,26 @@ function App() {
       setMarkdownBlocks(blocks);
 
       setCurrentFile(path);
+      currentFileRef.current = path;
       setIsDirty(false);
       showSuccessToast(`已加载 ${path.split(/[/\\]/).pop()}`);
+
+      // 记录到最近文件，并恢复上次的滚动位置
+      invoke<RecentFile[]>('add_recent_file', { path }).then(setRecentFiles).catch(() => {});
+      const session = await invoke<FileSession | null>('get_file_session', { path }).catch(() => null);
+      if (session && session.scroll_block > 0 && session.scroll_block < blocks.length) {
+        setTimeout(() => {
+          leftVirtuosoRef.current?.scrollToIndex({ index: session.scroll_block, align: 'start', behavior: 'auto' });
+        }, 50);
+      }
       return true;
     } catch (error) {
-      showErrorToast(`读取文件失败: ${error}`);
+      showErrorToast(`读取文件失败: ${formatError(error)}`);
       return false;
     } finally {
       setIsLoading(false);
     }
-  }, [isMarkdownPath, isDirty, parseMarkdownToBlocks, showErrorToast, showSuccessToast]);
+  }, [isMarkdownPath, isDirty, currentFile, untitledId, parseMarkdownToBlocks, showErrorToast, showSuccessToast]);
 
   // 监听窗口拖拽导入
   useEffect(() => {
@@ -596,6 +1558,25 @@ function App() {
     };
   }, [isMarkdownPath, loadMarkdownFromPath, showErrorToast]);
 
+  // 程序已在运行时双击 Markdown 文件（或通过“打开方式”），由后端读取后转交过来
+  useEffect(() => {
+    let unlistenOpen: (() => void) | null = null;
+    let unlistenError: (() => void) | null = null;
+    const setup = async () => {
+      unlistenOpen = await listen<{ path: string; content: string }>('open-file', async (event) => {
+        await loadMarkdownFromPath(event.payload.path, false, event.payload.content);
+      });
+      unlistenError = await listen<{ path: string; message: string }>('open-file-error', (event) => {
+        showErrorToast(`无法打开文件: ${event.payload.message}`);
+      });
+    };
+    setup();
+    return () => {
+      if (unlistenOpen) unlistenOpen();
+      if (unlistenError) unlistenError();
+    };
+  }, [loadMarkdownFromPath, showErrorToast]);
+
   // 启动时检查：是否通过“拖到 exe”方式携带了 Markdown 路径
   useEffect(() => {
     const loadFromLaunchPath = async () => {
@@ -612,6 +1593,74 @@ function App() {
     loadFromLaunchPath();
   }, [loadMarkdownFromPath]);
 
+  useEffect(() => {
+    currentFileRef.current = currentFile;
+  }, [currentFile]);
+
+  // 启动时读取最近文件列表（同时移除已不存在的文件）
+  useEffect(() => {
+    invoke<RecentFile[]>('get_recent_files').then(setRecentFiles).catch(() => {});
+  }, []);
+
+  // 固定或取消固定当前文件
+  const handleTogglePin = useCallback(async () => {
+    if (!currentFile) return;
+    const pinned = recentFiles.some(file => file.path === currentFile && file.pinned);
+    try {
+      setRecentFiles(await invoke<RecentFile[]>('pin_recent_file', { path: currentFile, pinned: !pinned }));
+    } catch (error) {
+      showErrorToast(`更新最近文件失败: ${formatError(error)}`);
+    }
+  }, [currentFile, recentFiles, showErrorToast]);
+
+  // 有未保存的更改时自动保存草稿（停止编辑后 3 秒写入），崩溃后可以恢复
+  useEffect(() => {
+    if (!isDirty || !markdownContent) return;
+    const timer = setTimeout(() => {
+      invoke('autosave_draft', { docId: currentFile ?? untitledId, content: markdownContent }).catch(() => {
+        // 草稿保存失败不打扰用户，下次编辑时重试
+      });
+    }, AUTOSAVE_DELAY_MS);
+    return () => clearTimeout(timer);
+  }, [isDirty, currentFile, untitledId, markdownContent]);
+
+  // 启动时检查上次异常退出留下的草稿
+  const draftsChecked = useRef(false);
+  useEffect(() => {
+    if (draftsChecked.current) return;
+    draftsChecked.current = true;
+
+    const recoverDrafts = async () => {
+      try {
+        const drafts = await invoke<RecoveredDraft[]>('list_recovered_drafts');
+        for (const draft of drafts) {
+          const untitled = draft.doc_id.startsWith('untitled:');
+          const name = untitled ? '未命名文档' : draft.doc_id.split(/[/\\]/).pop();
+          const time = new Date(draft.updated_ms).toLocaleString();
+          const restore = await window.confirm(`发现 ${name} 未保存的草稿（${time}），是否恢复？\n选择“取消”将删除该草稿。`);
+          if (!restore) {
+            await invoke('discard_draft', { docId: draft.doc_id });
+            continue;
+          }
+          setMarkdownContent(draft.content);
+          setMarkdownBlocks(await parseMarkdownToBlocks(draft.content));
+          if (untitled) {
+            setUntitledId(draft.doc_id);
+          } else {
+            setCurrentFile(draft.doc_id);
+          }
+          setIsDirty(true);
+          showSuccessToast(`已恢复 ${name} 的草稿，请保存`);
+          break;
+        }
+      } catch {
+        // 读取草稿失败不影响正常使用
+      }
+    };
+
+    recoverDrafts();
+  }, [parseMarkdownToBlocks, showSuccessToast]);
+
   // 选择 Markdown 文件
   const handleSelectFile = useCallback(async () => {
     try {
@@ -627,10 +1676,109 @@ function App() {
         await loadMarkdownFromPath(selected as string);
       }
     } catch (error) {
-      showErrorToast(`打开文件失败: ${error}`);
+      showErrorToast(`打开文件失败: ${formatError(error)}`);
     }
   }, [loadMarkdownFromPath, showErrorToast]);
 
+  // 选择修订导出对比的旧版本，已选择时取消对比
+  const handleSelectCompare = useCallback(async () => {
+    if (compareWith) {
+      setCompareWith(null);
+      return;
+    }
+    try {
+      const selected = await open({
+        multiple: false,
+        filters: [{
+          name: 'Markdown',
+          extensions: ['md', 'markdown']
+        }]
+      });
+      if (selected) {
+        setCompareWith(selected as string);
+        showSuccessToast(`导出时将与 ${(selected as string).split(/[/\\]/).pop()} 对比，标出修订内容`);
+      }
+    } catch (error) {
+      showErrorToast(`选择旧版本失败: ${formatError(error)}`);
+    }
+  }, [compareWith, showSuccessToast, showErrorToast]);
+
+  // 导入 Word / HTML 文档：转换为源文件旁的 Markdown 文件后打开
+  const handleImportDocument = useCallback(async () => {
+    try {
+      const selected = await open({
+        multiple: false,
+        filters: [{
+          name: 'Word / HTML 文档',
+          extensions: ['docx', 'html', 'htm']
+        }]
+      });
+      if (!selected) return;
+
+      setIsLoading(true);
+      setLoadingMessage('正在转换文档...');
+      const result = await invoke<{ markdown_path: string; markdown: string; images: number; warnings: string[] }>(
+        'import_document',
+        { path: selected as string }
+      );
+      setIsLoading(false);
+      if (await loadMarkdownFromPath(result.markdown_path, false, result.markdown)) {
+        const warningText = result.warnings.length > 0 ? `，${result.warnings.length} 个警告` : '';
+        showSuccessToast(`已导入为 ${result.markdown_path.split(/[/\\]/).pop()}，提取图片 ${result.images} 张${warningText}`);
+      }
+    } catch (error) {
+      setIsLoading(false);
+      showErrorToast(`导入文档失败: ${formatError(error)}`);
+    }
+  }, [loadMarkdownFromPath, showSuccessToast, showErrorToast]);
+
+  // 检查文件夹：对其中所有 Markdown 文件执行格式、链接与资源检查，并保存汇总报告
+  const handleValidateWorkspace = useCallback(async () => {
+    try {
+      const root = await open({ directory: true, multiple: false });
+      if (!root) return;
+      const reportPath = await save({
+        filters: [
+          { name: 'HTML 报告', extensions: ['html'] },
+          { name: 'JSON 报告', extensions: ['json'] }
+        ],
+        defaultPath: 'validation-report.html'
+      });
+
+      setIsLoading(true);
+      setLoadingMessage('正在检查文件夹...');
+      const report = await invoke<{ file_count: number; error_count: number; warning_count: number; passed: boolean }>(
+        'validate_workspace',
+        { root: root as string, reportPath }
+      );
+      setIsLoading(false);
+      const summary = `共 ${report.file_count} 个文件，${report.error_count} 个错误，${report.warning_count} 个警告`;
+      if (report.passed) {
+        showSuccessToast(`检查通过：${summary}`);
+      } else {
+        showErrorToast(`检查未通过：${summary}`);
+      }
+    } catch (error) {
+      setIsLoading(false);
+      showErrorToast(`检查文件夹失败: ${formatError(error)}`);
+    }
+  }, [showSuccessToast, showErrorToast]);
+
+  // 预览中点击文档链接：解析目标并打开
+  const handlePreviewLinkClick = useCallback(async (e: ReactMouseEvent) => {
+    const href = (e.target as HTMLElement).closest('a')?.getAttribute('href');
+    if (!href?.startsWith(OPEN_DOCUMENT_URL)) return;
+    e.preventDefault();
+
+    const target = new URL(href).searchParams.get('target') ?? '';
+    try {
+      const path = await invoke<string>('resolve_document_link', { from: currentFile, target });
+      await loadMarkdownFromPath(path);
+    } catch (error) {
+      showErrorToast(`无法打开链接的文档: ${formatError(error)}`);
+    }
+  }, [currentFile, loadMarkdownFromPath, showErrorToast]);
+
   // 保存文件
   const handleSave = useCallback(async () => {
     if (!currentFile || !markdownContent) return;
@@ -640,9 +1788,10 @@ function App() {
       setLoadingMessage('正在保存文件...');
       await writeTextFile(currentFile, markdownContent);
       setIsDirty(false);
+      invoke('discard_draft', { docId: currentFile }).catch(() => {});
       showSuccessToast('文件已保存');
     } catch (error) {
-      showErrorToast(`保存失败: ${error}`);
+      showErrorToast(`保存失败: ${formatError(error)}`);
     } finally {
       setIsLoading(false);
     }
@@ -666,15 +1815,17 @@ function App() {
       setIsLoading(true);
       setLoadingMessage('正在另存为...');
       await writeTextFile(savePath, markdownContent);
+      invoke('discard_draft', { docId: currentFile ?? untitledId }).catch(() => {});
+      if (!currentFile) setUntitledId(`untitled:${crypto.randomUUID()}`);
       setCurrentFile(savePath);
       setIsDirty(false);
       showSuccessToast('文件已另存为');
     } catch (error) {
-      showErrorToast(`另存为失败: ${error}`);
+      showErrorToast(`另存为失败: ${formatError(error)}`);
     } finally {
       setIsLoading(false);
     }
-  }, [currentFile, markdownContent, showSuccessToast, showErrorToast]);
+  }, [currentFile, untitledId, markdownContent, showSuccessToast, showErrorToast]);
 
   // 恢复文件（丢弃更改）
   const handleRestore = useCallback(async () => {
@@ -694,16 +1845,103 @@ function App() {
       setMarkdownBlocks(blocks);
       
       setIsDirty(false);
+      invoke('discard_draft', { docId: currentFile }).catch(() => {});
       showSuccessToast('已恢复到原始状态');
     } catch (error) {
-      showErrorToast(`恢复失败: ${error}`);
+      showErrorToast(`恢复失败: ${formatError(error)}`);
     } finally {
       setIsLoading(false);
     }
   }, [currentFile, showSuccessToast, showErrorToast, parseMarkdownToBlocks]);
 
+  // 生成导出用的 HTML（PDF 与图片导出共用）。
+  // 尽力导出时整体渲染失败则逐块渲染，失败的块输出 render-failure 标记，由后端替换为错误占位框
+  const renderExportHtml = useCallback(async (markdown: string, bestEffort = false) => {
+    // 严格模式下只使用 CommonMark 解析，不加载扩展插件
+    const strict = parserMode === 'strict';
+    let processor: any = unified().use(remarkParse);
+    if (!strict) processor = processor.use(remarkGfm).use(remarkMath);
+    processor = processor.use(remarkCodeMeta).use(remarkRehype, { allowDangerousHtml: true }).use(rehypeRaw);
+    // 公式引擎为 MathJax 或 none 时保留 TeX 原文，由导出页面处理
+    const { math_engine: mathEngine } = await invoke<{ math_engine: string }>('get_settings');
+    if (!strict) processor = processor.use(rehypeLineBreaks, { mode: lineBreaks }).use(rehypeMathInHtml).use(rehypeSmartQuotes, { style: quoteStyle }).use(rehypeTypography, { rules: typography });
+    if (!strict && mathEngine === 'katex') processor = processor.use(rehypeKatex, katexOptions);
+    processor = processor.use(rehypeStringify);
+    try {
+      const processed = await processor.process(stripFrontMatter(markdown));
+      return processed.toString();
+    } catch (error) {
+      if (!bestEffort) throw error;
+      const blocks = await parseMarkdownToBlocks(markdown);
+      const parts = await Promise.all(blocks.map(async block => {
+        try {
+          return (await processor.process(stripFrontMatter(block.content))).toString();
+        } catch (blockError) {
+          const message = formatError(blockError).replace(/&/g, '&amp;').replace(/"/g, '&quot;').replace(/</g, '&lt;');
+          return `<div class="render-failure" data-start-line="${block.startLine}" data-end-line="${block.endLine}" data-error="${message}"></div>`;
+        }
+      }));
+      return parts.join('\n');
+    }
+  }, [parserMode, lineBreaks, quoteStyle, typography, parseMarkdownToBlocks]);
+
+  // 导出预览：内容或导出选项变化后重新生成页面
+  useEffect(() => {
+    if (!exportPreview || !currentFile || !markdownContent) {
+      setExportPreviewUrl(null);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(async () => {
+      try {
+        // 与导出相同，先经过预处理管线
+        const transformed = await invoke<string>('apply_transforms', { markdown: markdownContent, sourcePath: currentFile });
+        const html = await renderExportHtml(transformed, true);
+        const url = await invoke<string | null>('preview_export_page', {
+          htmlContent: html,
+          title: currentFile.split(/[/\\]/).pop()?.replace(/\.(md|markdown)$/i, '') ?? 'document',
+          options: {
+            mode: parserMode,
+            markdown: transformed,
+            source_path: currentFile,
+            profile: redactedExport ? 'redacted' : 'internal',
+            watermark: draftExport ? { text: '草稿' } : null,
+            writing_mode: verticalExport ? 'vertical' : null,
+            notes: notePlacement || null,
+            layout: pageLayout || null,
+            paper: paperSize || null,
+            best_effort: true
+          }
+        });
+        if (!cancelled) setExportPreviewUrl(url);
+      } catch (error) {
+        if (!cancelled) showErrorToast(`生成导出预览失败: ${formatError(error)}`);
+      }
+    }, 800);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [exportPreview, currentFile, markdownContent, parserMode, renderExportHtml, redactedExport, draftExport, verticalExport, notePlacement, pageLayout, paperSize, showErrorToast]);
+
+  // 复制为富文本：代码高亮与公式（MathML）预先渲染，可直接粘贴到 Word / 邮件
+  const handleCopyRichHtml = useCallback(async (selection?: BlockSelection) => {
+    if (!markdownContent) return;
+    try {
+      const result = await invoke<{ formulas: number; code_blocks: number; warnings: string[] }>('copy_as_rich_html', {
+        markdown: markdownContent,
+        selection: selection ?? null,
+        sourcePath: currentFile,
+      });
+      const warningText = result.warnings.length > 0 ? `，${result.warnings.length} 个警告` : '';
+      showSuccessToast(`已复制为富文本（公式 ${result.formulas} 个，代码块 ${result.code_blocks} 个${warningText}）`);
+    } catch (error) {
+      showErrorToast(`复制失败: ${formatError(error)}`);
+    }
+  }, [markdownContent, currentFile, showSuccessToast, showErrorToast]);
+
   // 导出为 PDF
-  const handleExportPdf = useCallback(async () => {
+  const handleExportPdf = useCallback(async (selection?: BlockSelection) => {
     if (!markdownContent) {
       showErrorToast('请先选择一个 Markdown 文件');
       return;
@@ -721,35 +1959,255 @@ function App() {
       if (!savePath) return;
 
       setIsLoading(true);
-      setLoadingMessage('正在生成 HTML 内容...');
+      setLoadingMessage('正在执行代码块...');
       await new Promise(resolve => setTimeout(resolve, 10));
 
-      const processed = await unified()
-        .use(remarkParse)
-        .use(remarkGfm)
-        .use(remarkMath)
-        .use(remarkRehype, { allowDangerousHtml: true })
-        .use(rehypeRaw)
-        .use(rehypeMathInHtml)
-        .use(rehypeKatex)
-        .use(rehypeStringify)
-        .process(markdownContent);
-      const previewHtml = processed.toString();
+      // 部分导出：按块边界截取选中的内容
+      let source = markdownContent;
+      if (selection) {
+        const selected = await invoke<{ markdown: string }>('select_markdown', { markdown: markdownContent, selection });
+        source = selected.markdown;
+      }
+
+      // 修订导出：与旧版本合并，标出新增与删除的内容
+      if (compareWith) {
+        source = await invoke<string>('track_changes', { oldPath: compareWith, markdown: source, mode: parserMode });
+      }
+
+      // 预处理管线（include 展开、自定义替换等，见后端 transforms 模块）
+      const transformed = await invoke<string>('apply_transforms', { markdown: source, sourcePath: currentFile });
+
+      // 文学化模式：执行 {run} 代码块并嵌入输出（未开启时原样返回）
+      const literate = await runLiterate(transformed, currentFile);
+
+      setLoadingMessage('正在生成 HTML 内容...');
+      const previewHtml = await renderExportHtml(literate, bestEffortExport);
 
       setLoadingMessage('正在启动渲染引擎...');
-      await invoke('export_to_pdf', {
+      const exportId = `export-${Date.now()}`;
+      setActiveExportId(exportId);
+      const report = await invokeExport<ExportReport>('export_to_pdf', {
         htmlContent: previewHtml,
         outputPath: savePath,
-        title: currentFile ? currentFile.split(/[/\\\\]/).pop()?.replace(/\.(md|markdown)$/i, '') : 'document'
+        title: currentFile ? currentFile.split(/[/\\\\]/).pop()?.replace(/\.(md|markdown)$/i, '') : 'document',
+        options: {
+          mode: parserMode,
+          markdown: literate,
+          source_path: currentFile,
+          profile: redactedExport ? 'redacted' : 'internal',
+          watermark: draftExport ? { text: '草稿' } : null,
+          writing_mode: verticalExport ? 'vertical' : null,
+          notes: notePlacement || null,
+          layout: pageLayout || null,
+          paper: paperSize || null,
+          pdf_standard: pdfStandard || null,
+          compare_with: compareWith,
+          optimize: optimizeExport ? {} : null,
+          protection: exportPassword ? { user_password: exportPassword } : null,
+          best_effort: bestEffortExport,
+          export_id: exportId
+        }
       });
 
       setIsLoading(false);
-      showSuccessToast('PDF 导出成功！');
+      setActiveExportId(null);
+      const seconds = (report.total_ms / 1000).toFixed(1);
+      const warningText = report.warnings.length > 0 ? `，${report.warnings.length} 个警告` : '';
+      const toMb = (bytes: number) => (bytes / 1024 / 1024).toFixed(1);
+      const optimizationText = report.optimization
+        ? `，体积 ${toMb(report.optimization.size_before)} MB → ${toMb(report.optimization.size_after)} MB`
+        : '';
+      const failureText = report.failures.length > 0
+        ? `，${report.failures.length} 处渲染失败（第 ${report.failures.map(f => f.start_line).join('、')} 行）`
+        : '';
+      showSuccessToast(`PDF 导出成功！共 ${report.page_count} 页，耗时 ${seconds} 秒${optimizationText}${warningText}${failureText}`);
+    } catch (error) {
+      setIsLoading(false);
+      setActiveExportId(null);
+      if ((error as BackendError)?.code === 'CANCELLED') {
+        showErrorToast(formatError(error));
+      } else {
+        showErrorToast(`导出 PDF 失败: ${formatError(error)}`);
+      }
+    }
+  }, [markdownContent, currentFile, parserMode, renderExportHtml, redactedExport, draftExport, verticalExport, notePlacement, pageLayout, paperSize, pdfStandard, compareWith, optimizeExport, exportPassword, bestEffortExport, showSuccessToast, showErrorToast]);
+
+  // 按导出历史中的选项重新导出：重新读取源文件并渲染，输出到原来的路径
+  const handleReexport = useCallback(async (record: ExportRecord) => {
+    if (!record.source_path) return;
+    try {
+      setIsLoading(true);
+      setLoadingMessage('正在读取源文件...');
+      let content = await invoke<string>('read_markdown_file', { path: record.source_path });
+      if (record.options.compare_with) {
+        content = await invoke<string>('track_changes', { oldPath: record.options.compare_with, markdown: content, mode: record.options.mode });
+      }
+      const transformed = await invoke<string>('apply_transforms', { markdown: content, sourcePath: record.source_path });
+      const literate = await runLiterate(transformed, record.source_path);
+
+      setLoadingMessage('正在生成 HTML 内容...');
+      const previewHtml = await renderExportHtml(literate, record.options.best_effort);
+
+      setLoadingMessage('正在启动渲染引擎...');
+      const report = await invoke<ExportReport>('reexport', { historyId: record.id, htmlContent: previewHtml });
+      setIsLoading(false);
+      const seconds = (report.total_ms / 1000).toFixed(1);
+      showSuccessToast(`已重新导出 ${record.output_path.split(/[/\\]/).pop()}：共 ${report.page_count} 页，耗时 ${seconds} 秒`);
     } catch (error) {
       setIsLoading(false);
-      showErrorToast(`导出 PDF 失败: ${error}`);
+      showErrorToast(`重新导出失败: ${formatError(error)}`);
     }
-  }, [markdownContent, currentFile, showSuccessToast, showErrorToast]);
+  }, [renderExportHtml, showSuccessToast, showErrorToast]);
+
+  // 清空导出历史
+  const handleClearExportHistory = useCallback(async () => {
+    try {
+      await invoke('clear_export_history');
+      setExportHistory([]);
+    } catch (error) {
+      showErrorToast(`清空导出历史失败: ${formatError(error)}`);
+    }
+  }, [showErrorToast]);
+
+  // 启用或停用一个预处理转换
+  const handleToggleTransform = useCallback(async (transform: TransformInfo) => {
+    try {
+      setTransforms(await invoke<TransformInfo[]>('set_transform_enabled', { name: transform.name, enabled: !transform.enabled }));
+    } catch (error) {
+      showErrorToast(`修改预处理失败: ${formatError(error)}`);
+    }
+  }, [showErrorToast]);
+
+  // 预处理转换上移一位
+  const handleMoveTransformUp = useCallback(async (index: number) => {
+    const order = transforms.map(t => t.name);
+    [order[index - 1], order[index]] = [order[index], order[index - 1]];
+    try {
+      setTransforms(await invoke<TransformInfo[]>('reorder_transforms', { order }));
+    } catch (error) {
+      showErrorToast(`调整预处理顺序失败: ${formatError(error)}`);
+    }
+  }, [transforms, showErrorToast]);
+
+  // 监视当前文件：外部编辑器保存后自动导出到同名 PDF
+  const handleToggleWatch = useCallback(async (enabled: boolean) => {
+    try {
+      if (!enabled) {
+        await invoke('stop_watch_export', { watchId });
+        setWatchId(null);
+        return;
+      }
+      if (!currentFile) return;
+      const id = await invoke<string>('watch_export', {
+        path: currentFile,
+        options: {
+          mode: parserMode,
+          profile: redactedExport ? 'redacted' : 'internal',
+          watermark: draftExport ? { text: '草稿' } : null,
+          writing_mode: verticalExport ? 'vertical' : null,
+          notes: notePlacement || null,
+          layout: pageLayout || null,
+          paper: paperSize || null,
+          pdf_standard: pdfStandard || null,
+          compare_with: compareWith,
+          optimize: optimizeExport ? {} : null,
+          best_effort: bestEffortExport
+        }
+      });
+      setWatchId(id);
+      showSuccessToast(`正在监视 ${currentFile.split(/[/\\]/).pop()}，保存后自动导出`);
+    } catch (error) {
+      showErrorToast(`监视导出失败: ${formatError(error)}`);
+    }
+  }, [watchId, currentFile, parserMode, redactedExport, draftExport, verticalExport, notePlacement, pageLayout, paperSize, pdfStandard, compareWith, optimizeExport, bestEffortExport, showSuccessToast, showErrorToast]);
+
+  // 切换文件时停止监视
+  useEffect(() => {
+    return () => {
+      invoke('stop_watch_export', { path: currentFile }).catch(() => {});
+      setWatchId(null);
+    };
+  }, [currentFile]);
+
+  // 监视导出的结果
+  useEffect(() => {
+    const complete = listen<{ watch_id: string; report: ExportReport }>('watch-export-complete', (event) => {
+      const { report } = event.payload;
+      showSuccessToast(`已自动导出 ${report.output_path.split(/[/\\]/).pop()}：共 ${report.page_count} 页`);
+    });
+    const failed = listen<{ watch_id: string; error: unknown }>('watch-export-error', (event) => {
+      showErrorToast(`自动导出失败: ${formatError(event.payload.error)}`);
+    });
+    return () => {
+      complete.then(unlisten => unlisten());
+      failed.then(unlisten => unlisten());
+    };
+  }, [showSuccessToast, showErrorToast]);
+
+  // 导出为图片（每页一张，格式按保存的扩展名选择 PNG 或 JPEG）
+  const handleExportImages = useCallback(async () => {
+    if (!markdownContent) {
+      showErrorToast('请先选择一个 Markdown 文件');
+      return;
+    }
+
+    try {
+      const savePath = await save({
+        filters: [
+          { name: 'PNG 图片', extensions: ['png'] },
+          { name: 'JPEG 图片', extensions: ['jpg', 'jpeg'] }
+        ],
+        defaultPath: currentFile ? currentFile.replace(/\.(md|markdown)$/i, '.png') : 'document.png'
+      });
+
+      if (!savePath) return;
+
+      setIsLoading(true);
+      setLoadingMessage('正在执行代码块...');
+      const source = compareWith
+        ? await invoke<string>('track_changes', { oldPath: compareWith, markdown: markdownContent, mode: parserMode })
+        : markdownContent;
+      const transformed = await invoke<string>('apply_transforms', { markdown: source, sourcePath: currentFile });
+      const literate = await runLiterate(transformed, currentFile);
+
+      setLoadingMessage('正在生成 HTML 内容...');
+      const previewHtml = await renderExportHtml(literate, bestEffortExport);
+
+      setLoadingMessage('正在启动渲染引擎...');
+      const exportId = `export-${Date.now()}`;
+      setActiveExportId(exportId);
+      const report = await invokeExport<{ files: string[] }>('export_to_images', {
+        htmlContent: previewHtml,
+        outputPath: savePath,
+        title: currentFile ? currentFile.split(/[/\\]/).pop()?.replace(/\.(md|markdown)$/i, '') : 'document',
+        images: {
+          format: /\.jpe?g$/i.test(savePath) ? 'jpeg' : 'png',
+          layout: 'pages'
+        },
+        options: {
+          mode: parserMode,
+          markdown: literate,
+          source_path: currentFile,
+          profile: redactedExport ? 'redacted' : 'internal',
+          watermark: draftExport ? { text: '草稿' } : null,
+          best_effort: bestEffortExport,
+          export_id: exportId
+        }
+      });
+
+      setIsLoading(false);
+      setActiveExportId(null);
+      showSuccessToast(`图片导出成功！共 ${report.files.length} 张`);
+    } catch (error) {
+      setIsLoading(false);
+      setActiveExportId(null);
+      if ((error as BackendError)?.code === 'CANCELLED') {
+        showErrorToast(formatError(error));
+      } else {
+        showErrorToast(`导出图片失败: ${formatError(error)}`);
+      }
+    }
+  }, [markdownContent, currentFile, parserMode, renderExportHtml, redactedExport, draftExport, compareWith, bestEffortExport, showSuccessToast, showErrorToast]);
 
   // 格式化 Markdown
   const handleFormatMarkdown = useCallback(async () => {
@@ -797,6 +2255,94 @@ function App() {
             >
               打开
             </Button>
+            <Button
+              appearance="secondary"
+              icon={<ArrowImportRegular />}
+              onClick={handleImportDocument}
+            >
+              导入
+            </Button>
+            <Button
+              appearance="secondary"
+              icon={<CheckmarkCircleRegular />}
+              onClick={handleValidateWorkspace}
+            >
+              检查文件夹
+            </Button>
+            <Menu>
+              <MenuTrigger disableButtonEnhancement>
+                <Button appearance="secondary" icon={<HistoryRegular />} disabled={recentFiles.length === 0}>
+                  最近
+                </Button>
+              </MenuTrigger>
+              <MenuPopover>
+                <MenuList>
+                  {recentFiles.map(file => (
+                    <MenuItem
+                      key={file.path}
+                      disabled={!file.exists}
+                      onClick={() => loadMarkdownFromPath(file.path)}
+                    >
+                      {file.pinned ? '📌 ' : ''}{file.path.split(/[/\\]/).pop()}{file.exists ? '' : '（文件不存在）'}
+                    </MenuItem>
+                  ))}
+                  {currentFile && <MenuDivider />}
+                  {currentFile && (
+                    <MenuItem onClick={handleTogglePin}>
+                      {recentFiles.some(file => file.path === currentFile && file.pinned) ? '取消固定当前文件' : '固定当前文件'}
+                    </MenuItem>
+                  )}
+                </MenuList>
+              </MenuPopover>
+            </Menu>
+            <Menu>
+              <MenuTrigger disableButtonEnhancement>
+                <Button appearance="secondary" icon={<DocumentPdfRegular />} disabled={exportHistory.length === 0}>
+                  导出历史
+                </Button>
+              </MenuTrigger>
+              <MenuPopover>
+                <MenuList>
+                  {exportHistory.map(record => (
+                    <MenuItem
+                      key={record.id}
+                      disabled={!record.source_path || record.protected}
+                      onClick={() => handleReexport(record)}
+                    >
+                      重新导出 {record.output_path.split(/[/\\]/).pop()}
+                      （{new Date(record.exported_ms).toLocaleString()}，{record.page_count} 页
+                      {record.protected ? '，已加密' : ''}）
+                    </MenuItem>
+                  ))}
+                  <MenuDivider />
+                  <MenuItem onClick={handleClearExportHistory}>清空导出历史</MenuItem>
+                </MenuList>
+              </MenuPopover>
+            </Menu>
+            <Menu>
+              <MenuTrigger disableButtonEnhancement>
+                <Button appearance="secondary" disabled={transforms.length === 0}>
+                  预处理
+                </Button>
+              </MenuTrigger>
+              <MenuPopover>
+                <MenuList>
+                  <MenuItem disabled>只作用于导出与导出预览，分块预览显示源文本</MenuItem>
+                  <MenuDivider />
+                  {transforms.map(transform => (
+                    <MenuItem key={transform.name} onClick={() => handleToggleTransform(transform)}>
+                      {transform.enabled ? '✓' : '　'} {transform.name}（{transform.description || '自定义替换'}）
+                    </MenuItem>
+                  ))}
+                  <MenuDivider />
+                  {transforms.slice(1).map((transform, index) => (
+                    <MenuItem key={`up-${transform.name}`} onClick={() => handleMoveTransformUp(index + 1)}>
+                      上移 {transform.name}
+                    </MenuItem>
+                  ))}
+                </MenuList>
+              </MenuPopover>
+            </Menu>
             <Button
               appearance="secondary"
               icon={<WandRegular />}
@@ -829,14 +2375,110 @@ function App() {
             >
               恢复
             </Button>
+            <Switch
+              label="涂黑版"
+              checked={redactedExport}
+              onChange={(_, data) => setRedactedExport(data.checked)}
+            />
+            <Switch
+              label="草稿水印"
+              checked={draftExport}
+              onChange={(_, data) => setDraftExport(data.checked)}
+            />
+            <Switch
+              label="竖排"
+              checked={verticalExport}
+              onChange={(_, data) => setVerticalExport(data.checked)}
+            />
+            <Switch
+              label="尽力导出"
+              checked={bestEffortExport}
+              onChange={(_, data) => setBestEffortExport(data.checked)}
+            />
+            <Select
+              value={notePlacement}
+              onChange={(_, data) => setNotePlacement(data.value as NotePlacement | '')}
+            >
+              <option value="">注释：按文档</option>
+              <option value="footnotes">脚注</option>
+              <option value="page">脚注（页底）</option>
+              <option value="chapter">尾注（每章）</option>
+              <option value="document">尾注（文末）</option>
+            </Select>
+            <Select
+              value={pageLayout}
+              onChange={(_, data) => setPageLayout(data.value as PageLayout | '')}
+            >
+              <option value="">分栏：按文档</option>
+              <option value="single">单栏</option>
+              <option value="two_column">两栏</option>
+            </Select>
+            <Select
+              value={paperSize}
+              onChange={(_, data) => setPaperSize(data.value as PaperSize | '')}
+            >
+              <option value="">纸张：按文档</option>
+              <option value="a4">A4</option>
+              <option value="booklet">小册子（A5）</option>
+            </Select>
+            <Select
+              value={pdfStandard}
+              onChange={(_, data) => setPdfStandard(data.value as PdfStandard | '')}
+            >
+              <option value="">PDF 标准：按文档</option>
+              <option value="standard">普通 PDF</option>
+              <option value="tagged">带标签（无障碍）</option>
+              <option value="pdf_a">PDF/A（归档）</option>
+            </Select>
+            <Button
+              appearance={compareWith ? 'primary' : 'secondary'}
+              onClick={handleSelectCompare}
+              disabled={!markdownContent}
+              title={compareWith ?? undefined}
+            >
+              {compareWith ? '取消对比' : '对比旧版本'}
+            </Button>
+            <Switch
+              label="监视导出"
+              checked={watchId !== null}
+              disabled={!currentFile}
+              onChange={(_, data) => handleToggleWatch(data.checked)}
+            />
+            <Switch
+              label="压缩图片"
+              checked={optimizeExport}
+              onChange={(_, data) => setOptimizeExport(data.checked)}
+            />
+            <Input
+              type="password"
+              placeholder="PDF 打开密码"
+              value={exportPassword}
+              onChange={(_, data) => setExportPassword(data.value)}
+            />
             <Button
               appearance="primary"
               icon={<DocumentPdfRegular />}
-              onClick={handleExportPdf}
+              onClick={() => handleExportPdf()}
               disabled={!markdownContent}
             >
               导出为 PDF
             </Button>
+            <Button
+              appearance="secondary"
+              icon={<ImageRegular />}
+              onClick={handleExportImages}
+              disabled={!markdownContent}
+            >
+              导出为图片
+            </Button>
+            <Button
+              appearance="secondary"
+              icon={<ClipboardCodeRegular />}
+              onClick={() => handleCopyRichHtml()}
+              disabled={!markdownContent}
+            >
+              复制为富文本
+            </Button>
           </div>
         </header>
 
@@ -864,6 +2506,7 @@ function App() {
                   scrollerRef={leftScrollerCallback}
                   style={{ height: '100%' }}
                   data={markdownBlocks}
+                  computeItemKey={(_, block) => block.id}
                   rangeChanged={handleLeftRangeChanged}
                   itemContent={(index, block) => (
                     <div className={styles.blockContainer}>
@@ -883,11 +2526,26 @@ function App() {
                           onClick={() => handleDeleteBlock(index)}
                           title="删除区块"
                         />
+                        <Button
+                          size="small"
+                          appearance="subtle"
+                          icon={<DocumentPdfRegular />}
+                          onClick={() => handleExportPdf({ kind: 'section', line: blockStartLine(markdownBlocks, index) })}
+                          title="导出本节（标题区块导出到下一个同级标题之前）"
+                        />
+                        <Button
+                          size="small"
+                          appearance="subtle"
+                          icon={<ClipboardCodeRegular />}
+                          onClick={() => handleCopyRichHtml({ kind: 'section', line: blockStartLine(markdownBlocks, index) })}
+                          title="复制本节为富文本"
+                        />
                       </div>
                       <textarea
                         className={styles.editorRow}
                         value={block.content}
                         onChange={(e) => handleBlockChange(index, e.target.value)}
+                        onPaste={(e) => handleImagePaste(index, e)}
                         spellCheck={false}
                       />
                     </div>
@@ -900,18 +2558,27 @@ function App() {
             <div className={styles.pane}>
               <div className={styles.paneHeader}>
                 <Body1><b>PDF 预览</b></Body1>
+                <Switch
+                  label="导出预览"
+                  checked={exportPreview}
+                  disabled={!currentFile}
+                  onChange={(_, data) => setExportPreview(data.checked)}
+                />
                 <Body1>共 {markdownContent.length} 字符</Body1>
               </div>
-              <div className={`${styles.scrollArea} markdown-preview`}>
-                {markdownContent ? (
+              <div className={`${styles.scrollArea} markdown-preview`} onClickCapture={handlePreviewLinkClick}>
+                {exportPreview && exportPreviewUrl ? (
+                  <iframe className={styles.exportPreviewFrame} src={exportPreviewUrl} title="导出预览" />
+                ) : markdownContent ? (
                   <Virtuoso
                     ref={rightVirtuosoRef}
                     scrollerRef={rightScrollerCallback}
                     style={{ height: '100%' }}
                     data={markdownBlocks}
+                    computeItemKey={(_, block) => block.id}
                     rangeChanged={handleRightRangeChanged}
                     itemContent={(index, block) => (
-                      <div className={`${styles.previewRow} ${styles.blockContainer}`}>
+                      <div className={`${styles.previewRow} ${styles.blockContainer}`} onClick={(e) => handleTaskToggle(index, e)}>
                         <div className={`${styles.blockToolbar} block-toolbar`}>
                           <Button
                             size="small"
@@ -930,8 +2597,9 @@ function App() {
                           />
                         </div>
                         <ReactMarkdown
-                          remarkPlugins={[remarkGfm, remarkMath]}
-                          rehypePlugins={[rehypeRaw, rehypeMathInHtml, rehypeKatex]}
+                          remarkPlugins={parserMode === 'strict' ? [] : [remarkGfm, remarkMath]}
+                          rehypePlugins={parserMode === 'strict' ? [rehypeRaw, [rehypeAssetBase, { base: assetBaseUrl }]] : [rehypeRaw, [rehypeAssetBase, { base: assetBaseUrl }], rehypeTaskCheckboxes, [rehypeCallouts, { styles: calloutStyles }], [rehypeLineBreaks, { mode: lineBreaks }], rehypeHeadingPageBreaks, rehypeUiMarkup, rehypeDocumentLinks, rehypeMathInHtml, rehypeRuby, rehypeBadges, [rehypeSmartQuotes, { style: quoteStyle }], [rehypeTypography, { rules: typography }], [rehypeAbbreviations, { abbreviations }], [rehypeKatex, katexOptions]]}
+                          urlTransform={previewUrlTransform}
                         >
                           {block.content}
                         </ReactMarkdown>
@@ -955,6 +2623,14 @@ function App() {
             <Card className={styles.loadingCard}>
               <Spinner size="large" />
               <Body1>{loadingMessage}</Body1>
+              {activeExportId && (
+                <Button
+                  appearance="secondary"
+                  onClick={() => invoke('cancel_export', { exportId: activeExportId })}
+                >
+                  取消导出
+                </Button>
+              )}
             </Card>
           </div>
         )}